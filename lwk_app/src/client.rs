@@ -1,4 +1,5 @@
 use std::net::SocketAddr;
+use std::path::Path;
 
 use lwk_jade::TIMEOUT;
 use lwk_wollet::UnvalidatedRecipient;
@@ -11,20 +12,77 @@ use serde_json::Value;
 use crate::error::Error;
 use crate::method::Method;
 use crate::reqwest_transport::ReqwestHttpTransport;
-use crate::{request, response};
+use crate::{request, response, Config};
 
 pub struct Client {
     client: jsonrpc::Client,
 }
 
+/// Requests to be sent together with [`Client::send_batch`]
+#[derive(Debug, Default)]
+pub struct Batch {
+    requests: Vec<(Method, Option<Box<RawValue>>)>,
+}
+
+impl Batch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a request to the batch, its result will be at the same position in the results
+    /// returned by [`Client::send_batch`]
+    pub fn add<Req: Serialize>(&mut self, method: Method, req: Option<Req>) -> Result<(), Error> {
+        let params = req.map(|req| to_raw_value(&req)).transpose()?;
+        self.requests.push((method, params));
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+}
+
 impl Client {
     pub fn new(addr: SocketAddr) -> Result<Self, Error> {
-        let url = format!("http://{addr}");
-        let transport = ReqwestHttpTransport::new(url, TIMEOUT);
+        Self::with_auth(addr, None, None)
+    }
+
+    /// Create a client authenticating with the given `credentials` (user, password).
+    ///
+    /// If `tls_cert` is some, connect via TLS trusting the certificates in the given PEM file.
+    pub fn with_auth(
+        addr: SocketAddr,
+        credentials: Option<(String, String)>,
+        tls_cert: Option<&Path>,
+    ) -> Result<Self, Error> {
+        let (url, root_certs) = match tls_cert {
+            Some(path) => {
+                let pem = std::fs::read(path)?;
+                let certs = reqwest::Certificate::from_pem_bundle(&pem)?;
+                (format!("https://{addr}"), certs)
+            }
+            None => (format!("http://{addr}"), vec![]),
+        };
+        let transport = ReqwestHttpTransport::new(url, TIMEOUT, credentials, root_certs);
         let client = jsonrpc::Client::with_transport(transport);
         Ok(Self { client })
     }
 
+    /// Create a client for the server started with `config`, picking up the credentials from
+    /// the cookie file, if it's used
+    pub fn from_config(config: &Config) -> Result<Self, Error> {
+        let credentials = if config.rpc_cookie {
+            Some(lwk_tiny_jrpc::auth::read_cookie(&config.cookie_path()?)?)
+        } else {
+            None
+        };
+        Self::with_auth(config.addr, credentials, config.rpc_tls_cert.as_deref())
+    }
+
     pub(crate) fn make_request<Req, Res>(
         &self,
         method: Method,
@@ -40,13 +98,52 @@ impl Client {
         log::trace!("---> {}", serde_json::to_string(&request)?);
         let response = self.client.send_request(request)?;
         log::trace!("<--- {}", serde_json::to_string(&response)?);
-        match response.result.as_ref() {
-            Some(result) => Ok(serde_json::from_str(result.get())?),
-            None => match response.error {
-                Some(rpc_err) => Err(Error::RpcError(rpc_err)),
-                None => Err(Error::NeitherResultNorErrorSet),
-            },
+        parse_response(response)
+    }
+
+    /// Send all the requests in `batch` with a single round trip.
+    ///
+    /// The returned results are in the same order of the requests in the batch, each request
+    /// succeeds or fails independently. Use `Res = Value` if the batch contains requests with
+    /// different response types.
+    pub fn send_batch<Res>(&self, batch: &Batch) -> Result<Vec<Result<Res, Error>>, Error>
+    where
+        Res: DeserializeOwned,
+    {
+        if batch.is_empty() {
+            return Ok(vec![]);
         }
+        let methods: Vec<_> = batch.requests.iter().map(|(m, _)| m.to_string()).collect();
+        let requests: Vec<_> = methods
+            .iter()
+            .zip(batch.requests.iter())
+            .map(|(method, (_, params))| self.client.build_request(method, params.as_deref()))
+            .collect();
+        log::trace!("---> {}", serde_json::to_string(&requests)?);
+        let responses = self.client.send_batch(&requests)?;
+        log::trace!("<--- {}", serde_json::to_string(&responses)?);
+        Ok(responses
+            .into_iter()
+            .enumerate()
+            .map(|(i, response)| match response {
+                Some(response) => parse_response(response),
+                None => Err(Error::MissingBatchResponse(i)),
+            })
+            .collect())
+    }
+
+    /// The balances of the wallets with the given `names`, fetched with a single batch request
+    pub fn wallet_balances(
+        &self,
+        names: Vec<String>,
+        with_tickers: bool,
+    ) -> Result<Vec<Result<response::WalletBalance, Error>>, Error> {
+        let mut batch = Batch::new();
+        for name in names {
+            let req = request::WalletBalance { name, with_tickers };
+            batch.add(Method::WalletBalance, Some(req))?;
+        }
+        self.send_batch(&batch)
     }
 
     pub fn version(&self) -> Result<response::Version, Error> {
@@ -94,8 +191,19 @@ impl Client {
         self.make_request(Method::WalletList, None::<Box<RawValue>>)
     }
 
-    pub fn wallet_load(&self, descriptor: String, name: String) -> Result<response::Wallet, Error> {
-        let req = request::WalletLoad { descriptor, name };
+    pub fn wallet_load(
+        &self,
+        descriptor: String,
+        name: String,
+        network: Option<String>,
+        birthday_height: Option<u32>,
+    ) -> Result<response::Wallet, Error> {
+        let req = request::WalletLoad {
+            descriptor,
+            name,
+            network,
+            birthday_height,
+        };
         self.make_request(Method::WalletLoad, Some(req))
     }
 
@@ -113,6 +221,11 @@ impl Client {
         self.make_request(Method::SignerList, None::<Box<RawValue>>)
     }
 
+    pub fn wallet_portfolio(&self, name: String) -> Result<response::WalletPortfolio, Error> {
+        let req = request::WalletPortfolio { name };
+        self.make_request(Method::WalletPortfolio, Some(req))
+    }
+
     pub fn wallet_balance(
         &self,
         name: String,
@@ -146,12 +259,14 @@ impl Client {
         addressees: Vec<UnvalidatedRecipient>,
         fee_rate: Option<f32>,
         enable_ct_discount: bool,
+        lock_inputs: Option<u32>,
     ) -> Result<response::Pset, Error> {
         let req = request::WalletSendMany {
             addressees: addressees.into_iter().map(unvalidate_addressee).collect(),
             fee_rate,
             name,
             enable_ct_discount,
+            lock_inputs,
         };
         self.make_request(Method::WalletSendMany, Some(req))
     }
@@ -161,11 +276,13 @@ impl Client {
         name: String,
         address: String,
         fee_rate: Option<f32>,
+        lock_inputs: Option<u32>,
     ) -> Result<response::Pset, Error> {
         let req = request::WalletDrain {
             address,
             fee_rate,
             name,
+            lock_inputs,
         };
         self.make_request(Method::WalletDrain, Some(req))
     }
@@ -223,6 +340,32 @@ impl Client {
         self.make_request(Method::SignerSign, Some(req))
     }
 
+    pub fn signer_set_policy(
+        &self,
+        name: String,
+        policy: Option<request::SpendingPolicy>,
+    ) -> Result<response::Empty, Error> {
+        let req = request::SignerSetPolicy { name, policy };
+        self.make_request(Method::SignerSetPolicy, Some(req))
+    }
+
+    pub fn signer_signed_psets(
+        &self,
+        name: Option<String>,
+    ) -> Result<response::SignedPsets, Error> {
+        let req = request::SignerSignedPsets { name };
+        self.make_request(Method::SignerSignedPsets, Some(req))
+    }
+
+    pub fn pset_decode(
+        &self,
+        pset: String,
+        network: Option<String>,
+    ) -> Result<response::PsetDecode, Error> {
+        let req = request::PsetDecode { pset, network };
+        self.make_request(Method::PsetDecode, Some(req))
+    }
+
     pub fn wallet_broadcast(
         &self,
         name: String,
@@ -270,17 +413,81 @@ impl Client {
         self.make_request(Method::WalletPsetDetails, Some(req))
     }
 
+    pub fn wallet_lock_utxo(
+        &self,
+        name: String,
+        txid: String,
+        vout: u32,
+        blocks: Option<u32>,
+    ) -> Result<response::Empty, Error> {
+        let req = request::WalletLockUtxo {
+            name,
+            txid,
+            vout,
+            blocks,
+        };
+        self.make_request(Method::WalletLockUtxo, Some(req))
+    }
+
+    pub fn wallet_unlock_utxo(
+        &self,
+        name: String,
+        txid: String,
+        vout: u32,
+    ) -> Result<response::Empty, Error> {
+        let req = request::WalletUnlockUtxo { name, txid, vout };
+        self.make_request(Method::WalletUnlockUtxo, Some(req))
+    }
+
+    pub fn wallet_locked_utxos(&self, name: String) -> Result<response::WalletLockedUtxos, Error> {
+        let req = request::WalletLockedUtxos { name };
+        self.make_request(Method::WalletLockedUtxos, Some(req))
+    }
+
+    pub fn wallet_backup(&self, name: String) -> Result<response::WalletBackup, Error> {
+        let req = request::WalletBackup { name };
+        self.make_request(Method::WalletBackup, Some(req))
+    }
+
+    pub fn wallet_snapshot(&self, name: String) -> Result<response::WalletSnapshot, Error> {
+        let req = request::WalletSnapshot { name };
+        self.make_request(Method::WalletSnapshot, Some(req))
+    }
+
+    pub fn wallet_restore(&self, backup: String, name: String) -> Result<response::Wallet, Error> {
+        let req = request::WalletRestore { backup, name };
+        self.make_request(Method::WalletRestore, Some(req))
+    }
+
     pub fn wallet_utxos(&self, name: String) -> Result<response::WalletUtxos, Error> {
         let req = request::WalletUtxos { name };
         self.make_request(Method::WalletUtxos, Some(req))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn wallet_txs(
         &self,
         name: String,
         with_tickers: bool,
+        asset: Option<String>,
+        tx_type: Option<String>,
+        min_height: Option<u32>,
+        max_height: Option<u32>,
+        address: Option<String>,
+        offset: usize,
+        limit: Option<usize>,
     ) -> Result<response::WalletTxs, Error> {
-        let req = request::WalletTxs { name, with_tickers };
+        let req = request::WalletTxs {
+            name,
+            with_tickers,
+            asset,
+            tx_type,
+            min_height,
+            max_height,
+            address,
+            offset,
+            limit,
+        };
         self.make_request(Method::WalletTxs, Some(req))
     }
 
@@ -332,6 +539,8 @@ impl Client {
         address_token: Option<String>,
         contract: Option<String>,
         fee_rate: Option<f32>,
+        blind_issuance: bool,
+        lock_inputs: Option<u32>,
     ) -> Result<response::Pset, Error> {
         let req = request::WalletIssue {
             name,
@@ -341,10 +550,13 @@ impl Client {
             address_token,
             contract,
             fee_rate,
+            blind_issuance,
+            lock_inputs,
         };
         self.make_request(Method::WalletIssue, Some(req))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn wallet_reissue(
         &self,
         name: String,
@@ -352,6 +564,8 @@ impl Client {
         satoshi_asset: u64,
         address_asset: Option<String>,
         fee_rate: Option<f32>,
+        blind_issuance: bool,
+        lock_inputs: Option<u32>,
     ) -> Result<response::Pset, Error> {
         let req = request::WalletReissue {
             name,
@@ -359,6 +573,8 @@ impl Client {
             satoshi_asset,
             address_asset,
             fee_rate,
+            blind_issuance,
+            lock_inputs,
         };
         self.make_request(Method::WalletReissue, Some(req))
     }
@@ -369,12 +585,14 @@ impl Client {
         asset: String,
         satoshi_asset: u64,
         fee_rate: Option<f32>,
+        lock_inputs: Option<u32>,
     ) -> Result<response::Pset, Error> {
         let req = request::WalletBurn {
             name,
             asset,
             satoshi_asset,
             fee_rate,
+            lock_inputs,
         };
         self.make_request(Method::WalletBurn, Some(req))
     }
@@ -427,13 +645,21 @@ impl Client {
         self.make_request(Method::AssetRemove, Some(req))
     }
 
-    pub fn asset_from_explorer(&self, asset_id: String) -> Result<response::Empty, Error> {
-        let req = request::AssetFromExplorer { asset_id };
+    pub fn asset_from_explorer(
+        &self,
+        asset_id: String,
+        network: Option<String>,
+    ) -> Result<response::Empty, Error> {
+        let req = request::AssetFromExplorer { asset_id, network };
         self.make_request(Method::AssetFromExplorer, Some(req))
     }
 
-    pub fn asset_publish(&self, asset_id: String) -> Result<response::AssetPublish, Error> {
-        let req = request::AssetPublish { asset_id };
+    pub fn asset_publish(
+        &self,
+        asset_id: String,
+        network: Option<String>,
+    ) -> Result<response::AssetPublish, Error> {
+        let req = request::AssetPublish { asset_id, network };
         self.make_request(Method::AssetPublish, Some(req))
     }
 
@@ -454,6 +680,15 @@ impl Client {
         self.make_request(Method::Scan, None::<Box<RawValue>>)
     }
 
+    pub fn wait_for_event(
+        &self,
+        after: Option<u64>,
+        timeout: Option<u64>,
+    ) -> Result<response::Events, Error> {
+        let req = request::WaitForEvent { after, timeout };
+        self.make_request(Method::WaitForEvent, Some(req))
+    }
+
     pub fn stop(&self) -> Result<Value, Error> {
         // TODO discriminate only stop error
         let _: Result<Value, Error> = self.make_request(Method::Stop, None::<Box<RawValue>>);
@@ -461,6 +696,16 @@ impl Client {
     }
 }
 
+fn parse_response<Res: DeserializeOwned>(response: jsonrpc::Response) -> Result<Res, Error> {
+    match response.result.as_ref() {
+        Some(result) => Ok(serde_json::from_str(result.get())?),
+        None => match response.error {
+            Some(rpc_err) => Err(Error::RpcError(rpc_err)),
+            None => Err(Error::NeitherResultNorErrorSet),
+        },
+    }
+}
+
 fn unvalidate_addressee(a: lwk_wollet::UnvalidatedRecipient) -> request::UnvalidatedAddressee {
     request::UnvalidatedAddressee {
         satoshi: a.satoshi,
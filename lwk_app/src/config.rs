@@ -12,6 +12,69 @@ use std::time::Duration;
 
 use crate::{consts, Error};
 
+/// A user allowed to call the RPC server via HTTP Basic authentication
+#[derive(Clone)]
+pub struct RpcUser {
+    pub name: String,
+    pub password: String,
+
+    /// If true, the user can call only the methods in [`crate::method::READ_ONLY_METHODS`]
+    pub read_only: bool,
+}
+
+impl std::fmt::Debug for RpcUser {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RpcUser")
+            .field("name", &self.name)
+            .field("read_only", &self.read_only)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The secret used to encrypt the app state
+#[derive(Clone)]
+pub enum StateSecret {
+    Passphrase(String),
+
+    /// A file whose content is used as secret
+    KeyFile(PathBuf),
+}
+
+impl std::fmt::Debug for StateSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateSecret::Passphrase(_) => write!(f, "Passphrase(..)"),
+            StateSecret::KeyFile(path) => f.debug_tuple("KeyFile").field(path).finish(),
+        }
+    }
+}
+
+/// The backends used for a network served in addition to [`Config::network`]
+#[derive(Clone, Debug)]
+pub struct NetworkBackend {
+    pub network: ElementsNetwork,
+    pub electrum_url: String,
+    pub tls: bool,
+    pub validate_domain: bool,
+    pub explorer_url: String,
+    pub esplora_api_url: String,
+    pub registry_url: String,
+}
+
+impl From<&Config> for NetworkBackend {
+    fn from(config: &Config) -> Self {
+        Self {
+            network: config.network,
+            electrum_url: config.electrum_url.clone(),
+            tls: config.tls,
+            validate_domain: config.validate_domain,
+            explorer_url: config.explorer_url.clone(),
+            esplora_api_url: config.esplora_api_url.clone(),
+            registry_url: config.registry_url.clone(),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Config {
     /// The address where the RPC server is listening or the client is connecting to
@@ -30,6 +93,32 @@ pub struct Config {
     pub registry_url: String,
     pub timeout: Duration,
     pub scanning_interval: Duration,
+
+    /// URLs receiving a POST request with every wallet event
+    pub webhooks: Vec<String>,
+
+    /// How many times the delivery of an event to a webhook is retried before giving up
+    pub webhook_retries: u32,
+
+    /// If true, RPC calls are authenticated with the credentials written in [`Config::cookie_path`]
+    pub rpc_cookie: bool,
+
+    /// Users allowed to call the RPC server, if not empty RPC calls require authentication
+    pub rpc_users: Vec<RpcUser>,
+
+    /// PEM file with the certificate chain used to serve the RPC over TLS, clients trust the
+    /// certificates in this file
+    pub rpc_tls_cert: Option<PathBuf>,
+
+    /// PEM file with the private key of `rpc_tls_cert`
+    pub rpc_tls_key: Option<PathBuf>,
+
+    /// If some, the state (including the mnemonics of persisted signers) is encrypted
+    pub state_secret: Option<StateSecret>,
+
+    /// Networks served in addition to `network`, wallets on these networks must specify it when
+    /// loaded, while signers and assets always use `network`
+    pub additional_networks: Vec<NetworkBackend>,
 }
 
 impl Config {
@@ -46,6 +135,14 @@ impl Config {
             registry_url: "https://assets-testnet.blockstream.info/".into(),
             timeout: TIMEOUT,
             scanning_interval: consts::SCANNING_INTERVAL,
+            webhooks: vec![],
+            webhook_retries: consts::WEBHOOK_RETRIES,
+            rpc_cookie: false,
+            rpc_users: vec![],
+            rpc_tls_cert: None,
+            rpc_tls_key: None,
+            state_secret: None,
+            additional_networks: vec![],
         }
     }
 
@@ -62,6 +159,14 @@ impl Config {
             registry_url: "https://assets.blockstream.info/".into(),
             timeout: TIMEOUT,
             scanning_interval: consts::SCANNING_INTERVAL,
+            webhooks: vec![],
+            webhook_retries: consts::WEBHOOK_RETRIES,
+            rpc_cookie: false,
+            rpc_users: vec![],
+            rpc_tls_cert: None,
+            rpc_tls_key: None,
+            state_secret: None,
+            additional_networks: vec![],
         }
     }
 
@@ -83,6 +188,14 @@ impl Config {
             timeout: TIMEOUT,
             // Scan more frequently while testing
             scanning_interval: Duration::from_secs(1),
+            webhooks: vec![],
+            webhook_retries: consts::WEBHOOK_RETRIES,
+            rpc_cookie: false,
+            rpc_users: vec![],
+            rpc_tls_cert: None,
+            rpc_tls_key: None,
+            state_secret: None,
+            additional_networks: vec![],
         }
     }
 
@@ -111,11 +224,96 @@ impl Config {
 
     /// Returns the path of the state file under datadir
     pub fn state_path(&self) -> Result<PathBuf, Error> {
+        let mut path = self.datadir()?;
+        path.push("state.lwk");
+        Ok(path)
+    }
+
+    /// Returns the path of the audit log of the signed PSETs under datadir
+    pub fn audit_log_path(&self) -> Result<PathBuf, Error> {
+        let mut path = self.datadir()?;
+        path.push("signed_psets.lwk");
+        Ok(path)
+    }
+
+    /// Returns the path of the plaintext state file used by previous versions, which is migrated
+    /// to [`Config::state_path`] at startup
+    pub fn legacy_state_path(&self) -> Result<PathBuf, Error> {
         let mut path = self.datadir()?;
         path.push("state.json");
         Ok(path)
     }
 
+    /// Returns the path of the cookie file under datadir, containing the RPC credentials
+    pub fn cookie_path(&self) -> Result<PathBuf, Error> {
+        let mut path = self.datadir()?;
+        path.push(".cookie");
+        Ok(path)
+    }
+
+    /// Returns the path of the file under datadir containing the address of the server serving
+    /// this network in addition to its main one
+    pub fn server_addr_path(&self) -> Result<PathBuf, Error> {
+        let mut path = self.datadir()?;
+        path.push(".server_addr");
+        Ok(path)
+    }
+
+    /// True if the RPC server requires authentication
+    pub fn rpc_auth_required(&self) -> bool {
+        self.rpc_cookie || !self.rpc_users.is_empty()
+    }
+
+    /// Serve also the network of `other`, using its backends
+    pub fn add_network(&mut self, other: &Config) -> Result<(), Error> {
+        let name = other.network.as_str();
+        if self.networks().iter().any(|n| n.as_str() == name) {
+            return Err(Error::Generic(format!("Network {name} is already served")));
+        }
+        self.additional_networks.push(other.into());
+        Ok(())
+    }
+
+    /// All the networks served, starting with the main one
+    pub fn networks(&self) -> Vec<ElementsNetwork> {
+        std::iter::once(self.network)
+            .chain(self.additional_networks.iter().map(|n| n.network))
+            .collect()
+    }
+
+    /// The served network with the given name, the main network if `name` is none
+    pub fn network_from_str(&self, name: Option<&str>) -> Result<ElementsNetwork, Error> {
+        let Some(name) = name else {
+            return Ok(self.network);
+        };
+        self.networks()
+            .into_iter()
+            .find(|n| n.as_str() == name)
+            .ok_or_else(|| Error::NetworkNotServed(name.to_string()))
+    }
+
+    /// This config with the backends of `network`, which must be served
+    pub fn for_network(&self, network: ElementsNetwork) -> Result<Config, Error> {
+        if network == self.network {
+            return Ok(self.clone());
+        }
+        let backend = self
+            .additional_networks
+            .iter()
+            .find(|n| n.network == network)
+            .ok_or_else(|| Error::NetworkNotServed(network.as_str().to_string()))?;
+        Ok(Config {
+            network: backend.network,
+            electrum_url: backend.electrum_url.clone(),
+            tls: backend.tls,
+            validate_domain: backend.validate_domain,
+            explorer_url: backend.explorer_url.clone(),
+            esplora_api_url: backend.esplora_api_url.clone(),
+            registry_url: backend.registry_url.clone(),
+            ..self.clone()
+        })
+    }
+
     /// True if Liquid mainnet
     pub fn is_mainnet(&self) -> bool {
         matches!(self.network, ElementsNetwork::Liquid)
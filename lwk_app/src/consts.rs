@@ -6,3 +6,18 @@ pub const DEFAULT_ADDR: SocketAddrV4 = SocketAddrV4::new(Ipv4Addr::new(127, 0, 0
 pub const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 pub const SCANNING_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Maximum number of events kept in memory for `wait_for_event`
+pub const MAX_EVENTS: usize = 1_000;
+
+/// Default time `wait_for_event` waits for new events
+pub const WAIT_FOR_EVENT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default number of retries for delivering an event to a webhook
+pub const WEBHOOK_RETRIES: u32 = 5;
+
+/// The state is compacted when more records than this, or than the last snapshot, are appended
+pub const STATE_COMPACTION_MIN_RECORDS: usize = 100;
+
+/// Scrypt cost parameter used to derive the state encryption key
+pub const STATE_KDF_LOG_N: u8 = 15;
@@ -8,6 +8,9 @@ pub enum Error {
     #[error("Tiny HTTP Error: {0}")]
     TinyHttp(#[from] Box<dyn std::error::Error + Send + Sync + 'static>),
 
+    #[error("Tiny JRPC Error: {0}")]
+    TinyJrpc(#[from] lwk_tiny_jrpc::Error),
+
     #[error("JSON RPC HTTP Client Error: {0}")]
     JsonRpcHttp(#[from] jsonrpc::simple_http::Error),
 
@@ -50,6 +53,9 @@ pub enum Error {
     #[error("In the response received neither the result nor the error are set")]
     NeitherResultNorErrorSet,
 
+    #[error("No response received for the request at position {0} of the batch")]
+    MissingBatchResponse(usize),
+
     #[error("Rpc returned an error {0:?}")]
     RpcError(jsonrpc::error::RpcError),
 
@@ -62,6 +68,9 @@ pub enum Error {
     #[error(transparent)]
     QrError(#[from] lwk_common::QrError),
 
+    #[error("Network '{0}' is not served")]
+    NetworkNotServed(String),
+
     #[error("Wallet '{0}' does not exist")]
     WalletNotExist(String),
 
@@ -107,6 +116,18 @@ pub enum Error {
     #[error("Received stop command")]
     Stop,
 
+    #[error("The state file has version {0}, which is not supported by this version of LWK")]
+    StateVersion(u32),
+
+    #[error("The state is encrypted, specify the passphrase or the key file")]
+    StateEncrypted,
+
+    #[error("Cannot decrypt the state, the passphrase or the key file is wrong")]
+    StateDecrypt,
+
+    #[error("Spending policy violation: {0}")]
+    PolicyViolation(#[from] crate::policy::PolicyViolation),
+
     // TODO remove into specific errors
     #[error("Generic error {0}")]
     Generic(String),
@@ -128,6 +149,7 @@ impl Error {
             Error::SignerAlreadyLoaded(_) => {
                 ImplementationDefinedCode::new(-32_011).expect("static")
             }
+            Error::PolicyViolation(_) => ImplementationDefinedCode::new(-32_014).expect("static"),
 
             _ => lwk_tiny_jrpc::error::GENERIC,
         }
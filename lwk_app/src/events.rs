@@ -0,0 +1,301 @@
+//! Wallet events
+//!
+//! Events are detected by the scanning thread comparing a wallet before and after applying an
+//! update. They are kept in memory, so that clients can long-poll them with `wait_for_event`, and
+//! they are delivered to the configured webhooks.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use lwk_rpc_model::response::{self, Event, EventKind};
+use lwk_wollet::elements::Txid;
+use lwk_wollet::{TxQuery, Wollet};
+
+use crate::{consts, convert_tx, Config, Error};
+
+/// The wallet data needed to detect what changed after an update
+pub struct WolletSnapshot {
+    txs: HashMap<String, response::Tx>,
+    balance: HashMap<String, i64>,
+    tip: u32,
+}
+
+impl WolletSnapshot {
+    pub fn new(
+        wollet: &Wollet,
+        explorer_url: &str,
+        memos: &HashMap<Txid, String>,
+    ) -> Result<Self, Error> {
+        let txs = wollet
+            .query_transaction_summaries(&TxQuery::new())?
+            .txs
+            .iter()
+            .map(|tx| (tx.txid.to_string(), convert_tx(tx, explorer_url, memos)))
+            .collect();
+        let balance = wollet
+            .balance()?
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v as i64))
+            .collect();
+        Ok(Self {
+            txs,
+            balance,
+            tip: wollet.tip().height(),
+        })
+    }
+}
+
+pub struct Events {
+    events: VecDeque<Event>,
+
+    /// Id of the last event, 0 if no events happened
+    last_id: u64,
+
+    /// If some, events are sent here to be delivered to webhooks
+    webhooks: Option<Sender<Event>>,
+}
+
+impl Events {
+    pub fn new(webhooks: Option<Sender<Event>>) -> Self {
+        Self {
+            events: VecDeque::new(),
+            last_id: 0,
+            webhooks,
+        }
+    }
+
+    pub fn last_id(&self) -> u64 {
+        self.last_id
+    }
+
+    /// The events with id greater than `id`
+    pub fn after(&self, id: u64) -> Vec<Event> {
+        self.events.iter().filter(|e| e.id > id).cloned().collect()
+    }
+
+    fn push(
+        &mut self,
+        wallet: &str,
+        kind: EventKind,
+        tx: Option<response::Tx>,
+        balance: Option<HashMap<String, i64>>,
+        height: Option<u32>,
+    ) {
+        self.last_id += 1;
+        let event = Event {
+            id: self.last_id,
+            wallet: wallet.to_string(),
+            kind,
+            tx,
+            balance,
+            height,
+        };
+        log::debug!("new event {event:?}");
+        if let Some(sender) = self.webhooks.as_ref() {
+            let _ = sender.send(event.clone());
+        }
+        if self.events.len() == consts::MAX_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
+    /// Push the events describing the changes from `before` to `after`
+    pub fn push_diff(&mut self, wallet: &str, before: &WolletSnapshot, after: &WolletSnapshot) {
+        if before.tip != after.tip {
+            self.push(wallet, EventKind::NewTip, None, None, Some(after.tip));
+        }
+
+        let mut txs: Vec<_> = after.txs.values().collect();
+        txs.sort_by(|a, b| {
+            (a.height.is_none(), a.height, &a.txid).cmp(&(b.height.is_none(), b.height, &b.txid))
+        });
+        for tx in txs {
+            let kind = match before.txs.get(&tx.txid) {
+                None => EventKind::NewTx,
+                Some(old) if old.height.is_none() && tx.height.is_some() => EventKind::TxConfirmed,
+                Some(old) if old.height.is_some() && old.height != tx.height => {
+                    EventKind::TxReorged
+                }
+                Some(_) => continue,
+            };
+            self.push(wallet, kind, Some(tx.clone()), None, None);
+        }
+
+        let mut dropped: Vec<_> = before
+            .txs
+            .values()
+            .filter(|tx| !after.txs.contains_key(&tx.txid))
+            .collect();
+        dropped.sort_by(|a, b| a.txid.cmp(&b.txid));
+        for tx in dropped {
+            self.push(wallet, EventKind::TxDropped, Some(tx.clone()), None, None);
+        }
+
+        if before.balance != after.balance {
+            let balance = Some(after.balance.clone());
+            self.push(wallet, EventKind::BalanceChanged, None, balance, None);
+        }
+    }
+}
+
+/// Spawn a thread delivering the events received from `receiver` to the webhooks in `config`
+///
+/// The thread ends when `is_running` is set to false or when every sender has been dropped
+pub fn spawn_webhooks(
+    receiver: Receiver<Event>,
+    config: &Config,
+    is_running: Arc<AtomicBool>,
+) -> JoinHandle<()> {
+    let urls = config.webhooks.clone();
+    let retries = config.webhook_retries;
+    let timeout = config.timeout;
+    std::thread::spawn(move || {
+        let client = match reqwest::blocking::Client::builder()
+            .timeout(timeout)
+            .build()
+        {
+            Ok(client) => client,
+            Err(e) => {
+                log::error!("Cannot create the webhooks client: {e}");
+                return;
+            }
+        };
+        loop {
+            match receiver.recv_timeout(Duration::from_millis(100)) {
+                Ok(event) => {
+                    for url in urls.iter() {
+                        deliver(&client, url, &event, retries, &is_running);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    if !is_running.load(Ordering::Relaxed) {
+                        break;
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    })
+}
+
+fn deliver(
+    client: &reqwest::blocking::Client,
+    url: &str,
+    event: &Event,
+    retries: u32,
+    is_running: &AtomicBool,
+) {
+    for attempt in 0..=retries {
+        match client.post(url).json(event).send() {
+            Ok(response) if response.status().is_success() => return,
+            Ok(response) => log::warn!(
+                "Webhook {url} replied {} to event {}",
+                response.status(),
+                event.id
+            ),
+            Err(e) => log::warn!("Webhook {url} failed for event {}: {e}", event.id),
+        }
+        if attempt == retries {
+            break;
+        }
+
+        // Exponential backoff, checking the stop signal while waiting
+        let wait = Duration::from_secs(1 << attempt.min(6));
+        let start = Instant::now();
+        while start.elapsed() < wait {
+            if !is_running.load(Ordering::Relaxed) {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+    log::error!("Giving up delivering event {} to webhook {url}", event.id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tx(txid: &str, height: Option<u32>) -> response::Tx {
+        response::Tx {
+            txid: txid.to_string(),
+            height,
+            timestamp: None,
+            balance: HashMap::new(),
+            fee: 0,
+            type_: "incoming".to_string(),
+            unblinded_url: "".to_string(),
+            memo: "".to_string(),
+        }
+    }
+
+    fn snapshot(txs: &[response::Tx], lbtc: i64, tip: u32) -> WolletSnapshot {
+        WolletSnapshot {
+            txs: txs.iter().map(|t| (t.txid.clone(), t.clone())).collect(),
+            balance: [("lbtc".to_string(), lbtc)].into_iter().collect(),
+            tip,
+        }
+    }
+
+    fn kinds(events: &[Event]) -> Vec<EventKind> {
+        events.iter().map(|e| e.kind).collect()
+    }
+
+    #[test]
+    fn events_diff() {
+        let mut events = Events::new(None);
+        let s0 = snapshot(&[], 0, 100);
+        let s1 = snapshot(&[tx("a", None)], 10, 100);
+        events.push_diff("w", &s0, &s1);
+        assert_eq!(
+            kinds(&events.after(0)),
+            vec![EventKind::NewTx, EventKind::BalanceChanged]
+        );
+        assert_eq!(events.last_id(), 2);
+
+        let s2 = snapshot(&[tx("a", Some(101))], 10, 101);
+        events.push_diff("w", &s1, &s2);
+        let new = events.after(2);
+        assert_eq!(kinds(&new), vec![EventKind::NewTip, EventKind::TxConfirmed]);
+        assert_eq!(new[0].height, Some(101));
+
+        let s3 = snapshot(&[tx("a", None), tx("b", None)], 15, 101);
+        events.push_diff("w", &s2, &s3);
+        assert_eq!(
+            kinds(&events.after(4)),
+            vec![
+                EventKind::TxReorged,
+                EventKind::NewTx,
+                EventKind::BalanceChanged
+            ]
+        );
+
+        let s4 = snapshot(&[tx("a", None)], 10, 101);
+        events.push_diff("w", &s3, &s4);
+        let new = events.after(7);
+        assert_eq!(
+            kinds(&new),
+            vec![EventKind::TxDropped, EventKind::BalanceChanged]
+        );
+        assert_eq!(new[0].tx.as_ref().unwrap().txid, "b");
+
+        events.push_diff("w", &s4, &s4);
+        assert_eq!(events.last_id(), 9);
+    }
+
+    #[test]
+    fn events_max() {
+        let mut events = Events::new(None);
+        for i in 0..consts::MAX_EVENTS + 10 {
+            events.push("w", EventKind::NewTip, None, None, Some(i as u32));
+        }
+        let all = events.after(0);
+        assert_eq!(all.len(), consts::MAX_EVENTS);
+        assert_eq!(all[0].id, 11);
+    }
+}
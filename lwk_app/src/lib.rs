@@ -11,14 +11,15 @@
 //!
 //! All the requests and responses data model are in the [`lwk_rpc_model`] crate.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::net::SocketAddr;
 use std::num::NonZeroU8;
 use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::channel;
 use std::sync::{Arc, Mutex};
 use std::thread::{sleep, JoinHandle};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use lwk_common::{
     address_to_text_qr, address_to_uri_qr, keyorigin_xpub_from_str, multisig_desc, singlesig_desc,
@@ -30,48 +31,60 @@ use lwk_jade::get_receive_address::Variant;
 use lwk_jade::register_multisig::{JadeDescriptor, RegisterMultisigParams};
 use lwk_jade::Jade;
 use lwk_signer::{AnySigner, SwSigner};
-use lwk_tiny_jrpc::{tiny_http, JsonRpcServer, Request, Response};
-use lwk_wollet::bitcoin::bip32::Fingerprint;
+use lwk_tiny_jrpc::{JsonRpcServer, Request, Response};
+use lwk_wollet::bitcoin::bip32::{Fingerprint, KeySource};
 use lwk_wollet::bitcoin::XKeyIdentifier;
 use lwk_wollet::elements::encode::serialize;
 use lwk_wollet::elements::hex::{FromHex, ToHex};
 use lwk_wollet::elements::pset::PartiallySignedTransaction;
-use lwk_wollet::elements::{Address, AssetId, Txid};
+use lwk_wollet::elements::{Address, AssetId, OutPoint, Txid};
 use lwk_wollet::elements_miniscript::descriptor::{Descriptor, DescriptorType, WshInner};
 use lwk_wollet::elements_miniscript::miniscript::decode::Terminal;
 use lwk_wollet::elements_miniscript::{DescriptorPublicKey, ForEachKey};
-use lwk_wollet::Wollet;
-use lwk_wollet::{BlockchainBackend, WolletDescriptor};
-use serde_json::Value;
+use lwk_wollet::{BlockchainBackend, ElementsNetwork, WolletDescriptor};
+use lwk_wollet::{Registry, TxQuery, Wollet, WolletBackup};
 
-use crate::explorer::{get_registry_data, get_tx};
+use crate::events::{Events, WolletSnapshot};
+use crate::explorer::get_tx;
 use crate::method::Method;
 use crate::state::{AppAsset, AppSigner, State};
+use crate::store::Store;
 use lwk_rpc_model::{request, response};
 
-pub use client::Client;
-pub use config::Config;
+pub use client::{Batch, Client};
+pub use config::{Config, NetworkBackend, RpcUser, StateSecret};
 pub use error::Error;
+pub use lwk_tiny_jrpc::auth::read_cookie;
 pub use lwk_tiny_jrpc::RpcError;
 
 mod client;
 mod config;
 pub mod consts;
 mod error;
+mod events;
 mod explorer;
 pub mod method;
+pub mod offline;
+mod policy;
 mod reqwest_transport;
 mod state;
+mod store;
 
 pub struct App {
     rpc: Option<JsonRpcServer>,
     config: Config,
 
-    /// Set to false to stop the background scanning thread
+    /// Credentials used by the app itself to call the server, if authentication is required
+    app_user: Option<lwk_tiny_jrpc::User>,
+
+    /// Set to false to stop the background scanning and webhooks threads
     is_scanning: Arc<AtomicBool>,
 
     /// Handle of the scanning thread
     scanning_handle: Option<JoinHandle<()>>,
+
+    /// Handle of the thread delivering events to webhooks, if any is configured
+    webhooks_handle: Option<JoinHandle<()>>,
 }
 
 impl App {
@@ -81,22 +94,19 @@ impl App {
         Ok(App {
             rpc: None,
             config,
+            app_user: None,
             scanning_handle: None,
+            webhooks_handle: None,
             is_scanning: Arc::new(AtomicBool::new(false)),
         })
     }
 
-    fn apply_request(&self, client: &Client, line: &str) -> Result<(), Error> {
-        let r: Request = serde_json::from_str(line)?;
-        let method: Method = r.method.parse()?;
-        let _value: Value = client.make_request(method, r.params)?;
-        Ok(())
-    }
-
     pub fn run(&mut self) -> Result<(), Error> {
         if self.rpc.is_some() {
             return Err(error::Error::AlreadyStarted);
         }
+        let (webhooks_sender, webhooks_receiver) = channel();
+        let with_webhooks = !self.config.webhooks.is_empty();
         let mut state = State {
             config: self.config.clone(),
             wollets: Default::default(),
@@ -104,49 +114,78 @@ impl App {
             assets: Default::default(),
             tx_memos: Default::default(),
             addr_memos: Default::default(),
-            do_persist: false,
+            store: None,
+            policies: Default::default(),
+            signed_psets: vec![],
+            audit_store: None,
             scan_loops_started: 0,
             scan_loops_completed: 0,
             interrupt_wait: false,
+            events: Events::new(with_webhooks.then_some(webhooks_sender)),
         };
         state.insert_policy_asset();
         let state = Arc::new(Mutex::new(state));
-        let server = tiny_http::Server::http(self.config.addr)
-            .map_err(|_| Error::ServerStart(self.config.addr.to_string()))?;
 
         // TODO, for some reasons, using the default number of threads (4) cause a request to be
         // replied after 15 seconds, using 1 instead seems to not have that issue.
-        let config = lwk_tiny_jrpc::Config::builder()
+        // `wait_for_event` is served on its own thread, otherwise it would block the only worker
+        let mut config = lwk_tiny_jrpc::Config::builder()
             .with_num_threads(NonZeroU8::new(1).expect("static"))
-            .build();
-
-        let rpc = lwk_tiny_jrpc::JsonRpcServer::new(server, config, state.clone(), method_handler);
-        let path = self.config.state_path()?;
-        match std::fs::read_to_string(&path) {
-            Ok(string) => {
-                log::info!(
-                    "Loading previous state, {} elements",
-                    string.lines().count()
-                );
-
-                let client = self.client()?;
-
-                for (n, line) in string.lines().enumerate() {
-                    self.apply_request(&client, line).map_err(|err| {
-                        Error::StartStateLoad(err.to_string(), n + 1, path.display().to_string())
-                    })?
-                }
+            .with_long_running_method(Method::WaitForEvent.to_string());
+        if self.config.rpc_auth_required() {
+            // Used by the client returned by `App::client`
+            let app_user = lwk_tiny_jrpc::User::random("__app__");
+            config = config.with_user(app_user.clone());
+            self.app_user = Some(app_user);
+        }
+        if self.config.rpc_cookie {
+            config = config.with_cookie_file(Some(self.config.cookie_path()?));
+        }
+        for user in self.config.rpc_users.iter() {
+            let mut rpc_user = lwk_tiny_jrpc::User::new(&user.name, &user.password);
+            if user.read_only {
+                let methods = method::READ_ONLY_METHODS.iter().map(|m| m.to_string());
+                rpc_user = rpc_user.with_allowed_methods(methods);
             }
-            Err(_) => {
-                log::info!("There is no previous state at {path:?}");
+            config = config.with_user(rpc_user);
+        }
+        match (&self.config.rpc_tls_cert, &self.config.rpc_tls_key) {
+            (Some(cert), Some(key)) => {
+                let tls = lwk_tiny_jrpc::TlsConfig::from_pem_files(cert, key)?;
+                config = config.with_tls(Some(tls));
+            }
+            (None, None) => (),
+            _ => {
+                return Err(Error::Generic(
+                    "TLS requires both the certificate and the private key".into(),
+                ))
             }
         }
-        state.lock().map_err(|e| e.to_string())?.do_persist = true;
 
+        let mut rpc = lwk_tiny_jrpc::JsonRpcServer::bind(
+            self.config.addr,
+            config.build(),
+            state.clone(),
+            method_handler,
+        )
+        .map_err(|_| Error::ServerStart(self.config.addr.to_string()))?;
+        if let Err(e) = self.load_state(&state) {
+            rpc.stop();
+            rpc.join_threads();
+            return Err(e);
+        }
         self.rpc = Some(rpc);
+        self.write_additional_networks_files()?;
 
         // Wallets scanning thread
         self.is_scanning.store(true, Ordering::Relaxed);
+        if with_webhooks {
+            self.webhooks_handle = Some(events::spawn_webhooks(
+                webhooks_receiver,
+                &self.config,
+                self.is_scanning.clone(),
+            ));
+        }
         let is_scanning = self.is_scanning.clone();
         let state_scanning = state.clone();
         let scanning_interval = self.config.scanning_interval;
@@ -175,44 +214,76 @@ impl App {
                 let mut s = state_scanning.lock().expect("state lock poison");
                 s.interrupt_wait = false;
                 s.scan_loops_started += 1;
-                let wollets_names: Vec<_> = s.wollets.iter().map(|e| e.0.to_owned()).collect();
+                let wollets_names: Vec<_> = s
+                    .wollets
+                    .iter()
+                    .map(|(name, w)| (name.to_owned(), w.network()))
+                    .collect();
                 let config = s.config.clone();
                 (wollets_names, config)
             };
 
-            match config.electrum_client() {
-                Ok(mut electrum_client) => {
-                    for name in wollets_names {
-                        let state = match state_scanning
-                            .lock()
-                            .expect("state lock poison")
-                            .wollets
-                            .get(&name)
-                        {
-                            Ok(w) => w.state(),
-                            Err(_) => continue,
-                        };
-
-                        match electrum_client.full_scan(&state) {
-                            Ok(Some(update)) => {
-                                let mut s = state_scanning.lock().expect("state lock poison");
-                                let _ = match s.wollets.get_mut(&name) {
-                                    Ok(wollet) => wollet.apply_update(update),
-                                    Err(_) => continue,
-                                };
+            // Each network is scanned with its own backend
+            let mut disconnected = false;
+            for network in config.networks() {
+                let electrum_client = config
+                    .for_network(network)
+                    .and_then(|config| config.electrum_client());
+                let mut electrum_client = match electrum_client {
+                    Ok(electrum_client) => electrum_client,
+                    Err(_) => {
+                        log::info!(
+                            "Cannot create an electrum client for {}, are we conected? Retrying in one sec",
+                            network.as_str()
+                        );
+                        disconnected = true;
+                        continue;
+                    }
+                };
+                let names = wollets_names.iter().filter(|(_, n)| *n == network);
+                for (name, _) in names {
+                    let state = match state_scanning
+                        .lock()
+                        .expect("state lock poison")
+                        .wollets
+                        .get(name)
+                    {
+                        Ok(w) => w.state(),
+                        Err(_) => continue,
+                    };
+
+                    match electrum_client.full_scan(&state) {
+                        Ok(Some(update)) => {
+                            let mut s = state_scanning.lock().expect("state lock poison");
+                            let explorer_url = match s.wollet_config(name) {
+                                Ok(config) => config.explorer_url,
+                                Err(_) => continue,
+                            };
+                            let memos = s.tx_memos.for_wollet(name);
+                            let wollet = match s.wollets.get_mut(name) {
+                                Ok(wollet) => wollet,
+                                Err(_) => continue,
+                            };
+                            // Do not notify the whole history on the first scan
+                            let before = (!wollet.never_scanned())
+                                .then(|| WolletSnapshot::new(wollet, &explorer_url, &memos));
+                            let _ = wollet.apply_update(update);
+                            if let Some(Ok(before)) = before {
+                                if let Ok(after) =
+                                    WolletSnapshot::new(wollet, &explorer_url, &memos)
+                                {
+                                    s.events.push_diff(name, &before, &after);
+                                }
                             }
-                            Ok(None) => (),
-                            Err(_) => continue,
                         }
+                        Ok(None) => (),
+                        Err(_) => continue,
                     }
                 }
-                Err(_) => {
-                    log::info!(
-                        "Cannot create an electrum client, are we conected? Retrying in one sec"
-                    );
-                    sleep(Duration::from_secs(1))
-                }
-            };
+            }
+            if disconnected {
+                sleep(Duration::from_secs(1))
+            }
 
             let mut s = state_scanning.lock().expect("state lock poison");
             s.scan_loops_completed += 1;
@@ -222,6 +293,46 @@ impl App {
         Ok(())
     }
 
+    /// Load the previous state from the store, migrating the legacy plaintext log if present
+    fn load_state(&self, state: &Arc<Mutex<State>>) -> Result<(), Error> {
+        let path = self.config.state_path()?;
+        let legacy_path = self.config.legacy_state_path()?;
+        if self.config.state_secret.is_none() {
+            log::warn!(
+                "The state in {path:?} is not encrypted, persisted mnemonics are in plaintext"
+            );
+        }
+        let (store, mut requests) = Store::open(&path, self.config.state_secret.as_ref())?;
+        let mut load_path = path.clone();
+        if !path.exists() && legacy_path.exists() {
+            log::info!("Migrating the previous state from {legacy_path:?}");
+            requests = store::read_legacy(&legacy_path)?;
+            load_path = legacy_path.clone();
+        }
+        log::info!("Loading previous state, {} elements", requests.len());
+        for (n, request) in requests.into_iter().enumerate() {
+            apply_request(request, state.clone()).map_err(|err| {
+                Error::StartStateLoad(err.to_string(), n + 1, load_path.display().to_string())
+            })?
+        }
+
+        let (audit_store, signed_psets) = Store::open(
+            &self.config.audit_log_path()?,
+            self.config.state_secret.as_ref(),
+        )?;
+
+        let mut s = state.lock()?;
+        s.store = Some(store);
+        s.audit_store = Some(audit_store);
+        s.signed_psets = signed_psets;
+        s.persist_all()?;
+        if load_path == legacy_path {
+            // The legacy file may contain mnemonics in plaintext, its content is now in the store
+            std::fs::remove_file(&legacy_path)?;
+        }
+        Ok(())
+    }
+
     pub fn stop(&self) -> Result<(), Error> {
         self.is_scanning.store(false, Ordering::Relaxed);
         match self.rpc.as_ref() {
@@ -252,11 +363,61 @@ impl App {
         if let Some(scanning_handle) = self.scanning_handle.take() {
             let _ = scanning_handle.join();
         }
+        if let Some(webhooks_handle) = self.webhooks_handle.take() {
+            let _ = webhooks_handle.join();
+        }
+        self.remove_additional_networks_files();
         Ok(())
     }
 
-    fn client(&self) -> Result<Client, Error> {
-        Client::new(self.config.addr)
+    /// Write the server address and the cookie in the datadir of the additional networks, so that
+    /// clients of those networks find this server
+    fn write_additional_networks_files(&self) -> Result<(), Error> {
+        // This server is now the one serving its main network
+        let _ = std::fs::remove_file(self.config.server_addr_path()?);
+        for network in self.config.networks().into_iter().skip(1) {
+            let config = self.config.for_network(network)?;
+            std::fs::write(config.server_addr_path()?, self.config.addr.to_string())?;
+            if self.config.rpc_cookie {
+                std::fs::copy(self.config.cookie_path()?, config.cookie_path()?)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn remove_additional_networks_files(&self) {
+        for network in self.config.networks().into_iter().skip(1) {
+            if let Ok(config) = self.config.for_network(network) {
+                if let Ok(path) = config.server_addr_path() {
+                    let _ = std::fs::remove_file(path);
+                }
+                if let (true, Ok(path)) = (self.config.rpc_cookie, config.cookie_path()) {
+                    let _ = std::fs::remove_file(path);
+                }
+            }
+        }
+    }
+
+    /// A client for this server, with full access if authentication is required
+    pub fn client(&self) -> Result<Client, Error> {
+        let credentials = self
+            .app_user
+            .as_ref()
+            .map(|u| (u.name().to_string(), u.password().to_string()));
+        Client::with_auth(
+            self.config.addr,
+            credentials,
+            self.config.rpc_tls_cert.as_deref(),
+        )
+    }
+}
+
+/// Apply a request loaded from the persisted state
+fn apply_request(request: Request, state: Arc<Mutex<State>>) -> Result<(), Error> {
+    let response = inner_method_handler(request, state)?;
+    match response.error {
+        Some(e) => Err(Error::Generic(e.to_string())),
+        None => Ok(()),
     }
 }
 
@@ -299,12 +460,17 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             )
         }
         Method::Version => {
-            let network = state.lock()?.config.network.as_str().to_string();
+            let config = state.lock()?.config.clone();
             Response::result(
                 request.id,
                 serde_json::to_value(response::Version {
                     version: consts::APP_VERSION.into(),
-                    network,
+                    network: config.network.as_str().to_string(),
+                    networks: config
+                        .networks()
+                        .iter()
+                        .map(|n| n.as_str().to_string())
+                        .collect(),
                 })?,
             )
         }
@@ -313,11 +479,15 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             let mut s = state.lock()?;
             // TODO recognize different name same descriptor?
 
+            let network = s.config.network_from_str(r.network.as_deref())?;
             let desc: WolletDescriptor = r.descriptor.parse()?;
-            if desc.is_mainnet() != s.config.is_mainnet() {
+            if desc.is_mainnet() != matches!(network, ElementsNetwork::Liquid) {
                 return Err(Error::Generic("Descriptor is for the wrong network".into()));
             }
-            let wollet = Wollet::with_fs_persist(s.config.network, desc, &s.config.datadir)?;
+            let mut wollet = Wollet::with_fs_persist(network, desc, &s.config.datadir)?;
+            if r.birthday_height.is_some() {
+                wollet.set_birthday(r.birthday_height)?;
+            }
             s.wollets.insert(&r.name, wollet)?;
 
             s.persist(&request)?;
@@ -397,6 +567,7 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             let r: request::SignerUnload = serde_json::from_value(params)?;
             let mut s = state.lock()?;
             let removed = s.signers.remove(&r.name)?;
+            s.policies.remove(&r.name);
             let signer: response::Signer = signer_response_from(&r.name, &removed)?;
             s.persist_all()?;
             Response::result(
@@ -535,6 +706,35 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
                 serde_json::to_value(response::WalletBalance { balance })?,
             )
         }
+        Method::WalletPortfolio => {
+            let r: request::WalletPortfolio = serde_json::from_value(params)?;
+            let s = state.lock()?;
+            let wollet = s.wollets.get(&r.name)?;
+            let contracts = s.assets.iter().filter_map(|(id, asset)| match asset {
+                AppAsset::RegistryAsset(d) => Some((id, d.contract())),
+                _ => None,
+            });
+            let assets = wollet
+                .portfolio(contracts)?
+                .into_iter()
+                .map(|a| response::PortfolioAsset {
+                    asset: a.asset.to_string(),
+                    total: a.format(a.balance.total()),
+                    ticker: a.ticker,
+                    name: a.name,
+                    precision: a.precision,
+                    confirmed: a.balance.confirmed,
+                    trusted_pending: a.balance.trusted_pending,
+                    untrusted_pending: a.balance.untrusted_pending,
+                    frozen: a.balance.frozen,
+                    issuance_txid: a.issuance.map(|i| i.txid.to_string()),
+                })
+                .collect();
+            Response::result(
+                request.id,
+                serde_json::to_value(response::WalletPortfolio { assets })?,
+            )
+        }
         Method::WalletSendMany => {
             let r: request::WalletSendMany = serde_json::from_value(params)?;
             let mut s = state.lock()?;
@@ -552,6 +752,9 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             if r.enable_ct_discount {
                 builder = builder.enable_ct_discount();
             }
+            if let Some(blocks) = r.lock_inputs {
+                builder = builder.lock_inputs(blocks);
+            }
             let mut tx = builder.finish()?;
 
             add_contracts(&mut tx, s.assets.iter());
@@ -568,12 +771,15 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             let wollet: &mut Wollet = s.wollets.get_mut(&r.name)?;
 
             let address = Address::from_str(&r.address)?;
-            let mut tx = wollet
+            let mut builder = wollet
                 .tx_builder()
                 .drain_lbtc_wallet()
                 .drain_lbtc_to(address)
-                .fee_rate(r.fee_rate)
-                .finish()?;
+                .fee_rate(r.fee_rate);
+            if let Some(blocks) = r.lock_inputs {
+                builder = builder.lock_inputs(blocks);
+            }
+            let mut tx = builder.finish()?;
 
             add_contracts(&mut tx, s.assets.iter());
             Response::result(
@@ -642,7 +848,7 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             let r: request::SignerRegisterMultisig = serde_json::from_value(params)?;
             let mut s = state.lock()?;
 
-            let network = s.config.jade_network();
+            let network = s.wollet_config(&r.wallet)?.jade_network();
             let descriptor = s.wollets.get(&r.wallet)?.descriptor().clone();
             let signer = s.get_available_signer(&r.name)?;
 
@@ -678,14 +884,46 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             let r: request::SignerSign = serde_json::from_value(params)?;
             let mut s = state.lock()?;
 
-            let signer = s.get_available_signer(&r.name)?;
-
             let mut pset =
                 PartiallySignedTransaction::from_str(&r.pset).map_err(|e| e.to_string())?;
 
-            signer.sign(&mut pset)?;
+            let timestamp = policy::now();
+            let checked = match s.policies.get(&r.name) {
+                Some(policy) => {
+                    let wollet = s.wollets.get(policy.wallet())?;
+                    let balance = policy.check(wollet, &pset, &s.signed_psets, timestamp)?;
+                    Some((policy.wallet().to_string(), balance))
+                }
+                None => None,
+            };
 
-            // TODO we may want to return other details such as if signatures have been added
+            let signer = s.get_available_signer(&r.name)?;
+            let signatures_added = signer.sign(&mut pset)?;
+
+            let txid = pset
+                .extract_tx()
+                .map_err(|e| Error::Generic(e.to_string()))?
+                .txid();
+            let (wallet, balance, fee) = match checked {
+                Some((wallet, balance)) => {
+                    let balances = balance
+                        .balances
+                        .iter()
+                        .map(|(k, v)| (k.to_string(), *v))
+                        .collect();
+                    (Some(wallet), Some(balances), Some(balance.fee))
+                }
+                None => (None, None, None),
+            };
+            s.record_signed_pset(response::SignedPset {
+                timestamp,
+                signer: r.name.clone(),
+                txid: txid.to_string(),
+                signatures_added,
+                wallet,
+                balance,
+                fee,
+            })?;
 
             Response::result(
                 request.id,
@@ -694,6 +932,36 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
                 })?,
             )
         }
+        Method::SignerSetPolicy => {
+            let r: request::SignerSetPolicy = serde_json::from_value(params)?;
+            let mut s = state.lock()?;
+            s.signers.get(&r.name)?;
+            match r.policy {
+                Some(spec) => {
+                    let policy = policy::Policy::new(spec)?;
+                    s.policies.insert(r.name, policy);
+                }
+                None => {
+                    s.policies.remove(&r.name);
+                }
+            }
+            s.persist_all()?;
+            Response::result(request.id, serde_json::to_value(response::Empty {})?)
+        }
+        Method::SignerSignedPsets => {
+            let r: request::SignerSignedPsets = serde_json::from_value(params)?;
+            let s = state.lock()?;
+            let psets = s
+                .signed_psets
+                .iter()
+                .filter(|p| r.name.as_ref().map_or(true, |n| n == &p.signer))
+                .cloned()
+                .collect();
+            Response::result(
+                request.id,
+                serde_json::to_value(response::SignedPsets { psets })?,
+            )
+        }
         Method::WalletBroadcast => {
             let r: request::WalletBroadcast = serde_json::from_value(params)?;
             let mut s = state.lock()?;
@@ -702,10 +970,22 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             let mut pset =
                 PartiallySignedTransaction::from_str(&r.pset).map_err(|e| e.to_string())?;
             let tx = wollet.finalize(&mut pset)?;
-            let electrum_client = s.config.electrum_client()?;
+            let config = s.wollet_config(&r.name)?;
+            let electrum_client = config.electrum_client()?;
 
             if !r.dry_run {
                 electrum_client.broadcast(&tx)?;
+
+                // Do not wait for the next scan to consider the inputs spent
+                let memos = s.tx_memos.for_wollet(&r.name);
+                let wollet = s.wollets.get_mut(&r.name)?;
+                let before = WolletSnapshot::new(wollet, &config.explorer_url, &memos);
+                wollet.apply_transaction(tx.clone())?;
+                if let Ok(before) = before {
+                    if let Ok(after) = WolletSnapshot::new(wollet, &config.explorer_url, &memos) {
+                        s.events.push_diff(&r.name, &before, &after);
+                    }
+                }
             }
 
             Response::result(
@@ -795,73 +1075,30 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             let pset = PartiallySignedTransaction::from_str(&r.pset).map_err(|e| e.to_string())?;
             let details = wollet.get_details(&pset)?;
             let mut warnings = vec![];
-            let has_signatures_from = details
-                .fingerprints_has()
-                .iter()
-                .map(|f| response::SignerShortDetails {
-                    name: s.signers.name_from_fingerprint(f, &mut warnings),
-                    fingerprint: f.to_string(),
-                })
-                .collect();
-            let missing_signatures_from = details
-                .fingerprints_missing()
-                .iter()
-                .map(|f| response::SignerShortDetails {
-                    name: s.signers.name_from_fingerprint(f, &mut warnings),
-                    fingerprint: f.to_string(),
-                })
-                .collect();
-            let mut balance: HashMap<String, i64> = details
-                .balance
-                .balances
-                .into_iter()
-                .map(|(k, v)| (k.to_string(), v))
-                .collect();
+            let mut details = convert_pset_details(details, |f| {
+                s.signers.name_from_fingerprint(f, &mut warnings)
+            });
             if r.with_tickers {
-                balance = s.replace_id_with_ticker(balance);
+                details.balance = s.replace_id_with_ticker(details.balance);
             }
-            let issuances = details
-                .issuances
-                .iter()
-                .enumerate()
-                .filter(|(_, e)| e.is_issuance())
-                .map(|(vin, e)| response::Issuance {
-                    asset: e.asset().expect("issuance").to_string(),
-                    token: e.token().expect("issuance").to_string(),
-                    is_confidential: e.is_confidential(),
-                    vin: vin as u32,
-                    asset_satoshi: e.asset_satoshi().unwrap_or(0),
-                    token_satoshi: e.token_satoshi().unwrap_or(0),
-                    prev_txid: e.prev_txid().expect("issuance").to_string(),
-                    prev_vout: e.prev_vout().expect("issuance"),
-                })
-                .collect();
-            let reissuances = details
-                .issuances
-                .iter()
-                .enumerate()
-                .filter(|(_, e)| e.is_reissuance())
-                .map(|(vin, e)| response::Reissuance {
-                    asset: e.asset().expect("reissuance").to_string(),
-                    token: e.token().expect("reissuance").to_string(),
-                    is_confidential: e.is_confidential(),
-                    vin: vin as u32,
-                    asset_satoshi: e.asset_satoshi().unwrap_or(0),
-                })
-                .collect();
+            details.warnings = warnings.join(", ");
 
-            Response::result(
-                request.id,
-                serde_json::to_value(response::WalletPsetDetails {
-                    has_signatures_from,
-                    missing_signatures_from,
-                    balance,
-                    fee: details.balance.fee,
-                    issuances,
-                    reissuances,
-                    warnings: warnings.join(", "),
-                })?,
-            )
+            Response::result(request.id, serde_json::to_value(details)?)
+        }
+        Method::PsetDecode => {
+            let r: request::PsetDecode = serde_json::from_value(params)?;
+            let s = state.lock()?;
+            let network = s.config.network_from_str(r.network.as_deref())?;
+            let pset = PartiallySignedTransaction::from_str(&r.pset).map_err(|e| e.to_string())?;
+            let analysis = lwk_common::pset_analysis(&pset, network.address_params());
+
+            let mut warnings = vec![];
+            let mut decode = convert_pset_analysis(analysis, |f| {
+                s.signers.name_from_fingerprint(f, &mut warnings)
+            });
+            decode.warnings = warnings.join(", ");
+
+            Response::result(request.id, serde_json::to_value(decode)?)
         }
         Method::WalletUtxos => {
             let r: request::WalletUtxos = serde_json::from_value(params)?;
@@ -876,11 +1113,31 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
         Method::WalletTxs => {
             let r: request::WalletTxs = serde_json::from_value(params)?;
             let mut s = state.lock()?;
-            let explorer_url = s.config.explorer_url.clone();
+            let explorer_url = s.wollet_config(&r.name)?.explorer_url;
             let memos = s.tx_memos.for_wollet(&r.name);
             let wollet = s.wollets.get_mut(&r.name)?;
-            let mut txs: Vec<response::Tx> = wollet
-                .transactions()?
+            let mut query = TxQuery::new().offset(r.offset);
+            if let Some(limit) = r.limit {
+                query = query.limit(limit);
+            }
+            if let Some(asset) = r.asset.as_deref() {
+                query = query.asset(AssetId::from_str(asset)?);
+            }
+            if let Some(tx_type) = r.tx_type.as_deref() {
+                query = query.tx_type(tx_type);
+            }
+            if let Some(height) = r.min_height {
+                query = query.min_height(height);
+            }
+            if let Some(height) = r.max_height {
+                query = query.max_height(height);
+            }
+            if let Some(address) = r.address.as_deref() {
+                query = query.address(&Address::from_str(address)?);
+            }
+            let page = wollet.query_transaction_summaries(&query)?;
+            let mut txs: Vec<response::Tx> = page
+                .txs
                 .iter()
                 .map(|tx| convert_tx(tx, &explorer_url, &memos))
                 .collect();
@@ -891,18 +1148,22 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             }
             Response::result(
                 request.id,
-                serde_json::to_value(response::WalletTxs { txs })?,
+                serde_json::to_value(response::WalletTxs {
+                    txs,
+                    total: page.total,
+                })?,
             )
         }
         Method::WalletTx => {
             let r: request::WalletTx = serde_json::from_value(params)?;
             let mut s = state.lock()?;
+            let esplora_api_url = s.wollet_config(&r.name)?.esplora_api_url;
             let wollet = s.wollets.get_mut(&r.name)?;
             let txid = Txid::from_str(&r.txid)?;
             let tx = if let Some(tx) = wollet.transaction(&txid)? {
-                tx.tx.clone()
+                tx.tx
             } else if r.from_explorer {
-                get_tx(&s.config.esplora_api_url, &txid)?
+                get_tx(&esplora_api_url, &txid)?
             } else {
                 return Err(Error::WalletTxNotFound(r.txid, r.name));
             };
@@ -931,11 +1192,111 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             s.persist(&request)?;
             Response::result(request.id, serde_json::to_value(response::Empty {})?)
         }
+        Method::WalletLockUtxo => {
+            let r: request::WalletLockUtxo = serde_json::from_value(params)?;
+            let s = state.lock()?;
+            let wollet = s.wollets.get(&r.name)?;
+            let outpoint = OutPoint::new(Txid::from_str(&r.txid)?, r.vout);
+            let expiry = r.blocks.map(|b| wollet.tip().height().saturating_add(b));
+            wollet.lock_utxo(outpoint, expiry)?;
+            Response::result(request.id, serde_json::to_value(response::Empty {})?)
+        }
+        Method::WalletUnlockUtxo => {
+            let r: request::WalletUnlockUtxo = serde_json::from_value(params)?;
+            let s = state.lock()?;
+            let wollet = s.wollets.get(&r.name)?;
+            let outpoint = OutPoint::new(Txid::from_str(&r.txid)?, r.vout);
+            if !wollet.unlock_utxo(&outpoint)? {
+                return Err(Error::Generic(format!("Utxo {outpoint} is not locked")));
+            }
+            Response::result(request.id, serde_json::to_value(response::Empty {})?)
+        }
+        Method::WalletLockedUtxos => {
+            let r: request::WalletLockedUtxos = serde_json::from_value(params)?;
+            let s = state.lock()?;
+            let wollet = s.wollets.get(&r.name)?;
+            let utxos = wollet
+                .locked_utxos()?
+                .into_iter()
+                .map(|l| response::LockedUtxo {
+                    txid: l.outpoint.txid.to_string(),
+                    vout: l.outpoint.vout,
+                    expiry_height: l.expiry,
+                })
+                .collect();
+            Response::result(
+                request.id,
+                serde_json::to_value(response::WalletLockedUtxos { utxos })?,
+            )
+        }
+        Method::WalletBackup => {
+            let r: request::WalletBackup = serde_json::from_value(params)?;
+            let s = state.lock()?;
+            let wollet = s.wollets.get(&r.name)?;
+            let tx_memos = s.tx_memos.for_wollet(&r.name);
+            let addr_memos = s.addr_memos.for_wollet(&r.name);
+            let labels = tx_memos
+                .into_iter()
+                .map(|(txid, memo)| (txid.to_string(), memo))
+                .chain(
+                    addr_memos
+                        .into_iter()
+                        .map(|(address, memo)| (address.to_string(), memo)),
+                )
+                .collect();
+            let backup = WolletBackup::new(wollet).with_labels(labels);
+            Response::result(
+                request.id,
+                serde_json::to_value(response::WalletBackup {
+                    backup: backup.to_string(),
+                })?,
+            )
+        }
+        Method::WalletSnapshot => {
+            let r: request::WalletSnapshot = serde_json::from_value(params)?;
+            let s = state.lock()?;
+            let wollet = s.wollets.get(&r.name)?;
+            let snapshot = offline::snapshot(wollet)?;
+            Response::result(request.id, serde_json::to_value(snapshot)?)
+        }
+        Method::WalletRestore => {
+            let r: request::WalletRestore = serde_json::from_value(params)?;
+            let mut s = state.lock()?;
+            let backup: WolletBackup = r.backup.parse()?;
+            let network = s.config.network_from_str(Some(backup.network().as_str()))?;
+            if network != backup.network() {
+                return Err(Error::Generic("Backup is for the wrong network".into()));
+            }
+            let desc = backup.descriptor().clone();
+            let mut wollet = Wollet::with_fs_persist(network, desc.clone(), &s.config.datadir)?;
+            if wollet.birthday().is_none() {
+                wollet.set_birthday(backup.birthday_height())?;
+            }
+            s.wollets.insert(&r.name, wollet)?;
+            for (key, memo) in backup.labels() {
+                if let Ok(txid) = Txid::from_str(key) {
+                    s.tx_memos.set(&r.name, &txid, memo)?;
+                } else if let Ok(address) = Address::from_str(key) {
+                    s.addr_memos.set(&r.name, &address, memo)?;
+                } else {
+                    log::warn!("Ignoring backup label for unknown reference {key}");
+                }
+            }
+            s.persist_all()?;
+
+            Response::result(
+                request.id,
+                serde_json::to_value(response::Wallet {
+                    descriptor: desc.to_string(),
+                    name: r.name,
+                })?,
+            )
+        }
         Method::WalletIssue => {
             let r: request::WalletIssue = serde_json::from_value(params)?;
             let mut s = state.lock()?;
             let wollet = s.wollets.get_mut(&r.name)?;
-            let tx = wollet
+            let mut builder = wollet
                 .tx_builder()
                 .issue_asset(
                     r.satoshi_asset,
@@ -947,7 +1308,11 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
                         .transpose()?,
                 )?
                 .fee_rate(r.fee_rate)
-                .finish()?;
+                .blind_issuance(r.blind_issuance);
+            if let Some(blocks) = r.lock_inputs {
+                builder = builder.lock_inputs(blocks);
+            }
+            let tx = builder.finish()?;
             Response::result(
                 request.id,
                 serde_json::to_value(response::Pset {
@@ -962,7 +1327,7 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             let issuance_tx = s.get_issuance_tx(&asset_id);
             let wollet = s.wollets.get_mut(&r.name)?;
 
-            let mut pset = wollet
+            let mut builder = wollet
                 .tx_builder()
                 .reissue_asset(
                     asset_id,
@@ -971,7 +1336,11 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
                     issuance_tx,
                 )?
                 .fee_rate(r.fee_rate)
-                .finish()?;
+                .blind_issuance(r.blind_issuance);
+            if let Some(blocks) = r.lock_inputs {
+                builder = builder.lock_inputs(blocks);
+            }
+            let mut pset = builder.finish()?;
 
             add_contracts(&mut pset, s.assets.iter());
             Response::result(
@@ -987,11 +1356,14 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             let asset_id = AssetId::from_str(&r.asset)?;
             let wollet = s.wollets.get_mut(&r.name)?;
 
-            let mut pset = wollet
+            let mut builder = wollet
                 .tx_builder()
                 .add_burn(r.satoshi_asset, asset_id)?
-                .fee_rate(r.fee_rate)
-                .finish()?;
+                .fee_rate(r.fee_rate);
+            if let Some(blocks) = r.lock_inputs {
+                builder = builder.lock_inputs(blocks);
+            }
+            let mut pset = builder.finish()?;
 
             add_contracts(&mut pset, s.assets.iter());
             Response::result(
@@ -1010,6 +1382,7 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
                 precision: r.precision,
                 ticker: r.ticker,
                 version: r.version,
+                extra: Default::default(),
             };
             c.validate()?; // TODO: validation should be done at Contract creation
 
@@ -1074,9 +1447,22 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
             if s.get_asset(&asset_id).is_ok() {
                 return Err(Error::AssetAlreadyInserted(r.asset_id));
             }
-            let registry_data = get_registry_data(&s.config.registry_url, &asset_id)?;
-            let txid = Txid::from_str(&registry_data.issuance_txin.txid)?;
-            let issuance_tx = get_tx(&s.config.esplora_api_url, &txid)?;
+            let network = s.config.network_from_str(r.network.as_deref())?;
+            let config = s.config.for_network(network)?;
+            // Domain proofs can't be served for regtest assets
+            let is_regtest = matches!(network, ElementsNetwork::ElementsRegtest { .. });
+            let registry_data = Registry::new(&config.registry_url)
+                .verify_domain(!is_regtest)
+                .fetch(&asset_id)?;
+            let txid = registry_data.issuance_txin.txid;
+            let issuance_tx = get_tx(&config.esplora_api_url, &txid)?;
+            let txin = issuance_tx
+                .input
+                .get(registry_data.issuance_txin.vin as usize)
+                .ok_or_else(|| Error::Generic("registry issuance input not found".into()))?;
+            if lwk_wollet::asset_ids(txin, &registry_data.contract)?.0 != asset_id {
+                return Err(lwk_wollet::Error::ContractDoesNotCommitToAssetId.into());
+            }
             s.insert_asset(asset_id, issuance_tx, registry_data.contract)?;
             // convert the request to an AssetInsert to skip network calls
             let asset_insert_request = s.get_asset(&asset_id)?.request().expect("asset");
@@ -1124,22 +1510,35 @@ fn inner_method_handler(request: Request, state: Arc<Mutex<State>>) -> Result<Re
         Method::Stop => {
             return Err(Error::Stop);
         }
+        Method::WaitForEvent => {
+            let r: request::WaitForEvent = serde_json::from_value(params)?;
+            let events = wait_for_event(&state, r.after, r.timeout.map(Duration::from_secs))?;
+            Response::result(
+                request.id,
+                serde_json::to_value(response::Events { events })?,
+            )
+        }
         Method::AssetPublish => {
             let r: request::AssetPublish = serde_json::from_value(params)?;
             let asset_id =
                 AssetId::from_str(&r.asset_id).map_err(|e| Error::Generic(e.to_string()))?;
             let s = state.lock()?;
+            let network = s.config.network_from_str(r.network.as_deref())?;
+            let config = s.config.for_network(network)?;
             let asset = s.get_asset(&asset_id)?;
             if let AppAsset::RegistryAsset(asset) = asset {
                 let client = reqwest::blocking::Client::new();
-                let url = &s.config.registry_url;
+                let url = &config.registry_url;
                 let contract = asset.contract();
                 let data = serde_json::json!({"asset_id": asset_id, "contract": contract});
                 log::debug!("posting {data:?} as json to {url} ");
                 let response = client.post(url).json(&data).send()?;
                 let mut result = response.text()?;
-                if result.contains("failed verifying linked entity") {
-                    let domain = contract.entity.domain();
+                if let Some(domain) = contract
+                    .entity
+                    .domain()
+                    .filter(|_| result.contains("failed verifying linked entity"))
+                {
                     result = format!("https://{domain}/.well-known/liquid-asset-proof-{asset_id} must contain the following 'Authorize linking the domain name {domain} to the Liquid asset {asset_id}'");
                 }
                 Response::result(
@@ -1179,6 +1578,30 @@ fn scan(state: &Arc<Mutex<State>>) -> Result<(), Error> {
     Ok(())
 }
 
+fn wait_for_event(
+    state: &Arc<Mutex<State>>,
+    after: Option<u64>,
+    timeout: Option<Duration>,
+) -> Result<Vec<response::Event>, Error> {
+    let (after, timeout) = {
+        let s = state.lock()?;
+        let after = after.unwrap_or_else(|| s.events.last_id());
+        // Do not wait more than the client is willing to
+        let timeout = timeout
+            .unwrap_or(consts::WAIT_FOR_EVENT_TIMEOUT)
+            .min(s.config.timeout);
+        (after, timeout)
+    };
+    let start = Instant::now();
+    loop {
+        let events = state.lock()?.events.after(after);
+        if !events.is_empty() || start.elapsed() >= timeout {
+            return Ok(events);
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
 fn unvalidated_addressee(a: request::UnvalidatedAddressee) -> lwk_wollet::UnvalidatedRecipient {
     lwk_wollet::UnvalidatedRecipient {
         satoshi: a.satoshi,
@@ -1223,6 +1646,40 @@ fn add_contracts<'a>(
     }
 }
 
+/// Split the issuances of the inputs in issuances and reissuances
+fn convert_issuances(
+    issuances: &[lwk_common::Issuance],
+) -> (Vec<response::Issuance>, Vec<response::Reissuance>) {
+    let new_issuances = issuances
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.is_issuance())
+        .map(|(vin, e)| response::Issuance {
+            asset: e.asset().expect("issuance").to_string(),
+            token: e.token().expect("issuance").to_string(),
+            is_confidential: e.is_confidential(),
+            vin: vin as u32,
+            asset_satoshi: e.asset_satoshi().unwrap_or(0),
+            token_satoshi: e.token_satoshi().unwrap_or(0),
+            prev_txid: e.prev_txid().expect("issuance").to_string(),
+            prev_vout: e.prev_vout().expect("issuance"),
+        })
+        .collect();
+    let reissuances = issuances
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.is_reissuance())
+        .map(|(vin, e)| response::Reissuance {
+            asset: e.asset().expect("reissuance").to_string(),
+            token: e.token().expect("reissuance").to_string(),
+            is_confidential: e.is_confidential(),
+            vin: vin as u32,
+            asset_satoshi: e.asset_satoshi().unwrap_or(0),
+        })
+        .collect();
+    (new_issuances, reissuances)
+}
+
 fn convert_utxo(u: &lwk_wollet::WalletTxOut) -> response::Utxo {
     response::Utxo {
         txid: u.outpoint.txid.to_string(),
@@ -1235,7 +1692,7 @@ fn convert_utxo(u: &lwk_wollet::WalletTxOut) -> response::Utxo {
 }
 
 fn convert_tx(
-    tx: &lwk_wollet::WalletTx,
+    tx: &lwk_wollet::WalletTxSummary,
     explorer_url: &str,
     memos: &HashMap<Txid, String>,
 ) -> response::Tx {
@@ -1257,10 +1714,107 @@ fn convert_tx(
     }
 }
 
+/// Convert the details of a PSET, signers are named with `name`
+///
+/// Balance has asset ids and warnings are empty, the caller adds them if needed.
+fn convert_pset_details(
+    details: lwk_common::PsetDetails,
+    mut name: impl FnMut(&Fingerprint) -> Option<String>,
+) -> response::WalletPsetDetails {
+    let mut signers = |fingerprints: BTreeSet<Fingerprint>| -> Vec<_> {
+        fingerprints
+            .iter()
+            .map(|f| response::SignerShortDetails {
+                name: name(f),
+                fingerprint: f.to_string(),
+            })
+            .collect()
+    };
+    let has_signatures_from = signers(details.fingerprints_has());
+    let missing_signatures_from = signers(details.fingerprints_missing());
+    let balance = details
+        .balance
+        .balances
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+    let (issuances, reissuances) = convert_issuances(&details.issuances);
+    response::WalletPsetDetails {
+        has_signatures_from,
+        missing_signatures_from,
+        balance,
+        fee: details.balance.fee,
+        issuances,
+        reissuances,
+        warnings: String::new(),
+    }
+}
+
+/// Convert a PSET decoded without a wallet, signers are named with `name`
+///
+/// Warnings are empty, the caller adds them if needed.
+fn convert_pset_analysis(
+    analysis: lwk_common::PsetAnalysis,
+    mut name: impl FnMut(&Fingerprint) -> Option<String>,
+) -> response::PsetDecode {
+    let mut signers = |keys: &[(_, KeySource)]| -> Vec<_> {
+        keys.iter()
+            .map(|(_, (f, _))| response::SignerShortDetails {
+                name: name(f),
+                fingerprint: f.to_string(),
+            })
+            .collect()
+    };
+    let inputs = analysis
+        .inputs
+        .iter()
+        .map(|i| response::PsetDecodeInput {
+            txid: i.previous_outpoint.txid.to_string(),
+            vout: i.previous_outpoint.vout,
+            script_pubkey: i.script_pubkey.as_ref().map(|s| s.to_hex()),
+            script_type: i.script_type.map(|t| t.to_string()),
+            address: i.address.as_ref().map(|a| a.to_string()),
+            blinded: i.blinded,
+            asset: i.asset.map(|a| a.to_string()),
+            satoshi: i.satoshi,
+            sighash: i.sighash.map(|h| h.to_string()),
+            has_signatures_from: signers(&i.signatures.has_signature),
+            missing_signatures_from: signers(&i.signatures.missing_signature),
+            finalized: i.finalized,
+        })
+        .collect();
+    let outputs = analysis
+        .outputs
+        .iter()
+        .map(|o| response::PsetDecodeOutput {
+            script_pubkey: o.script_pubkey.to_hex(),
+            script_type: o.script_type.to_string(),
+            address: o.address.as_ref().map(|a| a.to_string()),
+            blinded: o.blinded,
+            asset: o.asset.map(|a| a.to_string()),
+            satoshi: o.satoshi,
+        })
+        .collect();
+    let issuances: Vec<_> = analysis.inputs.into_iter().map(|i| i.issuance).collect();
+    let (issuances, reissuances) = convert_issuances(&issuances);
+    response::PsetDecode {
+        inputs,
+        outputs,
+        fee: analysis.fee,
+        vsize: analysis.vsize,
+        fee_rate: analysis.fee_rate,
+        issuances,
+        reissuances,
+        warnings: String::new(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::net::TcpListener;
 
+    use serde_json::{json, Value};
+
     use super::*;
 
     fn app_random_port() -> App {
@@ -1293,4 +1847,269 @@ mod tests {
         app.stop().unwrap();
         app.join_threads().unwrap();
     }
+
+    #[test]
+    fn batch() {
+        let mut app = app_random_port();
+        let client = app.client().unwrap();
+
+        let mut batch = Batch::new();
+        batch.add(Method::Version, None::<Value>).unwrap();
+        let req = request::WalletDetails {
+            name: "missing".to_string(),
+        };
+        batch.add(Method::WalletDetails, Some(req)).unwrap();
+        batch.add(Method::SignerList, None::<Value>).unwrap();
+        let results = client.send_batch::<Value>(&batch).unwrap();
+        assert_eq!(results.len(), 3);
+        let version: response::Version =
+            serde_json::from_value(results[0].as_ref().unwrap().clone()).unwrap();
+        assert_eq!(version.version, consts::APP_VERSION);
+        assert!(matches!(results[1], Err(Error::RpcError(_))));
+        assert!(results[2].is_ok());
+
+        let balances = client
+            .wallet_balances(vec!["a".to_string(), "b".to_string()], false)
+            .unwrap();
+        assert_eq!(balances.len(), 2);
+        assert!(balances.iter().all(|b| b.is_err()));
+
+        app.stop().unwrap();
+        app.join_threads().unwrap();
+    }
+
+    #[test]
+    fn read_only_user() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut config = Config::default_testnet(tempdir.path().to_path_buf());
+        config.addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        config.rpc_users = vec![RpcUser {
+            name: "ro".to_string(),
+            password: "pass".to_string(),
+            read_only: true,
+        }];
+        let mut app = App::new(config).unwrap();
+        app.run().unwrap();
+        let url = app.addr().to_string();
+        let client =
+            jsonrpc::Client::simple_http(&url, Some("ro".into()), Some("pass".into())).unwrap();
+
+        let request = client.build_request("version", None);
+        assert!(client.send_request(request).unwrap().result.is_some());
+
+        // Methods changing the state or disclosing the wallets are forbidden
+        for method in [
+            Method::Scan,
+            Method::WalletSendMany,
+            Method::WalletDrain,
+            Method::WalletIssue,
+            Method::WalletReissue,
+            Method::WalletBurn,
+            Method::WalletBackup,
+            Method::WalletSnapshot,
+        ] {
+            let method = method.to_string();
+            let request = client.build_request(&method, None);
+            let error = client.send_request(request).unwrap().error.unwrap();
+            assert_eq!(
+                error.message,
+                format!("Method '{method}' is not allowed for this user")
+            );
+        }
+
+        app.stop().unwrap();
+        app.join_threads().unwrap();
+    }
+
+    fn run_app(datadir: &std::path::Path, secret: Option<&str>) -> Result<App, Error> {
+        let mut config = Config::default_testnet(datadir.to_path_buf());
+        config.addr = TcpListener::bind("127.0.0.1:0")?.local_addr()?;
+        config.state_secret = secret.map(|s| StateSecret::Passphrase(s.to_string()));
+        let mut app = App::new(config)?;
+        app.run()?;
+        Ok(app)
+    }
+
+    fn stop_app(mut app: App) {
+        app.stop().unwrap();
+        app.join_threads().unwrap();
+    }
+
+    #[test]
+    fn encrypted_state() {
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let tempdir = tempfile::tempdir().unwrap();
+        let datadir = tempdir.path();
+
+        // the legacy plaintext state is migrated
+        let legacy = Config::default_testnet(datadir.to_path_buf())
+            .legacy_state_path()
+            .unwrap();
+        let request = json!({"jsonrpc": "2.0", "method": "signer_load_software", "params": {"name": "s1", "mnemonic": mnemonic, "persist": true}});
+        std::fs::write(&legacy, format!("{request}\n")).unwrap();
+
+        let app = run_app(datadir, Some("pass")).unwrap();
+        assert!(!legacy.exists());
+        let client = app.client().unwrap();
+        assert_eq!(client.signer_list().unwrap().signers.len(), 1);
+        client.signer_unload("s1".to_string()).unwrap();
+        client
+            .signer_load_software("s2".to_string(), mnemonic.to_string(), true)
+            .unwrap();
+        let path = app.config.state_path().unwrap();
+        stop_app(app);
+
+        let content = std::fs::read_to_string(path).unwrap();
+        assert!(!content.contains("abandon"));
+
+        let app = run_app(datadir, Some("pass")).unwrap();
+        let signers = app.client().unwrap().signer_list().unwrap().signers;
+        assert_eq!(signers.len(), 1);
+        assert_eq!(signers[0].name, "s2");
+        stop_app(app);
+
+        assert!(matches!(
+            run_app(datadir, Some("wrong")),
+            Err(Error::StateDecrypt)
+        ));
+        assert!(matches!(run_app(datadir, None), Err(Error::StateEncrypted)));
+    }
+
+    #[test]
+    fn spending_policy() {
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let tempdir = tempfile::tempdir().unwrap();
+        let datadir = tempdir.path();
+        let pset = PartiallySignedTransaction::new_v2().to_string();
+        let policy = request::SpendingPolicy {
+            wallet: "w".to_string(),
+            daily_limits: HashMap::new(),
+            allowed_addresses: vec![],
+            max_fee_rate: Some(100.0),
+            allowed_assets: vec![],
+            time_windows: vec![],
+        };
+
+        let app = run_app(datadir, Some("pass")).unwrap();
+        let client = app.client().unwrap();
+        let err = client
+            .signer_set_policy("s".to_string(), Some(policy.clone()))
+            .unwrap_err();
+        assert!(err.to_string().contains("does not exist"));
+        client
+            .signer_load_software("s".to_string(), mnemonic.to_string(), true)
+            .unwrap();
+        let mut invalid = policy.clone();
+        invalid.allowed_assets.push("invalid".to_string());
+        assert!(client
+            .signer_set_policy("s".to_string(), Some(invalid))
+            .is_err());
+        client
+            .signer_set_policy("s".to_string(), Some(policy))
+            .unwrap();
+        stop_app(app);
+
+        // the policy is persisted and checked before signing
+        let app = run_app(datadir, Some("pass")).unwrap();
+        let client = app.client().unwrap();
+        let err = client
+            .signer_sign("s".to_string(), pset.clone())
+            .unwrap_err();
+        assert!(err.to_string().contains("Wallet 'w' does not exist"));
+        assert!(client.signer_signed_psets(None).unwrap().psets.is_empty());
+
+        client.signer_set_policy("s".to_string(), None).unwrap();
+        client.signer_sign("s".to_string(), pset).unwrap();
+        stop_app(app);
+
+        // the audit log is persisted
+        let app = run_app(datadir, Some("pass")).unwrap();
+        let client = app.client().unwrap();
+        let psets = client.signer_signed_psets(None).unwrap().psets;
+        assert_eq!(psets.len(), 1);
+        assert_eq!(psets[0].signer, "s");
+        assert_eq!(psets[0].signatures_added, 0);
+        assert!(psets[0].wallet.is_none());
+        let others = client.signer_signed_psets(Some("other".to_string()));
+        assert!(others.unwrap().psets.is_empty());
+        stop_app(app);
+
+        let path = Config::default_testnet(datadir.to_path_buf())
+            .audit_log_path()
+            .unwrap();
+        let content = std::fs::read_to_string(path).unwrap();
+        assert!(!content.contains("signatures_added"));
+    }
+
+    #[test]
+    fn multiple_networks() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let datadir = tempdir.path().to_path_buf();
+        let mut config = Config::default_testnet(datadir.clone());
+        config.addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        config
+            .add_network(&Config::default_mainnet(datadir.clone()))
+            .unwrap();
+        assert!(config
+            .add_network(&Config::default_mainnet(datadir))
+            .is_err());
+
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let desc = |is_mainnet| {
+            let signer = SwSigner::new(mnemonic, is_mainnet).unwrap();
+            let blinding = lwk_common::DescriptorBlindingKey::Slip77;
+            singlesig_desc(&signer, lwk_common::Singlesig::Wpkh, blinding, is_mainnet).unwrap()
+        };
+
+        let mut app = App::new(config.clone()).unwrap();
+        app.run().unwrap();
+        let client = app.client().unwrap();
+        let version = client.version().unwrap();
+        assert_eq!(version.network, "liquid-testnet");
+        assert_eq!(version.networks, vec!["liquid-testnet", "liquid"]);
+        let server_addr_path = config.for_network(ElementsNetwork::Liquid).unwrap();
+        let server_addr_path = server_addr_path.server_addr_path().unwrap();
+        let server_addr = std::fs::read_to_string(&server_addr_path).unwrap();
+        assert_eq!(server_addr, config.addr.to_string());
+        assert!(!config.server_addr_path().unwrap().exists());
+
+        client
+            .wallet_load(desc(false), "t".into(), None, None)
+            .unwrap();
+        let main = Some("liquid".to_string());
+        client
+            .wallet_load(desc(true), "m".into(), main, None)
+            .unwrap();
+        let err = client
+            .wallet_load(desc(true), "x".into(), None, None)
+            .unwrap_err();
+        assert!(err.to_string().contains("wrong network"));
+        let regtest = Some("liquid-regtest".to_string());
+        let err = client
+            .wallet_load(desc(false), "r".into(), regtest, None)
+            .unwrap_err();
+        assert!(err.to_string().contains("not served"));
+
+        let address = client.wallet_address("m".into(), Some(0), None, false, None);
+        assert!(address.unwrap().address.starts_with("lq1"));
+        let address = client.wallet_address("t".into(), Some(0), None, false, None);
+        assert!(address.unwrap().address.starts_with("tlq1"));
+        stop_app(app);
+        assert!(!server_addr_path.exists());
+
+        // wallets are reloaded on their network
+        let mut app = App::new(config).unwrap();
+        app.run().unwrap();
+        let client = app.client().unwrap();
+        assert_eq!(client.wallet_list().unwrap().wallets.len(), 2);
+        let address = client.wallet_address("m".into(), Some(0), None, false, None);
+        assert!(address.unwrap().address.starts_with("lq1"));
+        stop_app(app);
+    }
 }
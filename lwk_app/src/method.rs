@@ -20,12 +20,14 @@ pub enum Method {
     Version,
     Scan,
     Stop,
+    WaitForEvent,
     WalletLoad,
     WalletUnload,
     WalletList,
     WalletDetails,
     WalletAddress,
     WalletBalance,
+    WalletPortfolio,
     WalletUtxos,
     WalletTxs,
     WalletTx,
@@ -40,6 +42,12 @@ pub enum Method {
     WalletMultisigDescriptor,
     WalletSetTxMemo,
     WalletSetAddrMemo,
+    WalletLockUtxo,
+    WalletUnlockUtxo,
+    WalletLockedUtxos,
+    WalletBackup,
+    WalletRestore,
+    WalletSnapshot,
     SignerGenerate,
     SignerJadeId,
     SignerLoadSoftware,
@@ -50,6 +58,8 @@ pub enum Method {
     SignerDetails,
     SignerXpub,
     SignerSign,
+    SignerSetPolicy,
+    SignerSignedPsets,
     SignerSinglesigDescriptor,
     SignerRegisterMultisig,
     AssetContract,
@@ -59,7 +69,35 @@ pub enum Method {
     AssetDetails,
     AssetFromExplorer,
     AssetPublish,
+    PsetDecode,
 }
+/// Methods that neither access secret material nor change the server state
+///
+/// They are the only ones allowed to read-only RPC users.
+pub const READ_ONLY_METHODS: &[Method] = &[
+    Method::Schema,
+    Method::Version,
+    Method::WaitForEvent,
+    Method::WalletList,
+    Method::WalletDetails,
+    Method::WalletAddress,
+    Method::WalletBalance,
+    Method::WalletPortfolio,
+    Method::WalletUtxos,
+    Method::WalletTxs,
+    Method::WalletTx,
+    Method::WalletCombine,
+    Method::WalletPsetDetails,
+    Method::WalletMultisigDescriptor,
+    Method::WalletLockedUtxos,
+    Method::SignerList,
+    Method::SignerSignedPsets,
+    Method::AssetContract,
+    Method::AssetList,
+    Method::AssetDetails,
+    Method::PsetDecode,
+];
+
 impl Method {
     pub(crate) fn schema(&self, direction: request::Direction) -> Result<Value, serde_json::Error> {
         serde_json::to_value(match direction {
@@ -68,12 +106,14 @@ impl Method {
                 Method::Version => schema_for!(request::Empty),
                 Method::Scan => schema_for!(request::Empty),
                 Method::Stop => schema_for!(request::Empty),
+                Method::WaitForEvent => schema_for!(request::WaitForEvent),
                 Method::WalletLoad => schema_for!(request::WalletLoad),
                 Method::WalletUnload => schema_for!(request::WalletUnload),
                 Method::WalletList => schema_for!(request::Empty),
                 Method::WalletDetails => schema_for!(request::WalletDetails),
                 Method::WalletAddress => schema_for!(request::WalletAddress),
                 Method::WalletBalance => schema_for!(request::WalletBalance),
+                Method::WalletPortfolio => schema_for!(request::WalletPortfolio),
                 Method::WalletUtxos => schema_for!(request::WalletUtxos),
                 Method::WalletTxs => schema_for!(request::WalletTxs),
                 Method::WalletTx => schema_for!(request::WalletTx),
@@ -88,6 +128,12 @@ impl Method {
                 Method::WalletMultisigDescriptor => schema_for!(request::WalletMultisigDescriptor),
                 Method::WalletSetTxMemo => schema_for!(request::WalletSetTxMemo),
                 Method::WalletSetAddrMemo => schema_for!(request::WalletSetAddrMemo),
+                Method::WalletLockUtxo => schema_for!(request::WalletLockUtxo),
+                Method::WalletUnlockUtxo => schema_for!(request::WalletUnlockUtxo),
+                Method::WalletLockedUtxos => schema_for!(request::WalletLockedUtxos),
+                Method::WalletBackup => schema_for!(request::WalletBackup),
+                Method::WalletSnapshot => schema_for!(request::WalletSnapshot),
+                Method::WalletRestore => schema_for!(request::WalletRestore),
                 Method::SignerGenerate => schema_for!(request::Empty),
                 Method::SignerJadeId => schema_for!(request::Empty),
                 Method::SignerLoadSoftware => schema_for!(request::SignerLoadSoftware),
@@ -98,6 +144,8 @@ impl Method {
                 Method::SignerDetails => schema_for!(request::SignerDetails),
                 Method::SignerXpub => schema_for!(request::SignerXpub),
                 Method::SignerSign => schema_for!(request::SignerSign),
+                Method::SignerSetPolicy => schema_for!(request::SignerSetPolicy),
+                Method::SignerSignedPsets => schema_for!(request::SignerSignedPsets),
                 Method::SignerSinglesigDescriptor => {
                     schema_for!(request::SignerSinglesigDescriptor)
                 }
@@ -109,18 +157,21 @@ impl Method {
                 Method::AssetDetails => schema_for!(request::AssetDetails),
                 Method::AssetFromExplorer => schema_for!(request::AssetFromExplorer),
                 Method::AssetPublish => schema_for!(request::AssetPublish),
+                Method::PsetDecode => schema_for!(request::PsetDecode),
             },
             Direction::Response => match self {
                 Method::Schema => return serde_json::from_str(include_str!("../schema.json")),
                 Method::Version => schema_for!(response::Version),
                 Method::Scan => schema_for!(response::Empty),
                 Method::Stop => schema_for!(request::Empty),
+                Method::WaitForEvent => schema_for!(response::Events),
                 Method::WalletLoad => schema_for!(response::Wallet),
                 Method::WalletUnload => schema_for!(response::WalletUnload),
                 Method::WalletList => schema_for!(response::WalletList),
                 Method::WalletDetails => schema_for!(response::WalletDetails),
                 Method::WalletAddress => schema_for!(response::WalletAddress),
                 Method::WalletBalance => schema_for!(response::WalletBalance),
+                Method::WalletPortfolio => schema_for!(response::WalletPortfolio),
                 Method::WalletUtxos => schema_for!(response::WalletUtxos),
                 Method::WalletTxs => schema_for!(response::WalletTxs),
                 Method::WalletTx => schema_for!(response::WalletTx),
@@ -135,6 +186,12 @@ impl Method {
                 Method::WalletMultisigDescriptor => schema_for!(response::WalletMultisigDescriptor),
                 Method::WalletSetTxMemo => schema_for!(response::Empty),
                 Method::WalletSetAddrMemo => schema_for!(response::Empty),
+                Method::WalletLockUtxo => schema_for!(response::Empty),
+                Method::WalletUnlockUtxo => schema_for!(response::Empty),
+                Method::WalletLockedUtxos => schema_for!(response::WalletLockedUtxos),
+                Method::WalletBackup => schema_for!(response::WalletBackup),
+                Method::WalletSnapshot => schema_for!(response::WalletSnapshot),
+                Method::WalletRestore => schema_for!(response::Wallet),
                 Method::SignerGenerate => schema_for!(response::SignerGenerate),
                 Method::SignerJadeId => schema_for!(response::JadeId),
                 Method::SignerLoadSoftware => schema_for!(response::Signer),
@@ -145,6 +202,8 @@ impl Method {
                 Method::SignerDetails => schema_for!(response::SignerDetails),
                 Method::SignerXpub => schema_for!(response::SignerXpub),
                 Method::SignerSign => schema_for!(response::Pset),
+                Method::SignerSetPolicy => schema_for!(response::Empty),
+                Method::SignerSignedPsets => schema_for!(response::SignedPsets),
                 Method::SignerSinglesigDescriptor => {
                     schema_for!(response::SignerSinglesigDescriptor)
                 }
@@ -156,6 +215,7 @@ impl Method {
                 Method::AssetDetails => schema_for!(response::AssetDetails),
                 Method::AssetFromExplorer => schema_for!(request::Empty),
                 Method::AssetPublish => schema_for!(response::AssetPublish),
+                Method::PsetDecode => schema_for!(response::PsetDecode),
             },
         })
     }
@@ -170,12 +230,14 @@ impl FromStr for Method {
             "version" => Method::Version,
             "scan" => Method::Scan,
             "stop" => Method::Stop,
+            "wait_for_event" => Method::WaitForEvent,
             "wallet_load" => Method::WalletLoad,
             "wallet_unload" => Method::WalletUnload,
             "wallet_list" => Method::WalletList,
             "wallet_details" => Method::WalletDetails,
             "wallet_address" => Method::WalletAddress,
             "wallet_balance" => Method::WalletBalance,
+            "wallet_portfolio" => Method::WalletPortfolio,
             "wallet_utxos" => Method::WalletUtxos,
             "wallet_txs" => Method::WalletTxs,
             "wallet_tx" => Method::WalletTx,
@@ -190,6 +252,12 @@ impl FromStr for Method {
             "wallet_multisig_descriptor" => Method::WalletMultisigDescriptor,
             "wallet_set_tx_memo" => Method::WalletSetTxMemo,
             "wallet_set_addr_memo" => Method::WalletSetAddrMemo,
+            "wallet_lock_utxo" => Method::WalletLockUtxo,
+            "wallet_unlock_utxo" => Method::WalletUnlockUtxo,
+            "wallet_locked_utxos" => Method::WalletLockedUtxos,
+            "wallet_backup" => Method::WalletBackup,
+            "wallet_snapshot" => Method::WalletSnapshot,
+            "wallet_restore" => Method::WalletRestore,
             "signer_generate" => Method::SignerGenerate,
             "signer_jade_id" => Method::SignerJadeId,
             "signer_load_software" => Method::SignerLoadSoftware,
@@ -200,6 +268,8 @@ impl FromStr for Method {
             "signer_details" => Method::SignerDetails,
             "signer_xpub" => Method::SignerXpub,
             "signer_sign" => Method::SignerSign,
+            "signer_set_policy" => Method::SignerSetPolicy,
+            "signer_signed_psets" => Method::SignerSignedPsets,
             "signer_singlesig_descriptor" => Method::SignerSinglesigDescriptor,
             "signer_register_multisig" => Method::SignerRegisterMultisig,
             "asset_contract" => Method::AssetContract,
@@ -209,6 +279,7 @@ impl FromStr for Method {
             "asset_details" => Method::AssetDetails,
             "asset_from_explorer" => Method::AssetFromExplorer,
             "asset_publish" => Method::AssetPublish,
+            "pset_decode" => Method::PsetDecode,
             _ => {
                 return Err(MethodNotExist {
                     name: s.to_string(),
@@ -225,12 +296,14 @@ impl std::fmt::Display for Method {
             Method::Version => "version",
             Method::Scan => "scan",
             Method::Stop => "stop",
+            Method::WaitForEvent => "wait_for_event",
             Method::WalletLoad => "wallet_load",
             Method::WalletUnload => "wallet_unload",
             Method::WalletList => "wallet_list",
             Method::WalletDetails => "wallet_details",
             Method::WalletAddress => "wallet_address",
             Method::WalletBalance => "wallet_balance",
+            Method::WalletPortfolio => "wallet_portfolio",
             Method::WalletUtxos => "wallet_utxos",
             Method::WalletTxs => "wallet_txs",
             Method::WalletTx => "wallet_tx",
@@ -245,6 +318,12 @@ impl std::fmt::Display for Method {
             Method::WalletMultisigDescriptor => "wallet_multisig_descriptor",
             Method::WalletSetTxMemo => "wallet_set_tx_memo",
             Method::WalletSetAddrMemo => "wallet_set_addr_memo",
+            Method::WalletLockUtxo => "wallet_lock_utxo",
+            Method::WalletUnlockUtxo => "wallet_unlock_utxo",
+            Method::WalletLockedUtxos => "wallet_locked_utxos",
+            Method::WalletBackup => "wallet_backup",
+            Method::WalletSnapshot => "wallet_snapshot",
+            Method::WalletRestore => "wallet_restore",
             Method::SignerGenerate => "signer_generate",
             Method::SignerJadeId => "signer_jade_id",
             Method::SignerLoadSoftware => "signer_load_software",
@@ -255,6 +334,8 @@ impl std::fmt::Display for Method {
             Method::SignerDetails => "signer_details",
             Method::SignerXpub => "signer_xpub",
             Method::SignerSign => "signer_sign",
+            Method::SignerSetPolicy => "signer_set_policy",
+            Method::SignerSignedPsets => "signer_signed_psets",
             Method::SignerSinglesigDescriptor => "signer_singlesig_descriptor",
             Method::SignerRegisterMultisig => "signer_register_multisig",
             Method::AssetContract => "asset_contract",
@@ -264,6 +345,7 @@ impl std::fmt::Display for Method {
             Method::AssetDetails => "asset_details",
             Method::AssetFromExplorer => "asset_from_explorer",
             Method::AssetPublish => "asset_publish",
+            Method::PsetDecode => "pset_decode",
         };
         write!(f, "{}", s)
     }
@@ -0,0 +1,205 @@
+//! Signing and transaction creation without a running [`App`](crate::App)
+//!
+//! An air-gapped machine loads the signer from its mnemonic and the wallet from a
+//! [`response::WalletSnapshot`] exported by an online server with `wallet_snapshot`.
+//! Nothing is persisted, the PSETs created or signed are returned to the caller.
+//!
+//! A snapshot discloses the wallet descriptor, including its blinding key, and all its
+//! transactions: it must be transferred as the descriptor is.
+
+use std::str::FromStr;
+
+use base64::Engine;
+use lwk_common::Signer;
+use lwk_signer::{SignerError, SwSigner};
+use lwk_wollet::elements::pset::PartiallySignedTransaction;
+use lwk_wollet::elements::{OutPoint, Txid};
+use lwk_wollet::{ElementsNetwork, NoPersist, UnvalidatedRecipient, Update, Wollet, WolletBackup};
+
+use crate::{convert_pset_analysis, convert_pset_details, Error};
+use lwk_rpc_model::response;
+
+/// Sign `pset` with the software signer of `mnemonic`
+///
+/// The signatures status in the response names no signer, the fingerprint of the one
+/// used is in [`response::OfflineSign::fingerprint`].
+pub fn sign(
+    network: ElementsNetwork,
+    mnemonic: &str,
+    pset: &str,
+) -> Result<response::OfflineSign, Error> {
+    let mut pset = PartiallySignedTransaction::from_str(pset).map_err(|e| e.to_string())?;
+    let is_mainnet = matches!(network, ElementsNetwork::Liquid);
+    let signer = SwSigner::new(mnemonic.trim(), is_mainnet)?;
+    let signatures_added = signer.sign(&mut pset).map_err(SignerError::from)?;
+
+    let analysis = lwk_common::pset_analysis(&pset, network.address_params());
+    Ok(response::OfflineSign {
+        pset: pset.to_string(),
+        fingerprint: signer.fingerprint().to_string(),
+        signatures_added,
+        details: convert_pset_analysis(analysis, |_| None),
+    })
+}
+
+/// Create the snapshot of a wallet, as returned by `wallet_snapshot`
+pub(crate) fn snapshot(wollet: &Wollet) -> Result<response::WalletSnapshot, Error> {
+    let updates = wollet
+        .updates()
+        .map_err(|e| Error::Generic(e.to_string()))?
+        .iter()
+        .map(|u| {
+            let bytes = u.serialize().map_err(|e| Error::Generic(e.to_string()))?;
+            Ok(base64::engine::general_purpose::STANDARD.encode(bytes))
+        })
+        .collect::<Result<_, Error>>()?;
+    let locked_utxos = wollet
+        .locked_utxos()?
+        .into_iter()
+        .map(|l| response::LockedUtxo {
+            txid: l.outpoint.txid.to_string(),
+            vout: l.outpoint.vout,
+            expiry_height: l.expiry,
+        })
+        .collect();
+    Ok(response::WalletSnapshot {
+        backup: WolletBackup::new(wollet).to_string(),
+        updates,
+        locked_utxos,
+    })
+}
+
+/// A wallet restored from a [`response::WalletSnapshot`]
+pub struct OfflineWallet {
+    wollet: Wollet,
+}
+
+impl OfflineWallet {
+    /// Restore the wallet from the JSON of a [`response::WalletSnapshot`]
+    pub fn from_snapshot(network: ElementsNetwork, snapshot: &str) -> Result<Self, Error> {
+        let snapshot: response::WalletSnapshot = serde_json::from_str(snapshot)?;
+        let backup: WolletBackup = snapshot.backup.parse()?;
+        if backup.network().as_str() != network.as_str() {
+            return Err(Error::Generic("Snapshot is for the wrong network".into()));
+        }
+        let desc = backup.descriptor().clone();
+        let mut wollet = Wollet::new(backup.network(), NoPersist::new(), desc)?;
+        wollet.set_birthday(backup.birthday_height())?;
+        for update in snapshot.updates {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(update)
+                .map_err(|e| Error::Generic(e.to_string()))?;
+            let update = Update::deserialize(&bytes).map_err(|e| Error::Generic(e.to_string()))?;
+            wollet.apply_update(update)?;
+        }
+        for locked in snapshot.locked_utxos {
+            let outpoint = OutPoint::new(Txid::from_str(&locked.txid)?, locked.vout);
+            wollet.lock_utxo(outpoint, locked.expiry_height)?;
+        }
+        Ok(Self { wollet })
+    }
+
+    /// The balance of the wallet at the time of the snapshot
+    pub fn balance(&self) -> Result<response::WalletBalance, Error> {
+        let balance = self
+            .wollet
+            .balance()?
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v as i64))
+            .collect();
+        Ok(response::WalletBalance { balance })
+    }
+
+    /// Create an unsigned PSET as `wallet_send_many` does
+    pub fn send_many(
+        &self,
+        recipients: Vec<UnvalidatedRecipient>,
+        fee_rate: Option<f32>,
+        enable_ct_discount: bool,
+    ) -> Result<response::Pset, Error> {
+        let mut builder = self
+            .wollet
+            .tx_builder()
+            .set_unvalidated_recipients(&recipients)?
+            .fee_rate(fee_rate);
+        if enable_ct_discount {
+            builder = builder.enable_ct_discount();
+        }
+        let pset = builder.finish()?;
+        Ok(response::Pset {
+            pset: pset.to_string(),
+        })
+    }
+
+    /// The details of `pset` from the point of view of the wallet, signers are not named
+    pub fn pset_details(&self, pset: &str) -> Result<response::WalletPsetDetails, Error> {
+        let pset = PartiallySignedTransaction::from_str(pset).map_err(|e| e.to_string())?;
+        let details = self.wollet.get_details(&pset)?;
+        Ok(convert_pset_details(details, |_| None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lwk_wollet::{ElementsNetwork, NoPersist, Wollet};
+
+    use super::*;
+
+    const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn offline_sign() {
+        let pset = include_str!("../../lwk_jade/test_data/pset_to_be_signed.base64");
+        let network = ElementsNetwork::LiquidTestnet;
+
+        let r = sign(network, &format!("{MNEMONIC}\n"), pset).unwrap();
+        assert_eq!(r.fingerprint, "73c5da0a");
+        assert_eq!(r.signatures_added, 1);
+        assert_ne!(r.pset, pset);
+        let input = &r.details.inputs[0];
+        assert_eq!(input.has_signatures_from[0].fingerprint, "73c5da0a");
+        assert!(input.has_signatures_from[0].name.is_none());
+
+        let err = sign(network, "wrong mnemonic", pset).unwrap_err();
+        assert!(matches!(err, Error::SignerNew(_)));
+    }
+
+    #[test]
+    fn offline_wallet() {
+        let desc = "ct(slip77(9c8e4f05c7711a98c838be228bcb84924d4570ca53f35fa1c793e58841d47023),elwpkh([73c5da0a/84'/1'/0']tpubDC8msFGeGuwnKG9Upg7DM2b4DaRqg3CUZa5g8v2SRQ6K4NSkxUgd7HsL2XVWbVm39yBA4LAxysQAm397zwQSQoQgewGiYZqrA9DsP4zbQ1M/<0;1>/*))";
+        let network = ElementsNetwork::LiquidTestnet;
+        let wollet = Wollet::new(network, NoPersist::new(), desc.parse().unwrap()).unwrap();
+        let mut response = snapshot(&wollet).unwrap();
+        assert!(response.locked_utxos.is_empty());
+        let snapshot = serde_json::to_string(&response).unwrap();
+
+        let wallet = OfflineWallet::from_snapshot(network, &snapshot).unwrap();
+        let balance = wallet.balance().unwrap().balance;
+        assert!(balance.values().all(|v| *v == 0));
+
+        let recipient = UnvalidatedRecipient {
+            satoshi: 1_000,
+            address: wollet.address(None).unwrap().address().to_string(),
+            asset: network.policy_asset().to_string(),
+        };
+        let err = wallet.send_many(vec![recipient], None, false).unwrap_err();
+        assert!(err.to_string().contains("Insufficient funds"), "{err}");
+
+        let err = OfflineWallet::from_snapshot(ElementsNetwork::Liquid, &snapshot).err();
+        let err = err.expect("snapshot is for testnet");
+        assert_eq!(
+            err.to_string(),
+            "Generic error Snapshot is for the wrong network"
+        );
+
+        // Locks of utxos not in the updates are rejected
+        response.locked_utxos.push(response::LockedUtxo {
+            txid: OutPoint::default().txid.to_string(),
+            vout: 0,
+            expiry_height: None,
+        });
+        let snapshot = serde_json::to_string(&response).unwrap();
+        let err = OfflineWallet::from_snapshot(network, &snapshot).err();
+        assert!(err.expect("unknown utxo").to_string().contains("not found"));
+    }
+}
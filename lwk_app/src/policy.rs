@@ -0,0 +1,366 @@
+//! Spending policies
+//!
+//! A policy is attached to a signer with `signer_set_policy` and checked before `signer_sign`
+//! signs a PSET: if any rule is violated the PSET is not signed. Every signed PSET is recorded in
+//! the audit log, which is also used to enforce the daily limits.
+
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use lwk_common::{is_mine, PsetBalance};
+use lwk_rpc_model::{request, response};
+use lwk_wollet::elements::pset::PartiallySignedTransaction;
+use lwk_wollet::elements::{Address, AssetId, Script};
+use lwk_wollet::Wollet;
+
+use crate::Error;
+
+/// Seconds in the period of the daily limits
+const DAY: u64 = 24 * 60 * 60;
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum PolicyViolation {
+    #[error("Sending {sent} of asset {asset} exceeds the daily limit of {limit}, {sent_before} were sent in the last 24 hours")]
+    DailyLimit {
+        asset: AssetId,
+        limit: u64,
+        sent: u64,
+        sent_before: u64,
+    },
+
+    #[error("Output script {0} is not in the allowed addresses")]
+    AddressNotAllowed(Script),
+
+    #[error("Fee rate {0:.1} sat/kvB exceeds the maximum of {1} sat/kvB")]
+    FeeRate(f32, f32),
+
+    #[error("The balance of asset {0} cannot change")]
+    AssetNotAllowed(AssetId),
+
+    #[error("Signing is not allowed at this time")]
+    OutsideTimeWindow,
+
+    #[error("Input {0} does not belong to the wallet of the policy")]
+    InputNotInWallet(usize),
+}
+
+/// A parsed [`request::SpendingPolicy`]
+pub struct Policy {
+    spec: request::SpendingPolicy,
+    daily_limits: HashMap<AssetId, u64>,
+    allowed_scripts: HashSet<Script>,
+    allowed_assets: HashSet<AssetId>,
+}
+
+impl Policy {
+    pub fn new(spec: request::SpendingPolicy) -> Result<Self, Error> {
+        let daily_limits = spec
+            .daily_limits
+            .iter()
+            .map(|(asset, limit)| Ok((AssetId::from_str(asset)?, *limit)))
+            .collect::<Result<_, Error>>()?;
+        let allowed_scripts = spec
+            .allowed_addresses
+            .iter()
+            .map(|a| Ok(Address::from_str(a)?.script_pubkey()))
+            .collect::<Result<_, Error>>()?;
+        let allowed_assets = spec
+            .allowed_assets
+            .iter()
+            .map(|a| Ok(AssetId::from_str(a)?))
+            .collect::<Result<_, Error>>()?;
+        for w in spec.time_windows.iter() {
+            if w.start_hour > 23 || w.end_hour > 24 {
+                return Err(Error::Generic(format!(
+                    "Invalid time window {}-{}",
+                    w.start_hour, w.end_hour
+                )));
+            }
+        }
+        Ok(Self {
+            spec,
+            daily_limits,
+            allowed_scripts,
+            allowed_assets,
+        })
+    }
+
+    pub fn spec(&self) -> &request::SpendingPolicy {
+        &self.spec
+    }
+
+    /// The wallet whose balance changes are checked
+    pub fn wallet(&self) -> &str {
+        &self.spec.wallet
+    }
+
+    /// Check the PSET against the policy at the unix time `now`, returning its balance
+    ///
+    /// Every input must belong to the wallet of the policy, otherwise the signer could sign
+    /// inputs whose spending is not accounted in the balance.
+    /// `log` contains the previously signed PSETs, used for the daily limits.
+    pub fn check(
+        &self,
+        wollet: &Wollet,
+        pset: &PartiallySignedTransaction,
+        log: &[response::SignedPset],
+        now: u64,
+    ) -> Result<PsetBalance, Error> {
+        if !in_time_windows(&self.spec.time_windows, now) {
+            return Err(PolicyViolation::OutsideTimeWindow.into());
+        }
+
+        for (idx, input) in pset.inputs().iter().enumerate() {
+            let mine = match input.witness_utxo.as_ref() {
+                Some(txout) => is_mine(
+                    &txout.script_pubkey,
+                    wollet.descriptor(),
+                    &input.bip32_derivation,
+                )
+                .map_err(|e| Error::Generic(e.to_string()))?,
+                None => false,
+            };
+            if !mine {
+                return Err(PolicyViolation::InputNotInWallet(idx).into());
+            }
+        }
+
+        let balance = wollet.get_details(pset)?.balance;
+
+        if !self.allowed_assets.is_empty() {
+            let changed = balance.balances.iter().filter(|(_, v)| **v != 0);
+            for (asset, _) in changed {
+                if !self.allowed_assets.contains(asset) {
+                    return Err(PolicyViolation::AssetNotAllowed(*asset).into());
+                }
+            }
+        }
+
+        if !self.allowed_scripts.is_empty() {
+            for output in pset.outputs() {
+                let script = &output.script_pubkey;
+                if script.is_empty()
+                    || self.allowed_scripts.contains(script)
+                    || is_mine(script, wollet.descriptor(), &output.bip32_derivation)
+                        .map_err(|e| Error::Generic(e.to_string()))?
+                {
+                    continue;
+                }
+                return Err(PolicyViolation::AddressNotAllowed(script.clone()).into());
+            }
+        }
+
+        if let Some(max_fee_rate) = self.spec.max_fee_rate {
+            let tx = pset
+                .extract_tx()
+                .map_err(|e| Error::Generic(e.to_string()))?;
+            let weight = tx.weight() + tx.input.len() * wollet.max_weight_to_satisfy();
+            let vsize = (weight + 3) / 4;
+            let fee_rate = balance.fee as f32 * 1000.0 / vsize as f32;
+            if fee_rate > max_fee_rate {
+                return Err(PolicyViolation::FeeRate(fee_rate, max_fee_rate).into());
+            }
+        }
+
+        // A PSET signed more than once, for instance by several signers of a multisig, spends
+        // only once: count each transaction once and not the one being signed
+        let txid = pset
+            .extract_tx()
+            .map_err(|e| Error::Generic(e.to_string()))?
+            .txid()
+            .to_string();
+        let recent: HashMap<&str, &HashMap<String, i64>> = log
+            .iter()
+            .filter(|e| e.wallet.as_deref() == Some(self.wallet()))
+            .filter(|e| e.timestamp + DAY > now)
+            .filter(|e| e.txid != txid)
+            .filter_map(|e| Some((e.txid.as_str(), e.balance.as_ref()?)))
+            .collect();
+
+        for (asset, limit) in self.daily_limits.iter() {
+            let sending = sent(balance.balances.get(asset).cloned());
+            if sending == 0 {
+                continue;
+            }
+            let key = asset.to_string();
+            let sent_before: u64 = recent
+                .values()
+                .map(|balance| sent(balance.get(&key).cloned()))
+                .sum();
+            if sent_before + sending > *limit {
+                return Err(PolicyViolation::DailyLimit {
+                    asset: *asset,
+                    limit: *limit,
+                    sent: sending,
+                    sent_before,
+                }
+                .into());
+            }
+        }
+
+        Ok(balance)
+    }
+}
+
+/// The current unix time in seconds
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Whether the unix time `now` is inside one of the windows, or there are no windows
+fn in_time_windows(windows: &[request::TimeWindow], now: u64) -> bool {
+    let hour = ((now / 3600) % 24) as u8;
+    windows.is_empty()
+        || windows.iter().any(|w| {
+            if w.start_hour <= w.end_hour {
+                w.start_hour <= hour && hour < w.end_hour
+            } else {
+                hour >= w.start_hour || hour < w.end_hour
+            }
+        })
+}
+
+/// The satoshi sent given the balance change of an asset
+fn sent(balance: Option<i64>) -> u64 {
+    balance.map_or(0, |v| if v < 0 { v.unsigned_abs() } else { 0 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec() -> request::SpendingPolicy {
+        request::SpendingPolicy {
+            wallet: "w".into(),
+            daily_limits: HashMap::new(),
+            allowed_addresses: vec![],
+            max_fee_rate: None,
+            allowed_assets: vec![],
+            time_windows: vec![],
+        }
+    }
+
+    #[test]
+    fn test_policy() {
+        assert_eq!(sent(None), 0);
+        assert_eq!(sent(Some(10)), 0);
+        assert_eq!(sent(Some(-10)), 10);
+
+        let window = |start_hour, end_hour| request::TimeWindow {
+            start_hour,
+            end_hour,
+        };
+        let at = |hour: u64| 1_700_000_000 / DAY * DAY + hour * 3600 + 59;
+        assert!(in_time_windows(&[], at(3)));
+        assert!(in_time_windows(&[window(9, 17)], at(9)));
+        assert!(!in_time_windows(&[window(9, 17)], at(17)));
+        assert!(in_time_windows(&[window(22, 6)], at(23)));
+        assert!(in_time_windows(&[window(22, 6)], at(2)));
+        assert!(!in_time_windows(&[window(22, 6)], at(12)));
+        assert!(in_time_windows(&[window(22, 6), window(12, 13)], at(12)));
+
+        let mut invalid = spec();
+        invalid.allowed_assets.push("wrong".into());
+        assert!(Policy::new(invalid).is_err());
+        let mut invalid = spec();
+        invalid.time_windows.push(window(24, 1));
+        assert!(Policy::new(invalid).is_err());
+
+        let policy = Policy::new(spec()).unwrap();
+        assert_eq!(policy.wallet(), "w");
+    }
+
+    #[test]
+    fn test_policy_same_pset_signed_twice() {
+        use lwk_wollet::ElementsNetwork;
+
+        let desc = "ct(slip77(0c11648c2c6df4f9dacdb4c8d35d6166d94cea2b9ad37833a82210bb7c9f5fb4),elwpkh([f5acc2fd/84'/1'/0']tpubDCtKfsNyRhULjZ9XMS4VKKtVcPdVDi8MKUbcSD9MJDyjRu1A2ND5MiipozyyspBT9bg8upEp7a8EAgFxNxXn1d7QkdbL52Ty5jiSLcxPt1P/<0;1>/*))";
+        let network = ElementsNetwork::LiquidTestnet;
+        let wollet = Wollet::without_persist(network, desc.parse().unwrap()).unwrap();
+        let pset = include_str!("../../lwk_common/test_data/pset_details/pset_tx_builder.base64");
+        let pset = PartiallySignedTransaction::from_str(pset).unwrap();
+
+        let asset = "5ac9f65c0efcc4775e0baec4ec03abdde22473cd3cf33c0419ca290e0751b225";
+        let policy_with_limit = |limit| {
+            let mut spec = spec();
+            spec.daily_limits.insert(asset.into(), limit);
+            Policy::new(spec).unwrap()
+        };
+        let signed = |txid: &str| response::SignedPset {
+            timestamp: 0,
+            signer: "s".into(),
+            txid: txid.into(),
+            signatures_added: 1,
+            wallet: Some("w".into()),
+            balance: Some(HashMap::from([(asset.to_string(), -10_002_491)])),
+            fee: Some(2491),
+        };
+        let txid = pset.extract_tx().unwrap().txid().to_string();
+        let other_txid = "00".repeat(32);
+
+        // The same PSET signed twice is counted once
+        let policy = policy_with_limit(15_000_000);
+        let balance = policy.check(&wollet, &pset, &[], 0).unwrap();
+        assert_eq!(
+            balance.balances.get(&asset.parse().unwrap()),
+            Some(&-10_002_491)
+        );
+        let log = vec![signed(&txid)];
+        policy.check(&wollet, &pset, &log, 0).unwrap();
+        let log = vec![signed(&txid), signed(&txid)];
+        policy.check(&wollet, &pset, &log, 0).unwrap();
+
+        let log = vec![signed(&other_txid)];
+        let err = policy.check(&wollet, &pset, &log, 0).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::PolicyViolation(PolicyViolation::DailyLimit {
+                sent: 10_002_491,
+                sent_before: 10_002_491,
+                ..
+            })
+        ));
+
+        // Another transaction signed twice is counted once
+        let policy = policy_with_limit(25_000_000);
+        let log = vec![signed(&other_txid), signed(&txid), signed(&other_txid)];
+        policy.check(&wollet, &pset, &log, 0).unwrap();
+    }
+
+    #[test]
+    fn test_policy_foreign_input() {
+        use lwk_wollet::elements::pset::Input;
+        use lwk_wollet::elements::{OutPoint, TxOut};
+        use lwk_wollet::ElementsNetwork;
+
+        let desc = "ct(slip77(9c8e4f05c7711a98c838be228bcb84924d4570ca53f35fa1c793e58841d47023),elwpkh([73c5da0a/84'/1'/0']tpubDC8msFGeGuwnKG9Upg7DM2b4DaRqg3CUZa5g8v2SRQ6K4NSkxUgd7HsL2XVWbVm39yBA4LAxysQAm397zwQSQoQgewGiYZqrA9DsP4zbQ1M/<0;1>/*))";
+        let network = ElementsNetwork::LiquidTestnet;
+        let wollet = Wollet::without_persist(network, desc.parse().unwrap()).unwrap();
+        let policy = Policy::new(spec()).unwrap();
+
+        let mut pset = PartiallySignedTransaction::new_v2();
+        let mut input = Input::from_prevout(OutPoint::default());
+        pset.add_input(input.clone());
+        let err = policy.check(&wollet, &pset, &[], 0).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::PolicyViolation(PolicyViolation::InputNotInWallet(0))
+        ));
+
+        input.witness_utxo = Some(TxOut {
+            script_pubkey: Script::from(vec![0x51]),
+            ..Default::default()
+        });
+        let mut pset = PartiallySignedTransaction::new_v2();
+        pset.add_input(input);
+        let err = policy.check(&wollet, &pset, &[], 0).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::PolicyViolation(PolicyViolation::InputNotInWallet(0))
+        ));
+    }
+}
@@ -2,30 +2,59 @@ use std::{fmt, time::Duration};
 
 use jsonrpc::{Request, Response};
 
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct ReqwestHttpTransport {
     /// URL of the RPC server.
     url: String,
     /// timeout only supports second granularity.
     timeout: Duration,
+    /// User and password for HTTP Basic authentication
+    credentials: Option<(String, String)>,
+    /// Additional trusted root certificates, for servers using a self-signed certificate
+    root_certs: Vec<reqwest::Certificate>,
 }
 
 impl ReqwestHttpTransport {
-    pub fn new(url: String, timeout: Duration) -> Self {
-        ReqwestHttpTransport { url, timeout }
+    pub fn new(
+        url: String,
+        timeout: Duration,
+        credentials: Option<(String, String)>,
+        root_certs: Vec<reqwest::Certificate>,
+    ) -> Self {
+        ReqwestHttpTransport {
+            url,
+            timeout,
+            credentials,
+            root_certs,
+        }
     }
     fn request<R>(&self, req: impl serde::Serialize) -> Result<R, crate::Error>
     where
         R: for<'a> serde::de::Deserialize<'a>,
     {
-        let client = reqwest::blocking::ClientBuilder::new()
-            .timeout(self.timeout)
-            .build()?;
-        let response = client.post(&self.url).json(&req).send()?;
+        let mut builder = reqwest::blocking::ClientBuilder::new().timeout(self.timeout);
+        for cert in self.root_certs.iter() {
+            builder = builder.add_root_certificate(cert.clone());
+        }
+        let client = builder.build()?;
+        let mut request = client.post(&self.url).json(&req);
+        if let Some((user, password)) = self.credentials.as_ref() {
+            request = request.basic_auth(user, Some(password));
+        }
+        let response = request.send()?;
         Ok(response.json()?)
     }
 }
 
+impl fmt::Debug for ReqwestHttpTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReqwestHttpTransport")
+            .field("url", &self.url)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
 impl From<crate::Error> for jsonrpc::Error {
     fn from(value: crate::Error) -> Self {
         match value {
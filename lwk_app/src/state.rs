@@ -1,13 +1,11 @@
 use std::collections::HashMap;
-use std::fs::{File, OpenOptions};
-use std::io::Write;
 use std::net::SocketAddr;
 use std::str::FromStr;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::Duration;
 
 use lwk_common::Signer;
 use lwk_jade::{Jade, Network};
-use lwk_rpc_model::request;
+use lwk_rpc_model::{request, response};
 use lwk_signer::AnySigner;
 use lwk_signer::SwSigner;
 use lwk_tiny_jrpc::Request;
@@ -23,7 +21,10 @@ use lwk_wollet::Wollet;
 use serde::Serialize;
 
 use crate::config::Config;
+use crate::events::Events;
 use crate::method::Method;
+use crate::policy::Policy;
+use crate::store::Store;
 use crate::Error;
 
 #[derive(Debug)]
@@ -270,7 +271,18 @@ pub struct State {
     pub assets: Assets,
     pub tx_memos: TxMemos,
     pub addr_memos: AddrMemos,
-    pub do_persist: bool,
+
+    /// Where the state is persisted, none while the previous state is being loaded
+    pub store: Option<Store>,
+
+    /// Spending policies checked before signing, by signer name
+    pub policies: HashMap<String, Policy>,
+
+    /// Audit log of the signed PSETs, oldest first
+    pub signed_psets: Vec<response::SignedPset>,
+
+    /// Where the audit log is persisted, none while the previous state is being loaded
+    pub audit_store: Option<Store>,
 
     /// Number of scan loops started
     pub scan_loops_started: u32,
@@ -280,6 +292,9 @@ pub struct State {
 
     /// Signal the scanning thread that we don't want to wait anymore
     pub interrupt_wait: bool,
+
+    /// Wallet events detected by the scanning thread
+    pub events: Events,
 }
 
 impl Wollets {
@@ -524,10 +539,17 @@ impl AddrMemos {
 
 impl State {
     pub fn insert_policy_asset(&mut self) {
-        let asset_id = self.config.network.policy_asset();
-        self.assets
-            .0
-            .insert(asset_id, AppAsset::PolicyAsset(asset_id));
+        for network in self.config.networks() {
+            let asset_id = network.policy_asset();
+            self.assets
+                .0
+                .insert(asset_id, AppAsset::PolicyAsset(asset_id));
+        }
+    }
+
+    /// The config with the backends of the network of the given wallet
+    pub fn wollet_config(&self, name: &str) -> Result<Config, Error> {
+        self.config.for_network(self.wollets.get(name)?.network())
     }
 
     pub fn get_asset(&self, asset: &AssetId) -> Result<&AppAsset, Error> {
@@ -584,35 +606,27 @@ impl State {
     }
 
     pub fn persist<T: Serialize>(&mut self, data: T) -> Result<(), Error> {
-        if self.do_persist {
-            let data = serde_json::to_string(&data)?;
-            let path = self.config.state_path()?;
-            let mut file = OpenOptions::new()
-                .create_new(!path.exists())
-                .append(true)
-                .open(path)?;
-            writeln!(file, "{}", data)?;
-            file.sync_all()?;
+        let needs_compaction = match self.store.as_mut() {
+            Some(store) => {
+                store.append(&data)?;
+                store.needs_compaction()
+            }
+            None => false,
+        };
+        if needs_compaction {
+            self.persist_all()?;
         }
         Ok(())
     }
 
+    /// Replace the persisted state with a snapshot of the current one
     pub fn persist_all(&mut self) -> Result<(), Error> {
-        let path = self.config.state_path()?;
-        let mut temp = path.clone();
-        let millis = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("Clock may have gone backwards")
-            .as_millis();
-        temp.set_file_name(millis.to_string());
-        let mut file = File::create(&temp)?;
-        for req in self.as_requests()? {
-            let data = serde_json::to_string(&req)?;
-            writeln!(file, "{}", data)?;
+        if self.store.is_some() {
+            let requests = self.as_requests()?;
+            if let Some(store) = self.store.as_mut() {
+                store.compact(&requests)?;
+            }
         }
-        std::fs::rename(temp, path)?;
-        file.sync_all()?;
-
         Ok(())
     }
 
@@ -624,6 +638,8 @@ impl State {
             let params = request::WalletLoad {
                 descriptor: w.descriptor().to_string(),
                 name: n.to_string(),
+                network: Some(w.network().as_str().to_string()),
+                birthday_height: w.birthday(),
             };
             let r = Request {
                 jsonrpc: "2.0".into(),
@@ -722,6 +738,21 @@ impl State {
             requests.push(r);
         }
 
+        // Policies
+        for (n, p) in self.policies.iter() {
+            let params = request::SignerSetPolicy {
+                name: n.to_string(),
+                policy: Some(p.spec().clone()),
+            };
+            let r = Request {
+                jsonrpc: "2.0".into(),
+                id: None,
+                method: Method::SignerSetPolicy.to_string(),
+                params: Some(serde_json::to_value(params)?),
+            };
+            requests.push(r);
+        }
+
         // Assets
         for (_, a) in self.assets.iter() {
             if let Some(r) = a.request() {
@@ -732,6 +763,15 @@ impl State {
         Ok(requests)
     }
 
+    /// Append a signed PSET to the audit log
+    pub fn record_signed_pset(&mut self, signed: response::SignedPset) -> Result<(), Error> {
+        if let Some(store) = self.audit_store.as_mut() {
+            store.append(&signed)?;
+        }
+        self.signed_psets.push(signed);
+        Ok(())
+    }
+
     /// Get an available signer identified by name.
     ///
     /// In some cases, like with a jade not currently linked, it may try to connect to it first
@@ -0,0 +1,380 @@
+//! Persistence of the app state
+//!
+//! The state is kept as a log of the requests needed to rebuild it. The file starts with a
+//! versioned header, followed by one record per request. At startup, and when the log grows too
+//! much, the file is rewritten with a snapshot of the current state, see `State::as_requests`.
+//!
+//! The same format is used for the audit log of the signed PSETs, with one record per PSET.
+//!
+//! If a passphrase or a key file is configured, records are encrypted with AES-256-GCM-SIV,
+//! using a key derived with scrypt. Otherwise they are in plaintext, including the mnemonics of
+//! the persisted signers, and only the file permissions protect them: on unix the files are
+//! created readable and writable by the owner only.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use aes_gcm_siv::aead::generic_array::GenericArray;
+use aes_gcm_siv::aead::{Aead, NewAead};
+use aes_gcm_siv::Aes256GcmSiv;
+use base64::Engine;
+use lwk_tiny_jrpc::Request;
+use lwk_wollet::elements::hex::{FromHex, ToHex};
+use rand::{thread_rng, Rng};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::config::StateSecret;
+use crate::{consts, Error};
+
+/// Current version of the state file format
+pub const STORE_VERSION: u32 = 1;
+
+/// Encrypted in the header, to detect a wrong passphrase or key file before reading the records
+const CHECK_PLAINTEXT: &[u8] = b"lwk state";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    version: u32,
+    encryption: Option<Encryption>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Encryption {
+    /// Only "scrypt" is supported
+    kdf: String,
+    log_n: u8,
+    r: u32,
+    p: u32,
+
+    /// Hex encoded salt
+    salt: String,
+
+    /// `CHECK_PLAINTEXT` encrypted with the derived key
+    check: String,
+}
+
+pub struct Store {
+    path: PathBuf,
+    encryption: Option<(Encryption, Aes256GcmSiv)>,
+
+    /// Number of records in the last snapshot
+    snapshot_len: usize,
+
+    /// Number of records appended after the last snapshot
+    appended: usize,
+}
+
+impl Store {
+    /// Open the store at `path`, returning it with the records it contains, usually requests.
+    ///
+    /// The records are decrypted with `secret` if the file is encrypted. The following writes are
+    /// encrypted if `secret` is some, an existing plaintext store is rewritten encrypted.
+    pub fn open<T: Serialize + DeserializeOwned>(
+        path: &Path,
+        secret: Option<&StateSecret>,
+    ) -> Result<(Self, Vec<T>), Error> {
+        let secret = secret.map(|s| s.bytes()).transpose()?;
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let mut lines = content.lines().filter(|l| !l.trim().is_empty());
+
+        let (encryption, requests, plain_file) = match lines.next() {
+            None => {
+                let encryption = secret.as_deref().map(new_encryption).transpose()?;
+                (encryption, vec![], false)
+            }
+            Some(line) => {
+                let header: Header = serde_json::from_str(line)?;
+                if header.version > STORE_VERSION {
+                    return Err(Error::StateVersion(header.version));
+                }
+                let file_encrypted = header.encryption.is_some();
+                let encryption = match (header.encryption, secret.as_deref()) {
+                    (Some(encryption), Some(secret)) => {
+                        let cipher = derive_cipher(&encryption, secret)?;
+                        let check =
+                            decrypt(&cipher, &encryption.check).map_err(|_| Error::StateDecrypt)?;
+                        if check != CHECK_PLAINTEXT {
+                            return Err(Error::StateDecrypt);
+                        }
+                        Some((encryption, cipher))
+                    }
+                    (Some(_), None) => return Err(Error::StateEncrypted),
+                    (None, Some(secret)) => Some(new_encryption(secret)?),
+                    (None, None) => None,
+                };
+                let requests = lines
+                    .map(
+                        |line| match encryption.as_ref().filter(|_| file_encrypted) {
+                            Some((_, cipher)) => {
+                                let plaintext = decrypt(cipher, line)?;
+                                Ok(serde_json::from_slice(&plaintext)?)
+                            }
+                            None => Ok(serde_json::from_str(line)?),
+                        },
+                    )
+                    .collect::<Result<Vec<T>, Error>>()?;
+                (encryption, requests, !file_encrypted)
+            }
+        };
+
+        let mut store = Self {
+            path: path.to_path_buf(),
+            encryption,
+            snapshot_len: requests.len(),
+            appended: 0,
+        };
+        if plain_file && store.encryption.is_some() {
+            // Do not mix plaintext and encrypted records
+            store.compact(&requests)?;
+        }
+        Ok((store, requests))
+    }
+
+    /// Append a record to the log
+    pub fn append<T: Serialize>(&mut self, request: &T) -> Result<(), Error> {
+        let record = self.record(request)?;
+        let mut file = private_options()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        if file.metadata()?.len() == 0 {
+            writeln!(file, "{}", self.header()?)?;
+        }
+        writeln!(file, "{}", record)?;
+        file.sync_all()?;
+        self.appended += 1;
+        Ok(())
+    }
+
+    /// True if enough records have been appended since the last snapshot that compacting
+    /// is worthwhile
+    pub fn needs_compaction(&self) -> bool {
+        self.appended > self.snapshot_len.max(consts::STATE_COMPACTION_MIN_RECORDS)
+    }
+
+    /// Replace the content of the store with the given snapshot of the state
+    pub fn compact<T: Serialize>(&mut self, requests: &[T]) -> Result<(), Error> {
+        let mut temp = self.path.clone();
+        temp.set_extension("tmp");
+        let mut file = private_options()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&temp)?;
+        writeln!(file, "{}", self.header()?)?;
+        for request in requests {
+            writeln!(file, "{}", self.record(request)?)?;
+        }
+        file.sync_all()?;
+        fs::rename(temp, &self.path)?;
+        self.snapshot_len = requests.len();
+        self.appended = 0;
+        Ok(())
+    }
+
+    fn header(&self) -> Result<String, Error> {
+        let header = Header {
+            version: STORE_VERSION,
+            encryption: self.encryption.as_ref().map(|(e, _)| e.clone()),
+        };
+        Ok(serde_json::to_string(&header)?)
+    }
+
+    fn record<T: Serialize>(&self, request: &T) -> Result<String, Error> {
+        let data = serde_json::to_string(request)?;
+        Ok(match self.encryption.as_ref() {
+            Some((_, cipher)) => encrypt(cipher, data.as_bytes())?,
+            None => data,
+        })
+    }
+}
+
+/// Options to create files readable and writable by the owner only, on unix
+fn private_options() -> OpenOptions {
+    let mut options = OpenOptions::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+}
+
+/// Read the requests from the log used before the versioned store, one JSON request per line
+pub fn read_legacy(path: &Path) -> Result<Vec<Request>, Error> {
+    fs::read_to_string(path)?
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| Ok(serde_json::from_str(l)?))
+        .collect()
+}
+
+fn new_encryption(secret: &[u8]) -> Result<(Encryption, Aes256GcmSiv), Error> {
+    let salt: [u8; 16] = thread_rng().gen();
+    let mut encryption = Encryption {
+        kdf: "scrypt".to_string(),
+        log_n: consts::STATE_KDF_LOG_N,
+        r: 8,
+        p: 1,
+        salt: salt.to_hex(),
+        check: String::new(),
+    };
+    let cipher = derive_cipher(&encryption, secret)?;
+    encryption.check = encrypt(&cipher, CHECK_PLAINTEXT)?;
+    Ok((encryption, cipher))
+}
+
+fn derive_cipher(encryption: &Encryption, secret: &[u8]) -> Result<Aes256GcmSiv, Error> {
+    if encryption.kdf != "scrypt" {
+        return Err(Error::Generic(format!(
+            "Unsupported state key derivation '{}'",
+            encryption.kdf
+        )));
+    }
+    let salt = Vec::<u8>::from_hex(&encryption.salt)?;
+    let params = scrypt::Params::new(encryption.log_n, encryption.r, encryption.p, 32)
+        .map_err(|e| Error::Generic(format!("Invalid scrypt params: {e}")))?;
+    let mut key = [0u8; 32];
+    scrypt::scrypt(secret, &salt, &params, &mut key)
+        .map_err(|e| Error::Generic(format!("Key derivation failed: {e}")))?;
+    Ok(Aes256GcmSiv::new(GenericArray::from_slice(&key)))
+}
+
+/// Returns base64(nonce || ciphertext)
+fn encrypt(cipher: &Aes256GcmSiv, plaintext: &[u8]) -> Result<String, Error> {
+    let nonce: [u8; 12] = thread_rng().gen();
+    let ciphertext = cipher
+        .encrypt(GenericArray::from_slice(&nonce), plaintext)
+        .map_err(|_| Error::Generic("Encryption failed".into()))?;
+    let mut data = nonce.to_vec();
+    data.extend(ciphertext);
+    Ok(base64::engine::general_purpose::STANDARD.encode(data))
+}
+
+fn decrypt(cipher: &Aes256GcmSiv, record: &str) -> Result<Vec<u8>, Error> {
+    let data = base64::engine::general_purpose::STANDARD
+        .decode(record.trim())
+        .map_err(|_| Error::StateDecrypt)?;
+    if data.len() < 12 {
+        return Err(Error::StateDecrypt);
+    }
+    let (nonce, ciphertext) = data.split_at(12);
+    cipher
+        .decrypt(GenericArray::from_slice(nonce), ciphertext)
+        .map_err(|_| Error::StateDecrypt)
+}
+
+impl StateSecret {
+    fn bytes(&self) -> Result<Vec<u8>, Error> {
+        Ok(match self {
+            StateSecret::Passphrase(p) => p.as_bytes().to_vec(),
+            StateSecret::KeyFile(path) => fs::read(path)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(name: &str) -> Request {
+        Request {
+            jsonrpc: "2.0".into(),
+            id: None,
+            method: "wallet_unload".into(),
+            params: Some(serde_json::json!({ "name": name })),
+        }
+    }
+
+    fn names(requests: &[Request]) -> Vec<String> {
+        requests
+            .iter()
+            .map(|r| {
+                r.params.as_ref().unwrap()["name"]
+                    .as_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn store_encrypted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state");
+        let secret = StateSecret::Passphrase("correct horse".into());
+
+        let (mut store, requests) = Store::open::<Request>(&path, Some(&secret)).unwrap();
+        assert!(requests.is_empty());
+        store.append(&request("a")).unwrap();
+        store.append(&request("b")).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("wallet_unload"));
+
+        let (mut store, requests) = Store::open::<Request>(&path, Some(&secret)).unwrap();
+        assert_eq!(names(&requests), vec!["a", "b"]);
+        store.compact(&[request("c")]).unwrap();
+
+        let (_, requests) = Store::open::<Request>(&path, Some(&secret)).unwrap();
+        assert_eq!(names(&requests), vec!["c"]);
+
+        let wrong = StateSecret::Passphrase("wrong".into());
+        assert!(matches!(
+            Store::open::<Request>(&path, Some(&wrong)),
+            Err(Error::StateDecrypt)
+        ));
+        assert!(matches!(
+            Store::open::<Request>(&path, None),
+            Err(Error::StateEncrypted)
+        ));
+    }
+
+    #[test]
+    fn store_plain_and_legacy() {
+        let dir = tempfile::tempdir().unwrap();
+        let legacy = dir.path().join("state.json");
+        let lines: Vec<_> = [request("a"), request("b")]
+            .iter()
+            .map(|r| serde_json::to_string(r).unwrap())
+            .collect();
+        fs::write(&legacy, lines.join("\n")).unwrap();
+        let requests = read_legacy(&legacy).unwrap();
+        assert_eq!(names(&requests), vec!["a", "b"]);
+
+        let path = dir.path().join("state");
+        let (mut store, _) = Store::open::<Request>(&path, None).unwrap();
+        store.compact(&requests).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        for _ in 0..consts::STATE_COMPACTION_MIN_RECORDS {
+            store.append(&request("x")).unwrap();
+            assert!(!store.needs_compaction());
+        }
+        store.append(&request("x")).unwrap();
+        assert!(store.needs_compaction());
+
+        let (_, requests) = Store::open::<Request>(&path, None).unwrap();
+        assert_eq!(requests.len(), consts::STATE_COMPACTION_MIN_RECORDS + 3);
+
+        // a plain store is encrypted when opened with a secret
+        let secret = StateSecret::Passphrase("pass".into());
+        let (_, requests) = Store::open::<Request>(&path, Some(&secret)).unwrap();
+        assert_eq!(requests.len(), consts::STATE_COMPACTION_MIN_RECORDS + 3);
+        let (_, requests) = Store::open::<Request>(&path, Some(&secret)).unwrap();
+        assert_eq!(requests.len(), consts::STATE_COMPACTION_MIN_RECORDS + 3);
+        assert!(matches!(
+            Store::open::<Request>(&path, None),
+            Err(Error::StateEncrypted)
+        ));
+    }
+}
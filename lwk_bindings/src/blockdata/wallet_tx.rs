@@ -86,8 +86,8 @@ mod tests {
                 1000,
                 elements::confidential::ValueBlindingFactor::zero(),
             ),
-            wildcard_index: 10,
-            ext_int: lwk_wollet::Chain::External,
+            wildcard_index: Some(10),
+            ext_int: Some(lwk_wollet::Chain::External),
         };
 
         let tx_hex = include_str!("../../../lwk_jade/test_data/pset_to_be_signed_transaction.hex")
@@ -31,12 +31,12 @@ impl WalletTxOut {
         Arc::new(self.inner.unblinded.into())
     }
 
-    pub fn wildcard_index(&self) -> u32 {
+    pub fn wildcard_index(&self) -> Option<u32> {
         self.inner.wildcard_index
     }
 
-    pub fn ext_int(&self) -> Chain {
-        self.inner.ext_int.into()
+    pub fn ext_int(&self) -> Option<Chain> {
+        self.inner.ext_int.map(Into::into)
     }
 }
 
@@ -59,8 +59,8 @@ mod tests {
                 1000,
                 elements::confidential::ValueBlindingFactor::zero(),
             ),
-            wildcard_index: 10,
-            ext_int: lwk_wollet::Chain::External,
+            wildcard_index: Some(10),
+            ext_int: Some(lwk_wollet::Chain::External),
         };
 
         let wallet_tx_out: WalletTxOut = el.clone().into();
@@ -81,6 +81,6 @@ mod tests {
 
         assert_eq!(wallet_tx_out.wildcard_index(), el.wildcard_index);
 
-        assert_eq!(wallet_tx_out.ext_int(), el.ext_int.into());
+        assert_eq!(wallet_tx_out.ext_int(), el.ext_int.map(Into::into));
     }
 }
@@ -56,6 +56,7 @@ impl Contract {
             precision,
             ticker,
             version,
+            extra: Default::default(),
         };
         inner.validate()?; // TODO validate should be the constructor
         Ok(Arc::new(Self { inner }))
@@ -9,11 +9,15 @@ mod desc;
 mod electrum_client;
 mod error;
 mod esplora_client;
+mod lock;
 mod mnemonic;
 mod network;
 mod persister;
+mod portfolio;
 mod precision;
 mod pset;
+mod query;
+mod registry;
 mod signer;
 mod test_env;
 mod tx_builder;
@@ -41,11 +45,15 @@ pub use desc::WolletDescriptor;
 pub use electrum_client::ElectrumClient;
 pub use error::LwkError;
 pub use esplora_client::EsploraClient;
+pub use lock::LockedUtxo;
 pub use mnemonic::Mnemonic;
 pub use network::Network;
 pub use persister::{ForeignPersister, ForeignPersisterLink};
+pub use portfolio::PortfolioAsset;
 pub use precision::Precision;
-pub use pset::{Pset, PsetInput};
+pub use pset::{Pset, PsetAnalysis, PsetInput, PsetInputAnalysis, PsetOutputAnalysis};
+pub use query::{TxPage, TxQuery};
+pub use registry::Registry;
 pub use test_env::TestEnv;
 pub use tx_builder::TxBuilder;
 pub use update::Update;
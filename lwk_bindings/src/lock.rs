@@ -0,0 +1,21 @@
+use std::sync::Arc;
+
+use crate::OutPoint;
+
+/// A wallet utxo excluded from coin selection, see [`lwk_wollet::LockedUtxo`]
+#[derive(uniffi::Record)]
+pub struct LockedUtxo {
+    pub outpoint: Arc<OutPoint>,
+
+    /// The height at which the lock is released, if missing the utxo is locked until unlocked
+    pub expiry: Option<u32>,
+}
+
+impl From<lwk_wollet::LockedUtxo> for LockedUtxo {
+    fn from(locked: lwk_wollet::LockedUtxo) -> Self {
+        Self {
+            outpoint: Arc::new(locked.outpoint.into()),
+            expiry: locked.expiry,
+        }
+    }
+}
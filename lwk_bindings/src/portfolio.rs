@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use crate::{types::AssetId, Txid};
+
+/// An asset held by the wallet, see [`lwk_wollet::PortfolioAsset`]
+#[derive(uniffi::Record)]
+pub struct PortfolioAsset {
+    pub asset: AssetId,
+
+    /// Amount in confirmed outputs
+    pub confirmed: u64,
+
+    /// Amount in unconfirmed outputs of transactions spending wallet outputs, like our change
+    pub trusted_pending: u64,
+
+    /// Amount in unconfirmed outputs of transactions created by others
+    pub untrusted_pending: u64,
+
+    /// Amount in outputs that can't be spent, like immature coinbase outputs
+    pub frozen: u64,
+
+    /// The ticker, if the contract of the asset is known
+    pub ticker: Option<String>,
+
+    /// The name, if the contract of the asset is known
+    pub name: Option<String>,
+
+    /// The precision, 0 if the contract of the asset is not known
+    pub precision: u8,
+
+    /// The total balance formatted according to the precision
+    pub formatted: String,
+
+    /// The issuance transaction of the asset, if made by the wallet
+    pub issuance_txid: Option<Arc<Txid>>,
+}
+
+impl From<lwk_wollet::PortfolioAsset> for PortfolioAsset {
+    fn from(asset: lwk_wollet::PortfolioAsset) -> Self {
+        Self {
+            asset: asset.asset.into(),
+            confirmed: asset.balance.confirmed,
+            trusted_pending: asset.balance.trusted_pending,
+            untrusted_pending: asset.balance.untrusted_pending,
+            frozen: asset.balance.frozen,
+            formatted: asset.format(asset.balance.total()),
+            ticker: asset.ticker,
+            name: asset.name,
+            precision: asset.precision,
+            issuance_txid: asset.issuance.map(|i| Arc::new(i.txid.into())),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{types::AssetId, LwkError, Script, Transaction, Txid};
+use crate::{types::AssetId, Address, LwkError, Network, OutPoint, Script, Transaction, Txid};
 use elements::pset::{Input, PartiallySignedTransaction};
 use std::{fmt::Display, sync::Arc};
 
@@ -42,6 +42,12 @@ impl Pset {
             .map(|i| Arc::new(i.clone().into()))
             .collect()
     }
+
+    /// Decode every input and output, without requiring a wallet, see [`lwk_common::pset_analysis()`]
+    pub fn analysis(&self, network: &Network) -> PsetAnalysis {
+        let network: lwk_wollet::ElementsNetwork = (*network).into();
+        lwk_common::pset_analysis(&self.inner, network.address_params()).into()
+    }
 }
 
 impl Pset {
@@ -105,9 +111,130 @@ impl PsetInput {
     }
 }
 
+/// A PSET decoded without a wallet, see [`lwk_common::PsetAnalysis`]
+#[derive(uniffi::Record)]
+pub struct PsetAnalysis {
+    pub inputs: Vec<PsetInputAnalysis>,
+
+    pub outputs: Vec<PsetOutputAnalysis>,
+
+    /// The sum of the fee outputs
+    pub fee: Option<u64>,
+
+    /// The estimated size once signed, in virtual bytes
+    pub vsize: Option<u64>,
+
+    /// The estimated fee rate in sat/kvB
+    pub fee_rate: Option<f32>,
+}
+
+/// An input of a decoded PSET, see [`lwk_common::PsetInputAnalysis`]
+#[derive(uniffi::Record)]
+pub struct PsetInputAnalysis {
+    pub previous_outpoint: Arc<OutPoint>,
+
+    /// The script pubkey of the previous output, if the PSET contains it
+    pub script_pubkey: Option<Arc<Script>>,
+
+    pub script_type: Option<String>,
+
+    /// The unconfidential address of the previous output
+    pub address: Option<Arc<Address>>,
+
+    /// Whether the previous output is blinded, if the PSET contains it
+    pub blinded: Option<bool>,
+
+    /// The asset, if explicit or proved by the blind asset proof
+    pub asset: Option<AssetId>,
+
+    /// The amount, if explicit or proved by the blind value proof
+    pub satoshi: Option<u64>,
+
+    /// If the input has an issuance or a reissuance, the asset id
+    pub issuance_asset: Option<AssetId>,
+
+    pub sighash: Option<String>,
+
+    /// Fingerprints of the signers that signed the input
+    pub has_signature: Vec<String>,
+
+    /// Fingerprints of the signers that did not sign the input yet
+    pub missing_signature: Vec<String>,
+
+    pub finalized: bool,
+}
+
+/// An output of a decoded PSET, see [`lwk_common::PsetOutputAnalysis`]
+#[derive(uniffi::Record)]
+pub struct PsetOutputAnalysis {
+    pub script_pubkey: Arc<Script>,
+
+    pub script_type: String,
+
+    /// The address, confidential if the output has a blinding key
+    pub address: Option<Arc<Address>>,
+
+    /// Whether the output has the asset or the value commitment
+    pub blinded: bool,
+
+    /// The asset, if explicit or proved by the blind asset proof
+    pub asset: Option<AssetId>,
+
+    /// The amount, if explicit or proved by the blind value proof
+    pub satoshi: Option<u64>,
+}
+
+impl From<lwk_common::PsetAnalysis> for PsetAnalysis {
+    fn from(analysis: lwk_common::PsetAnalysis) -> Self {
+        Self {
+            inputs: analysis.inputs.into_iter().map(Into::into).collect(),
+            outputs: analysis.outputs.into_iter().map(Into::into).collect(),
+            fee: analysis.fee,
+            vsize: analysis.vsize.map(|v| v as u64),
+            fee_rate: analysis.fee_rate,
+        }
+    }
+}
+
+impl From<lwk_common::PsetInputAnalysis> for PsetInputAnalysis {
+    fn from(input: lwk_common::PsetInputAnalysis) -> Self {
+        let fingerprints = |keys: &[(_, elements::bitcoin::bip32::KeySource)]| {
+            keys.iter().map(|(_, (f, _))| f.to_string()).collect()
+        };
+        Self {
+            previous_outpoint: Arc::new(input.previous_outpoint.into()),
+            script_pubkey: input.script_pubkey.map(|s| Arc::new(s.into())),
+            script_type: input.script_type.map(|t| t.to_string()),
+            address: input.address.map(|a| Arc::new(a.into())),
+            blinded: input.blinded,
+            asset: input.asset.map(Into::into),
+            satoshi: input.satoshi,
+            issuance_asset: input.issuance.asset().map(Into::into),
+            sighash: input.sighash.map(|h| h.to_string()),
+            has_signature: fingerprints(&input.signatures.has_signature),
+            missing_signature: fingerprints(&input.signatures.missing_signature),
+            finalized: input.finalized,
+        }
+    }
+}
+
+impl From<lwk_common::PsetOutputAnalysis> for PsetOutputAnalysis {
+    fn from(output: lwk_common::PsetOutputAnalysis) -> Self {
+        Self {
+            script_pubkey: Arc::new(output.script_pubkey.into()),
+            script_type: output.script_type.to_string(),
+            address: output.address.map(|a| Arc::new(a.into())),
+            blinded: output.blinded,
+            asset: output.asset.map(Into::into),
+            satoshi: output.satoshi,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Pset;
+    use crate::Network;
 
     #[test]
     fn pset_roundtrip() {
@@ -132,5 +259,16 @@ mod tests {
 
         assert!(pset_in.issuance_asset().is_none());
         assert!(pset_in.issuance_token().is_none());
+
+        let analysis = pset.analysis(&Network::regtest_default());
+        assert_eq!(analysis.inputs.len(), tx.inputs().len());
+        assert_eq!(analysis.outputs.len(), tx.outputs().len());
+        let input = &analysis.inputs[0];
+        assert_eq!(
+            input.script_pubkey.as_ref().map(|s| s.to_string()),
+            pset_in.previous_script_pubkey().map(|s| s.to_string())
+        );
+        assert!(!input.finalized);
+        assert!(analysis.fee.is_some());
     }
 }
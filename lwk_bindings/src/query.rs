@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use crate::{types::AssetId, Address, WalletTx};
+
+/// A query on the wallet transactions, see [`lwk_wollet::TxQuery`]
+#[derive(uniffi::Record)]
+pub struct TxQuery {
+    /// Skip this number of matching transactions
+    #[uniffi(default = 0)]
+    pub offset: u32,
+
+    /// Return at most this number of transactions
+    #[uniffi(default = None)]
+    pub limit: Option<u32>,
+
+    /// Only the transactions changing the balance of this asset
+    #[uniffi(default = None)]
+    pub asset: Option<AssetId>,
+
+    /// Only the transactions of this type, for instance "incoming" or "outgoing"
+    #[uniffi(default = None)]
+    pub tx_type: Option<String>,
+
+    /// Only the transactions confirmed at this height or later, or unconfirmed
+    #[uniffi(default = None)]
+    pub min_height: Option<u32>,
+
+    /// Only the transactions confirmed at this height or earlier
+    #[uniffi(default = None)]
+    pub max_height: Option<u32>,
+
+    /// Only the transactions sending to or spending from this address
+    #[uniffi(default = None)]
+    pub address: Option<Arc<Address>>,
+}
+
+impl From<TxQuery> for lwk_wollet::TxQuery {
+    fn from(query: TxQuery) -> Self {
+        let mut result = lwk_wollet::TxQuery::new().offset(query.offset as usize);
+        if let Some(limit) = query.limit {
+            result = result.limit(limit as usize);
+        }
+        if let Some(asset) = query.asset {
+            result = result.asset(asset.into());
+        }
+        if let Some(tx_type) = query.tx_type {
+            result = result.tx_type(&tx_type);
+        }
+        if let Some(height) = query.min_height {
+            result = result.min_height(height);
+        }
+        if let Some(height) = query.max_height {
+            result = result.max_height(height);
+        }
+        if let Some(address) = query.address {
+            result = result.address(address.as_ref().as_ref());
+        }
+        result
+    }
+}
+
+/// A page of wallet transactions, see [`lwk_wollet::TxPage`]
+#[derive(uniffi::Record)]
+pub struct TxPage {
+    /// The transactions in the page, most recent first
+    pub txs: Vec<Arc<WalletTx>>,
+
+    /// The number of transactions matching the query, regardless of offset and limit
+    pub total: u32,
+}
+
+impl From<lwk_wollet::TxPage> for TxPage {
+    fn from(page: lwk_wollet::TxPage) -> Self {
+        Self {
+            txs: page.txs.into_iter().map(|tx| Arc::new(tx.into())).collect(),
+            total: page.total as u32,
+        }
+    }
+}
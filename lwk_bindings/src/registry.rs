@@ -0,0 +1,46 @@
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::{types::AssetId, Contract, LwkError};
+
+/// Wrapper over [`lwk_wollet::Registry`]
+#[derive(uniffi::Object, Debug)]
+pub struct Registry {
+    inner: Mutex<lwk_wollet::Registry>,
+}
+
+impl Registry {
+    pub fn inner_registry(
+        &self,
+    ) -> Result<
+        MutexGuard<'_, lwk_wollet::Registry>,
+        PoisonError<MutexGuard<'_, lwk_wollet::Registry>>,
+    > {
+        self.inner.lock()
+    }
+}
+
+#[uniffi::export]
+impl Registry {
+    /// Construct a registry client with an in-memory cache
+    #[uniffi::constructor]
+    pub fn new(url: &str) -> Arc<Self> {
+        Arc::new(Self {
+            inner: Mutex::new(lwk_wollet::Registry::new(url)),
+        })
+    }
+
+    /// Construct a registry client caching the verified data in `cache_dir`
+    #[uniffi::constructor]
+    pub fn with_cache_dir(url: &str, cache_dir: String) -> Result<Arc<Self>, LwkError> {
+        let cache = lwk_wollet::RegistryCache::with_dir(cache_dir)?;
+        Ok(Arc::new(Self {
+            inner: Mutex::new(lwk_wollet::Registry::with_cache(url, cache)),
+        }))
+    }
+
+    /// Fetch the verified contract of the given asset
+    pub fn fetch_contract(&self, asset_id: AssetId) -> Result<Arc<Contract>, LwkError> {
+        let data = self.inner.lock()?.fetch(&asset_id.into())?;
+        Ok(Arc::new(data.contract.into()))
+    }
+}
@@ -148,6 +148,35 @@ impl TxBuilder {
         Ok(())
     }
 
+    /// Blind the (re)issuance amounts, wrapper of [`lwk_wollet::TxBuilder::blind_issuance()`]
+    pub fn blind_issuance(&self, blind_issuance: bool) -> Result<(), LwkError> {
+        let mut lock = self.inner.lock()?;
+        let inner = lock.take().ok_or_else(builder_finished)?;
+        let new_inner = inner.blind_issuance(blind_issuance);
+        *lock = Some(new_inner);
+        Ok(())
+    }
+
+    /// Do not spend unconfirmed utxos received from others,
+    /// wrapper of [`lwk_wollet::TxBuilder::only_trusted()`]
+    pub fn only_trusted(&self, only_trusted: bool) -> Result<(), LwkError> {
+        let mut lock = self.inner.lock()?;
+        let inner = lock.take().ok_or_else(builder_finished)?;
+        let new_inner = inner.only_trusted(only_trusted);
+        *lock = Some(new_inner);
+        Ok(())
+    }
+
+    /// Lock the wallet utxos spent by the created PSET for the given number of blocks,
+    /// wrapper of [`lwk_wollet::TxBuilder::lock_inputs()`]
+    pub fn lock_inputs(&self, blocks: u32) -> Result<(), LwkError> {
+        let mut lock = self.inner.lock()?;
+        let inner = lock.take().ok_or_else(builder_finished)?;
+        let new_inner = inner.lock_inputs(blocks);
+        *lock = Some(new_inner);
+        Ok(())
+    }
+
     /// Enable CT discount
     pub fn enable_ct_discount(&self) -> Result<(), LwkError> {
         let mut lock = self.inner.lock()?;
@@ -3,7 +3,10 @@ use lwk_wollet::NoPersist;
 use crate::desc::WolletDescriptor;
 use crate::network::Network;
 use crate::types::AssetId;
-use crate::{AddressResult, ForeignPersisterLink, LwkError, Pset, Txid, Update, WalletTx};
+use crate::{
+    AddressResult, ForeignPersisterLink, LockedUtxo, LwkError, OutPoint, PortfolioAsset, Pset,
+    Registry, Transaction, TxPage, TxQuery, Txid, Update, WalletTx,
+};
 use std::sync::{MutexGuard, PoisonError};
 use std::{
     collections::HashMap,
@@ -78,6 +81,13 @@ impl Wollet {
         Ok(())
     }
 
+    /// Apply a transaction created by this wallet, see [`lwk_wollet::Wollet::apply_transaction()`]
+    pub fn apply_transaction(&self, tx: &Transaction) -> Result<(), LwkError> {
+        let mut wollet = self.inner.lock()?;
+        wollet.apply_transaction(tx.into())?;
+        Ok(())
+    }
+
     pub fn balance(&self) -> Result<HashMap<AssetId, u64>, LwkError> {
         let m: HashMap<_, _> = self
             .inner
@@ -89,6 +99,71 @@ impl Wollet {
         Ok(m)
     }
 
+    /// Get the balance and metadata of every asset held by the wallet
+    ///
+    /// Tickers and precisions are taken from the contracts cached by `registry`
+    pub fn portfolio(
+        &self,
+        registry: Option<Arc<Registry>>,
+    ) -> Result<Vec<PortfolioAsset>, LwkError> {
+        let wollet = self.inner.lock()?;
+        let portfolio = match registry {
+            Some(registry) => wollet.portfolio(registry.inner_registry()?.cache().contracts())?,
+            None => wollet.portfolio([])?,
+        };
+        Ok(portfolio.into_iter().map(Into::into).collect())
+    }
+
+    /// Get the wallet birthday, the height before which the wallet has no transactions
+    pub fn birthday(&self) -> Result<Option<u32>, LwkError> {
+        Ok(self.inner.lock()?.birthday())
+    }
+
+    /// Set the wallet birthday, so that the following scans skip the previous history
+    pub fn set_birthday(&self, birthday: Option<u32>) -> Result<(), LwkError> {
+        Ok(self.inner.lock()?.set_birthday(birthday)?)
+    }
+
+    /// Lock a wallet utxo, so that it's not spent by the transactions created by the wallet
+    ///
+    /// If `expiry` is set, the lock is released once the wallet tip reaches that height
+    pub fn lock_utxo(&self, outpoint: &OutPoint, expiry: Option<u32>) -> Result<(), LwkError> {
+        Ok(self.inner.lock()?.lock_utxo(outpoint.into(), expiry)?)
+    }
+
+    /// Unlock a wallet utxo, returns false if it wasn't locked
+    pub fn unlock_utxo(&self, outpoint: &OutPoint) -> Result<bool, LwkError> {
+        Ok(self.inner.lock()?.unlock_utxo(&outpoint.into())?)
+    }
+
+    /// Get the locked wallet utxos
+    pub fn locked_utxos(&self) -> Result<Vec<LockedUtxo>, LwkError> {
+        Ok(self
+            .inner
+            .lock()?
+            .locked_utxos()?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    /// Get the wallet transactions matching the query, most recent first
+    pub fn query_transactions(&self, query: TxQuery) -> Result<TxPage, LwkError> {
+        Ok(self.inner.lock()?.query_transactions(&query.into())?.into())
+    }
+
+    /// Get the wallet transactions changing the balance of the given asset
+    pub fn asset_transactions(&self, asset: AssetId) -> Result<Vec<Arc<WalletTx>>, LwkError> {
+        Ok(self
+            .inner
+            .lock()?
+            .asset_transactions(&asset.into())?
+            .into_iter()
+            .map(Into::into)
+            .map(Arc::new)
+            .collect())
+    }
+
     pub fn transactions(&self) -> Result<Vec<Arc<WalletTx>>, LwkError> {
         Ok(self
             .inner
@@ -23,6 +23,24 @@ pub struct Cli {
     #[arg(long, env)]
     pub addr: Option<SocketAddr>,
 
+    /// Credentials to authenticate to the server, in the form "user:password"
+    ///
+    /// If not specified, the credentials in the cookie file are used, if it exists
+    #[arg(long, env)]
+    pub rpc_user: Option<String>,
+
+    /// Cookie file containing the credentials to authenticate to the server
+    ///
+    /// Default is `$HOME/.lwk/<network>/.cookie`
+    #[arg(long, env)]
+    pub rpc_cookie_file: Option<PathBuf>,
+
+    /// PEM file with the certificate chain of the server, if specified the server is reached via TLS
+    ///
+    /// When starting the server, it's the certificate chain served
+    #[arg(long, env)]
+    pub rpc_tls_cert: Option<PathBuf>,
+
     /// The sub command
     #[command(subcommand)]
     pub command: CliCommand,
@@ -42,6 +60,12 @@ pub enum CliCommand {
     /// Asset commands
     Asset(AssetArgs),
 
+    /// PSET commands
+    Pset(PsetArgs),
+
+    /// Commands working without a running server, meant for air-gapped machines
+    Offline(OfflineArgs),
+
     /// Print JSON schema of RPC requests and responses
     ///
     /// E.g. `lwk_cli schema response wallet details` returns the response parameters for
@@ -75,7 +99,8 @@ impl CliCommand {
             self,
             CliCommand::Server(crate::args::ServerArgs {
                 command: ServerCommand::Start { .. },
-            }) | CliCommand::GenerateCompletion { .. }
+            }) | CliCommand::Offline(_)
+                | CliCommand::GenerateCompletion { .. }
         )
     }
 
@@ -85,7 +110,8 @@ impl CliCommand {
             self,
             CliCommand::Server(crate::args::ServerArgs {
                 command: ServerCommand::Start { .. },
-            }) | CliCommand::GenerateCompletion { .. }
+            }) | CliCommand::Offline(_)
+                | CliCommand::GenerateCompletion { .. }
                 | CliCommand::Generate { .. }
         )
     }
@@ -125,6 +151,7 @@ pub enum MainCommand {
     Wallet(WalletSubCommands),
     Signer(SignerSubCommands),
     Asset(AssetSubCommands),
+    Pset(PsetSubCommands),
     Schema,
 }
 
@@ -139,6 +166,7 @@ pub enum ServerSubCommandsEnum {
     // Start is a special command
     Scan,
     Stop,
+    WaitForEvent,
 }
 
 #[derive(Debug, Args)]
@@ -154,6 +182,7 @@ pub enum WalletSubCommandsEnum {
     List,
     Address,
     Balance,
+    Portfolio,
     Send,
     Issue,
     Reissue,
@@ -163,6 +192,11 @@ pub enum WalletSubCommandsEnum {
     Combine,
     PsetDetails,
     Utxos,
+    LockUtxo,
+    UnlockUtxo,
+    LockedUtxos,
+    Backup,
+    Snapshot,
     Txs,
     SetTxMemo,
     SetAddrMemo,
@@ -185,6 +219,8 @@ pub enum SignerSubCommandsEnum {
     Details,
     List,
     Sign,
+    SetPolicy,
+    SignedPsets,
     SinglesigDesc,
     Xpub,
 }
@@ -205,6 +241,17 @@ pub enum AssetSubCommandsEnum {
     Publish,
 }
 
+#[derive(Debug, Args)]
+pub struct PsetSubCommands {
+    #[command(subcommand)]
+    pub command: PsetSubCommandsEnum,
+}
+
+#[derive(Debug, Subcommand, ValueEnum, Clone)]
+pub enum PsetSubCommandsEnum {
+    Decode,
+}
+
 #[derive(ValueEnum, Clone, Debug)]
 pub enum SignerKind {
     Software,
@@ -316,6 +363,57 @@ pub enum SignerCommand {
         pset: String,
     },
 
+    /// Set the spending policy checked before the signer signs a PSET
+    ///
+    /// The policy replaces the previous one, if any.
+    SetPolicy {
+        #[arg(short, long, env)]
+        signer: String,
+
+        /// The wallet whose balance changes are checked
+        #[arg(long, required_unless_present = "remove")]
+        wallet: Option<String>,
+
+        /// Maximum satoshi sent in the last 24 hours in the form "asset_id:satoshi"
+        ///
+        /// Can be specified multiple times.
+        #[arg(long)]
+        daily_limit: Vec<String>,
+
+        /// Address allowed to receive funds from the wallet
+        ///
+        /// Can be specified multiple times. If not specified any address is allowed.
+        #[arg(long)]
+        allowed_address: Vec<String>,
+
+        /// Maximum fee rate in sat/kvB
+        #[arg(long)]
+        max_fee_rate: Option<f32>,
+
+        /// Asset whose wallet balance can change
+        ///
+        /// Can be specified multiple times. If not specified any asset is allowed.
+        #[arg(long)]
+        allowed_asset: Vec<String>,
+
+        /// Hours in UTC during which signing is allowed in the form "start-end", e.g. "9-17"
+        ///
+        /// Can be specified multiple times. If not specified signing is always allowed.
+        #[arg(long)]
+        time_window: Vec<String>,
+
+        /// Remove the spending policy of the signer
+        #[arg(long, conflicts_with = "wallet")]
+        remove: bool,
+    },
+
+    /// List the PSETs signed by the server
+    SignedPsets {
+        /// Only the PSETs signed by this signer
+        #[arg(short, long, env)]
+        signer: Option<String>,
+    },
+
     ///  Prints a singlesig descriptor using this signer key
     SinglesigDesc {
         #[arg(short, long, env)]
@@ -411,8 +509,17 @@ pub enum WalletCommand {
         #[arg(short, long, env)]
         wallet: String,
 
-        #[arg(short, long)]
-        descriptor: String,
+        #[arg(short, long, required_unless_present = "backup")]
+        descriptor: Option<String>,
+
+        /// Load the wallet from a backup file created with `wallet backup`, restoring its labels
+        #[arg(long, conflicts_with = "descriptor")]
+        backup: Option<PathBuf>,
+
+        /// The height before which the wallet has no transactions, so that scans skip the
+        /// previous history
+        #[arg(long, conflicts_with = "backup")]
+        birthday_height: Option<u32>,
     },
 
     /// Unload a wallet
@@ -465,6 +572,14 @@ pub enum WalletCommand {
         with_tickers: bool,
     },
 
+    /// Get the balance of every asset of the given wallet name, split by confirmation status,
+    /// with tickers and precisions of the inserted assets
+    Portfolio {
+        /// Wallet name
+        #[arg(short, long, env)]
+        wallet: String,
+    },
+
     /// Create an unsigned transaction (PSET)
     Send {
         /// Wallet name
@@ -489,6 +604,11 @@ pub enum WalletCommand {
         /// Note: this field will be removed once discounted CT will be enabled on all networks.
         #[arg(long)]
         enable_ct_discount: bool,
+
+        /// Lock the wallet inputs of the created PSET for this number of blocks, so that other
+        /// PSETs don't spend them, unless it's broadcast before
+        #[arg(long)]
+        lock_inputs: Option<u32>,
     },
 
     /// Drain the wallet of the policy asset
@@ -504,6 +624,11 @@ pub enum WalletCommand {
         /// Fee rate to use
         #[arg(long)]
         fee_rate: Option<f32>,
+
+        /// Lock the wallet inputs of the created PSET for this number of blocks, so that other
+        /// PSETs don't spend them, unless it's broadcast before
+        #[arg(long)]
+        lock_inputs: Option<u32>,
     },
 
     /// Issue an asset
@@ -538,6 +663,16 @@ pub enum WalletCommand {
         /// To optionally specify a fee
         #[arg(long)]
         fee_rate: Option<f32>,
+
+        /// Blind the issued amounts, so that only this wallet knows them.
+        /// Note that the reissuance token differs from the one of an explicit issuance
+        #[arg(long)]
+        blind_issuance: bool,
+
+        /// Lock the wallet inputs of the created PSET for this number of blocks, so that other
+        /// PSETs don't spend them, unless it's broadcast before
+        #[arg(long)]
+        lock_inputs: Option<u32>,
     },
 
     /// Reissue a previously issued asset, needs ownership of the issuance token
@@ -563,6 +698,15 @@ pub enum WalletCommand {
         /// To optionally specify a fee
         #[arg(long)]
         fee_rate: Option<f32>,
+
+        /// Blind the re-issued amount, so that only this wallet knows it
+        #[arg(long)]
+        blind_issuance: bool,
+
+        /// Lock the wallet inputs of the created PSET for this number of blocks, so that other
+        /// PSETs don't spend them, unless it's broadcast before
+        #[arg(long)]
+        lock_inputs: Option<u32>,
     },
 
     /// Burn an asset
@@ -583,6 +727,11 @@ pub enum WalletCommand {
         /// To optionally specify a fee
         #[arg(long)]
         fee_rate: Option<f32>,
+
+        /// Lock the wallet inputs of the created PSET for this number of blocks, so that other
+        /// PSETs don't spend them, unless it's broadcast before
+        #[arg(long)]
+        lock_inputs: Option<u32>,
     },
 
     /// Print a multisig descriptor
@@ -656,6 +805,64 @@ pub enum WalletCommand {
         wallet: String,
     },
 
+    /// Lock a wallet unspent transaction output, so that it's not spent by created transactions
+    LockUtxo {
+        /// Wallet name
+        #[arg(short, long, env)]
+        wallet: String,
+
+        /// Transaction id
+        #[arg(long)]
+        txid: String,
+
+        /// Output index
+        #[arg(long)]
+        vout: u32,
+
+        /// Release the lock after the given number of blocks, if missing the output is locked
+        /// until unlocked
+        #[arg(long)]
+        blocks: Option<u32>,
+    },
+
+    /// Unlock a wallet unspent transaction output
+    UnlockUtxo {
+        /// Wallet name
+        #[arg(short, long, env)]
+        wallet: String,
+
+        /// Transaction id
+        #[arg(long)]
+        txid: String,
+
+        /// Output index
+        #[arg(long)]
+        vout: u32,
+    },
+
+    /// Get the wallet backup file, containing descriptor, network, gap limit, labels and
+    /// birthday height
+    Backup {
+        /// Wallet name
+        #[arg(short, long, env)]
+        wallet: String,
+    },
+
+    /// Get the wallet snapshot file, containing the backup and the wallet updates, used by
+    /// `offline wallet` commands to create transactions without a server
+    Snapshot {
+        /// Wallet name
+        #[arg(short, long, env)]
+        wallet: String,
+    },
+
+    /// Get the locked wallet unspent transaction outputs
+    LockedUtxos {
+        /// Wallet name
+        #[arg(short, long, env)]
+        wallet: String,
+    },
+
     /// Get the wallet transactions
     Txs {
         /// Wallet name
@@ -665,6 +872,34 @@ pub enum WalletCommand {
         /// Replace asset ids with tickers when possible
         #[arg(long, action)]
         with_tickers: bool,
+
+        /// Only list the transactions changing the balance of this asset
+        #[arg(long)]
+        asset: Option<String>,
+
+        /// Only list the transactions of this type, for instance "incoming" or "outgoing"
+        #[arg(long = "type")]
+        tx_type: Option<String>,
+
+        /// Only list the transactions confirmed at this height or later, or unconfirmed
+        #[arg(long)]
+        min_height: Option<u32>,
+
+        /// Only list the transactions confirmed at this height or earlier
+        #[arg(long)]
+        max_height: Option<u32>,
+
+        /// Only list the transactions sending to or spending from this address
+        #[arg(long)]
+        address: Option<String>,
+
+        /// Skip this number of transactions
+        #[arg(long, default_value_t = 0)]
+        offset: usize,
+
+        /// List at most this number of transactions
+        #[arg(long)]
+        limit: Option<usize>,
     },
 
     /// Get a transaction
@@ -713,6 +948,97 @@ pub enum WalletCommand {
     },
 }
 
+#[derive(Debug, Args)]
+pub struct PsetArgs {
+    #[command(subcommand)]
+    pub command: PsetCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PsetCommand {
+    /// Decode every input and output of a PSET, without requiring a wallet
+    ///
+    /// Blinded amounts and assets are shown only if the PSET proves them with blind proofs.
+    Decode {
+        /// PSET in base64 format
+        #[arg(long)]
+        pset: String,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct OfflineArgs {
+    #[command(subcommand)]
+    pub command: OfflineCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum OfflineCommand {
+    /// Sign a PSET with a software signer loaded from its mnemonic
+    ///
+    /// Returns the signed PSET and the signatures status of every input.
+    Sign {
+        /// File containing the mnemonic, "-" to read it from the standard input
+        #[arg(long)]
+        mnemonic_file: PathBuf,
+
+        /// File containing the PSET in base64 format
+        #[arg(long)]
+        pset_file: PathBuf,
+    },
+
+    /// Wallet commands working on a snapshot file created with `wallet snapshot`
+    Wallet(OfflineWalletArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct OfflineWalletArgs {
+    #[command(subcommand)]
+    pub command: OfflineWalletCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum OfflineWalletCommand {
+    /// Get the balance of the wallet at the time of the snapshot
+    Balance {
+        /// Snapshot file created with `wallet snapshot`
+        #[arg(long)]
+        snapshot: PathBuf,
+    },
+
+    /// Create an unsigned transaction (PSET) spending the wallet outputs of the snapshot
+    Send {
+        /// Snapshot file created with `wallet snapshot`
+        #[arg(long)]
+        snapshot: PathBuf,
+
+        /// Specify a recipient in the form "address:satoshi:asset_id"
+        ///
+        /// Can be specified multiple times.
+        #[arg(long, required = true)]
+        recipient: Vec<String>,
+
+        /// Fee rate to use
+        #[arg(long)]
+        fee_rate: Option<f32>,
+
+        /// Enable ELIP200 discounted fees for Confidential Transactions
+        #[arg(long)]
+        enable_ct_discount: bool,
+    },
+
+    /// Get the details of a PSET from the point of view of the wallet
+    PsetDetails {
+        /// Snapshot file created with `wallet snapshot`
+        #[arg(long)]
+        snapshot: PathBuf,
+
+        /// File containing the PSET in base64 format
+        #[arg(long)]
+        pset_file: PathBuf,
+    },
+}
+
 #[derive(Debug, Args)]
 pub struct AssetArgs {
     #[command(subcommand)]
@@ -817,6 +1143,7 @@ pub struct ServerArgs {
 }
 
 #[derive(Debug, Subcommand)]
+#[allow(clippy::large_enum_variant)] // parsed once, boxing the start options isn't worth it
 pub enum ServerCommand {
     /// Start the server
     Start {
@@ -846,6 +1173,50 @@ pub enum ServerCommand {
         /// Interval between blockchain scans (seconds)
         #[arg(long)]
         scanning_interval: Option<u64>,
+
+        /// URL receiving a POST request with every wallet event, can be specified multiple times
+        #[arg(long)]
+        webhook: Vec<String>,
+
+        /// Require RPC authentication, writing random credentials in the cookie file
+        /// `<datadir>/<network>/.cookie`
+        #[arg(long)]
+        rpc_cookie: bool,
+
+        /// User allowed to call every RPC method, in the form "user:password"
+        ///
+        /// Can be specified multiple times.
+        #[arg(long)]
+        rpc_auth_user: Vec<String>,
+
+        /// User allowed to call only read-only RPC methods, which cannot sign, change the server
+        /// state or disclose the wallet descriptors, in the form "user:password"
+        ///
+        /// Can be specified multiple times.
+        #[arg(long)]
+        rpc_read_only_user: Vec<String>,
+
+        /// PEM file with the private key of the certificate in `--rpc-tls-cert`, serve the RPC via TLS
+        ///
+        /// Requires `--rpc-tls-cert`.
+        #[arg(long)]
+        rpc_tls_key: Option<PathBuf>,
+
+        /// Encrypt the server state, including the mnemonics of persisted signers, with a key
+        /// derived from this passphrase
+        #[arg(long, env, conflicts_with = "state_key_file")]
+        state_passphrase: Option<String>,
+
+        /// Encrypt the server state with a key derived from the content of this file
+        #[arg(long, env)]
+        state_key_file: Option<PathBuf>,
+
+        /// Serve also wallets of this network, in the form "<network>[=<electrum_url>]"
+        ///
+        /// Wallets are loaded on the network specified with `--network`. Can be specified
+        /// multiple times.
+        #[arg(long)]
+        additional_network: Vec<String>,
     },
 
     /// Wait until an entire blockchain scan has been completed
@@ -855,4 +1226,17 @@ pub enum ServerCommand {
     ///
     /// Alternatively the server can be stopped also with SIGINT (ctrl-c)
     Stop,
+
+    /// Wait for wallet events, like new transactions, confirmations or balance changes
+    WaitForEvent {
+        /// Return only events with an id greater than this one
+        ///
+        /// If not specified, only events happening after the call are returned
+        #[arg(long)]
+        after: Option<u64>,
+
+        /// Maximum time to wait for an event (seconds)
+        #[arg(long)]
+        timeout: Option<u64>,
+    },
 }
@@ -3,21 +3,29 @@
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
 use std::{
+    io::Read,
     net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::{Path, PathBuf},
     time::Duration,
 };
 
 use anyhow::{anyhow, Context};
-use clap::CommandFactory;
+use clap::{CommandFactory, ValueEnum};
 use env_logger::Env;
-use lwk_app::Config;
+use lwk_app::offline::OfflineWallet;
+use lwk_app::{Config, RpcUser, StateSecret};
+use lwk_rpc_model::request;
 use serde_json::Value;
 
-use crate::args::{AssetCommand, CliCommand, Network, ServerCommand, SignerCommand, WalletCommand};
+use crate::args::{
+    AssetCommand, CliCommand, Network, OfflineCommand, OfflineWalletCommand, PsetCommand,
+    ServerCommand, SignerCommand, WalletCommand,
+};
 pub use args::Cli;
 
 pub use args::{
-    AssetSubCommandsEnum, ServerSubCommandsEnum, SignerSubCommandsEnum, WalletSubCommandsEnum,
+    AssetSubCommandsEnum, PsetSubCommandsEnum, ServerSubCommandsEnum, SignerSubCommandsEnum,
+    WalletSubCommandsEnum,
 };
 
 mod args;
@@ -51,16 +59,38 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
     let addr = args
         .addr
         .unwrap_or_else(|| SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), default_port));
-    let client = lwk_app::Client::new(addr)?;
+    // A server serving this network in addition to its main one writes its address in the datadir
+    let server_addr_path = Config::default_home()?.join(network).join(".server_addr");
+    let server_addr = match (args.addr, std::fs::read_to_string(server_addr_path)) {
+        (None, Ok(server_addr)) => server_addr.trim().parse()?,
+        _ => addr,
+    };
+    let credentials = match args.rpc_user.as_deref() {
+        Some(user) => Some(parse_credentials(user)?),
+        None => {
+            let cookie = match args.rpc_cookie_file.clone() {
+                Some(path) => path,
+                None => Config::default_home()?.join(network).join(".cookie"),
+            };
+            // The cookie file exists only if the server requires it
+            cookie
+                .exists()
+                .then(|| lwk_app::read_cookie(&cookie))
+                .transpose()?
+        }
+    };
+    let client =
+        lwk_app::Client::with_auth(server_addr, credentials, args.rpc_tls_cert.as_deref())?;
 
     // verify the server is up if needed
     if args.command.requires_server_running() {
         let version = client
             .version()
-            .with_context(|| format!("Is the server at {:?} running?", addr))?;
+            .with_context(|| format!("Is the server at {:?} running?", server_addr))?;
         let server_network = version.network;
 
-        if server_network != network {
+        // A server can host wallets of multiple networks
+        if server_network != network && !version.networks.iter().any(|n| n == network) {
             return Err(anyhow!(
                 "Inconsistent networks (cli: {network}, server: {server_network})",
             ));
@@ -68,90 +98,131 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
     }
 
     Ok(match args.command {
-        CliCommand::Server(a) => {
-            match a.command {
-                ServerCommand::Start {
-                    electrum_url,
-                    #[cfg(feature = "registry")]
-                    registry_url,
-                    esplora_api_url,
-                    datadir,
-                    timeout,
-                    scanning_interval,
-                } => {
-                    let (tx, rx) = std::sync::mpsc::channel();
-                    let _ = ctrlc::try_set_handler(move || {
-                        tx.send(()).expect("Could not send signal on channel.")
-                    });
+        CliCommand::Server(a) => match a.command {
+            ServerCommand::Start {
+                electrum_url,
+                #[cfg(feature = "registry")]
+                registry_url,
+                esplora_api_url,
+                datadir,
+                timeout,
+                scanning_interval,
+                webhook,
+                rpc_cookie,
+                rpc_auth_user,
+                rpc_read_only_user,
+                rpc_tls_key,
+                state_passphrase,
+                state_key_file,
+                additional_network,
+            } => {
+                let (tx, rx) = std::sync::mpsc::channel();
+                let _ = ctrlc::try_set_handler(move || {
+                    tx.send(()).expect("Could not send signal on channel.")
+                });
 
-                    // start the app with default host/port
-                    let datadir = datadir.unwrap_or_else(|| {
-                        Config::default_home().unwrap_or(std::path::PathBuf::from("."))
-                    });
-                    let mut config = match args.network {
-                        Network::Mainnet => Config::default_mainnet(datadir),
-                        Network::Testnet => Config::default_testnet(datadir),
-                        Network::Regtest => Config::default_regtest(datadir),
-                    };
-                    if let Some(timeout) = timeout {
-                        config.timeout = Duration::from_secs(timeout);
-                    };
-                    if let Some(scanning_interval) = scanning_interval {
-                        config.scanning_interval = Duration::from_secs(scanning_interval);
+                // start the app with default host/port
+                let datadir = datadir.unwrap_or_else(|| {
+                    Config::default_home().unwrap_or(std::path::PathBuf::from("."))
+                });
+                let mut config = default_config(&args.network, datadir.clone());
+                for additional in additional_network {
+                    let (name, electrum_url) = match additional.split_once('=') {
+                        Some((name, url)) => (name, Some(url)),
+                        None => (additional.as_str(), None),
                     };
+                    let additional = Network::from_str(name, true).map_err(|e| anyhow!(e))?;
+                    let mut other = default_config(&additional, datadir.clone());
                     if let Some(url) = electrum_url {
-                        config.electrum_url = url;
-                    } else if let Network::Regtest = args.network {
-                        anyhow::bail!("on regtest you have to specify --electrum-url");
-                    };
-                    if let Some(url) = esplora_api_url {
-                        config.esplora_api_url = url;
-                    };
+                        other.electrum_url = url.to_string();
+                    } else if let Network::Regtest = additional {
+                        anyhow::bail!("on regtest you have to specify the electrum url");
+                    }
+                    config.add_network(&other)?;
+                }
+                if let Some(timeout) = timeout {
+                    config.timeout = Duration::from_secs(timeout);
+                };
+                if let Some(scanning_interval) = scanning_interval {
+                    config.scanning_interval = Duration::from_secs(scanning_interval);
+                };
+                config.webhooks = webhook;
+                config.rpc_cookie = rpc_cookie;
+                for (users, read_only) in [(rpc_auth_user, false), (rpc_read_only_user, true)] {
+                    for user in users {
+                        let (name, password) = parse_credentials(&user)?;
+                        config.rpc_users.push(RpcUser {
+                            name,
+                            password,
+                            read_only,
+                        });
+                    }
+                }
+                config.rpc_tls_cert = args.rpc_tls_cert.clone();
+                config.rpc_tls_key = rpc_tls_key;
+                config.state_secret = match (state_passphrase, state_key_file) {
+                    (Some(passphrase), _) => Some(StateSecret::Passphrase(passphrase)),
+                    (None, Some(path)) => Some(StateSecret::KeyFile(path)),
+                    (None, None) => None,
+                };
+                if let Some(url) = electrum_url {
+                    config.electrum_url = url;
+                } else if let Network::Regtest = args.network {
+                    anyhow::bail!("on regtest you have to specify --electrum-url");
+                };
+                if let Some(url) = esplora_api_url {
+                    config.esplora_api_url = url;
+                };
 
-                    #[cfg(feature = "registry")]
-                    if let Some(url) = registry_url {
-                        config.registry_url = url;
-                    };
+                #[cfg(feature = "registry")]
+                if let Some(url) = registry_url {
+                    config.registry_url = url;
+                };
 
-                    config.addr = addr;
-                    let mut app = lwk_app::App::new(config)?;
+                config.addr = addr;
+                let mut app = lwk_app::App::new(config.clone())?;
 
-                    app.run()?;
+                app.run()?;
 
-                    // get the app version
-                    let version = client.version()?.version;
-                    log::info!("App running version {}", version);
+                // get the app version, the cookie credentials are created at startup
+                let client = lwk_app::Client::from_config(&config)?;
+                let version = client.version()?.version;
+                log::info!("App running version {}", version);
 
-                    loop {
-                        match rx.recv_timeout(Duration::from_millis(100)) {
-                            Ok(_) => {
-                                log::debug!("Received ctrl-c signal");
+                loop {
+                    match rx.recv_timeout(Duration::from_millis(100)) {
+                        Ok(_) => {
+                            log::debug!("Received ctrl-c signal");
+                            break;
+                        }
+                        Err(_) => {
+                            if app.is_running().unwrap_or(false) {
+                                continue;
+                            } else {
+                                log::debug!("Received stop signal");
                                 break;
                             }
-                            Err(_) => {
-                                if app.is_running().unwrap_or(false) {
-                                    continue;
-                                } else {
-                                    log::debug!("Received stop signal");
-                                    break;
-                                }
-                            }
                         }
                     }
-                    app.stop()?;
-                    app.join_threads()?;
-                    log::info!("Threads ended");
-                }
-                ServerCommand::Scan => {
-                    client.scan()?;
-                }
-                ServerCommand::Stop => {
-                    client.stop()?;
                 }
+                app.stop()?;
+                app.join_threads()?;
+                log::info!("Threads ended");
+                Value::Null
             }
-
-            Value::Null
-        }
+            ServerCommand::Scan => {
+                client.scan()?;
+                Value::Null
+            }
+            ServerCommand::Stop => {
+                client.stop()?;
+                Value::Null
+            }
+            ServerCommand::WaitForEvent { after, timeout } => {
+                let r = client.wait_for_event(after, timeout)?;
+                serde_json::to_value(r)?
+            }
+        },
         CliCommand::Signer(a) => match a.command {
             SignerCommand::Generate => {
                 let j = client.signer_generate()?;
@@ -165,6 +236,40 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
                 let r = client.signer_sign(signer, pset)?;
                 serde_json::to_value(r)?
             }
+            SignerCommand::SetPolicy {
+                signer,
+                wallet,
+                daily_limit,
+                allowed_address,
+                max_fee_rate,
+                allowed_asset,
+                time_window,
+                remove,
+            } => {
+                let policy = match wallet {
+                    Some(wallet) if !remove => Some(request::SpendingPolicy {
+                        wallet,
+                        daily_limits: daily_limit
+                            .iter()
+                            .map(|l| parse_daily_limit(l))
+                            .collect::<Result<_, _>>()?,
+                        allowed_addresses: allowed_address,
+                        max_fee_rate,
+                        allowed_assets: allowed_asset,
+                        time_windows: time_window
+                            .iter()
+                            .map(|w| parse_time_window(w))
+                            .collect::<Result<_, _>>()?,
+                    }),
+                    _ => None,
+                };
+                let r = client.signer_set_policy(signer, policy)?;
+                serde_json::to_value(r)?
+            }
+            SignerCommand::SignedPsets { signer } => {
+                let r = client.signer_signed_psets(signer)?;
+                serde_json::to_value(r)?
+            }
             SignerCommand::LoadSoftware {
                 signer,
                 mnemonic,
@@ -220,8 +325,26 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
             }
         },
         CliCommand::Wallet(a) => match a.command {
-            WalletCommand::Load { descriptor, wallet } => {
-                let r = client.wallet_load(descriptor, wallet)?;
+            WalletCommand::Load {
+                descriptor,
+                wallet,
+                backup,
+                birthday_height,
+            } => {
+                let r = match (descriptor, backup) {
+                    (_, Some(path)) => {
+                        let backup = std::fs::read_to_string(&path)
+                            .with_context(|| format!("failed to read backup file {path:?}"))?;
+                        client.wallet_restore(backup, wallet)?
+                    }
+                    (Some(descriptor), None) => client.wallet_load(
+                        descriptor,
+                        wallet,
+                        Some(network.to_string()),
+                        birthday_height,
+                    )?,
+                    (None, None) => anyhow::bail!("Either descriptor or backup must be given"),
+                };
                 serde_json::to_value(r)?
             }
             WalletCommand::Unload { wallet } => {
@@ -235,11 +358,16 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
                 let r = client.wallet_balance(wallet, with_tickers)?;
                 serde_json::to_value(r)?
             }
+            WalletCommand::Portfolio { wallet } => {
+                let r = client.wallet_portfolio(wallet)?;
+                serde_json::to_value(r)?
+            }
             WalletCommand::Send {
                 wallet,
                 recipient,
                 fee_rate,
                 enable_ct_discount,
+                lock_inputs,
             } => {
                 let mut addressees = vec![];
                 for rec in recipient {
@@ -249,16 +377,22 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
                     );
                 }
 
-                let r =
-                    client.wallet_send_many(wallet, addressees, fee_rate, enable_ct_discount)?;
+                let r = client.wallet_send_many(
+                    wallet,
+                    addressees,
+                    fee_rate,
+                    enable_ct_discount,
+                    lock_inputs,
+                )?;
                 serde_json::to_value(r)?
             }
             WalletCommand::Drain {
                 wallet,
                 address,
                 fee_rate,
+                lock_inputs,
             } => {
-                let r = client.wallet_drain(wallet, address, fee_rate)?;
+                let r = client.wallet_drain(wallet, address, fee_rate, lock_inputs)?;
                 serde_json::to_value(r)?
             }
             WalletCommand::Address {
@@ -280,6 +414,8 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
                 address_token,
                 contract,
                 fee_rate,
+                blind_issuance,
+                lock_inputs,
             } => {
                 let r = client.wallet_issue(
                     wallet,
@@ -289,6 +425,8 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
                     address_token,
                     contract,
                     fee_rate,
+                    blind_issuance,
+                    lock_inputs,
                 )?;
                 serde_json::to_value(r)?
             }
@@ -298,9 +436,18 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
                 satoshi_asset,
                 address_asset,
                 fee_rate,
+                blind_issuance,
+                lock_inputs,
             } => {
-                let r =
-                    client.wallet_reissue(wallet, asset, satoshi_asset, address_asset, fee_rate)?;
+                let r = client.wallet_reissue(
+                    wallet,
+                    asset,
+                    satoshi_asset,
+                    address_asset,
+                    fee_rate,
+                    blind_issuance,
+                    lock_inputs,
+                )?;
                 serde_json::to_value(r)?
             }
             WalletCommand::Burn {
@@ -308,8 +455,9 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
                 asset,
                 satoshi_asset,
                 fee_rate,
+                lock_inputs,
             } => {
-                let r = client.wallet_burn(wallet, asset, satoshi_asset, fee_rate)?;
+                let r = client.wallet_burn(wallet, asset, satoshi_asset, fee_rate, lock_inputs)?;
                 serde_json::to_value(r)?
             }
             WalletCommand::MultisigDesc {
@@ -354,11 +502,53 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
                 let r = client.wallet_utxos(wallet)?;
                 serde_json::to_value(r)?
             }
+            WalletCommand::LockUtxo {
+                wallet,
+                txid,
+                vout,
+                blocks,
+            } => {
+                let r = client.wallet_lock_utxo(wallet, txid, vout, blocks)?;
+                serde_json::to_value(r)?
+            }
+            WalletCommand::UnlockUtxo { wallet, txid, vout } => {
+                let r = client.wallet_unlock_utxo(wallet, txid, vout)?;
+                serde_json::to_value(r)?
+            }
+            WalletCommand::Backup { wallet } => {
+                let r = client.wallet_backup(wallet)?;
+                serde_json::from_str(&r.backup)?
+            }
+            WalletCommand::Snapshot { wallet } => {
+                let r = client.wallet_snapshot(wallet)?;
+                serde_json::to_value(r)?
+            }
+            WalletCommand::LockedUtxos { wallet } => {
+                let r = client.wallet_locked_utxos(wallet)?;
+                serde_json::to_value(r)?
+            }
             WalletCommand::Txs {
                 wallet,
                 with_tickers,
+                asset,
+                tx_type,
+                min_height,
+                max_height,
+                address,
+                offset,
+                limit,
             } => {
-                let r = client.wallet_txs(wallet, with_tickers)?;
+                let r = client.wallet_txs(
+                    wallet,
+                    with_tickers,
+                    asset,
+                    tx_type,
+                    min_height,
+                    max_height,
+                    address,
+                    offset,
+                    limit,
+                )?;
                 serde_json::to_value(r)?
             }
             WalletCommand::Tx {
@@ -382,6 +572,60 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
                 serde_json::to_value(r)?
             }
         },
+        CliCommand::Pset(a) => match a.command {
+            PsetCommand::Decode { pset } => {
+                let r = client.pset_decode(pset, None)?;
+                serde_json::to_value(r)?
+            }
+        },
+        CliCommand::Offline(a) => {
+            let network = default_config(&args.network, PathBuf::new()).network;
+            match a.command {
+                OfflineCommand::Sign {
+                    mnemonic_file,
+                    pset_file,
+                } => {
+                    let mnemonic = read_input(&mnemonic_file)?;
+                    let pset = read_input(&pset_file)?;
+                    let r = lwk_app::offline::sign(network, &mnemonic, pset.trim())?;
+                    serde_json::to_value(r)?
+                }
+                OfflineCommand::Wallet(w) => match w.command {
+                    OfflineWalletCommand::Balance { snapshot } => {
+                        let wallet =
+                            OfflineWallet::from_snapshot(network, &read_input(&snapshot)?)?;
+                        serde_json::to_value(wallet.balance()?)?
+                    }
+                    OfflineWalletCommand::Send {
+                        snapshot,
+                        recipient,
+                        fee_rate,
+                        enable_ct_discount,
+                    } => {
+                        let mut addressees = vec![];
+                        for rec in recipient {
+                            addressees.push(
+                                rec.try_into()
+                                    .with_context(|| "error parsing recipient argument")?,
+                            );
+                        }
+                        let wallet =
+                            OfflineWallet::from_snapshot(network, &read_input(&snapshot)?)?;
+                        let r = wallet.send_many(addressees, fee_rate, enable_ct_discount)?;
+                        serde_json::to_value(r)?
+                    }
+                    OfflineWalletCommand::PsetDetails {
+                        snapshot,
+                        pset_file,
+                    } => {
+                        let wallet =
+                            OfflineWallet::from_snapshot(network, &read_input(&snapshot)?)?;
+                        let pset = read_input(&pset_file)?;
+                        serde_json::to_value(wallet.pset_details(pset.trim())?)?
+                    }
+                },
+            }
+        }
         CliCommand::Asset(a) => match a.command {
             AssetCommand::Contract {
                 domain,
@@ -419,11 +663,11 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
                 serde_json::to_value(r)?
             }
             AssetCommand::FromExplorer { asset } => {
-                let r = client.asset_from_explorer(asset)?;
+                let r = client.asset_from_explorer(asset, Some(network.to_string()))?;
                 serde_json::to_value(r)?
             }
             AssetCommand::Publish { asset } => {
-                let r = client.asset_publish(asset)?;
+                let r = client.asset_publish(asset, Some(network.to_string()))?;
                 serde_json::to_value(r)?
             }
         },
@@ -442,3 +686,52 @@ pub fn inner_main(args: args::Cli) -> anyhow::Result<Value> {
         }
     })
 }
+
+/// Parse credentials in the form "user:password"
+fn parse_credentials(s: &str) -> anyhow::Result<(String, String)> {
+    let (user, password) = s
+        .split_once(':')
+        .ok_or_else(|| anyhow!("credentials must be in the form \"user:password\""))?;
+    Ok((user.to_string(), password.to_string()))
+}
+
+/// Parse a daily limit in the form "asset_id:satoshi"
+fn parse_daily_limit(s: &str) -> anyhow::Result<(String, u64)> {
+    let (asset, satoshi) = s
+        .split_once(':')
+        .ok_or_else(|| anyhow!("daily limit must be in the form \"asset_id:satoshi\""))?;
+    let satoshi = satoshi
+        .parse()
+        .with_context(|| "error parsing daily limit satoshi")?;
+    Ok((asset.to_string(), satoshi))
+}
+
+/// Parse a time window in the form "start-end"
+fn parse_time_window(s: &str) -> anyhow::Result<request::TimeWindow> {
+    let (start, end) = s
+        .split_once('-')
+        .ok_or_else(|| anyhow!("time window must be in the form \"start-end\""))?;
+    Ok(request::TimeWindow {
+        start_hour: start.parse().with_context(|| "error parsing time window")?,
+        end_hour: end.parse().with_context(|| "error parsing time window")?,
+    })
+}
+
+/// Read the content of the file at `path`, or the standard input if `path` is "-"
+fn read_input(path: &Path) -> anyhow::Result<String> {
+    if path.as_os_str() == "-" {
+        let mut content = String::new();
+        std::io::stdin().read_to_string(&mut content)?;
+        Ok(content)
+    } else {
+        std::fs::read_to_string(path).with_context(|| format!("failed to read file {path:?}"))
+    }
+}
+
+fn default_config(network: &Network, datadir: std::path::PathBuf) -> Config {
+    match network {
+        Network::Mainnet => Config::default_mainnet(datadir),
+        Network::Testnet => Config::default_testnet(datadir),
+        Network::Regtest => Config::default_regtest(datadir),
+    }
+}
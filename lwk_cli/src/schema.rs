@@ -3,8 +3,8 @@ use lwk_rpc_model::request::Direction;
 use serde_json::Value;
 
 use crate::args::{
-    AssetSubCommandsEnum, DirectionCommand, MainCommand, SchemaArgs, ServerSubCommandsEnum,
-    SignerSubCommandsEnum, WalletSubCommandsEnum,
+    AssetSubCommandsEnum, DirectionCommand, MainCommand, PsetSubCommandsEnum, SchemaArgs,
+    ServerSubCommandsEnum, SignerSubCommandsEnum, WalletSubCommandsEnum,
 };
 
 pub(crate) fn schema(a: SchemaArgs, client: Client) -> Result<Value, anyhow::Error> {
@@ -14,6 +14,7 @@ pub(crate) fn schema(a: SchemaArgs, client: Client) -> Result<Value, anyhow::Err
             MainCommand::Wallet(w) => client.schema(w.command.into(), Direction::Request)?,
             MainCommand::Signer(s) => client.schema(s.command.into(), Direction::Request)?,
             MainCommand::Asset(s) => client.schema(s.command.into(), Direction::Request)?,
+            MainCommand::Pset(s) => client.schema(s.command.into(), Direction::Request)?,
             MainCommand::Schema => client.schema(Method::Schema, Direction::Request)?,
         },
         DirectionCommand::Response(res) => match res.command {
@@ -21,6 +22,7 @@ pub(crate) fn schema(a: SchemaArgs, client: Client) -> Result<Value, anyhow::Err
             MainCommand::Wallet(w) => client.schema(w.command.into(), Direction::Response)?,
             MainCommand::Signer(s) => client.schema(s.command.into(), Direction::Response)?,
             MainCommand::Asset(s) => client.schema(s.command.into(), Direction::Response)?,
+            MainCommand::Pset(s) => client.schema(s.command.into(), Direction::Response)?,
             MainCommand::Schema => client.schema(Method::Schema, Direction::Response)?,
         },
     })
@@ -31,6 +33,7 @@ impl From<ServerSubCommandsEnum> for Method {
         match value {
             ServerSubCommandsEnum::Scan => Method::Scan,
             ServerSubCommandsEnum::Stop => Method::Stop,
+            ServerSubCommandsEnum::WaitForEvent => Method::WaitForEvent,
         }
     }
 }
@@ -43,6 +46,7 @@ impl From<WalletSubCommandsEnum> for Method {
             WalletSubCommandsEnum::List => Method::WalletList,
             WalletSubCommandsEnum::Address => Method::WalletAddress,
             WalletSubCommandsEnum::Balance => Method::WalletBalance,
+            WalletSubCommandsEnum::Portfolio => Method::WalletPortfolio,
             WalletSubCommandsEnum::Send => Method::WalletSendMany,
             WalletSubCommandsEnum::Issue => Method::WalletIssue,
             WalletSubCommandsEnum::Reissue => Method::WalletReissue,
@@ -52,6 +56,11 @@ impl From<WalletSubCommandsEnum> for Method {
             WalletSubCommandsEnum::Combine => Method::WalletCombine,
             WalletSubCommandsEnum::PsetDetails => Method::WalletPsetDetails,
             WalletSubCommandsEnum::Utxos => Method::WalletUtxos,
+            WalletSubCommandsEnum::LockUtxo => Method::WalletLockUtxo,
+            WalletSubCommandsEnum::UnlockUtxo => Method::WalletUnlockUtxo,
+            WalletSubCommandsEnum::LockedUtxos => Method::WalletLockedUtxos,
+            WalletSubCommandsEnum::Backup => Method::WalletBackup,
+            WalletSubCommandsEnum::Snapshot => Method::WalletSnapshot,
             WalletSubCommandsEnum::Txs => Method::WalletTxs,
             WalletSubCommandsEnum::SetTxMemo => Method::WalletSetTxMemo,
             WalletSubCommandsEnum::SetAddrMemo => Method::WalletSetAddrMemo,
@@ -71,6 +80,8 @@ impl From<SignerSubCommandsEnum> for Method {
             SignerSubCommandsEnum::Details => Method::SignerDetails,
             SignerSubCommandsEnum::List => Method::SignerList,
             SignerSubCommandsEnum::Sign => Method::SignerSign,
+            SignerSubCommandsEnum::SetPolicy => Method::SignerSetPolicy,
+            SignerSubCommandsEnum::SignedPsets => Method::SignerSignedPsets,
             SignerSubCommandsEnum::SinglesigDesc => Method::SignerSinglesigDescriptor,
             SignerSubCommandsEnum::Xpub => Method::SignerXpub,
         }
@@ -89,3 +100,11 @@ impl From<AssetSubCommandsEnum> for Method {
         }
     }
 }
+
+impl From<PsetSubCommandsEnum> for Method {
+    fn from(value: PsetSubCommandsEnum) -> Self {
+        match value {
+            PsetSubCommandsEnum::Decode => Method::PsetDecode,
+        }
+    }
+}
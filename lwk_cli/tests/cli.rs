@@ -15,8 +15,8 @@ use lwk_containers::{testcontainers::clients, JadeEmulator, EMULATOR_PORT};
 use serde_json::Value;
 
 use lwk_cli::{
-    inner_main, AssetSubCommandsEnum, Cli, ServerSubCommandsEnum, SignerSubCommandsEnum,
-    WalletSubCommandsEnum,
+    inner_main, AssetSubCommandsEnum, Cli, PsetSubCommandsEnum, ServerSubCommandsEnum,
+    SignerSubCommandsEnum, WalletSubCommandsEnum,
 };
 use lwk_test_util::TestElectrumServer;
 use tempfile::TempDir;
@@ -337,6 +337,51 @@ fn test_state_regression() {
     t.join().unwrap();
 }
 
+#[test]
+fn test_encrypted_state() {
+    let server = lwk_test_util::setup();
+    let electrum_url = &server.electrs.electrum_url;
+    let addr = get_available_addr().unwrap();
+    let tmp = tempfile::tempdir().unwrap();
+    let datadir = tmp.path().display().to_string();
+    let cli = format!("cli --addr {addr} -n regtest");
+    let params = format!("--datadir {datadir} --electrum-url {electrum_url}");
+    let start = |passphrase: &'static str| {
+        let cli = cli.clone();
+        let params = params.clone();
+        let t = std::thread::spawn(move || {
+            sh(&format!(
+                "{cli} server start {params} --state-passphrase {passphrase}"
+            ));
+        });
+        std::thread::sleep(std::time::Duration::from_millis(1000));
+        t
+    };
+
+    let t = start("pass");
+    let mnemonic = lwk_test_util::TEST_MNEMONIC;
+    sh(&format!(
+        r#"{cli} signer load-software --persist true --mnemonic "{mnemonic}" --signer s1"#
+    ));
+    sh(&format!("{cli} server stop"));
+    t.join().unwrap();
+
+    let state = tmp.path().join("liquid-regtest").join("state.lwk");
+    let content = fs::read_to_string(state).unwrap();
+    assert!(!content.contains(mnemonic));
+
+    let t = start("pass");
+    let r = sh(&format!("{cli} signer list"));
+    assert_eq!(get_len(&r, "signers"), 1);
+    sh(&format!("{cli} server stop"));
+    t.join().unwrap();
+
+    let err = sh_err(&format!(
+        "{cli} server start {params} --state-passphrase wrong"
+    ));
+    assert!(err.contains("Cannot decrypt the state"));
+}
+
 #[test]
 fn test_start_stop_persist() {
     let (t, _tmp, cli, params, _server, _) = setup_cli(false);
@@ -566,7 +611,7 @@ fn test_wallet_load_unload_list() {
 
 #[test]
 fn test_wallet_memos() {
-    let (t, _tmp, cli, params, server, _) = setup_cli(false);
+    let (t, tmp, cli, params, server, _) = setup_cli(false);
 
     // Create 2 wallets
     sw_signer(&cli, "s1");
@@ -643,6 +688,19 @@ fn test_wallet_memos() {
     ));
     assert_eq!(addr_memo(&cli, "w1", index), memo1);
 
+    // The backup restores the wallet with its memos
+    let backup = sh(&format!("{cli} wallet backup -w w1"));
+    assert_eq!(backup.get("version").unwrap().as_u64().unwrap(), 1);
+    assert_eq!(get_str(&backup, "network"), "liquid-regtest");
+    assert!(backup.get("birthday_height").unwrap().as_u64().is_some());
+    let backup_path = tmp.path().join("w1.backup");
+    std::fs::write(&backup_path, backup.to_string()).unwrap();
+    sh(&format!("{cli} wallet unload --wallet w1"));
+    let backup_path = backup_path.display();
+    sh(&format!("{cli} wallet load -w w1 --backup {backup_path}"));
+    assert_eq!(tx_memo(&cli, "w1", &txid), memo1);
+    assert_eq!(addr_memo(&cli, "w1", index), memo1);
+
     // And unload w2 to trigger a global persistence
     sh(&format!("{cli} wallet unload --wallet w2"));
 
@@ -817,6 +875,17 @@ fn test_issue() {
     assert_eq!(balance.get(asset).unwrap().as_i64().unwrap(), 1000);
     assert_eq!(balance.get(token).unwrap().as_i64().unwrap(), 1);
 
+    let r = sh(&format!("{cli} pset decode --pset {pset}"));
+    assert_eq!(get_len(&r, "issuances"), 1);
+    assert!(r.get("fee_rate").unwrap().as_f64().unwrap() > 0.0);
+    let inputs = r.get("inputs").unwrap().as_array().unwrap();
+    assert_eq!(get_str(&inputs[0], "script_type"), "p2wpkh");
+    assert!(inputs[0].get("blinded").unwrap().as_bool().unwrap());
+    let missing = inputs[0].get("missing_signatures_from").unwrap();
+    assert_eq!(get_str(&missing[0], "name"), "s1");
+    let outputs = r.get("outputs").unwrap().as_array().unwrap();
+    assert!(outputs.iter().any(|o| get_str(o, "script_type") == "fee"));
+
     let r = sh(&format!(
         "{cli} wallet pset-details --wallet w1 -p {pset} --with-tickers"
     ));
@@ -899,6 +968,37 @@ fn test_issue() {
     let r = sh(&format!("{cli} wallet utxos --wallet w1"));
     assert_eq!(get_len(&r, "utxos"), 4);
 
+    let utxo = &r.get("utxos").unwrap().as_array().unwrap()[0];
+    let txid = get_str(utxo, "txid");
+    let vout = utxo.get("vout").unwrap().as_u64().unwrap();
+    let lock_args = format!("--wallet w1 --txid {txid} --vout {vout}");
+    sh(&format!("{cli} wallet lock-utxo {lock_args} --blocks 10"));
+    let r = sh(&format!("{cli} wallet locked-utxos --wallet w1"));
+    assert_eq!(get_len(&r, "utxos"), 1);
+    let locked = &r.get("utxos").unwrap().as_array().unwrap()[0];
+    assert_eq!(get_str(locked, "txid"), txid);
+    assert!(locked.get("expiry_height").unwrap().as_u64().is_some());
+    sh(&format!("{cli} wallet unlock-utxo {lock_args}"));
+    let r = sh(&format!("{cli} wallet locked-utxos --wallet w1"));
+    assert_eq!(get_len(&r, "utxos"), 0);
+    let err = sh_err(&format!("{cli} wallet unlock-utxo {lock_args}"));
+    assert!(err.contains("is not locked"));
+
+    // The inputs of the created PSETs are locked only if requested, until broadcast
+    let recipient = format!("--recipient {node_address}:1:{asset}");
+    sh(&format!("{cli} wallet send --wallet w1 {recipient}"));
+    let r = sh(&format!("{cli} wallet locked-utxos --wallet w1"));
+    assert_eq!(get_len(&r, "utxos"), 0);
+    let r = sh(&format!(
+        "{cli} wallet send --wallet w1 {recipient} --lock-inputs 10"
+    ));
+    let pset = get_str(&r, "pset");
+    let r = sh(&format!("{cli} wallet locked-utxos --wallet w1"));
+    assert!(get_len(&r, "utxos") > 0);
+    complete(&cli, "w1", pset, &["s1"]);
+    let r = sh(&format!("{cli} wallet locked-utxos --wallet w1"));
+    assert_eq!(get_len(&r, "utxos"), 0);
+
     let r = sh(&format!("{cli} wallet txs --wallet w1"));
     let txs = r.get("txs").unwrap().as_array().unwrap();
     assert!(!txs.is_empty());
@@ -934,6 +1034,42 @@ fn test_issue() {
     let balance = txs[0].get("balance").unwrap().as_object().unwrap();
     assert!(balance.contains_key("L-BTC"));
 
+    let r = sh(&format!("{cli} wallet portfolio --wallet w1"));
+    let assets = r.get("assets").unwrap().as_array().unwrap();
+    assert_eq!(assets[0].get("ticker").unwrap().as_str().unwrap(), "rL-BTC");
+    let a = assets
+        .iter()
+        .find(|a| a.get("asset").unwrap().as_str().unwrap() == asset)
+        .unwrap();
+    assert_eq!(a.get("name").unwrap().as_str().unwrap(), "example");
+    let confirmed = a.get("confirmed").unwrap().as_u64().unwrap();
+    assert_eq!(confirmed, get_balance(&cli, "w1", asset));
+    assert_eq!(a.get("trusted_pending").unwrap().as_u64().unwrap(), 0);
+    assert_eq!(a.get("untrusted_pending").unwrap().as_u64().unwrap(), 0);
+    assert_eq!(get_str(a, "issuance_txid"), issuance_txid);
+
+    let r = sh(&format!("{cli} wallet txs --wallet w1 --asset {asset}"));
+    let asset_txs = r.get("txs").unwrap().as_array().unwrap();
+    assert!(asset_txs.len() < txs.len());
+    for tx in asset_txs {
+        let balance = tx.get("balance").unwrap().as_object().unwrap();
+        assert!(balance.contains_key(asset));
+    }
+
+    let r = sh(&format!(
+        "{cli} wallet txs --wallet w1 --offset 1 --limit 1"
+    ));
+    assert_eq!(r.get("total").unwrap().as_u64().unwrap(), txs.len() as u64);
+    let page = r.get("txs").unwrap().as_array().unwrap();
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].get("txid"), txs[1].get("txid"));
+    let r = sh(&format!("{cli} wallet txs --wallet w1 --type issuance"));
+    let issuances = r.get("txs").unwrap().as_array().unwrap();
+    assert!(!issuances.is_empty());
+    for tx in issuances {
+        assert_eq!(get_str(tx, "type"), "issuance");
+    }
+
     // Move the reissuance token to another wallet and perform an "external" reissuance
     sw_signer(&cli, "s2");
     singlesig_wallet(&cli, "w2", "s2", "slip77", "wpkh");
@@ -1264,6 +1400,16 @@ fn test_schema() {
         assert!(result.get("$schema").is_some(), "failed for {}", cmd);
     }
 
+    for a in PsetSubCommandsEnum::value_variants() {
+        let a = a.to_possible_value();
+        let cmd = a.map(|e| e.get_name().to_string()).unwrap();
+        let result = sh(&format!("{cli} schema request pset {cmd}"));
+        assert!(result.get("$schema").is_some(), "failed for {}", cmd);
+
+        let result = sh(&format!("{cli} schema response pset {cmd}"));
+        assert!(result.get("$schema").is_some(), "failed for {}", cmd);
+    }
+
     for a in AssetSubCommandsEnum::value_variants() {
         let a = a.to_possible_value();
         let cmd = a.map(|e| e.get_name().to_string()).unwrap();
@@ -1436,6 +1582,42 @@ fn test_3of5() {
     t.join().unwrap();
 }
 
+#[test]
+fn test_blind_issuance() {
+    let (t, _tmp, cli, _params, server, _) = setup_cli(false);
+
+    sw_signer(&cli, "s1");
+    singlesig_wallet(&cli, "w1", "s1", "slip77", "wpkh");
+    fund(&server, &cli, "w1", 1_000_000);
+
+    let r = sh(&format!(
+        "{cli} wallet issue --wallet w1 --satoshi-asset 1000 --satoshi-token 1 --blind-issuance"
+    ));
+    let pset = get_str(&r, "pset");
+    let r = sh(&format!("{cli} wallet pset-details -w w1 -p {pset}"));
+    let issuance = &r.get("issuances").unwrap().as_array().unwrap()[0];
+    assert!(issuance.get("is_confidential").unwrap().as_bool().unwrap());
+    assert_eq!(
+        issuance.get("asset_satoshi").unwrap().as_u64().unwrap(),
+        1000
+    );
+    let (asset, token) = asset_ids_from_issuance_pset(&cli, "w1", pset);
+    let (asset, token) = (&asset, &token);
+    complete(&cli, "w1", pset, &["s1"]);
+    assert_eq!(1000, get_balance(&cli, "w1", asset));
+    assert_eq!(1, get_balance(&cli, "w1", token));
+
+    let r = sh(&format!(
+        "{cli} wallet reissue --wallet w1 --asset {asset} --satoshi-asset 1 --blind-issuance"
+    ));
+    complete(&cli, "w1", get_str(&r, "pset"), &["s1"]);
+    assert_eq!(1001, get_balance(&cli, "w1", asset));
+    assert_eq!(1, get_balance(&cli, "w1", token));
+
+    sh(&format!("{cli} server stop"));
+    t.join().unwrap();
+}
+
 #[test]
 fn test_start_errors() {
     let (t, _tmp, cli, params, _server, _) = setup_cli(false);
@@ -1447,6 +1629,39 @@ fn test_start_errors() {
     t.join().unwrap();
 }
 
+#[test]
+fn test_events() {
+    let (t, _tmp, cli, _params, server, _) = setup_cli(false);
+
+    sw_signer(&cli, "s1");
+    singlesig_wallet(&cli, "w1", "s1", "slip77", "wpkh");
+    fund(&server, &cli, "w1", 1_000_000);
+
+    let r = sh(&format!("{cli} server wait-for-event --timeout 1"));
+    assert!(r.get("events").unwrap().as_array().unwrap().is_empty());
+
+    let policy_asset = "5ac9f65c0efcc4775e0baec4ec03abdde22473cd3cf33c0419ca290e0751b225";
+    let addr = server.elementsd_getnewaddress().to_string();
+    let txid = send(&cli, "w1", &addr, policy_asset, 1000, &["s1"]);
+
+    let r = sh(&format!(
+        "{cli} server wait-for-event --after 0 --timeout 1"
+    ));
+    let events = r.get("events").unwrap().as_array().unwrap();
+    let new_tx = events
+        .iter()
+        .find(|e| get_str(e, "kind") == "new_tx")
+        .unwrap();
+    assert_eq!(get_str(new_tx, "wallet"), "w1");
+    assert_eq!(get_str(new_tx.get("tx").unwrap(), "txid"), txid);
+    assert!(events
+        .iter()
+        .any(|e| get_str(e, "kind") == "balance_changed"));
+
+    sh(&format!("{cli} server stop"));
+    t.join().unwrap();
+}
+
 #[test]
 fn test_send_all() {
     let (t, _tmp, cli, _params, server, _) = setup_cli(false);
@@ -1506,3 +1721,84 @@ fn test_ct_discount() {
     sh(&format!("{cli} server stop"));
     t.join().unwrap();
 }
+
+#[test]
+fn test_offline() {
+    let (t, tmp, cli, _params, server, _) = setup_cli(false);
+
+    let r = sh(&format!("{cli} signer generate"));
+    let mnemonic = get_str(&r, "mnemonic");
+    sh(&format!(
+        "{cli} signer load-software --mnemonic \"{mnemonic}\" --signer sw"
+    ));
+    singlesig_wallet(&cli, "w1", "sw", "slip77", "wpkh");
+    fund(&server, &cli, "w1", 1_000_000);
+    sh(&format!("{cli} signer unload --signer sw"));
+
+    let mnemonic_file = tmp.path().join("mnemonic");
+    fs::write(&mnemonic_file, format!("{mnemonic}\n")).unwrap();
+    let snapshot_path = tmp.path().join("snapshot.json");
+    let r = sh(&format!("{cli} wallet snapshot -w w1"));
+    assert_eq!(get_len(&r, "locked_utxos"), 0);
+    fs::write(&snapshot_path, r.to_string()).unwrap();
+    let snapshot = snapshot_path.display();
+    let mnemonic_file = mnemonic_file.display();
+
+    // Offline commands do not need the server, here pointing to an unused port
+    let addr = get_available_addr().unwrap();
+    let offline = format!("cli --addr {addr} -n regtest offline");
+
+    let policy_asset = "5ac9f65c0efcc4775e0baec4ec03abdde22473cd3cf33c0419ca290e0751b225";
+    let r = sh(&format!("{offline} wallet balance --snapshot {snapshot}"));
+    let balance = r.get("balance").unwrap().get(policy_asset).unwrap();
+    assert_eq!(balance.as_u64().unwrap(), 1_000_000);
+
+    let node_address = server.elementsd_getnewaddress();
+    let recipient = format!("--recipient {node_address}:1000:{policy_asset}");
+    let r = sh(&format!(
+        "{offline} wallet send --snapshot {snapshot} {recipient}"
+    ));
+    let pset_file = tmp.path().join("pset");
+    fs::write(&pset_file, get_str(&r, "pset")).unwrap();
+    let pset_file = pset_file.display();
+
+    let r = sh(&format!(
+        "{offline} wallet pset-details --snapshot {snapshot} --pset-file {pset_file}"
+    ));
+    assert_eq!(get_len(&r, "has_signatures_from"), 0);
+    assert_eq!(get_len(&r, "missing_signatures_from"), 1);
+
+    let r = sh(&format!(
+        "{offline} sign --mnemonic-file {mnemonic_file} --pset-file {pset_file}"
+    ));
+    assert_eq!(r.get("signatures_added").unwrap().as_u64().unwrap(), 1);
+    let details = r.get("details").unwrap();
+    let input = &details.get("inputs").unwrap().as_array().unwrap()[0];
+    assert_eq!(get_len(input, "has_signatures_from"), 1);
+    assert_eq!(get_len(input, "missing_signatures_from"), 0);
+    let pset = get_str(&r, "pset");
+
+    let r = sh(&format!("{cli} wallet broadcast -w w1 --pset {pset}"));
+    let txid = get_str(&r, "txid");
+    wait_tx(&cli, "w1", txid);
+
+    // The inputs locked by the server are not spent offline
+    sh(&format!(
+        "{cli} wallet send -w w1 {recipient} --lock-inputs 10"
+    ));
+    let r = sh(&format!("{cli} wallet snapshot -w w1"));
+    assert!(get_len(&r, "locked_utxos") > 0);
+    fs::write(&snapshot_path, r.to_string()).unwrap();
+    let err = sh_err(&format!(
+        "{offline} wallet send --snapshot {snapshot} {recipient}"
+    ));
+    assert!(err.contains("Insufficient funds"), "{err}");
+
+    let err = sh_err(&format!(
+        "cli -n testnet offline wallet balance --snapshot {snapshot}"
+    ));
+    assert!(err.contains("Snapshot is for the wrong network"));
+
+    sh(&format!("{cli} server stop"));
+    t.join().unwrap();
+}
@@ -0,0 +1,376 @@
+use std::fmt::Display;
+
+use elements::confidential::{Asset, Value};
+use elements::pset::{Input, Output, PartiallySignedTransaction, PsbtSighashType};
+use elements::secp256k1_zkp::{All, RangeProof, Secp256k1, SurjectionProof};
+use elements::{Address, AddressParams, AssetId, BlindAssetProofs, BlindValueProofs, OutPoint};
+use elements::{Script, TxOut};
+
+use crate::{Issuance, PsetSignatures};
+
+/// The type of a script pubkey
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptType {
+    /// The empty script of a fee output
+    Fee,
+    P2pkh,
+    P2sh,
+    P2wpkh,
+    P2wsh,
+    P2tr,
+    OpReturn,
+    Unknown,
+}
+
+impl ScriptType {
+    pub fn new(script: &Script) -> Self {
+        if script.is_empty() {
+            ScriptType::Fee
+        } else if script.is_p2pkh() {
+            ScriptType::P2pkh
+        } else if script.is_p2sh() {
+            ScriptType::P2sh
+        } else if script.is_v0_p2wpkh() {
+            ScriptType::P2wpkh
+        } else if script.is_v0_p2wsh() {
+            ScriptType::P2wsh
+        } else if script.is_v1_p2tr() {
+            ScriptType::P2tr
+        } else if script.is_op_return() {
+            ScriptType::OpReturn
+        } else {
+            ScriptType::Unknown
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScriptType::Fee => "fee",
+            ScriptType::P2pkh => "p2pkh",
+            ScriptType::P2sh => "p2sh",
+            ScriptType::P2wpkh => "p2wpkh",
+            ScriptType::P2wsh => "p2wsh",
+            ScriptType::P2tr => "p2tr",
+            ScriptType::OpReturn => "op_return",
+            ScriptType::Unknown => "unknown",
+        }
+    }
+}
+
+impl Display for ScriptType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// An input of a PSET, as seen by anyone
+#[derive(Debug, Clone)]
+pub struct PsetInputAnalysis {
+    pub previous_outpoint: OutPoint,
+
+    /// The script pubkey of the previous output, if the PSET contains it
+    pub script_pubkey: Option<Script>,
+
+    pub script_type: Option<ScriptType>,
+
+    /// The unconfidential address of the previous output
+    pub address: Option<Address>,
+
+    /// Whether the previous output is blinded, if the PSET contains it
+    pub blinded: Option<bool>,
+
+    /// The asset, if explicit or proved by the blind asset proof
+    pub asset: Option<AssetId>,
+
+    /// The amount, if explicit or proved by the blind value proof
+    pub satoshi: Option<u64>,
+
+    pub issuance: Issuance,
+
+    pub sighash: Option<PsbtSighashType>,
+
+    pub signatures: PsetSignatures,
+
+    /// Whether the input has the final script sig or witness
+    pub finalized: bool,
+}
+
+/// An output of a PSET, as seen by anyone
+#[derive(Debug, Clone)]
+pub struct PsetOutputAnalysis {
+    pub script_pubkey: Script,
+
+    pub script_type: ScriptType,
+
+    /// The address, confidential if the output has a blinding key
+    pub address: Option<Address>,
+
+    /// Whether the output has the asset or the value commitment
+    pub blinded: bool,
+
+    /// The asset, if explicit or proved by the blind asset proof
+    pub asset: Option<AssetId>,
+
+    /// The amount, if explicit or proved by the blind value proof
+    pub satoshi: Option<u64>,
+}
+
+/// Details of a PSET that do not depend on a wallet, see [`pset_analysis()`]
+#[derive(Debug, Clone)]
+pub struct PsetAnalysis {
+    pub inputs: Vec<PsetInputAnalysis>,
+
+    pub outputs: Vec<PsetOutputAnalysis>,
+
+    /// The sum of the fee outputs, if any
+    pub fee: Option<u64>,
+
+    /// The estimated size of the transaction once signed, in virtual bytes
+    ///
+    /// Missing signatures are estimated from the script type, it's none if some can't be.
+    pub vsize: Option<usize>,
+
+    /// The estimated fee rate in sat/kvB
+    pub fee_rate: Option<f32>,
+}
+
+impl PsetAnalysis {
+    /// Number of signatures missing in all the inputs
+    pub fn missing_signatures(&self) -> usize {
+        self.inputs
+            .iter()
+            .filter(|i| !i.finalized)
+            .map(|i| i.signatures.missing_signature.len())
+            .sum()
+    }
+}
+
+/// Inspect every input and output of the PSET, without requiring any wallet
+///
+/// Blinded amounts and assets are returned only if the PSET proves them with blind proofs.
+/// Addresses are built with the given `params`.
+pub fn pset_analysis(
+    pset: &PartiallySignedTransaction,
+    params: &'static AddressParams,
+) -> PsetAnalysis {
+    let secp = Secp256k1::new();
+    let signatures = crate::pset_signatures(pset);
+    let inputs: Vec<_> = pset
+        .inputs()
+        .iter()
+        .zip(signatures)
+        .map(|(input, signatures)| analyze_input(&secp, input, signatures, params))
+        .collect();
+    let outputs: Vec<_> = pset
+        .outputs()
+        .iter()
+        .map(|output| analyze_output(&secp, output, params))
+        .collect();
+
+    let fees: Vec<_> = outputs
+        .iter()
+        .filter(|o| o.script_type == ScriptType::Fee)
+        .map(|o| o.satoshi)
+        .collect();
+    let fee = if fees.is_empty() {
+        None
+    } else {
+        fees.into_iter().sum()
+    };
+
+    let vsize = estimate_vsize(pset);
+    let fee_rate = match (fee, vsize) {
+        (Some(fee), Some(vsize)) if vsize > 0 => Some(fee as f32 * 1000.0 / vsize as f32),
+        _ => None,
+    };
+
+    PsetAnalysis {
+        inputs,
+        outputs,
+        fee,
+        vsize,
+        fee_rate,
+    }
+}
+
+fn analyze_input(
+    secp: &Secp256k1<All>,
+    input: &Input,
+    signatures: PsetSignatures,
+    params: &'static AddressParams,
+) -> PsetInputAnalysis {
+    let txout = input.witness_utxo.as_ref();
+    let script_pubkey = txout.map(|t| t.script_pubkey.clone());
+    let (asset, satoshi) = match txout {
+        Some(txout) => proved(
+            secp,
+            txout,
+            input.asset,
+            input.blind_asset_proof.as_deref(),
+            input.amount,
+            input.blind_value_proof.as_deref(),
+        ),
+        None => (None, None),
+    };
+    PsetInputAnalysis {
+        previous_outpoint: OutPoint::new(input.previous_txid, input.previous_output_index),
+        script_type: script_pubkey.as_ref().map(ScriptType::new),
+        address: script_pubkey
+            .as_ref()
+            .and_then(|s| Address::from_script(s, None, params)),
+        blinded: txout.map(|t| t.asset.is_confidential() || t.value.is_confidential()),
+        script_pubkey,
+        asset,
+        satoshi,
+        issuance: Issuance::new(input),
+        sighash: input.sighash_type,
+        signatures,
+        finalized: input.final_script_sig.is_some() || input.final_script_witness.is_some(),
+    }
+}
+
+fn analyze_output(
+    secp: &Secp256k1<All>,
+    output: &Output,
+    params: &'static AddressParams,
+) -> PsetOutputAnalysis {
+    let script_type = ScriptType::new(&output.script_pubkey);
+    let blinded = output.asset_comm.is_some() || output.amount_comm.is_some();
+    let (asset, satoshi) = if blinded {
+        proved(
+            secp,
+            &output.to_txout(),
+            output.asset,
+            output.blind_asset_proof.as_deref(),
+            output.amount,
+            output.blind_value_proof.as_deref(),
+        )
+    } else {
+        (output.asset, output.amount)
+    };
+    let blinder = output.blinding_key.map(|k| k.inner);
+    PsetOutputAnalysis {
+        address: Address::from_script(&output.script_pubkey, blinder, params),
+        script_pubkey: output.script_pubkey.clone(),
+        script_type,
+        blinded,
+        asset,
+        satoshi,
+    }
+}
+
+/// The asset and amount of `txout`, if explicit or if the claimed ones are proved
+fn proved(
+    secp: &Secp256k1<All>,
+    txout: &TxOut,
+    asset: Option<AssetId>,
+    asset_proof: Option<&SurjectionProof>,
+    satoshi: Option<u64>,
+    value_proof: Option<&RangeProof>,
+) -> (Option<AssetId>, Option<u64>) {
+    let asset = match (txout.asset, asset, asset_proof) {
+        (Asset::Explicit(asset), _, _) => Some(asset),
+        (Asset::Confidential(comm), Some(asset), Some(proof)) => proof
+            .blind_asset_proof_verify(secp, asset, comm)
+            .then_some(asset),
+        _ => None,
+    };
+    let satoshi = match (txout.value, satoshi, value_proof, txout.asset) {
+        (Value::Explicit(satoshi), _, _, _) => Some(satoshi),
+        (Value::Confidential(comm), Some(satoshi), Some(proof), Asset::Confidential(gen)) => proof
+            .blind_value_proof_verify(secp, satoshi, gen, comm)
+            .then_some(satoshi),
+        _ => None,
+    };
+    (asset, satoshi)
+}
+
+/// Estimate the transaction size once all the inputs are signed
+fn estimate_vsize(pset: &PartiallySignedTransaction) -> Option<usize> {
+    let tx = pset.extract_tx().ok()?;
+    let mut weight = tx.weight();
+    for input in pset.inputs() {
+        if input.final_script_sig.is_some() || input.final_script_witness.is_some() {
+            continue;
+        }
+        weight += estimate_satisfaction_weight(input)?;
+    }
+    Some((weight + 3) / 4)
+}
+
+/// Estimate the weight of the script sig and witness of an input, signing with the keys in
+/// `bip32_derivation`, which is an upper bound for multisig
+fn estimate_satisfaction_weight(input: &Input) -> Option<usize> {
+    const SIG: usize = 73;
+    const KEY: usize = 34;
+    let script_pubkey = &input.witness_utxo.as_ref()?.script_pubkey;
+    let keys = input.bip32_derivation.len().max(1);
+    let wsh = |script: &Script| 1 + 1 + keys * SIG + 3 + script.len();
+    Some(match ScriptType::new(script_pubkey) {
+        ScriptType::P2wpkh => 1 + SIG + KEY,
+        ScriptType::P2wsh => wsh(input.witness_script.as_ref()?),
+        ScriptType::P2tr => 1 + 66,
+        ScriptType::P2sh => {
+            let redeem_script = input.redeem_script.as_ref()?;
+            let script_sig = 4 * (1 + redeem_script.len());
+            match ScriptType::new(redeem_script) {
+                ScriptType::P2wpkh => script_sig + 1 + SIG + KEY,
+                ScriptType::P2wsh => script_sig + wsh(input.witness_script.as_ref()?),
+                _ => return None,
+            }
+        }
+        ScriptType::P2pkh => 4 * (SIG + KEY),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use elements::pset::PartiallySignedTransaction;
+    use elements::AddressParams;
+
+    use super::{pset_analysis, ScriptType};
+
+    #[test]
+    fn test_pset_analysis() {
+        let pset_str = include_str!("../test_data/pset_details/pset.base64");
+        let pset: PartiallySignedTransaction = pset_str.parse().unwrap();
+        let analysis = pset_analysis(&pset, &AddressParams::LIQUID_TESTNET);
+
+        assert_eq!(analysis.inputs.len(), 2);
+        let input = &analysis.inputs[0];
+        assert_eq!(input.script_type, Some(ScriptType::P2wpkh));
+        assert_eq!(input.blinded, Some(true));
+        // blinded inputs without blind proofs
+        assert_eq!(input.asset, None);
+        assert_eq!(input.satoshi, None);
+        assert!(input.issuance.is_null());
+        assert_eq!(input.sighash, None);
+        assert!(!input.finalized);
+        assert_eq!(analysis.missing_signatures(), 2);
+
+        assert_eq!(analysis.outputs.len(), 4);
+        let output = &analysis.outputs[0];
+        assert_eq!(output.script_type, ScriptType::P2wpkh);
+        assert!(output.blinded);
+        let asset = "38fca2d939696061a8f76d4e6b5eecd54e3b4221c846f24a6b279e79952850a5";
+        assert_eq!(output.asset.unwrap().to_string(), asset);
+        assert_eq!(output.satoshi, Some(1));
+        assert!(output.address.as_ref().unwrap().is_blinded());
+        let fee = &analysis.outputs[3];
+        assert_eq!(fee.script_type, ScriptType::Fee);
+        assert!(!fee.blinded);
+        assert!(fee.address.is_none());
+
+        assert_eq!(analysis.fee, Some(377));
+        assert_eq!(analysis.vsize, Some(3768));
+        let fee_rate = analysis.fee_rate.unwrap();
+        assert!((100.0..101.0).contains(&fee_rate));
+
+        let pset_str = include_str!("../test_data/pset_details/pset2.base64");
+        let pset: PartiallySignedTransaction = pset_str.parse().unwrap();
+        let analysis = pset_analysis(&pset, &AddressParams::LIQUID_TESTNET);
+        assert_eq!(analysis.missing_signatures(), 0);
+        assert_eq!(analysis.outputs[0].script_type, ScriptType::P2sh);
+    }
+}
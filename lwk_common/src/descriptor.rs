@@ -48,7 +48,7 @@ pub fn singlesig_desc<S: Signer>(
     Ok(format!("{desc}#{checksum}"))
 }
 
-fn fmt_path(path: &DerivationPath) -> String {
+pub(crate) fn fmt_path(path: &DerivationPath) -> String {
     path.to_string().replace("m/", "").replace('\'', "h")
 }
 
@@ -11,7 +11,7 @@ pub enum Error {
     #[error("Input #{idx} has a pegin, but it's not supported")]
     InputPeginUnsupported { idx: usize },
 
-    #[error("Input #{idx} has a blinded issuance, but it's not supported")]
+    #[error("Input #{idx} has a blinded issuance without valid blind proofs")]
     InputBlindedIssuance { idx: usize },
 
     #[error("Input #{idx} is not blinded")]
@@ -25,6 +25,9 @@ pub enum Error {
     )]
     InputCommitmentsMismatch { idx: usize },
 
+    #[error("Input #{idx} has no rangeproof and invalid blind proofs")]
+    InputInvalidBlindProofs { idx: usize },
+
     #[error("Output #{idx} has none asset")]
     OutputAssetNone { idx: usize },
 
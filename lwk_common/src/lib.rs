@@ -4,11 +4,15 @@
 //!
 //!   * Utils to inspect a PSET: get the net effect of a PSET on a given wallet [`pset_balance()`], or get how many
 //!  signatures are missing , and which signers should provide them [`pset_signatures()`].
+//!  * Decode every input and output of a PSET without a wallet [`pset_analysis()`].
+//!  * Transport PSETs and descriptors with animated QR codes, see [`UrEncoder`] and [`UrDecoder`].
 //!  * [`Signer`] trait: contains the methods to be implemented by a signer such as signing a pset or
 //!  returning an xpub
 //!
 //!  To avoid circular dependencies this crate must not depend on other crate of the workspace
 
+mod analysis;
+mod bsms;
 mod descriptor;
 mod error;
 mod keyorigin_xpub;
@@ -17,7 +21,12 @@ pub mod precision;
 mod qr;
 mod segwit;
 mod signer;
+mod ur;
 
+pub use crate::analysis::{
+    pset_analysis, PsetAnalysis, PsetInputAnalysis, PsetOutputAnalysis, ScriptType,
+};
+pub use crate::bsms::{BsmsDescriptorRecord, BsmsError, BsmsKeyRecord, BsmsToken};
 pub use crate::descriptor::{
     multisig_desc, singlesig_desc, Bip, DescriptorBlindingKey, InvalidBipVariant,
     InvalidBlindingKeyVariant, InvalidMultisigVariant, InvalidSinglesigVariant, Multisig,
@@ -29,7 +38,11 @@ pub use crate::model::*;
 pub use crate::precision::Precision;
 pub use crate::qr::*;
 pub use crate::segwit::is_provably_segwit;
-pub use crate::signer::Signer;
+pub use crate::signer::{message_signature, MessageSigner, Signer};
+pub use crate::ur::{
+    descriptor_ur_encoder, pset_prune_proofs, pset_ur_encoder, UrDecoder, UrEncoder, UrError,
+    DESCRIPTOR_UR_TYPE, PSET_UR_TYPE,
+};
 
 use elements::confidential::{Asset, Value};
 use elements_miniscript::confidential::bare::tweak_private_key;
@@ -38,6 +51,7 @@ use elements_miniscript::descriptor::DescriptorSecretKey;
 use elements_miniscript::elements::bitcoin::secp256k1::SecretKey;
 use elements_miniscript::elements::{
     bitcoin::{bip32::KeySource, key::PublicKey},
+    hashes::Hash,
     opcodes::all::OP_RETURN,
     pset::PartiallySignedTransaction,
     script::Builder,
@@ -80,7 +94,8 @@ pub fn derive_blinding_key(
     }
 }
 
-fn commitments(
+/// Compute the asset and value commitments of an output from its secrets
+pub fn commitments(
     secp: &Secp256k1<All>,
     txout_secrets: &TxOutSecrets,
 ) -> (Generator, PedersenCommitment) {
@@ -98,7 +113,9 @@ fn commitments(
     (asset_comm, amount_comm)
 }
 
-fn is_mine(
+/// Whether `script_pubkey` belongs to `descriptor`, at one of the derivation paths of
+/// `bip32_derivation`
+pub fn is_mine(
     script_pubkey: &Script,
     descriptor: &ConfidentialDescriptor<DescriptorPublicKey>,
     bip32_derivation: &BTreeMap<PublicKey, KeySource>,
@@ -154,9 +171,10 @@ pub fn pset_balance(
                     return Err(Error::InputPeginUnsupported { idx });
                 }
                 if input.has_issuance() {
-                    let issuance = input.asset_issuance();
-                    if issuance.amount.is_confidential()
-                        || issuance.inflation_keys.is_confidential()
+                    // Blinded issuances must prove their amounts
+                    let issuance = Issuance::new(input);
+                    if (issuance.is_asset_confidential() && issuance.asset_satoshi().is_none())
+                        || (issuance.is_token_confidential() && issuance.token_satoshi().is_none())
                     {
                         return Err(Error::InputBlindedIssuance { idx });
                     }
@@ -168,6 +186,32 @@ pub fn pset_balance(
                     _ => return Err(Error::InputNotBlinded { idx }),
                 };
 
+                // Without the rangeproof, as in pruned PSETs, the amount and asset must be proven
+                // by the blind proofs
+                if input.in_utxo_rangeproof.is_none() {
+                    let (asset, amount) = match (
+                        input.asset,
+                        input.blind_asset_proof.as_ref(),
+                        input.amount,
+                        input.blind_value_proof.as_ref(),
+                    ) {
+                        (Some(asset), Some(asset_proof), Some(amount), Some(value_proof))
+                            if asset_proof.blind_asset_proof_verify(&secp, asset, asset_comm)
+                                && value_proof.blind_value_proof_verify(
+                                    &secp,
+                                    amount,
+                                    asset_comm,
+                                    amount_comm,
+                                ) =>
+                        {
+                            (asset, amount)
+                        }
+                        _ => return Err(Error::InputInvalidBlindProofs { idx }),
+                    };
+                    *balances.entry(asset).or_default() -= amount as i64;
+                    continue;
+                }
+
                 // We expect the input to be unblindable with the descriptor blinding key
                 let private_blinding_key = derive_blinding_key(descriptor, &txout.script_pubkey)
                     .ok_or(Error::MissingPrivateBlindingKey)?;
@@ -293,6 +337,16 @@ pub fn burn_script() -> Script {
     Builder::new().push_opcode(OP_RETURN).into_script()
 }
 
+/// Create the script whose blinding key blinds the (re)issuance amounts of the input spending
+/// `prevout`, as Elements Core wallet does
+pub fn issuance_blinding_script(prevout: &OutPoint) -> Script {
+    Builder::new()
+        .push_opcode(OP_RETURN)
+        .push_slice(prevout.txid.as_byte_array())
+        .push_int(prevout.vout as i64)
+        .into_script()
+}
+
 #[cfg(test)]
 mod test {
     use elements::{pset::PartiallySignedTransaction, AssetId};
@@ -2,9 +2,10 @@ use elements_miniscript::elements::bitcoin::{
     bip32::{Fingerprint, KeySource},
     key::PublicKey,
 };
+use elements_miniscript::elements::confidential::Value;
 use elements_miniscript::elements::pset::Input;
-use elements_miniscript::elements::secp256k1_zkp::ZERO_TWEAK;
-use elements_miniscript::elements::{AssetId, AssetIssuance, OutPoint, Txid};
+use elements_miniscript::elements::secp256k1_zkp::{Generator, RangeProof, Secp256k1, ZERO_TWEAK};
+use elements_miniscript::elements::{AssetId, AssetIssuance, BlindValueProofs, OutPoint, Txid};
 use std::collections::{BTreeMap, BTreeSet};
 
 #[derive(Debug, Clone)]
@@ -25,6 +26,8 @@ pub struct Issuance {
     token: AssetId,
     prev_output: OutPoint,
     inner: AssetIssuance,
+    asset_satoshi: Option<u64>,
+    token_satoshi: Option<u64>,
 }
 
 impl Issuance {
@@ -32,11 +35,26 @@ impl Issuance {
         // There are meaningless if inner is null
         let (asset, token) = input.issuance_ids();
         let prev_output = OutPoint::new(input.previous_txid, input.previous_output_index);
+        let inner = input.asset_issuance();
+        let asset_satoshi = satoshi(
+            inner.amount,
+            input.issuance_value_amount,
+            input.in_issuance_blind_value_proof.as_deref(),
+            asset,
+        );
+        let token_satoshi = satoshi(
+            inner.inflation_keys,
+            input.issuance_inflation_keys,
+            input.in_issuance_blind_inflation_keys_proof.as_deref(),
+            token,
+        );
         Self {
             asset,
             token,
             prev_output,
-            inner: input.asset_issuance(),
+            inner,
+            asset_satoshi,
+            token_satoshi,
         }
     }
 
@@ -53,15 +71,25 @@ impl Issuance {
     }
 
     pub fn is_confidential(&self) -> bool {
-        self.inner.amount.is_confidential() || self.inner.inflation_keys.is_confidential()
+        self.is_asset_confidential() || self.is_token_confidential()
+    }
+
+    pub fn is_asset_confidential(&self) -> bool {
+        self.inner.amount.is_confidential()
+    }
+
+    pub fn is_token_confidential(&self) -> bool {
+        self.inner.inflation_keys.is_confidential()
     }
 
+    /// The issued asset amount, if confidential it's returned only if the PSET proves it
     pub fn asset_satoshi(&self) -> Option<u64> {
-        self.inner.amount.explicit()
+        self.asset_satoshi
     }
 
+    /// The issued token amount, if confidential it's returned only if the PSET proves it
     pub fn token_satoshi(&self) -> Option<u64> {
-        self.inner.inflation_keys.explicit()
+        self.token_satoshi
     }
 
     pub fn asset(&self) -> Option<AssetId> {
@@ -81,6 +109,26 @@ impl Issuance {
     }
 }
 
+/// The explicit amount of an issuance value, for confidential values check the blind value proof
+fn satoshi(
+    value: Value,
+    amount: Option<u64>,
+    proof: Option<&RangeProof>,
+    asset: AssetId,
+) -> Option<u64> {
+    match (value, amount, proof) {
+        (Value::Explicit(satoshi), _, _) => Some(satoshi),
+        (Value::Confidential(comm), Some(satoshi), Some(proof)) => {
+            let secp = Secp256k1::new();
+            let gen = Generator::new_unblinded(&secp, asset.into_tag());
+            proof
+                .blind_value_proof_verify(&secp, satoshi, gen, comm)
+                .then_some(satoshi)
+        }
+        _ => None,
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PsetDetails {
     pub balance: PsetBalance,
@@ -50,8 +50,24 @@ pub fn address_to_uri_qr(
     address: &Address,
     pixel_per_module: Option<u8>,
 ) -> Result<String, QrError> {
-    let address = address_to_qr_text(address);
-    let qr_code = qr_code::QrCode::new(address)?;
+    text_to_uri_qr(&address_to_qr_text(address), pixel_per_module)
+}
+
+/// Convert the given UR part in a string representing a QR code to be consumed from a terminal
+///
+/// The part is uppercased to use the alphanumeric mode, see [`crate::UrEncoder`]
+pub fn ur_to_text_qr(part: &str) -> Result<String, QrError> {
+    let qr_code = qr_code::QrCode::new(part.to_ascii_uppercase())?;
+    Ok(qr_code.to_string(true, 3))
+}
+
+/// Convert the given UR part to an image uri, see [`address_to_uri_qr`] for the format
+pub fn ur_to_uri_qr(part: &str, pixel_per_module: Option<u8>) -> Result<String, QrError> {
+    text_to_uri_qr(&part.to_ascii_uppercase(), pixel_per_module)
+}
+
+fn text_to_uri_qr(text: &str, pixel_per_module: Option<u8>) -> Result<String, QrError> {
+    let qr_code = qr_code::QrCode::new(text)?;
     let mut bmp = qr_code.to_bmp();
     if let Some(pixel_per_module) = pixel_per_module {
         bmp = bmp.add_white_border(2)?;
@@ -119,4 +135,15 @@ mod test {
         let uri_qr = address_to_uri_qr(&address, Some(4)).unwrap();
         assert_eq!(uri_qr, "data:image/bmp;base64,Qk0eEQAAAAAAAD4AAAAoAAAAtAAAALQAAAABAAEAAAAAAOAQAAAAAgAAAAIAAAIAAAACAAAA////AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA////8P8P//8P8ADwAP8PAPAA///wAAAA////8P8P//8P8ADwAP8PAPAA///wAAAA////8P8P//8P8ADwAP8PAPAA///wAAAA////8P8P//8P8ADwAP8PAPAA///wAAAA8AAA8ADwDw/wD//wAPD/8P8AAA8AAAAA8AAA8ADwDw/wD//wAPD/8P8AAA8AAAAA8AAA8ADwDw/wD//wAPD/8P8AAA8AAAAA8AAA8ADwDw/wD//wAPD/8P8AAA8AAAAA8P/w8PAPD/APDwD/8P//APAP//DwAAAA8P/w8PAPD/APDwD/8P//APAP//DwAAAA8P/w8PAPD/APDwD/8P//APAP//DwAAAA8P/w8PAPD/APDwD/8P//APAP//DwAAAA8P/w8ADw8PAPAPD/DwAA/wAA8A8AAAAA8P/w8ADw8PAPAPD/DwAA/wAA8A8AAAAA8P/w8ADw8PAPAPD/DwAA/wAA8A8AAAAA8P/w8ADw8PAPAPD/DwAA/wAA8A8AAAAA8P/w8PD//wDwDwDwAADw8P////8AAAAA8P/w8PD//wDwDwDwAADw8P////8AAAAA8P/w8PD//wDwDwDwAADw8P////8AAAAA8P/w8PD//wDwDwDwAADw8P////8AAAAA8AAA8ADwD/8AAA/wAA8P8PAA///wAAAA8AAA8ADwD/8AAA/wAA8P8PAA///wAAAA8AAA8ADwD/8AAA/wAA8P8PAA///wAAAA8AAA8ADwD/8AAA/wAA8P8PAA///wAAAA////8A8PD/D/DwDwDw/w//Dw8ADwAAAA////8A8PD/D/DwDwDw/w//Dw8ADwAAAA////8A8PD/D/DwDwDw/w//Dw8ADwAAAA////8A8PD/D/DwDwDw/w//Dw8ADwAAAAAAAAAP////8AAA8PAPD/APAA/wAAAAAAAAAAAP////8AAA8PAPD/APAA/wAAAAAAAAAAAP////8AAA8PAPD/APAA/wAAAAAAAAAAAP////8AAA8PAPD/APAA/wAAAAAA8P8A/wAAAA8PD//w/w8PAP//8ADwAAAA8P8A/wAAAA8PD//w/w8PAP//8ADwAAAA8P8A/wAAAA8PD//w/w8PAP//8ADwAAAA8P8A/wAAAA8PD//w/w8PAP//8ADwAAAAD/8PAA//APAP8PAPDwD//w8AD/8AAAAAD/8PAA//APAP8PAPDwD//w8AD/8AAAAAD/8PAA//APAP8PAPDwD//w8AD/8AAAAAD/8PAA//APAP8PAPDwD//w8AD/8AAAAA8P8P8AAPD/D///AAD//w8A//8PDwAAAA8P8P8AAPD/D///AAD//w8A//8PDwAAAA8P8P8AAPD/D///AAD//w8A//8PDwAAAA8P8P8AAPD/D///AAD//w8A//8PDwAAAAAPD/D/D/AP8P////AP///w8AAAAAAAAAAPD/D/D/AP8P////AP///w8AAAAAAAAAAPD/D/D/AP8P////AP///w8AAAAAAAAAAPD/D/D/AP8P////AP///w8AAAAAAAAAAP/w8A/w8ADw8P/wAPDw/w8P/w/wAAAAAP/w8A/w8ADw8P/wAPDw/w8P/w/wAAAAAP/w8A/w8ADw8P/wAPDw/w8P/w/wAAAAAP/w8A/w8ADw8P/wAPDw/w8P/w/wAAAA8A8AD/AA/wAPDw/wD/APAP/wAA8AAAAA8A8AD/AA/wAPDw/wD/APAP/wAA8AAAAA8A8AD/AA/wAPDw/wD/APAP/wAA8AAAAA8A8AD/AA/wAPDw/wD/APAP/wAA8AAAAA///w/w8PDwAAD/8A8ADwAP8PD/AAAAAA///w/w8PDwAAD/8A8ADwAP8PD/AAAAAA///w/w8PDwAAD/8A8ADwAP8PD/AAAAAA///w/w8PDwAAD/8A8ADwAP8PD/AAAAAAAPAPAPDwAP8PD/8A//DwAPD/D/DwAAAAAPAPAPDwAP8PD/8A//DwAPD/D/DwAAAAAPAPAPDwAP8PD/8A//DwAPD/D/DwAAAAAPAPAPDwAP8PD/8A//DwAPD/D/DwAAAAD/////8P8P8P////AA//D/D/8P8AAAAAD/////8P8P8P////AA//D/D/8P8AAAAAD/////8P8P8P////AA//D/D/8P8AAAAAD/////8P8P8P////AA//D/D/8P8AAAAA////D//wAP8P8PD/8PAP/w/wAAAAAAAA////D//wAP8P8PD/8PAP/w/wAAAAAAAA////D//wAP8P8PD/8PAP/w/wAAAAAAAA////D//wAP8P8PD/8PAP/w/wAAAAAAAADwAP8PAPAA8A8AAP/w8PAADwAA/wAAAADwAP8PAPAA8A8AAP/w8PAADwAA/wAAAADwAP8PAPAA8A8AAP/w8PAADwAA/wAAAADwAP8PAPAA8A8AAP/w8PAADwAA/wAAAADw//APAA8P8A8P8PD/D/APD/8P/wAAAADw//APAA8P8A8P8PD/D/APD/8P/wAAAADw//APAA8P8A8P8PD/D/APD/8P/wAAAADw//APAA8P8A8P8PD/D/APD/8P/wAAAA/w/w/w8A8P8A/wAA8PAAD/D///AAAAAA/w/w/w8A8P8A/wAA8PAAD/D///AAAAAA/w/w/w8A8P8A/wAA8PAAD/D///AAAAAA/w/w/w8A8P8A/wAA8PAAD/D///AAAAAAAA/wAP8P//8P8PAPD//wDw/wD/AAAAAAAA/wAP8P//8P8PAPD//wDw/wD/AAAAAAAA/wAP8P//8P8PAPD//wDw/wD/AAAAAAAA/wAP8P//8P8PAPD//wDw/wD/AAAAAA//8P8AAP8PDw/w8P8P//D/AP8AAAAAAA//8P8AAP8PDw/w8P8P//D/AP8AAAAAAA//8P8AAP8PDw/w8P8P//D/AP8AAAAAAA//8P8AAP8PDw/w8P8P//D/AP8AAAAAAA8P//D//w8AD/8PDwDwAA8A/wD//wAAAA8P//D//w8AD/8PDwDwAA8A/wD//wAAAA8P//D//w8AD/8PDwDwAA8A/wD//wAAAA8P//D//w8AD/8PDwDwAA8A/wD//wAAAA8A//8A8P8A8AAAD/D/DwDw/wD/8AAAAA8A//8A8P8A8AAAD/D/DwDw/wD/8AAAAA8A//8A8P8A8AAAD/D/DwDw/wD/8AAAAA8A//8A8P8A8AAAD/D/DwDw/wD/8AAAAA8AD/Dw8PAAAP/w8A//8PAPAPAPAAAAAA8AD/Dw8PAAAP/w8A//8PAPAPAPAAAAAA8AD/Dw8PAAAP/w8A//8PAPAPAPAAAAAA8AD/Dw8PAAAP/w8A//8PAPAPAPAAAAAAD/AP8AAP/wAA8A////AA//APAP/wAAAAD/AP8AAP/wAA8A////AA//APAP/wAAAAD/AP8AAP/wAA8A////AA//APAP/wAAAAD/AP8AAP/wAA8A////AA//APAP/wAAAAAAD/D/AA/w/wDw8P/w8AD/8PAPDwAAAAAAD/D/AA/w/wDw8P/w8AD/8PAPDwAAAAAAD/D/AA/w/wDw8P/w8AD/8PAPDwAAAAAAD/D/AA/w/wDw8P/w8AD/8PAPDwAAAA8P/w8P//APDw8AAA/w8P8PD/8ADwAAAA8P/w8P//APDw8AAA/w8P8PD/8ADwAAAA8P/w8P//APDw8AAA/w8P8PD/8ADwAAAA8P/w8P//APDw8AAA/w8P8PD/8ADwAAAA////Dw//AA8AAAAAAAAAD/8AAA8AAAAA////Dw//AA8AAAAAAAAAD/8AAA8AAAAA////Dw//AA8AAAAAAAAAD/8AAA8AAAAA////Dw//AA8AAAAAAAAAD/8AAA8AAAAA8P/w//AA/w//APAP8PAP8A//8P8AAAAA8P/w//AA/w//APAP8PAP8A//8P8AAAAA8P/w//AA/w//APAP8PAP8A//8P8AAAAA8P/w//AA/w//APAP8PAP8A//8P8AAAAAD//wD/AA//AAD//wD/AP8P/wAP/wAAAAD//wD/AA//AAD//wD/AP8P/wAP/wAAAAD//wD/AA//AAD//wD/AP8P/wAP/wAAAAD//wD/AA//AAD//wD/AP8P/wAP/wAAAA8PDw8AAA8PAA/wAAD/D/DwAA8A8AAAAA8PDw8AAA8PAA/wAAD/D/DwAA8A8AAAAA8PDw8AAA8PAA/wAAD/D/DwAA8A8AAAAA8PDw8AAA8PAA/wAAD/D/DwAA8A8AAAAAAAAAAA8P8P8A/w//D/8PAPAAAAAAAAAAAAAAAA8P8P8A/w//D/8PAPAAAAAAAAAAAAAAAA8P8P8A/w//D/8PAPAAAAAAAAAAAAAAAA8P8P8A/w//D/8PAPAAAAAAAAAA////8PDw8PDw8PDw8PDw8PD////wAAAA////8PDw8PDw8PDw8PDw8PD////wAAAA////8PDw8PDw8PDw8PDw8PD////wAAAA////8PDw8PDw8PDw8PDw8PD////wAAAA8AAA8A/w//8P/w8P8A8P8PDwAADwAAAA8AAA8A/w//8P/w8P8A8P8PDwAADwAAAA8AAA8A/w//8P/w8P8A8P8PDwAADwAAAA8AAA8A/w//8P/w8P8A8P8PDwAADwAAAA8P/w8P8A8P//8AAPAPAAAADw//DwAAAA8P/w8P8A8P//8AAPAPAAAADw//DwAAAA8P/w8P8A8P//8AAPAPAAAADw//DwAAAA8P/w8P8A8P//8AAPAPAAAADw//DwAAAA8P/w8A8A8PAP8A8A////D/Dw//DwAAAA8P/w8A8A8PAP8A8A////D/Dw//DwAAAA8P/w8A8A8PAP8A8A////D/Dw//DwAAAA8P/w8A8A8PAP8A8A////D/Dw//DwAAAA8P/w8ADw//8P8PAP//AADwDw//DwAAAA8P/w8ADw//8P8PAP//AADwDw//DwAAAA8P/w8ADw//8P8PAP//AADwDw//DwAAAA8P/w8ADw//8P8PAP//AADwDw//DwAAAA8AAA8PDw8PAA8P8P8P8PD/DwAADwAAAA8AAA8PDw8PAA8P8P8P8PD/DwAADwAAAA8AAA8PDw8PAA8P8P8P8PD/DwAADwAAAA8AAA8PDw8PAA8P8P8P8PD/DwAADwAAAA////8AAPAAD///APDwAA/wD////wAAAA////8AAPAAD///APDwAA/wD////wAAAA////8AAPAAD///APDwAA/wD////wAAAA////8AAPAAD///APDwAA/wD////wAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
     }
+
+    #[test]
+    fn test_ur_qr() {
+        let part = "ur:bytes/aeadaolazmjendeoti";
+        assert!(ur_to_text_qr(part).is_ok());
+        let uri_qr = ur_to_uri_qr(part, None).unwrap();
+        assert_eq!(
+            uri_qr,
+            text_to_uri_qr("UR:BYTES/AEADAOLAZMJENDEOTI", None).unwrap()
+        );
+    }
 }
@@ -4,8 +4,14 @@ use elements::{
     bitcoin::{
         self,
         bip32::{DerivationPath, Fingerprint, Xpub},
+        secp256k1::{
+            ecdsa::{RecoverableSignature, RecoveryId, Signature},
+            Message, PublicKey, Secp256k1,
+        },
+        sign_message::{signed_msg_hash, MessageSignature},
         XKeyIdentifier,
     },
+    hashes::Hash,
     pset::PartiallySignedTransaction,
 };
 use elements_miniscript::slip77::MasterBlindingKey;
@@ -61,3 +67,32 @@ pub trait Signer {
         Ok(self.xpub()?.network == bitcoin::NetworkKind::Main)
     }
 }
+
+/// A [`Signer`] able to sign messages, not every signer supports it
+pub trait MessageSigner: Signer {
+    /// Sign a message with the key derived at `path`, as Bitcoin Core `signmessage` does
+    fn sign_message(
+        &self,
+        message: &str,
+        path: &DerivationPath,
+    ) -> Result<MessageSignature, Self::Error>;
+}
+
+/// Make a message signature recoverable, for signers returning only the compact signature
+///
+/// Returns `None` if `signature` is not a signature of `message` for `public_key`.
+pub fn message_signature(
+    signature: &Signature,
+    message: &str,
+    public_key: &PublicKey,
+) -> Option<MessageSignature> {
+    let secp = Secp256k1::verification_only();
+    let msg = Message::from_digest(signed_msg_hash(message).to_byte_array());
+    let compact = signature.serialize_compact();
+    (0..4).find_map(|id| {
+        let id = RecoveryId::from_i32(id).ok()?;
+        let signature = RecoverableSignature::from_compact(&compact, id).ok()?;
+        (secp.recover_ecdsa(&msg, &signature).ok()? == *public_key)
+            .then(|| MessageSignature::new(signature, true))
+    })
+}
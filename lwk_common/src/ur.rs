@@ -0,0 +1,906 @@
+//! Uniform Resources, [BCR-2020-005](https://github.com/BlockchainCommons/Research/blob/master/papers/bcr-2020-005-ur.md),
+//! to transport PSETs and descriptors with animated QR codes
+//!
+//! Data larger than a single QR code is split in fragments, then the [`UrEncoder`] emits an
+//! endless sequence of parts: the first ones contain a fragment each, the following ones
+//! combine several fragments with a fountain code. The [`UrDecoder`] rebuilds the data from
+//! any sufficiently large subset of parts, received in any order, so a camera scanning the
+//! animation can miss frames.
+//!
+//! PSETs are encoded as `crypto-psbt`, descriptors as `output-descriptor`. Before encoding a
+//! PSET for a signer consider [`pset_prune_proofs`].
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use elements::encode::{deserialize, serialize};
+use elements::hashes::{sha256, Hash};
+use elements::pset::PartiallySignedTransaction;
+use thiserror::Error;
+
+/// The UR type of PSETs, the same of Bitcoin PSBTs
+pub const PSET_UR_TYPE: &str = "crypto-psbt";
+
+/// The UR type of descriptors
+pub const DESCRIPTOR_UR_TYPE: &str = "output-descriptor";
+
+/// The minimum length of a fragment, shorter messages are sent in a single part
+const MIN_FRAGMENT_LEN: usize = 10;
+
+/// The maximum length of a message received in multiple parts, much larger than the PSETs
+/// exchanged with QR codes
+const MAX_MESSAGE_LEN: usize = 100_000;
+
+/// The 256 bytewords, the minimal encoding uses their first and last letter
+const BYTEWORDS: &str = "ableacidalsoapexaquaarchatomauntawayaxisbackbaldbarnbeltbetabiasbluebodybragbrewbulbbuzzcalmcashcatschefcityclawcodecolacookcostcruxcurlcuspcyandarkdatadaysdelidicedietdoordowndrawdropdrumdulldutyeacheasyechoedgeepicevenexamexiteyesfactfairfernfigsfilmfishfizzflapflewfluxfoxyfreefrogfuelfundgalagamegeargemsgiftgirlglowgoodgraygrimgurugushgyrohalfhanghardhawkheathelphighhillholyhopehornhutsicedideaidleinchinkyintoirisironitemjadejazzjoinjoltjowljudojugsjumpjunkjurykeepkenokeptkeyskickkilnkingkitekiwiknoblamblavalazyleaflegsliarlimplionlistlogoloudloveluaulucklungmainmanymathmazememomenumeowmildmintmissmonknailnavyneednewsnextnoonnotenumbobeyoboeomitonyxopenovalowlspaidpartpeckplaypluspoempoolposepuffpumapurrquadquizraceramprealredorichroadrockroofrubyruinrunsrustsafesagascarsetssilkskewslotsoapsolosongstubsurfswantacotasktaxitenttiedtimetinytoiltombtoystriptunatwinuglyundouniturgeuservastveryvetovialvibeviewvisavoidvowswallwandwarmwaspwavewaxywebswhatwhenwhizwolfworkyankyawnyellyogayurtzapszerozestzinczonezoom";
+
+#[derive(Error, Debug)]
+pub enum UrError {
+    #[error("Invalid UR \"{0}\"")]
+    InvalidUr(String),
+
+    #[error("Invalid UR type \"{0}\"")]
+    InvalidType(String),
+
+    #[error("Invalid bytewords")]
+    InvalidBytewords,
+
+    #[error("Invalid checksum")]
+    InvalidChecksum,
+
+    #[error("Invalid CBOR: {0}")]
+    InvalidCbor(&'static str),
+
+    #[error("Maximum fragment length must be at least {MIN_FRAGMENT_LEN}")]
+    InvalidFragmentLength,
+
+    #[error("Cannot encode an empty message")]
+    EmptyMessage,
+
+    #[error("Part is not consistent with the previously received ones")]
+    InconsistentPart,
+
+    #[error("Message of {0} bytes is longer than the maximum of {MAX_MESSAGE_LEN}")]
+    MessageTooLong(usize),
+
+    #[error("Expected UR type \"{expected}\", found \"{found}\"")]
+    UnexpectedType { expected: String, found: String },
+
+    #[error(transparent)]
+    Encode(#[from] elements::encode::Error),
+
+    #[error(transparent)]
+    Utf8(#[from] std::string::FromUtf8Error),
+}
+
+/// Encode a message in an endless sequence of UR parts
+#[derive(Debug, Clone)]
+pub struct UrEncoder {
+    ur_type: String,
+    message: Vec<u8>,
+    message_len: usize,
+    checksum: u32,
+    fragments: Vec<Vec<u8>>,
+    seq_num: u32,
+}
+
+impl UrEncoder {
+    /// Create an encoder of `message`, which must be a CBOR data item of type `ur_type`
+    ///
+    /// Messages longer than `max_fragment_len` bytes are split in several parts.
+    pub fn new(ur_type: &str, message: &[u8], max_fragment_len: usize) -> Result<Self, UrError> {
+        check_type(ur_type)?;
+        if message.is_empty() {
+            return Err(UrError::EmptyMessage);
+        }
+        if max_fragment_len < MIN_FRAGMENT_LEN {
+            return Err(UrError::InvalidFragmentLength);
+        }
+        let fragment_len = nominal_fragment_len(message.len(), max_fragment_len);
+        let fragments = message
+            .chunks(fragment_len)
+            .map(|c| {
+                let mut fragment = c.to_vec();
+                fragment.resize(fragment_len, 0);
+                fragment
+            })
+            .collect();
+        Ok(Self {
+            ur_type: ur_type.to_string(),
+            message: message.to_vec(),
+            message_len: message.len(),
+            checksum: crc32(message),
+            fragments,
+            seq_num: 0,
+        })
+    }
+
+    /// The number of fragments, at least this many parts are needed to decode the message
+    pub fn fragment_count(&self) -> usize {
+        self.fragments.len()
+    }
+
+    /// Whether the message fits in a single part, which doesn't need an animated QR code
+    pub fn is_single_part(&self) -> bool {
+        self.fragments.len() == 1
+    }
+
+    /// The next part, in lowercase
+    ///
+    /// Parts should be uppercased before creating the QR code to use its alphanumeric mode.
+    pub fn next_part(&mut self) -> String {
+        if self.is_single_part() {
+            return format!("ur:{}/{}", self.ur_type, bytewords_encode(&self.message));
+        }
+        self.seq_num = self.seq_num.wrapping_add(1);
+        let seq_len = self.fragments.len();
+        let mut data = vec![0u8; self.fragments[0].len()];
+        for i in choose_fragments(self.seq_num, seq_len, self.checksum) {
+            xor_into(&mut data, &self.fragments[i]);
+        }
+
+        let mut part = vec![];
+        cbor_header(&mut part, 4, 5);
+        cbor_header(&mut part, 0, self.seq_num as u64);
+        cbor_header(&mut part, 0, seq_len as u64);
+        cbor_header(&mut part, 0, self.message_len as u64);
+        cbor_header(&mut part, 0, self.checksum as u64);
+        cbor_header(&mut part, 2, data.len() as u64);
+        part.extend(data);
+
+        format!(
+            "ur:{}/{}-{}/{}",
+            self.ur_type,
+            self.seq_num,
+            seq_len,
+            bytewords_encode(&part)
+        )
+    }
+}
+
+/// Decode UR parts, received in any order
+#[derive(Debug, Default)]
+pub struct UrDecoder {
+    ur_type: Option<String>,
+    seq_len: usize,
+    message_len: usize,
+    checksum: u32,
+    fragment_len: usize,
+    simple: BTreeMap<usize, Vec<u8>>,
+    mixed: Vec<(BTreeSet<usize>, Vec<u8>)>,
+    message: Option<Vec<u8>>,
+}
+
+impl UrDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Receive a part, in lowercase or uppercase
+    ///
+    /// Parts received after the message is complete are ignored.
+    pub fn receive(&mut self, part: &str) -> Result<(), UrError> {
+        let part = part.trim().to_ascii_lowercase();
+        let invalid = || UrError::InvalidUr(part.clone());
+        let body = part.strip_prefix("ur:").ok_or_else(invalid)?;
+        let components: Vec<&str> = body.split('/').collect();
+        let ur_type = components[0];
+        check_type(ur_type)?;
+        if let Some(expected) = self.ur_type.as_ref() {
+            if expected != ur_type {
+                return Err(UrError::UnexpectedType {
+                    expected: expected.clone(),
+                    found: ur_type.to_string(),
+                });
+            }
+        }
+
+        match components[..] {
+            [_, bytewords] => {
+                let message = bytewords_decode(bytewords)?;
+                self.ur_type = Some(ur_type.to_string());
+                self.message = Some(message);
+                Ok(())
+            }
+            [_, sequence, bytewords] => {
+                let (seq_num, seq_len) = sequence.split_once('-').ok_or_else(invalid)?;
+                let seq_num: u32 = seq_num.parse().map_err(|_| invalid())?;
+                let seq_len: usize = seq_len.parse().map_err(|_| invalid())?;
+                let (part_seq_num, part_seq_len, message_len, checksum, data) =
+                    parse_part(&bytewords_decode(bytewords)?)?;
+                if message_len > MAX_MESSAGE_LEN {
+                    return Err(UrError::MessageTooLong(message_len));
+                }
+                if seq_num == 0
+                    || seq_len == 0
+                    || part_seq_num != seq_num
+                    || part_seq_len != seq_len
+                    || data.len() < MIN_FRAGMENT_LEN.min(message_len).max(1)
+                    || seq_len
+                        .checked_mul(data.len())
+                        .map_or(true, |len| message_len > len)
+                {
+                    return Err(invalid());
+                }
+                // Shorter fragments, or more than needed by the message, would only make
+                // choosing the fragments of the mixed parts slower
+                if seq_len > message_len.div_ceil(data.len()) {
+                    return Err(invalid());
+                }
+
+                if self.ur_type.is_none() {
+                    self.ur_type = Some(ur_type.to_string());
+                    self.seq_len = seq_len;
+                    self.message_len = message_len;
+                    self.checksum = checksum;
+                    self.fragment_len = data.len();
+                } else if self.seq_len != seq_len
+                    || self.message_len != message_len
+                    || self.checksum != checksum
+                    || self.fragment_len != data.len()
+                {
+                    return Err(UrError::InconsistentPart);
+                }
+                if self.message.is_some() {
+                    return Ok(());
+                }
+
+                self.add(choose_fragments(seq_num, seq_len, checksum), data);
+                if self.simple.len() == self.seq_len {
+                    let mut message: Vec<u8> = self.simple.values().flatten().cloned().collect();
+                    message.truncate(self.message_len);
+                    if crc32(&message) != self.checksum {
+                        return Err(UrError::InvalidChecksum);
+                    }
+                    self.message = Some(message);
+                }
+                Ok(())
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Whether the message has been decoded
+    pub fn is_complete(&self) -> bool {
+        self.message.is_some()
+    }
+
+    /// The fraction of fragments decoded, between 0 and 1
+    pub fn progress(&self) -> f32 {
+        match (&self.message, self.seq_len) {
+            (Some(_), _) => 1.0,
+            (None, 0) => 0.0,
+            (None, seq_len) => self.simple.len() as f32 / seq_len as f32,
+        }
+    }
+
+    /// The UR type, known after receiving the first part
+    pub fn ur_type(&self) -> Option<&str> {
+        self.ur_type.as_deref()
+    }
+
+    /// The decoded message, a CBOR data item
+    pub fn message(&self) -> Option<&[u8]> {
+        self.message.as_deref()
+    }
+
+    /// The decoded PSET, `None` if the message is not complete
+    pub fn pset(&self) -> Result<Option<PartiallySignedTransaction>, UrError> {
+        match self.typed_message(PSET_UR_TYPE)? {
+            Some(message) => {
+                let mut cbor = Cbor::new(message);
+                Ok(Some(deserialize(cbor.bytes(2)?)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// The decoded descriptor, `None` if the message is not complete
+    pub fn descriptor(&self) -> Result<Option<String>, UrError> {
+        match self.typed_message(DESCRIPTOR_UR_TYPE)? {
+            Some(message) => {
+                let mut cbor = Cbor::new(message);
+                let (major, len) = cbor.header()?;
+                if major != 5 || len == 0 || cbor.header()? != (0, 1) {
+                    return Err(UrError::InvalidCbor("expected the descriptor source"));
+                }
+                Ok(Some(String::from_utf8(cbor.bytes(3)?.to_vec())?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn typed_message(&self, expected: &str) -> Result<Option<&[u8]>, UrError> {
+        match (self.ur_type.as_deref(), self.message.as_deref()) {
+            (Some(found), _) if found != expected => Err(UrError::UnexpectedType {
+                expected: expected.to_string(),
+                found: found.to_string(),
+            }),
+            (_, message) => Ok(message),
+        }
+    }
+
+    /// Add a part, reducing it and the mixed parts with the known fragments
+    fn add(&mut self, indexes: BTreeSet<usize>, data: Vec<u8>) {
+        let mut queue = vec![(indexes, data)];
+        while let Some((mut indexes, mut data)) = queue.pop() {
+            for (i, fragment) in self.simple.iter() {
+                if indexes.remove(i) {
+                    xor_into(&mut data, fragment);
+                }
+            }
+            for (mixed_indexes, mixed_data) in self.mixed.iter() {
+                if mixed_indexes.len() < indexes.len() && mixed_indexes.is_subset(&indexes) {
+                    indexes = indexes.difference(mixed_indexes).cloned().collect();
+                    xor_into(&mut data, mixed_data);
+                }
+            }
+
+            match indexes.len() {
+                0 => (),
+                1 => {
+                    let i = *indexes.iter().next().expect("one index");
+                    self.simple.insert(i, data);
+                    let (affected, rest) = std::mem::take(&mut self.mixed)
+                        .into_iter()
+                        .partition(|(m, _)| m.contains(&i));
+                    self.mixed = rest;
+                    queue.extend(affected);
+                }
+                _ => {
+                    if self.mixed.iter().any(|(m, _)| *m == indexes) {
+                        continue;
+                    }
+                    let (affected, rest) = std::mem::take(&mut self.mixed)
+                        .into_iter()
+                        .partition(|(m, _)| indexes.is_subset(m));
+                    self.mixed = rest;
+                    self.mixed.push((indexes, data));
+                    queue.extend(affected);
+                }
+            }
+        }
+    }
+}
+
+/// Create an encoder of the PSET, as `crypto-psbt`
+pub fn pset_ur_encoder(
+    pset: &PartiallySignedTransaction,
+    max_fragment_len: usize,
+) -> Result<UrEncoder, UrError> {
+    let bytes = serialize(pset);
+    let mut message = vec![];
+    cbor_header(&mut message, 2, bytes.len() as u64);
+    message.extend(bytes);
+    UrEncoder::new(PSET_UR_TYPE, &message, max_fragment_len)
+}
+
+/// Create an encoder of the descriptor, as `output-descriptor` with only the source text
+pub fn descriptor_ur_encoder(
+    descriptor: &str,
+    max_fragment_len: usize,
+) -> Result<UrEncoder, UrError> {
+    let mut message = vec![];
+    cbor_header(&mut message, 5, 1);
+    cbor_header(&mut message, 0, 1);
+    cbor_header(&mut message, 3, descriptor.len() as u64);
+    message.extend(descriptor.as_bytes());
+    UrEncoder::new(DESCRIPTOR_UR_TYPE, &message, max_fragment_len)
+}
+
+/// Remove from the PSET the data not needed to sign it, to reduce its size
+///
+/// The rangeproofs of the previous outputs are removed from inputs having the explicit amount
+/// and asset with their blind proofs, which let the signer verify the amounts without
+/// unblinding. Previous transactions are removed from inputs having the previous output.
+///
+/// The rangeproofs and surjection proofs of outputs having blind proofs and no key derivations
+/// are replaced by the much smaller blind proofs: they are not committed by the signature hash,
+/// but a blinded output cannot be parsed without them. The resulting transaction is not valid.
+/// Outputs with key derivations, such as the change, keep their proofs, so that the signer can
+/// check with [`crate::pset_balance`] that it can unblind them.
+///
+/// The signed PSET must be merged into the original one, as in `original.merge(signed)`, to
+/// get a complete transaction with the original proofs.
+pub fn pset_prune_proofs(pset: &PartiallySignedTransaction) -> PartiallySignedTransaction {
+    let mut pset = pset.clone();
+    for input in pset.inputs_mut() {
+        if input.amount.is_some()
+            && input.blind_value_proof.is_some()
+            && input.asset.is_some()
+            && input.blind_asset_proof.is_some()
+        {
+            input.in_utxo_rangeproof = None;
+        }
+        if input.witness_utxo.is_some() {
+            input.non_witness_utxo = None;
+        }
+    }
+    for output in pset.outputs_mut() {
+        if !output.bip32_derivation.is_empty() {
+            continue;
+        }
+        if let (Some(value_proof), Some(asset_proof), Some(_), Some(_)) = (
+            output.blind_value_proof.as_ref(),
+            output.blind_asset_proof.as_ref(),
+            output.value_rangeproof.as_ref(),
+            output.asset_surjection_proof.as_ref(),
+        ) {
+            output.value_rangeproof = Some(value_proof.clone());
+            output.asset_surjection_proof = Some(asset_proof.clone());
+        }
+    }
+    pset
+}
+
+fn check_type(ur_type: &str) -> Result<(), UrError> {
+    let valid = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-';
+    if ur_type.is_empty() || !ur_type.chars().all(valid) {
+        return Err(UrError::InvalidType(ur_type.to_string()));
+    }
+    Ok(())
+}
+
+fn nominal_fragment_len(message_len: usize, max_fragment_len: usize) -> usize {
+    let max_fragment_count = (message_len / MIN_FRAGMENT_LEN).max(1);
+    (1..=max_fragment_count)
+        .map(|count| (message_len + count - 1) / count)
+        .find(|len| *len <= max_fragment_len)
+        .unwrap_or(MIN_FRAGMENT_LEN)
+}
+
+fn xor_into(data: &mut [u8], other: &[u8]) {
+    data.iter_mut().zip(other).for_each(|(a, b)| *a ^= b);
+}
+
+/// The indexes of the fragments combined in the part `seq_num`
+fn choose_fragments(seq_num: u32, seq_len: usize, checksum: u32) -> BTreeSet<usize> {
+    if seq_num as usize <= seq_len {
+        return BTreeSet::from([seq_num as usize - 1]);
+    }
+    let mut seed = seq_num.to_be_bytes().to_vec();
+    seed.extend(checksum.to_be_bytes());
+    let mut rng = Xoshiro256::new(sha256::Hash::hash(&seed).to_byte_array());
+
+    let weights: Vec<f64> = (1..=seq_len).map(|i| 1.0 / i as f64).collect();
+    let degree = AliasSampler::new(&weights).next(&mut rng) + 1;
+
+    // Only the first `degree` indexes of the shuffle are needed
+    let mut remaining: Vec<usize> = (0..seq_len).collect();
+    let mut chosen = BTreeSet::new();
+    while chosen.len() < degree {
+        let i = rng.next_int(0, remaining.len() as u64 - 1) as usize;
+        chosen.insert(remaining.remove(i));
+    }
+    chosen
+}
+
+/// The pseudorandom generator of the fountain code, xoshiro256**
+struct Xoshiro256 {
+    s: [u64; 4],
+}
+
+impl Xoshiro256 {
+    fn new(seed: [u8; 32]) -> Self {
+        let mut s = [0u64; 4];
+        for (i, chunk) in seed.chunks(8).enumerate() {
+            s[i] = u64::from_be_bytes(chunk.try_into().expect("8 bytes"));
+        }
+        Self { s }
+    }
+
+    fn next(&mut self) -> u64 {
+        let result = self.s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.s[1] << 17;
+        self.s[2] ^= self.s[0];
+        self.s[3] ^= self.s[1];
+        self.s[1] ^= self.s[2];
+        self.s[0] ^= self.s[3];
+        self.s[2] ^= t;
+        self.s[3] = self.s[3].rotate_left(45);
+        result
+    }
+
+    fn next_double(&mut self) -> f64 {
+        self.next() as f64 / (u64::MAX as f64 + 1.0)
+    }
+
+    fn next_int(&mut self, low: u64, high: u64) -> u64 {
+        (self.next_double() * (high - low + 1) as f64) as u64 + low
+    }
+}
+
+/// Walker's alias method, sampling indexes with the given weights
+struct AliasSampler {
+    probs: Vec<f64>,
+    aliases: Vec<usize>,
+}
+
+impl AliasSampler {
+    fn new(weights: &[f64]) -> Self {
+        let n = weights.len();
+        let sum: f64 = weights.iter().sum();
+        let mut p: Vec<f64> = weights.iter().map(|w| w * n as f64 / sum).collect();
+        let (mut small, mut large): (Vec<usize>, Vec<usize>) =
+            (0..n).rev().partition(|i| p[*i] < 1.0);
+
+        let mut probs = vec![0.0; n];
+        let mut aliases = vec![0; n];
+        while !small.is_empty() && !large.is_empty() {
+            let a = small.pop().expect("not empty");
+            let g = large.pop().expect("not empty");
+            probs[a] = p[a];
+            aliases[a] = g;
+            p[g] += p[a] - 1.0;
+            if p[g] < 1.0 {
+                small.push(g);
+            } else {
+                large.push(g);
+            }
+        }
+        for i in large.into_iter().chain(small) {
+            probs[i] = 1.0;
+        }
+        Self { probs, aliases }
+    }
+
+    fn next(&self, rng: &mut Xoshiro256) -> usize {
+        let r1 = rng.next_double();
+        let r2 = rng.next_double();
+        let i = (self.probs.len() as f64 * r1) as usize;
+        if r2 < self.probs[i] {
+            i
+        } else {
+            self.aliases[i]
+        }
+    }
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Minimal bytewords of `data` followed by its checksum
+fn bytewords_encode(data: &[u8]) -> String {
+    let words = BYTEWORDS.as_bytes();
+    let checksum = crc32(data).to_be_bytes();
+    let mut result = String::with_capacity((data.len() + 4) * 2);
+    for byte in data.iter().chain(checksum.iter()) {
+        let word = &words[*byte as usize * 4..*byte as usize * 4 + 4];
+        result.push(word[0] as char);
+        result.push(word[3] as char);
+    }
+    result
+}
+
+fn bytewords_decode(s: &str) -> Result<Vec<u8>, UrError> {
+    let words = BYTEWORDS.as_bytes();
+    let s = s.as_bytes();
+    if s.len() % 2 != 0 || s.len() < 10 {
+        return Err(UrError::InvalidBytewords);
+    }
+    let mut data = s
+        .chunks(2)
+        .map(|pair| {
+            words
+                .chunks(4)
+                .position(|w| w[0] == pair[0] && w[3] == pair[1])
+                .map(|i| i as u8)
+                .ok_or(UrError::InvalidBytewords)
+        })
+        .collect::<Result<Vec<u8>, _>>()?;
+    let checksum = data.split_off(data.len() - 4);
+    if crc32(&data).to_be_bytes()[..] != checksum[..] {
+        return Err(UrError::InvalidChecksum);
+    }
+    Ok(data)
+}
+
+fn cbor_header(out: &mut Vec<u8>, major: u8, value: u64) {
+    let major = major << 5;
+    match value {
+        0..=23 => out.push(major | value as u8),
+        24..=0xFF => out.extend([major | 24, value as u8]),
+        0x100..=0xFFFF => {
+            out.push(major | 25);
+            out.extend((value as u16).to_be_bytes());
+        }
+        0x1_0000..=0xFFFF_FFFF => {
+            out.push(major | 26);
+            out.extend((value as u32).to_be_bytes());
+        }
+        _ => {
+            out.push(major | 27);
+            out.extend(value.to_be_bytes());
+        }
+    }
+}
+
+/// Parse a part of a multi-part UR: sequence number and length, message length, checksum and data
+fn parse_part(part: &[u8]) -> Result<(u32, usize, usize, u32, Vec<u8>), UrError> {
+    let mut cbor = Cbor::new(part);
+    if cbor.header()? != (4, 5) {
+        return Err(UrError::InvalidCbor("expected an array of 5 elements"));
+    }
+    let seq_num = u32::try_from(cbor.uint()?).map_err(|_| UrError::InvalidCbor("seq_num"))?;
+    let seq_len = cbor.uint()? as usize;
+    let message_len = cbor.uint()? as usize;
+    let checksum = u32::try_from(cbor.uint()?).map_err(|_| UrError::InvalidCbor("checksum"))?;
+    let data = cbor.bytes(2)?.to_vec();
+    Ok((seq_num, seq_len, message_len, checksum, data))
+}
+
+/// A reader of the few CBOR items used by URs
+struct Cbor<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cbor<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], UrError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or(UrError::InvalidCbor("unexpected end"))?;
+        let result = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(result)
+    }
+
+    /// The major type and the value, or the length, of the next item
+    fn header(&mut self) -> Result<(u8, u64), UrError> {
+        let first = self.take(1)?[0];
+        let len = match first & 0x1F {
+            n @ 0..=23 => return Ok((first >> 5, n as u64)),
+            24 => 1,
+            25 => 2,
+            26 => 4,
+            27 => 8,
+            _ => return Err(UrError::InvalidCbor("unsupported length")),
+        };
+        let value = self
+            .take(len)?
+            .iter()
+            .fold(0u64, |v, b| (v << 8) | *b as u64);
+        Ok((first >> 5, value))
+    }
+
+    fn uint(&mut self) -> Result<u64, UrError> {
+        match self.header()? {
+            (0, value) => Ok(value),
+            _ => Err(UrError::InvalidCbor("expected an unsigned integer")),
+        }
+    }
+
+    /// A byte string, or a text string with major type 3
+    fn bytes(&mut self, major: u8) -> Result<&'a [u8], UrError> {
+        match self.header()? {
+            (m, len) if m == major => self.take(len as usize),
+            _ => Err(UrError::InvalidCbor("expected a string")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn make_message(len: usize) -> Vec<u8> {
+        let mut rng = Xoshiro256::new(sha256::Hash::hash(b"Wolf").to_byte_array());
+        (0..len).map(|_| rng.next_int(0, 255) as u8).collect()
+    }
+
+    #[test]
+    fn test_bytewords() {
+        assert_eq!(BYTEWORDS.len(), 256 * 4);
+        assert_eq!(bytewords_encode(&[0, 1, 2, 128, 255]), "aeadaolazmjendeoti");
+        assert_eq!(
+            bytewords_decode("aeadaolazmjendeoti").unwrap(),
+            vec![0, 1, 2, 128, 255]
+        );
+        assert!(matches!(
+            bytewords_decode("aeadaolazmjendeotu"),
+            Err(UrError::InvalidBytewords)
+        ));
+        assert!(matches!(
+            bytewords_decode("aeadaolazmjendeoto"),
+            Err(UrError::InvalidChecksum)
+        ));
+        assert_eq!(crc32(b"Hello, world!"), 0xebe6c6e6);
+    }
+
+    #[test]
+    fn test_ur_fountain() {
+        let mut message = vec![];
+        cbor_header(&mut message, 2, 256);
+        message.extend(make_message(256));
+
+        let mut encoder = UrEncoder::new("bytes", &message, 30).unwrap();
+        assert_eq!(encoder.fragment_count(), 9);
+        assert_eq!(
+            encoder.next_part(),
+            "ur:bytes/1-9/lpadascfadaxcywenbpljkhdcahkadaemejtswhhylkepmykhhtsytsnoyoyaxaedsuttydmmhhpktpmsrjtdkgslpgh"
+        );
+
+        // Skip some of the simple parts, decode with the mixed ones
+        let parts: Vec<String> = (2..40).map(|_| encoder.next_part()).collect();
+
+        // The mixed parts of the reference test vector
+        let expected = [
+            "ur:bytes/10-9/lpbkascfadaxcywenbpljkhdcahkadaemejtswhhylkepmykhhtsytsnoyoyaxaedsuttydmmhhpktpmsrjtwdkiplzs",
+            "ur:bytes/11-9/lpbdascfadaxcywenbpljkhdcahelbknlkuejnbadmssfhfrdpsbiegecpasvssovlgeykssjykklronvsjkvetiiapk",
+            "ur:bytes/12-9/lpbnascfadaxcywenbpljkhdcarllaluzmdmgstospeyiefmwejlwtpedamktksrvlcygmzemovovllarodtmtbnptrs",
+            "ur:bytes/13-9/lpbtascfadaxcywenbpljkhdcamtkgtpknghchchyketwsvwgwfdhpgmgtylctotzopdrpayoschcmhplffziachrfgd",
+            "ur:bytes/14-9/lpbaascfadaxcywenbpljkhdcapazewnvonnvdnsbyleynwtnsjkjndeoldydkbkdslgjkbbkortbelomueekgvstegt",
+            "ur:bytes/15-9/lpbsascfadaxcywenbpljkhdcaynmhpddpzmversbdqdfyrehnqzlugmjzmnmtwmrouohtstgsbsahpawkditkckynwt",
+            "ur:bytes/16-9/lpbeascfadaxcywenbpljkhdcawygekobamwtlihsnpalnsghenskkiynthdzotsimtojetprsttmukirlrsbtamjtpd",
+            "ur:bytes/17-9/lpbyascfadaxcywenbpljkhdcamklgftaxykpewyrtqzhydntpnytyisincxmhtbceaykolduortotiaiaiafhiaoyce",
+            "ur:bytes/18-9/lpbgascfadaxcywenbpljkhdcahkadaemejtswhhylkepmykhhtsytsnoyoyaxaedsuttydmmhhpktpmsrjtntwkbkwy",
+            "ur:bytes/19-9/lpbwascfadaxcywenbpljkhdcadekicpaajootjzpsdrbalpeywllbdsnbinaerkurspbncxgslgftvtsrjtksplcpeo",
+            "ur:bytes/20-9/lpbbascfadaxcywenbpljkhdcayapmrleeleaxpasfrtrdkncffwjyjzgyetdmlewtkpktgllepfrltataztksmhkbot",
+        ];
+        assert_eq!(parts[8..19], expected);
+        let mut decoder = UrDecoder::new();
+        for part in parts
+            .iter()
+            .filter(|p| !p.contains("/3-9/") && !p.contains("/7-9/"))
+        {
+            decoder.receive(&part.to_uppercase()).unwrap();
+            if decoder.is_complete() {
+                break;
+            }
+        }
+        assert!(decoder.is_complete());
+        assert_eq!(decoder.progress(), 1.0);
+        assert_eq!(decoder.ur_type(), Some("bytes"));
+        assert_eq!(decoder.message(), Some(&message[..]));
+
+        let mut decoder = UrDecoder::new();
+        decoder.receive(&parts[0]).unwrap();
+        assert!(!decoder.is_complete());
+        assert!(decoder.progress() > 0.0);
+        let err = decoder
+            .receive("ur:crypto-psbt/aeadaolazmjendeoti")
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Expected UR type \"bytes\", found \"crypto-psbt\""
+        );
+        assert!(UrEncoder::new("Bytes", &message, 30).is_err());
+        assert!(UrEncoder::new("bytes", &message, 5).is_err());
+
+        // Parts declaring more fragments than the message needs, or overflowing, are rejected
+        let part = |seq_len: u64, message_len: u64| {
+            let mut part = vec![];
+            cbor_header(&mut part, 4, 5);
+            cbor_header(&mut part, 0, 1);
+            cbor_header(&mut part, 0, seq_len);
+            cbor_header(&mut part, 0, message_len);
+            cbor_header(&mut part, 0, 0);
+            cbor_header(&mut part, 2, 10);
+            part.extend([0u8; 10]);
+            format!("ur:bytes/1-{seq_len}/{}", bytewords_encode(&part))
+        };
+        assert!(UrDecoder::new().receive(&part(3, 25)).is_ok());
+        for (seq_len, message_len) in [(4, 25), (1_000_000, 25), (3, 31), (u64::MAX, 25)] {
+            let err = UrDecoder::new()
+                .receive(&part(seq_len, message_len))
+                .unwrap_err();
+            assert!(matches!(err, UrError::InvalidUr(_)));
+        }
+        assert!(UrDecoder::new().receive(&part(10_000, 100_000)).is_ok());
+        for (seq_len, message_len) in [(100_000_000, 1_000_000_000), (1, u64::MAX)] {
+            let err = UrDecoder::new()
+                .receive(&part(seq_len, message_len))
+                .unwrap_err();
+            assert!(matches!(err, UrError::MessageTooLong(_)));
+        }
+    }
+
+    #[test]
+    fn test_ur_pset_descriptor() {
+        let pset = include_str!("../test_data/pset_details/pset.base64");
+        let pset: PartiallySignedTransaction = pset.trim().parse().unwrap();
+
+        // Without the explicit amounts the input rangeproof is needed
+        let mut with_tx = pset.clone();
+        with_tx.inputs_mut()[0].non_witness_utxo = Some(pset.extract_tx().unwrap());
+        let pruned = pset_prune_proofs(&with_tx);
+        assert!(serialize(&pruned).len() < serialize(&with_tx).len());
+        assert!(pruned.inputs()[0].in_utxo_rangeproof.is_some());
+        assert!(pruned.inputs()[0].non_witness_utxo.is_none());
+        let mut combined = pset.clone();
+        combined.merge(pruned.clone()).unwrap();
+        assert_eq!(combined.inputs(), pset.inputs());
+        assert_eq!(combined.outputs(), pset.outputs());
+
+        let mut encoder = pset_ur_encoder(&pruned, 200).unwrap();
+        assert!(!encoder.is_single_part());
+        let mut decoder = UrDecoder::new();
+        while !decoder.is_complete() {
+            decoder.receive(&encoder.next_part()).unwrap();
+        }
+        assert_eq!(decoder.pset().unwrap(), Some(pruned));
+        assert!(decoder.descriptor().is_err());
+
+        let desc = "ct(elip151,elwpkh(tpubD6NzVbkrYhZ4Was8nwnZi7eiWUNJq2LFpPSCMQLioUfUtT1e72GkRbmVeRAZc26j5MRUz2hRLsaVHJfs6L7ppNfLUrm9btQTuaEsLrT7D87/*))";
+        let mut encoder = descriptor_ur_encoder(desc, 1000).unwrap();
+        assert!(encoder.is_single_part());
+        let part = encoder.next_part();
+        assert!(part.starts_with("ur:output-descriptor/"));
+        assert_eq!(encoder.next_part(), part);
+        let mut decoder = UrDecoder::new();
+        decoder.receive(&part).unwrap();
+        assert_eq!(decoder.descriptor().unwrap().as_deref(), Some(desc));
+    }
+
+    #[test]
+    fn test_pset_prune_proofs() {
+        use elements::sighash::SighashCache;
+        use elements::EcdsaSighashType;
+
+        let pset = include_str!("../test_data/pset_details/pset_tx_builder.base64");
+        let pset: PartiallySignedTransaction = pset.trim().parse().unwrap();
+        let pruned = pset_prune_proofs(&pset);
+
+        // Most of the size of a PSET are the rangeproofs
+        let (len, pruned_len) = (serialize(&pset).len(), serialize(&pruned).len());
+        assert!(pruned_len * 2 < len, "{pruned_len} {len}");
+        assert!(pruned.inputs()[0].in_utxo_rangeproof.is_none());
+        assert!(pruned.inputs()[0].non_witness_utxo.is_none());
+        assert_eq!(
+            deserialize::<PartiallySignedTransaction>(&serialize(&pruned)).unwrap(),
+            pruned
+        );
+
+        // The signer can still check the balance, the change keeps its proofs
+        assert!(pruned.outputs()[0].value_rangeproof == pset.outputs()[0].value_rangeproof);
+        assert!(pruned.outputs()[1].value_rangeproof != pset.outputs()[1].value_rangeproof);
+        let desc = "ct(slip77(0c11648c2c6df4f9dacdb4c8d35d6166d94cea2b9ad37833a82210bb7c9f5fb4),elwpkh([f5acc2fd/84'/1'/0']tpubDCtKfsNyRhULjZ9XMS4VKKtVcPdVDi8MKUbcSD9MJDyjRu1A2ND5MiipozyyspBT9bg8upEp7a8EAgFxNxXn1d7QkdbL52Ty5jiSLcxPt1P/<0;1>/*))";
+        let desc = desc.parse().unwrap();
+        let balance = crate::pset_balance(&pset, &desc).unwrap();
+        assert_eq!(balance.fee, 2491);
+        assert_eq!(balance.balances.values().sum::<i64>(), -10_002_491);
+        let pruned_balance = crate::pset_balance(&pruned, &desc).unwrap();
+        assert_eq!(pruned_balance.fee, balance.fee);
+        assert_eq!(pruned_balance.balances, balance.balances);
+
+        // The signature hashes are unchanged
+        let (tx, pruned_tx) = (pset.extract_tx().unwrap(), pruned.extract_tx().unwrap());
+        let mut cache = SighashCache::new(&tx);
+        let mut pruned_cache = SighashCache::new(&pruned_tx);
+        for (idx, input) in pset.inputs().iter().enumerate() {
+            let txout = input.witness_utxo.as_ref().unwrap();
+            let sighash = |cache: &mut SighashCache<_>| {
+                cache.segwitv0_sighash(
+                    idx,
+                    &txout.script_pubkey,
+                    txout.value,
+                    EcdsaSighashType::All,
+                )
+            };
+            assert_eq!(sighash(&mut cache), sighash(&mut pruned_cache));
+        }
+
+        // Merging into the original restores the proofs
+        let mut combined = pset.clone();
+        combined.merge(pruned).unwrap();
+        assert_eq!(combined.inputs(), pset.inputs());
+        assert_eq!(combined.outputs(), pset.outputs());
+    }
+}
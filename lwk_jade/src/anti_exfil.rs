@@ -0,0 +1,196 @@
+//! Anti-exfil protocol, to prevent the Jade from leaking key material through signature nonces.
+//!
+//! For every signature the host:
+//! 1. generates random entropy with [`host_entropy()`] and sends its [`host_commitment()`],
+//! 2. receives the signer commitment, the nonce point before being tweaked with the host entropy,
+//! 3. sends the entropy and receives the signature,
+//! 4. checks with [`verify()`] that the signature nonce is the signer commitment tweaked with the
+//!    host entropy and that the signature is valid.
+//!
+//! This is the ECDSA sign-to-contract scheme of libsecp256k1-zkp, which is not exposed by
+//! rust-secp256k1-zkp.
+
+use elements::{
+    hashes::{sha256, Hash, HashEngine},
+    secp256k1_zkp::{ecdsa::Signature, Message, PublicKey, Scalar, Secp256k1},
+};
+use rand::RngCore;
+
+const DATA_TAG: &[u8] = b"s2c/ecdsa/data";
+const POINT_TAG: &[u8] = b"s2c/ecdsa/point";
+
+/// Order of the secp256k1 group
+const ORDER: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+];
+
+/// Random entropy to be used for a single signature
+pub fn host_entropy() -> [u8; 32] {
+    let mut entropy = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut entropy);
+    entropy
+}
+
+/// Commitment to the host entropy, sent to the Jade before it commits to its nonce
+pub fn host_commitment(host_entropy: &[u8; 32]) -> [u8; 32] {
+    tagged_hash(DATA_TAG, &[host_entropy])
+}
+
+/// Verify that `signature` is a valid signature of `message` for `public_key`, created with the
+/// nonce committed in `signer_commitment` tweaked with `host_entropy`.
+pub fn verify(
+    signature: &Signature,
+    message: &Message,
+    public_key: &PublicKey,
+    host_entropy: &[u8; 32],
+    signer_commitment: &[u8],
+) -> bool {
+    let secp = Secp256k1::verification_only();
+    let opening = match PublicKey::from_slice(signer_commitment) {
+        Ok(opening) => opening,
+        Err(_) => return false,
+    };
+    let tweak = tagged_hash(POINT_TAG, &[&opening.serialize(), host_entropy]);
+    let nonce = match Scalar::from_be_bytes(tweak)
+        .ok()
+        .and_then(|tweak| opening.add_exp_tweak(&secp, &tweak).ok())
+    {
+        Some(nonce) => nonce,
+        None => return false,
+    };
+
+    // The signature `r` is the x coordinate of the nonce reduced modulo the group order
+    let mut x = [0u8; 32];
+    x.copy_from_slice(&nonce.serialize()[1..]);
+    if x >= ORDER {
+        x = sub_order(x);
+    }
+    signature.serialize_compact()[..32] == x
+        && secp.verify_ecdsa(message, signature, public_key).is_ok()
+}
+
+/// Like [`verify()`] for a transaction signature, DER encoded followed by the `SIGHASH_ALL` byte
+pub(crate) fn verify_tx_signature(
+    sig: &[u8],
+    message: &Message,
+    public_key: &PublicKey,
+    host_entropy: &[u8; 32],
+    signer_commitment: &[u8],
+) -> bool {
+    match sig.split_last() {
+        Some((1, der)) => match Signature::from_der(der) {
+            Ok(signature) => verify(
+                &signature,
+                message,
+                public_key,
+                host_entropy,
+                signer_commitment,
+            ),
+            Err(_) => false,
+        },
+        _ => false,
+    }
+}
+
+fn tagged_hash(tag: &[u8], data: &[&[u8]]) -> [u8; 32] {
+    let tag = sha256::Hash::hash(tag);
+    let mut engine = sha256::Hash::engine();
+    engine.input(tag.as_byte_array());
+    engine.input(tag.as_byte_array());
+    for d in data {
+        engine.input(d);
+    }
+    sha256::Hash::from_engine(engine).to_byte_array()
+}
+
+fn sub_order(mut x: [u8; 32]) -> [u8; 32] {
+    let mut borrow = 0i16;
+    for i in (0..32).rev() {
+        let mut v = x[i] as i16 - ORDER[i] as i16 - borrow;
+        borrow = (v < 0) as i16;
+        if v < 0 {
+            v += 256;
+        }
+        x[i] = v as u8;
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use elements::secp256k1_zkp::{ecdsa::Signature, Message, PublicKey};
+
+    #[test]
+    fn anti_exfil() {
+        // commitment from Jade tests, created with wally_ae_host_commit_from_bytes
+        let host_entropy: [u8; 32] =
+            hex::decode("3f5540b9336af9bdd50a5b7f69fc2045a12e3b3e0740f7461902d882bf8a8820")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        assert_eq!(
+            hex::encode(super::host_commitment(&host_entropy)),
+            "7b61fad27ce2d95abca09f76bd7226e50212a8542f3ca274ee546cec4bc5c3bb"
+        );
+        assert_ne!(super::host_entropy(), super::host_entropy());
+
+        let public_key: PublicKey =
+            "02b49dcf2783aab55b76b11cc76c9fbb976bf7aad6247690e10e69dfab7a32e614"
+                .parse()
+                .unwrap();
+        let message = Message::from_digest_slice(
+            &hex::decode("ab530a13e45914982b79f9b7e3fba994cfd1f3fb22f71cea1afbf02b460c6d1d")
+                .unwrap(),
+        )
+        .unwrap();
+        let signer_commitment =
+            hex::decode("0217f53289eac961e5adc858d3ca50dab056ddca7a1a906c0815a0369312d1aa49")
+                .unwrap();
+        let signature = Signature::from_compact(&hex::decode("a69e6dbe6634439ad713652944d0239109e41a8ee56dfb36b8c0690f65e37db450415f4f0fbfcfe33307e22603501811b69a474d0efea32ee2c113550c3a2a0a").unwrap()).unwrap();
+
+        assert!(super::verify(
+            &signature,
+            &message,
+            &public_key,
+            &host_entropy,
+            &signer_commitment
+        ));
+
+        // a signer nonce not derived from the host entropy is rejected
+        let mut other_entropy = host_entropy;
+        other_entropy[0] ^= 1;
+        assert!(!super::verify(
+            &signature,
+            &message,
+            &public_key,
+            &other_entropy,
+            &signer_commitment
+        ));
+        let mut other_commitment = signer_commitment.clone();
+        other_commitment[0] ^= 1;
+        assert!(!super::verify(
+            &signature,
+            &message,
+            &public_key,
+            &host_entropy,
+            &other_commitment
+        ));
+        assert!(!super::verify(
+            &signature,
+            &message,
+            &public_key,
+            &host_entropy,
+            &[]
+        ));
+    }
+
+    #[test]
+    fn sub_order() {
+        let mut x = super::ORDER;
+        x[31] += 5;
+        let mut expected = [0u8; 32];
+        expected[31] = 5;
+        assert_eq!(super::sub_order(x), expected);
+    }
+}
@@ -1,10 +1,11 @@
-use std::collections::HashMap;
-
-use elements::pset::PartiallySignedTransaction;
+use elements::{
+    hashes::Hash, pset::PartiallySignedTransaction, secp256k1_zkp::Message, sighash::SighashCache,
+    EcdsaSighashType,
+};
 
 use crate::{
-    create_jade_sign_req, derivation_path_to_vec, protocol::GetSignatureParams, script_code_wpkh,
-    sign_liquid_tx::TxInputParams, Error,
+    anti_exfil, create_jade_sign_req, derivation_path_to_vec, protocol::GetSignatureParams,
+    script_code_wpkh, sign_liquid_tx::TxInputParams, Error,
 };
 
 use super::{Jade, Stream};
@@ -25,9 +26,11 @@ impl<S: Stream> Jade<S> {
         let sign_response = self.sign_liquid_tx(params).await?;
         assert!(sign_response);
 
-        let mut signers_commitment = HashMap::new();
+        let tx = pset.extract_tx()?;
+        let mut cache = SighashCache::new(&tx);
+        let mut pending = vec![];
 
-        for (i, input) in pset.inputs_mut().iter_mut().enumerate() {
+        for (i, input) in pset.inputs().iter().enumerate() {
             for (want_public_key, (fingerprint, derivation_path)) in input.bip32_derivation.iter() {
                 if &my_fingerprint == fingerprint {
                     let path: Vec<u32> = derivation_path_to_vec(derivation_path);
@@ -68,6 +71,7 @@ impl<S: Stream> Jade<S> {
                         ));
                     };
 
+                    let host_entropy = anti_exfil::host_entropy();
                     let params = TxInputParams {
                         is_witness: true,
                         script_code: script_code.as_bytes().to_vec(),
@@ -79,26 +83,44 @@ impl<S: Stream> Jade<S> {
                             .to_vec(),
                         path,
                         sighash: Some(1),
-                        ae_host_commitment: vec![1u8; 32], // TODO verify anti-exfil
+                        ae_host_commitment: anti_exfil::host_commitment(&host_entropy).to_vec(),
                     };
                     let signer_commitment: Vec<u8> = self.tx_input(params).await?.to_vec();
-                    signers_commitment.insert(*want_public_key, signer_commitment);
+                    let sighash =
+                        cache.segwitv0_sighash(i, &script_code, txout.value, EcdsaSighashType::All);
+                    let message = Message::from_digest(sighash.to_byte_array());
+                    pending.push((
+                        i,
+                        *want_public_key,
+                        host_entropy,
+                        signer_commitment,
+                        message,
+                    ));
                 }
             }
         }
 
-        for input in pset.inputs_mut().iter_mut() {
-            for (public_key, (_, _)) in input.bip32_derivation.iter() {
-                if let Some(_signer_commitment) = signers_commitment.get(public_key) {
-                    let params = GetSignatureParams {
-                        ae_host_entropy: vec![1u8; 32], // TODO verify anti-exfil
-                    };
-                    let sig: Vec<u8> = self.get_signature_for_tx(params).await?.to_vec();
-
-                    input.partial_sigs.insert(*public_key, sig);
-                    sigs_added_or_overwritten += 1;
-                }
+        let mut sigs = vec![];
+        for (i, public_key, host_entropy, signer_commitment, message) in pending {
+            let params = GetSignatureParams {
+                ae_host_entropy: host_entropy.to_vec(),
+            };
+            let sig: Vec<u8> = self.get_signature_for_tx(params).await?.to_vec();
+            if !anti_exfil::verify_tx_signature(
+                &sig,
+                &message,
+                &public_key.inner,
+                &host_entropy,
+                &signer_commitment,
+            ) {
+                return Err(Error::AntiExfilVerificationFailed(i));
             }
+            sigs.push((i, public_key, sig));
+        }
+
+        for (i, public_key, sig) in sigs {
+            pset.inputs_mut()[i].partial_sigs.insert(public_key, sig);
+            sigs_added_or_overwritten += 1;
         }
 
         Ok(sigs_added_or_overwritten)
@@ -95,6 +95,12 @@ pub enum Error {
     #[error("Previous script pubkey is wsh but witness script is missing in input {0}")]
     MissingWitnessScript(usize),
 
+    #[error("Jade signature for input {0} doesn't match the anti-exfil commitments")]
+    AntiExfilVerificationFailed(usize),
+
+    #[error("Jade message signature doesn't match the anti-exfil commitments")]
+    MessageAntiExfilVerificationFailed,
+
     #[error("Unsupported spending script pubkey: {0}")]
     UnsupportedScriptPubkeyType(String),
 
@@ -164,7 +164,7 @@ pub struct TxInputParams {
 
     pub sighash: Option<u32>,
 
-    /// 32 bytes anti-exfiltration commitment (random data not verified for now). TODO verify
+    /// 32 bytes anti-exfiltration host commitment, see [`crate::anti_exfil`]
     #[serde(with = "serde_bytes")]
     pub ae_host_commitment: Vec<u8>,
 }
@@ -15,14 +15,18 @@ use crate::register_multisig::{
 };
 use crate::sign_liquid_tx::{SignLiquidTxParams, TxInputParams};
 use crate::{
-    derivation_path_to_vec, json_to_cbor, try_parse_response, vec_to_derivation_path, Error,
-    Network, Result,
+    anti_exfil, derivation_path_to_vec, json_to_cbor, try_parse_response, vec_to_derivation_path,
+    Error, Network, Result,
 };
 use connection::Connection;
+use elements::bitcoin::base64::{self, Engine};
 use elements::bitcoin::bip32::{DerivationPath, Fingerprint, Xpub};
+use elements::bitcoin::sign_message::{signed_msg_hash, MessageSignature};
+use elements::hashes::Hash;
 use elements::pset::PartiallySignedTransaction;
+use elements::secp256k1_zkp::{ecdsa::Signature, Message};
 use elements_miniscript::slip77::{self, MasterBlindingKey};
-use lwk_common::Signer;
+use lwk_common::{MessageSigner, Signer};
 use serde::de::DeserializeOwned;
 use serde_bytes::ByteBuf;
 
@@ -420,6 +424,45 @@ impl Signer for &Jade {
     }
 }
 
+impl MessageSigner for &Jade {
+    fn sign_message(
+        &self,
+        message: &str,
+        path: &DerivationPath,
+    ) -> std::result::Result<MessageSignature, Self::Error> {
+        let public_key = Signer::derive_xpub(self, path)?.public_key;
+        let host_entropy = anti_exfil::host_entropy();
+        let params = SignMessageParams {
+            message: message.to_string(),
+            path: derivation_path_to_vec(path),
+            ae_host_commitment: anti_exfil::host_commitment(&host_entropy).to_vec(),
+        };
+        let signer_commitment = Jade::sign_message(self, params)?;
+        let params = GetSignatureParams {
+            ae_host_entropy: host_entropy.to_vec(),
+        };
+        let signature = self.get_signature_for_msg(params)?;
+        let signature = base64::engine::general_purpose::STANDARD
+            .decode(signature)
+            .map_err(|e| Error::Generic(e.to_string()))?;
+        let signature =
+            Signature::from_compact(&signature).map_err(|e| Error::Generic(e.to_string()))?;
+
+        let msg = Message::from_digest(signed_msg_hash(message).to_byte_array());
+        if !anti_exfil::verify(
+            &signature,
+            &msg,
+            &public_key,
+            &host_entropy,
+            &signer_commitment,
+        ) {
+            return Err(Error::MessageAntiExfilVerificationFailed);
+        }
+        lwk_common::message_signature(&signature, message, &public_key)
+            .ok_or(Error::MessageAntiExfilVerificationFailed)
+    }
+}
+
 impl Signer for Jade {
     type Error = crate::Error;
 
@@ -438,3 +481,13 @@ impl Signer for Jade {
         Signer::slip77_master_blinding_key(&self)
     }
 }
+
+impl MessageSigner for Jade {
+    fn sign_message(
+        &self,
+        message: &str,
+        path: &DerivationPath,
+    ) -> std::result::Result<MessageSignature, Self::Error> {
+        MessageSigner::sign_message(&self, message, path)
+    }
+}
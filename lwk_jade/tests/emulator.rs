@@ -14,6 +14,7 @@ use lwk_containers::{
     PinServer, PIN_SERVER_PORT,
 };
 use lwk_jade::{
+    anti_exfil,
     get_receive_address::{GetReceiveAddressParams, SingleOrMulti, Variant},
     protocol::{
         GetMasterBlindingKeyParams, GetSignatureParams, GetXpubParams, JadeState,
@@ -344,23 +345,19 @@ fn jade_sign_message() {
     let mut jade = TestJadeEmulator::new(&docker);
     jade.set_debug_mnemonic(TEST_MNEMONIC);
 
-    // TODO create anti exfil commitments
-    // The following are taken from jade tests, even though they may be random if we are not verifying.
-    // To create the commitment jade use wally_ae_host_commit_from_bytes, rust-secp at the moment
-    // doesn't expose exfil methods
-    let ae_host_commitment =
-        hex::decode("7b61fad27ce2d95abca09f76bd7226e50212a8542f3ca274ee546cec4bc5c3bb").unwrap();
-    let ae_host_entropy =
-        hex::decode("3f5540b9336af9bdd50a5b7f69fc2045a12e3b3e0740f7461902d882bf8a8820").unwrap();
+    let ae_host_entropy = anti_exfil::host_entropy();
+    let ae_host_commitment = anti_exfil::host_commitment(&ae_host_entropy).to_vec();
     let message = "Hello world!";
     let params = SignMessageParams {
         message: message.to_string(),
         path: vec![0],
         ae_host_commitment,
     };
-    let _signer_commitment: Vec<u8> = jade.jade.sign_message(params).unwrap().to_vec();
+    let signer_commitment: Vec<u8> = jade.jade.sign_message(params).unwrap().to_vec();
 
-    let params = GetSignatureParams { ae_host_entropy };
+    let params = GetSignatureParams {
+        ae_host_entropy: ae_host_entropy.to_vec(),
+    };
     let signature = jade.jade.get_signature_for_msg(params).unwrap();
     let signature_bytes = base64::engine::general_purpose::STANDARD
         .decode(signature)
@@ -379,7 +376,21 @@ fn jade_sign_message() {
         .verify_ecdsa(&message, &signature, &xpub.public_key)
         .is_ok());
 
-    //TODO verify anti-exfil
+    assert!(anti_exfil::verify(
+        &signature,
+        &message,
+        &xpub.public_key,
+        &ae_host_entropy,
+        &signer_commitment
+    ));
+    let other_entropy = anti_exfil::host_entropy();
+    assert!(!anti_exfil::verify(
+        &signature,
+        &message,
+        &xpub.public_key,
+        &other_entropy,
+        &signer_commitment
+    ));
 }
 
 #[test]
@@ -392,7 +403,9 @@ fn jade_sign_liquid_tx() {
     let mut pset: PartiallySignedTransaction = pset_base64.parse().unwrap();
     assert_eq!(pset.outputs().len(), 3);
 
-    jade.jade.sign(&mut pset).unwrap();
+    let signatures = jade.jade.sign(&mut pset).unwrap();
+    assert!(signatures > 0);
+    assert!(pset.inputs().iter().any(|i| !i.partial_sigs.is_empty()));
 }
 
 #[test]
@@ -31,6 +31,8 @@ use elements_miniscript::confidential::slip77;
 use elements_miniscript::elements::bitcoin::bip32::{
     ChildNumber, DerivationPath, Fingerprint, Xpub,
 };
+use elements_miniscript::elements::bitcoin::secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use elements_miniscript::elements::bitcoin::sign_message::MessageSignature;
 use elements_miniscript::elements::pset::PartiallySignedTransaction;
 use elements_miniscript::elements::{
     bitcoin::key::PublicKey,
@@ -42,7 +44,7 @@ use elements_miniscript::elements::{
     Script,
 };
 
-use lwk_common::Signer;
+use lwk_common::{MessageSigner, Signer};
 
 #[derive(Debug)]
 pub struct Ledger<T: Transport> {
@@ -251,6 +253,27 @@ impl<T: Transport> Signer for &Ledger<T> {
     }
 }
 
+impl<T: Transport> MessageSigner for &Ledger<T> {
+    fn sign_message(
+        &self,
+        message: &str,
+        path: &DerivationPath,
+    ) -> std::result::Result<MessageSignature, Self::Error> {
+        let (header, signature) = self
+            .client
+            .sign_message(message.as_bytes(), path)
+            .map_err(|e| Error::ClientError(format!("{e:?}")))?;
+        // The header is 27 + 4 (compressed key) + recovery id
+        let invalid =
+            || Error::InvalidResponse(format!("Invalid message signature header {header}"));
+        let id = header.checked_sub(31).ok_or_else(invalid)?;
+        let id = RecoveryId::from_i32(id as i32).map_err(|_| invalid())?;
+        let signature = RecoverableSignature::from_compact(&signature.serialize_compact(), id)
+            .map_err(|_| invalid())?;
+        Ok(MessageSignature::new(signature, true))
+    }
+}
+
 impl<T: Transport> Signer for Ledger<T> {
     type Error = crate::Error;
 
@@ -273,6 +296,16 @@ impl<T: Transport> Signer for Ledger<T> {
     }
 }
 
+impl<T: Transport> MessageSigner for Ledger<T> {
+    fn sign_message(
+        &self,
+        message: &str,
+        path: &DerivationPath,
+    ) -> std::result::Result<MessageSignature, Self::Error> {
+        MessageSigner::sign_message(&self, message, path)
+    }
+}
+
 // "duplicated" from Jade
 // taken and adapted from:
 // https://github.com/rust-bitcoin/rust-bitcoin/blob/37daf4620c71dc9332c3e08885cf9de696204bca/bitcoin/src/blockdata/script/borrowed.rs#L266
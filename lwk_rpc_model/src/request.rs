@@ -1,5 +1,6 @@
 //! Data models of every requests made via RPC
 
+use std::collections::HashMap;
 use std::net::SocketAddr;
 
 #[cfg(doc)]
@@ -41,6 +42,14 @@ pub struct WalletLoad {
 
     /// The name given to the wallet, will be needed for calls related to the wallet
     pub name: String,
+
+    /// The network of the wallet, one of the networks served ("liquid", "liquid-testnet" or
+    /// "liquid-regtest"), if not specified the server main network
+    pub network: Option<String>,
+
+    /// The height before which the wallet has no transactions, the scans skip the previous history
+    #[serde(default)]
+    pub birthday_height: Option<u32>,
 }
 
 /// Unload the wallet identified by the given name
@@ -129,6 +138,13 @@ pub struct WalletBalance {
     pub with_tickers: bool,
 }
 
+/// The portfolio of a wallet
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletPortfolio {
+    /// The wallet name
+    pub name: String,
+}
+
 /// Send a transaction from a wallet
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct WalletSendMany {
@@ -145,6 +161,9 @@ pub struct WalletSendMany {
     ///
     /// Note: this field will be removed once discounted CT will be enabled on all networks.
     pub enable_ct_discount: bool,
+
+    /// Lock the wallet inputs of the created PSET for this number of blocks, unless broadcast before
+    pub lock_inputs: Option<u32>,
 }
 
 ///  An addressee which has yet to be validated
@@ -175,6 +194,9 @@ pub struct WalletDrain {
 
     /// Optional fee rate in sat/kvB
     pub fee_rate: Option<f32>,
+
+    /// Lock the wallet inputs of the created PSET for this number of blocks, unless broadcast before
+    pub lock_inputs: Option<u32>,
 }
 
 /// A request containing information to create a single signature descriptor wallet
@@ -236,6 +258,61 @@ pub struct SignerSign {
     pub pset: String,
 }
 
+/// Rules checked before a signer signs a PSET, all the rules must be satisfied
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SpendingPolicy {
+    /// The wallet whose balance changes are checked, it must be loaded when signing
+    pub wallet: String,
+
+    /// Maximum satoshi of each asset sent in the last 24 hours, by asset id, fees included
+    #[serde(default)]
+    pub daily_limits: HashMap<String, u64>,
+
+    /// If not empty, the only addresses receiving outputs not belonging to the wallet
+    #[serde(default)]
+    pub allowed_addresses: Vec<String>,
+
+    /// Maximum fee rate in sat/kvB
+    pub max_fee_rate: Option<f32>,
+
+    /// If not empty, the only asset ids whose wallet balance can change
+    #[serde(default)]
+    pub allowed_assets: Vec<String>,
+
+    /// If not empty, signing is allowed only during these windows
+    #[serde(default)]
+    pub time_windows: Vec<TimeWindow>,
+}
+
+/// A daily window of hours in UTC, from `start_hour` included to `end_hour` excluded
+///
+/// If `start_hour` is greater than `end_hour` the window spans midnight.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+pub struct TimeWindow {
+    /// The first hour of the window, from 0 to 23
+    pub start_hour: u8,
+
+    /// The hour ending the window, from 0 to 24
+    pub end_hour: u8,
+}
+
+/// Request to set or remove the spending policy of a signer, returning [`response::Empty`]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SignerSetPolicy {
+    /// The signer name
+    pub name: String,
+
+    /// The policy, if missing the current policy is removed
+    pub policy: Option<SpendingPolicy>,
+}
+
+/// Request the audit log of the signed PSETs, returning [`response::SignedPsets`]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SignerSignedPsets {
+    /// Only the PSETs signed by this signer
+    pub name: Option<String>,
+}
+
 /// Request to broadcast a transaction
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct WalletBroadcast {
@@ -279,6 +356,13 @@ pub struct WalletIssue {
 
     /// The optional fee rate
     pub fee_rate: Option<f32>,
+
+    /// Blind the issued amounts, so that only the issuing wallet knows them
+    #[serde(default)]
+    pub blind_issuance: bool,
+
+    /// Lock the wallet inputs of the created PSET for this number of blocks, unless broadcast before
+    pub lock_inputs: Option<u32>,
 }
 
 /// Request to do a reissuance
@@ -298,6 +382,13 @@ pub struct WalletReissue {
 
     /// The optional fee rate
     pub fee_rate: Option<f32>,
+
+    /// Blind the reissued amount, so that only the reissuing wallet knows it
+    #[serde(default)]
+    pub blind_issuance: bool,
+
+    /// Lock the wallet inputs of the created PSET for this number of blocks, unless broadcast before
+    pub lock_inputs: Option<u32>,
 }
 
 /// Request to do burn an asset
@@ -314,6 +405,9 @@ pub struct WalletBurn {
 
     /// The optional fee rate
     pub fee_rate: Option<f32>,
+
+    /// Lock the wallet inputs of the created PSET for this number of blocks, unless broadcast before
+    pub lock_inputs: Option<u32>,
 }
 
 /// A request creating a contract in the JSON format expected by the issue call
@@ -361,6 +455,16 @@ pub struct WalletPsetDetails {
     pub with_tickers: bool,
 }
 
+/// Request to decode a PSET without a wallet
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PsetDecode {
+    /// The PSET in base64 to decode
+    pub pset: String,
+
+    /// The network of the addresses, if missing the default network served
+    pub network: Option<String>,
+}
+
 /// Request to get the wallet unspet transaction Outputs
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct WalletUtxos {
@@ -376,6 +480,34 @@ pub struct WalletTxs {
 
     /// Replace asset ids with tickers when possible
     pub with_tickers: bool,
+
+    /// Only return the transactions changing the balance of this asset
+    #[serde(default)]
+    pub asset: Option<String>,
+
+    /// Only return the transactions of this type, for instance "incoming" or "outgoing"
+    #[serde(default)]
+    pub tx_type: Option<String>,
+
+    /// Only return the transactions confirmed at this height or later, or unconfirmed
+    #[serde(default)]
+    pub min_height: Option<u32>,
+
+    /// Only return the transactions confirmed at this height or earlier
+    #[serde(default)]
+    pub max_height: Option<u32>,
+
+    /// Only return the transactions sending to or spending from this address
+    #[serde(default)]
+    pub address: Option<String>,
+
+    /// Skip this number of matching transactions
+    #[serde(default)]
+    pub offset: usize,
+
+    /// Return at most this number of transactions
+    #[serde(default)]
+    pub limit: Option<usize>,
 }
 
 /// Request to get a transaction
@@ -423,6 +555,11 @@ pub struct AssetRemove {
 pub struct AssetFromExplorer {
     /// The asset identifier
     pub asset_id: String,
+
+    /// The network whose registry and explorer are used, one of the networks served, if not
+    /// specified the server main network
+    #[serde(default)]
+    pub network: Option<String>,
 }
 
 /// Request to publish
@@ -430,6 +567,11 @@ pub struct AssetFromExplorer {
 pub struct AssetPublish {
     /// The asset identifier
     pub asset_id: String,
+
+    /// The network whose registry and explorer are used, one of the networks served, if not
+    /// specified the server main network
+    #[serde(default)]
+    pub network: Option<String>,
 }
 
 /// Request to obtain jade identifiers
@@ -439,6 +581,67 @@ pub struct SignerJadeId {
     pub emulator: Option<SocketAddr>,
 }
 
+/// Request to lock a wallet utxo, excluding it from coin selection
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletLockUtxo {
+    /// The wallet name
+    pub name: String,
+
+    /// Transaction id
+    pub txid: String,
+
+    /// Output index
+    pub vout: u32,
+
+    /// Release the lock after the given number of blocks, if missing the utxo is locked until
+    /// unlocked explicitly
+    pub blocks: Option<u32>,
+}
+
+/// Request to unlock a wallet utxo
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletUnlockUtxo {
+    /// The wallet name
+    pub name: String,
+
+    /// Transaction id
+    pub txid: String,
+
+    /// Output index
+    pub vout: u32,
+}
+
+/// Request the locked utxos of a wallet
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletLockedUtxos {
+    /// The wallet name
+    pub name: String,
+}
+
+/// Request the backup of a wallet, returning [`response::WalletBackup`]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletBackup {
+    /// The wallet name
+    pub name: String,
+}
+
+/// Request to load a wallet from a backup, returning [`response::Wallet`]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletRestore {
+    /// The content of the backup file, as returned by [`response::WalletBackup`]
+    pub backup: String,
+
+    /// The name given to the wallet
+    pub name: String,
+}
+
+/// Request a snapshot of a wallet, returning [`response::WalletSnapshot`]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletSnapshot {
+    /// The wallet name
+    pub name: String,
+}
+
 /// Request to set a transaction memo
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct WalletSetTxMemo {
@@ -465,6 +668,21 @@ pub struct WalletSetAddrMemo {
     pub memo: String,
 }
 
+/// Request to wait for wallet events, returning [`response::Events`]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WaitForEvent {
+    /// Return only events with an id greater than this one.
+    ///
+    /// If not set, only events happening after the request are returned
+    pub after: Option<u64>,
+
+    /// Maximum number of seconds to wait for an event, if not set a default is used
+    ///
+    /// While waiting, the server serves other requests, but it serves a limited number of
+    /// waiting requests at the same time and fails the others
+    pub timeout: Option<u64>,
+}
+
 #[cfg(test)]
 mod test {
     use schemars::schema_for;
@@ -475,7 +693,7 @@ mod test {
     fn test_json_schema() {
         let schema = schema_for!(WalletLoad);
         assert_eq!(
-            r#"{"$schema":"http://json-schema.org/draft-07/schema#","title":"WalletLoad","description":"Request to load a wallet in the server, returning [`response::Wallet`]","type":"object","required":["descriptor","name"],"properties":{"descriptor":{"description":"The read-only descriptor describing the wallet outputs","type":"string"},"name":{"description":"The name given to the wallet, will be needed for calls related to the wallet","type":"string"}}}"#,
+            r#"{"$schema":"http://json-schema.org/draft-07/schema#","title":"WalletLoad","description":"Request to load a wallet in the server, returning [`response::Wallet`]","type":"object","required":["descriptor","name"],"properties":{"birthday_height":{"description":"The height before which the wallet has no transactions, the scans skip the previous history","default":null,"type":["integer","null"],"format":"uint32","minimum":0.0},"descriptor":{"description":"The read-only descriptor describing the wallet outputs","type":"string"},"name":{"description":"The name given to the wallet, will be needed for calls related to the wallet","type":"string"},"network":{"description":"The network of the wallet, one of the networks served (\"liquid\", \"liquid-testnet\" or \"liquid-regtest\"), if not specified the server main network","type":["string","null"]}}}"#,
             serde_json::to_string(&schema).unwrap()
         );
     }
@@ -17,8 +17,12 @@ pub struct Version {
     /// The server version
     pub version: String,
 
-    /// The server network
+    /// The server main network
     pub network: String,
+
+    /// All the networks served, including the main one
+    #[serde(default)]
+    pub networks: Vec<String>,
 }
 
 /// Response for generate signer
@@ -104,6 +108,47 @@ pub struct WalletBalance {
     pub balance: HashMap<String, i64>,
 }
 
+/// Portfolio response
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletPortfolio {
+    /// The assets held by the wallet, L-BTC first
+    pub assets: Vec<PortfolioAsset>,
+}
+
+/// An asset held by a wallet
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PortfolioAsset {
+    /// The asset id
+    pub asset: String,
+
+    /// The ticker, if the contract of the asset is known
+    pub ticker: Option<String>,
+
+    /// The name, if the contract of the asset is known
+    pub name: Option<String>,
+
+    /// The precision, 0 if the contract of the asset is not known
+    pub precision: u8,
+
+    /// Amount in confirmed outputs, in satoshi
+    pub confirmed: u64,
+
+    /// Amount in unconfirmed outputs of transactions spending wallet outputs, in satoshi
+    pub trusted_pending: u64,
+
+    /// Amount in unconfirmed outputs of transactions created by others, in satoshi
+    pub untrusted_pending: u64,
+
+    /// Amount in outputs that can't be spent, like immature coinbase outputs, in satoshi
+    pub frozen: u64,
+
+    /// The total balance formatted according to the precision
+    pub total: String,
+
+    /// The id of the issuance transaction, if the asset was issued by the wallet
+    pub issuance_txid: Option<String>,
+}
+
 /// PSET response
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Pset {
@@ -111,6 +156,38 @@ pub struct Pset {
     pub pset: String,
 }
 
+/// A PSET signed by the server, recorded in the audit log
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SignedPset {
+    /// Unix timestamp of the signature
+    pub timestamp: u64,
+
+    /// The signer name
+    pub signer: String,
+
+    /// The id of the transaction
+    pub txid: String,
+
+    /// The number of signatures added
+    pub signatures_added: u32,
+
+    /// The wallet of the signer spending policy, if any
+    pub wallet: Option<String>,
+
+    /// The balance change of the wallet by asset, fee included, if the signer has a policy
+    pub balance: Option<HashMap<String, i64>>,
+
+    /// The fee, if the signer has a policy
+    pub fee: Option<u64>,
+}
+
+/// The audit log of the signed PSETs
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SignedPsets {
+    /// The signed PSETs, oldest first
+    pub psets: Vec<SignedPset>,
+}
+
 /// Response containing a single signature descriptor
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct SignerSinglesigDescriptor {
@@ -304,6 +381,96 @@ pub struct WalletPsetDetails {
     pub warnings: String,
 }
 
+/// An input of a decoded PSET
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PsetDecodeInput {
+    /// Previous output txid
+    pub txid: String,
+
+    /// Previous output vout
+    pub vout: u32,
+
+    /// Previous output script pubkey in hex, if the PSET contains it
+    pub script_pubkey: Option<String>,
+
+    /// Type of the previous output script pubkey
+    pub script_type: Option<String>,
+
+    /// Unconfidential address of the previous output
+    pub address: Option<String>,
+
+    /// Whether the previous output is blinded
+    pub blinded: Option<bool>,
+
+    /// Asset id, if explicit or proved by the blind proofs
+    pub asset: Option<String>,
+
+    /// Amount, if explicit or proved by the blind proofs
+    pub satoshi: Option<u64>,
+
+    /// The sighash type to use when signing
+    pub sighash: Option<String>,
+
+    /// Signatures contained in the input
+    pub has_signatures_from: Vec<SignerShortDetails>,
+
+    /// Signatures required to spend but missing in the input
+    pub missing_signatures_from: Vec<SignerShortDetails>,
+
+    /// Whether the input is finalized
+    pub finalized: bool,
+}
+
+/// An output of a decoded PSET
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PsetDecodeOutput {
+    /// Script pubkey in hex, empty for fee outputs
+    pub script_pubkey: String,
+
+    /// Type of the script pubkey
+    pub script_type: String,
+
+    /// Address, confidential if the output has a blinding key
+    pub address: Option<String>,
+
+    /// Whether the output has the asset or the amount commitment
+    pub blinded: bool,
+
+    /// Asset id, if explicit or proved by the blind proofs
+    pub asset: Option<String>,
+
+    /// Amount, if explicit or proved by the blind proofs
+    pub satoshi: Option<u64>,
+}
+
+/// A PSET decoded without a wallet
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PsetDecode {
+    /// Inputs of the PSET
+    pub inputs: Vec<PsetDecodeInput>,
+
+    /// Outputs of the PSET
+    pub outputs: Vec<PsetDecodeOutput>,
+
+    /// Sum of the fee outputs
+    pub fee: Option<u64>,
+
+    /// Estimated size once signed, in virtual bytes
+    pub vsize: Option<usize>,
+
+    /// Estimated fee rate in sat/kvB
+    pub fee_rate: Option<f32>,
+
+    /// Issuances contained in the PSET
+    pub issuances: Vec<Issuance>,
+
+    /// Reissuance contained in the PSET
+    pub reissuances: Vec<Reissuance>,
+
+    /// Warnings
+    pub warnings: String,
+}
+
 /// Unspent Transaction Output
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Utxo {
@@ -326,6 +493,65 @@ pub struct Utxo {
     pub value: u64,
 }
 
+/// A locked wallet utxo
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct LockedUtxo {
+    /// Transction ID
+    pub txid: String,
+
+    /// Output index
+    pub vout: u32,
+
+    /// The height at which the lock is released, if missing the utxo is locked until unlocked
+    pub expiry_height: Option<u32>,
+}
+
+/// Wallet locked utxos
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletLockedUtxos {
+    /// Locked UTXOs
+    pub utxos: Vec<LockedUtxo>,
+}
+
+/// Wallet backup
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletBackup {
+    /// The JSON backup file, containing descriptor, network, gap limit, labels and birthday height
+    pub backup: String,
+}
+
+/// Wallet snapshot, containing what is needed to create transactions without a server
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WalletSnapshot {
+    /// The JSON backup file, as in [`WalletBackup`]
+    pub backup: String,
+
+    /// The wallet updates in order, base64 encoded
+    ///
+    /// They are not encrypted, as the descriptor in the backup would decrypt them.
+    pub updates: Vec<String>,
+
+    /// The utxos locked at the time of the snapshot, not spent by the offline wallet
+    #[serde(default)]
+    pub locked_utxos: Vec<LockedUtxo>,
+}
+
+/// PSET signed without a server
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct OfflineSign {
+    /// The signed PSET in base64 format
+    pub pset: String,
+
+    /// The fingerprint of the signer
+    pub fingerprint: String,
+
+    /// The number of signatures added
+    pub signatures_added: u32,
+
+    /// The signed PSET decoded, with the signatures status of every input
+    pub details: PsetDecode,
+}
+
 /// Wallet unspent transaction outputs
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct WalletUtxos {
@@ -334,7 +560,7 @@ pub struct WalletUtxos {
 }
 
 /// Transaction
-#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Tx {
     /// Transction ID
     pub txid: String,
@@ -365,8 +591,11 @@ pub struct Tx {
 /// Wallet transactions
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct WalletTxs {
-    /// Transactions
+    /// Transactions, most recent first
     pub txs: Vec<Tx>,
+
+    /// The number of transactions matching the request filters, regardless of offset and limit
+    pub total: usize,
 }
 
 /// Transaction
@@ -420,6 +649,59 @@ pub struct JadeId {
     pub identifier: String,
 }
 
+/// The kind of a wallet event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// A transaction not previously known by the wallet has been found
+    NewTx,
+
+    /// A previously unconfirmed transaction has been included in a block
+    TxConfirmed,
+
+    /// A confirmed transaction has been moved to another block or back to the mempool
+    TxReorged,
+
+    /// A previously known transaction is no more in the wallet, for instance it has been replaced
+    /// or evicted from the mempool
+    TxDropped,
+
+    /// The wallet balance changed
+    BalanceChanged,
+
+    /// The wallet saw a new blockchain tip
+    NewTip,
+}
+
+/// A wallet event
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Event {
+    /// Incremental identifier of the event
+    pub id: u64,
+
+    /// The name of the affected wallet
+    pub wallet: String,
+
+    /// The kind of the event
+    pub kind: EventKind,
+
+    /// The affected transaction, present for transaction related events
+    pub tx: Option<Tx>,
+
+    /// The new wallet balance, present for [`EventKind::BalanceChanged`]
+    pub balance: Option<HashMap<String, i64>>,
+
+    /// The new tip height, present for [`EventKind::NewTip`]
+    pub height: Option<u32>,
+}
+
+/// Wallet events
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Events {
+    /// Events, ordered by id
+    pub events: Vec<Event>,
+}
+
 /// The wallet type // TODO move to response
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub enum WalletType {
@@ -12,8 +12,9 @@ pub use bip39;
 
 use elements_miniscript::bitcoin::bip32::{self, DerivationPath, Fingerprint};
 use elements_miniscript::elements::bitcoin::bip32::Xpub;
+use elements_miniscript::elements::bitcoin::sign_message::MessageSignature;
 use elements_miniscript::elements::pset::PartiallySignedTransaction;
-use lwk_common::Signer;
+use lwk_common::{MessageSigner, Signer};
 
 /// Possible errors when signing with [`AnySigner`]
 #[derive(thiserror::Error, Debug)]
@@ -70,6 +71,16 @@ impl Signer for AnySigner {
     }
 }
 
+impl MessageSigner for AnySigner {
+    fn sign_message(
+        &self,
+        message: &str,
+        path: &DerivationPath,
+    ) -> Result<MessageSignature, Self::Error> {
+        MessageSigner::sign_message(&self, message, path)
+    }
+}
+
 impl Signer for &AnySigner {
     type Error = SignerError;
 
@@ -123,3 +134,21 @@ impl Signer for &AnySigner {
         })
     }
 }
+
+impl MessageSigner for &AnySigner {
+    fn sign_message(
+        &self,
+        message: &str,
+        path: &DerivationPath,
+    ) -> Result<MessageSignature, Self::Error> {
+        Ok(match self {
+            AnySigner::Software(s) => s.sign_message(message, path)?,
+
+            #[cfg(feature = "jade")]
+            AnySigner::Jade(s, _) => MessageSigner::sign_message(s, message, path)?,
+
+            #[cfg(feature = "ledger")]
+            AnySigner::Ledger(s, _) => MessageSigner::sign_message(s, message, path)?,
+        })
+    }
+}
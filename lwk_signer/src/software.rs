@@ -4,18 +4,19 @@ use elements_miniscript::{
     elements::{
         bitcoin::{
             bip32::{self, Fingerprint, Xpriv, Xpub},
+            sign_message::{signed_msg_hash, MessageSignature},
             Network,
         },
         hashes::Hash,
         pset::PartiallySignedTransaction,
-        secp256k1_zkp::{All, Secp256k1},
+        secp256k1_zkp::{All, Message, Secp256k1},
         sighash::SighashCache,
     },
     elementssig_to_rawsig,
     psbt::PsbtExt,
     slip77::MasterBlindingKey,
 };
-use lwk_common::Signer;
+use lwk_common::{MessageSigner, Signer};
 
 /// Possible errors when signing with the software signer [`SwSigner`]
 #[derive(thiserror::Error, Debug)]
@@ -211,6 +212,19 @@ impl Signer for SwSigner {
     }
 }
 
+impl MessageSigner for SwSigner {
+    fn sign_message(
+        &self,
+        message: &str,
+        path: &DerivationPath,
+    ) -> Result<MessageSignature, Self::Error> {
+        let derived = self.xprv.derive_priv(&self.secp, path)?;
+        let msg = Message::from_digest(signed_msg_hash(message).to_byte_array());
+        let signature = self.secp.sign_ecdsa_recoverable(&msg, &derived.private_key);
+        Ok(MessageSignature::new(signature, true))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use elements_miniscript::elements::hex::ToHex;
@@ -281,4 +295,113 @@ mod tests {
         assert_ne!(sig_low_r, sig_no_grind);
         assert!(sig_low_r.len() < sig_no_grind.len());
     }
+
+    #[test]
+    fn sign_pruned_pset() {
+        // The mnemonic of the Ledger emulator, which signed this PSET in the Liquid Ledger App
+        // test vectors
+        let mnemonic = "glory promote mansion idle axis finger extra february uncover one trip resource lawn turtle enact monster seven myth punch hobby comfort wild raise skin";
+        let signer = SwSigner::new(mnemonic, false).unwrap();
+        let b64 = include_str!("../../lwk_common/test_data/pset_details/pset_tx_builder.base64");
+        let pset: PartiallySignedTransaction = b64.trim().parse().unwrap();
+        let mut pruned = lwk_common::pset_prune_proofs(&pset);
+        assert_eq!(signer.sign(&mut pruned).unwrap(), 1);
+        let sig = pruned.inputs()[0].partial_sigs.values().next().unwrap();
+        let expected = "3044022071965f8315a264773d8e635fb5bb8dfdb425b849b7aaafa8f1dcf1356e87947a02202eae7f9bdb1f00af3d1662a10b8efc82f9e7ecb1fc4f76a0b7905dab4fc6358801";
+        assert_eq!(sig.to_hex(), expected);
+
+        // Merged into the original PSET, the signature is valid for the complete transaction
+        let mut signed = pset.clone();
+        signed.merge(pruned).unwrap();
+        let mut original = pset;
+        signer.sign(&mut original).unwrap();
+        assert_eq!(
+            signed.inputs()[0].partial_sigs,
+            original.inputs()[0].partial_sigs
+        );
+    }
+
+    #[test]
+    fn sign_message() {
+        let signer = SwSigner::new(lwk_test_util::TEST_MNEMONIC, false).unwrap();
+        let path: DerivationPath = "m/48'/1'/0'/2'".parse().unwrap();
+        let message = "Hello world!";
+        let signature = signer.sign_message(message, &path).unwrap();
+
+        let secp = Secp256k1::new();
+        let public_key = signer.derive_xpub(&path).unwrap().public_key;
+        let msg_hash = signed_msg_hash(message);
+        assert_eq!(
+            signature.recover_pubkey(&secp, msg_hash).unwrap().inner,
+            public_key
+        );
+
+        // A compact signature can be made recoverable
+        let compact = signature.signature.to_standard();
+        let recovered = lwk_common::message_signature(&compact, message, &public_key).unwrap();
+        assert_eq!(recovered, signature);
+        assert!(lwk_common::message_signature(&compact, "other", &public_key).is_none());
+    }
+
+    #[test]
+    fn bsms() {
+        use lwk_common::{
+            Bip, BsmsDescriptorRecord, BsmsError, BsmsKeyRecord, BsmsToken, DescriptorBlindingKey,
+        };
+        let params = &elements_miniscript::elements::AddressParams::ELEMENTS;
+        let signer1 = SwSigner::new(lwk_test_util::TEST_MNEMONIC, false).unwrap();
+        let (signer2, _) = SwSigner::random(false).unwrap();
+
+        // Round 1, every signer sends its key record to the coordinator
+        let token = BsmsToken::standard();
+        let record1 = BsmsKeyRecord::new(&signer1, &token, "signer 1", false).unwrap();
+        let record2 = BsmsKeyRecord::new(&signer2, &token, "signer 2", false).unwrap();
+        assert_eq!(
+            record1.key(),
+            signer1.keyorigin_xpub(Bip::Bip87, false).unwrap()
+        );
+        let encrypted = record1.encrypted();
+        assert_eq!(
+            BsmsKeyRecord::from_encrypted(&encrypted, &token).unwrap(),
+            record1
+        );
+        let other_token = BsmsToken::standard();
+        assert!(BsmsKeyRecord::from_encrypted(&encrypted, &other_token).is_err());
+        let tampered = record1
+            .to_string()
+            .replace("signer 1", "signer 3")
+            .parse::<BsmsKeyRecord>()
+            .unwrap();
+        assert!(matches!(
+            tampered.verify(),
+            Err(BsmsError::InvalidSignature(_))
+        ));
+
+        // Round 2, the coordinator sends the descriptor record to every signer
+        let keys = [record1.clone(), record2];
+        let record =
+            BsmsDescriptorRecord::new(2, &keys, DescriptorBlindingKey::Slip77Rand, params).unwrap();
+        assert!(record.template().contains("/**"));
+        let encrypted = record.encrypted(&token);
+        let received = BsmsDescriptorRecord::from_encrypted(&encrypted, &token).unwrap();
+        assert_eq!(received, record);
+        let descriptor = received.verify(&record1, params).unwrap();
+        assert!(descriptor.contains(&format!("{}/<0;1>/*", record1.key())));
+
+        let (signer3, _) = SwSigner::random(false).unwrap();
+        let record3 = BsmsKeyRecord::new(&signer3, &token, "", false).unwrap();
+        assert!(matches!(
+            received.verify(&record3, params),
+            Err(BsmsError::MissingKey(_))
+        ));
+        let wrong_address = record
+            .to_string()
+            .replace(record.first_address(), "wrong")
+            .parse::<BsmsDescriptorRecord>()
+            .unwrap();
+        assert!(matches!(
+            wrong_address.verify(&record1, params),
+            Err(BsmsError::AddressMismatch { .. })
+        ));
+    }
 }
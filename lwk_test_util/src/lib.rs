@@ -343,6 +343,15 @@ impl TestElectrumServer {
         psbt.as_str().unwrap().to_string()
     }
 
+    pub fn elementsd_createrawtransaction(&self, inputs: Value, outputs: Value) -> String {
+        let tx: serde_json::Value = self
+            .elementsd
+            .client
+            .call("createrawtransaction", &[inputs, outputs])
+            .unwrap();
+        tx.as_str().unwrap().to_string()
+    }
+
     pub fn elementsd_expected_next(&self, base64: &str) -> String {
         let value: serde_json::Value = self
             .elementsd
@@ -0,0 +1,154 @@
+//! HTTP Basic authentication and per-method authorization
+
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+use base64::Engine;
+use rand::RngCore;
+
+/// The user name of the credentials written in the cookie file
+pub const COOKIE_USER: &str = "__cookie__";
+
+/// Credentials accepted by the server, optionally restricted to a set of methods
+#[derive(Debug, Clone)]
+pub struct User {
+    name: String,
+    password: String,
+
+    /// If some, the user can call only these methods
+    allowed_methods: Option<HashSet<String>>,
+}
+
+impl User {
+    /// A user allowed to call every method
+    pub fn new(name: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            password: password.into(),
+            allowed_methods: None,
+        }
+    }
+
+    /// A user with a random password, allowed to call every method
+    pub fn random(name: impl Into<String>) -> Self {
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let password = bytes.iter().fold(String::new(), |mut s, b| {
+            let _ = std::fmt::Write::write_fmt(&mut s, format_args!("{b:02x}"));
+            s
+        });
+        Self::new(name, password)
+    }
+
+    /// Restrict the user to the given methods
+    pub fn with_allowed_methods<I, S>(mut self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed_methods = Some(methods.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn password(&self) -> &str {
+        &self.password
+    }
+
+    /// Returns true if the user can call `method`
+    pub fn is_allowed(&self, method: &str) -> bool {
+        self.allowed_methods
+            .as_ref()
+            .map(|m| m.contains(method))
+            .unwrap_or(true)
+    }
+
+    fn matches(&self, name: &str, password: &str) -> bool {
+        // Compare the whole strings to avoid leaking the matching prefix length via timing
+        constant_time_eq(self.name.as_bytes(), name.as_bytes())
+            & constant_time_eq(self.password.as_bytes(), password.as_bytes())
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Create the cookie file at `path` containing random credentials, in the same format used by
+/// bitcoind (`__cookie__:<password>`), returning the corresponding user.
+pub(crate) fn write_cookie(path: &Path) -> io::Result<User> {
+    let user = User::random(COOKIE_USER);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    write!(file, "{}:{}", user.name, user.password)?;
+    file.sync_all()?;
+    Ok(user)
+}
+
+/// Read the credentials (user, password) from a cookie file created by the server
+pub fn read_cookie(path: &Path) -> io::Result<(String, String)> {
+    let content = fs::read_to_string(path)?;
+    let (user, password) = content
+        .trim()
+        .split_once(':')
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid cookie file"))?;
+    Ok((user.to_string(), password.to_string()))
+}
+
+/// Returns the user matching the credentials in the `Authorization` header of the request, if any
+pub(crate) fn authenticate<'a>(
+    users: &'a [User],
+    http_request: &tiny_http::Request,
+) -> Option<&'a User> {
+    let header = http_request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Authorization"))?;
+    let encoded = header.value.as_str().trim().strip_prefix("Basic ")?;
+    let decoded = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .ok()?;
+    let decoded = String::from_utf8(decoded).ok()?;
+    let (name, password) = decoded.split_once(':')?;
+    users.iter().find(|u| u.matches(name, password))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cookie_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sub").join(".cookie");
+        let user = write_cookie(&path).unwrap();
+        assert_eq!(user.name(), COOKIE_USER);
+        assert_eq!(user.password().len(), 64);
+        let (name, password) = read_cookie(&path).unwrap();
+        assert!(user.matches(&name, &password));
+        assert!(!user.matches(&name, "wrong"));
+    }
+
+    #[test]
+    fn allowed_methods() {
+        let user = User::new("a", "b");
+        assert!(user.is_allowed("whatever"));
+        let user = user.with_allowed_methods(["echo"]);
+        assert!(user.is_allowed("echo"));
+        assert!(!user.is_allowed("whatever"));
+    }
+}
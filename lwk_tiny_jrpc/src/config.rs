@@ -2,6 +2,10 @@ use std::{num::NonZeroU8, path::PathBuf};
 
 use tiny_http::Header;
 
+use crate::{auth::User, tls::TlsConfig};
+
+const DEFAULT_MAX_LONG_RUNNING: usize = 16;
+
 #[derive(Debug, Clone)]
 pub struct Config {
     /// Additional headers to add to GET and OPTIONS requests.
@@ -10,6 +14,23 @@ pub struct Config {
     pub num_threads: NonZeroU8,
     /// The path to serve HTTP GET requests from.
     pub serve_dir: Option<PathBuf>,
+    /// Users allowed to call the server via HTTP Basic authentication.
+    ///
+    /// If empty and no `cookie_file` is set, requests are not authenticated.
+    pub users: Vec<User>,
+    /// If set, random credentials are written to this file when the server starts, and removed
+    /// when the server threads are joined.
+    pub cookie_file: Option<PathBuf>,
+    /// If set, the server is reachable only via TLS, see [`crate::JsonRpcServer::bind`].
+    pub tls: Option<TlsConfig>,
+    /// Methods that may take long to complete, like long polls.
+    ///
+    /// A single request calling one of them is handled on a dedicated thread, so that it does
+    /// not prevent the `num_threads` workers from serving other requests.
+    pub long_running_methods: Vec<String>,
+    /// The maximum number of long running requests served at the same time, the requests
+    /// exceeding it fail.
+    pub max_long_running: usize,
 }
 
 impl Config {
@@ -24,6 +45,11 @@ impl Default for Config {
             headers: Vec::new(),
             num_threads: NonZeroU8::new(4).expect("non-zero"),
             serve_dir: None,
+            users: Vec::new(),
+            cookie_file: None,
+            tls: None,
+            long_running_methods: Vec::new(),
+            max_long_running: DEFAULT_MAX_LONG_RUNNING,
         }
     }
 }
@@ -32,6 +58,11 @@ pub struct ConfigBuilder {
     headers: Vec<Header>,
     num_threads: NonZeroU8,
     serve_dir: Option<PathBuf>,
+    users: Vec<User>,
+    cookie_file: Option<PathBuf>,
+    tls: Option<TlsConfig>,
+    long_running_methods: Vec<String>,
+    max_long_running: usize,
 }
 
 impl ConfigBuilder {
@@ -54,11 +85,41 @@ impl ConfigBuilder {
         self
     }
 
+    pub fn with_user(mut self, user: User) -> Self {
+        self.users.push(user);
+        self
+    }
+
+    pub fn with_cookie_file(mut self, path: Option<PathBuf>) -> Self {
+        self.cookie_file = path;
+        self
+    }
+
+    pub fn with_tls(mut self, tls: Option<TlsConfig>) -> Self {
+        self.tls = tls;
+        self
+    }
+
+    pub fn with_long_running_method(mut self, method: impl Into<String>) -> Self {
+        self.long_running_methods.push(method.into());
+        self
+    }
+
+    pub fn with_max_long_running(mut self, max: usize) -> Self {
+        self.max_long_running = max;
+        self
+    }
+
     pub fn build(self) -> Config {
         Config {
             headers: self.headers,
             num_threads: self.num_threads,
             serve_dir: self.serve_dir,
+            users: self.users,
+            cookie_file: self.cookie_file,
+            tls: self.tls,
+            long_running_methods: self.long_running_methods,
+            max_long_running: self.max_long_running,
         }
     }
 }
@@ -69,6 +130,11 @@ impl Default for ConfigBuilder {
             headers: Vec::new(),
             num_threads: NonZeroU8::new(4).expect("non-zero"),
             serve_dir: None,
+            users: Vec::new(),
+            cookie_file: None,
+            tls: None,
+            long_running_methods: Vec::new(),
+            max_long_running: DEFAULT_MAX_LONG_RUNNING,
         }
     }
 }
@@ -42,8 +42,23 @@ pub enum InnerError {
     #[error("Reserved method prefix 'rpc.'")]
     ReservedMethodPrefix,
 
+    #[error("Invalid request object: {0}")]
+    InvalidRequest(String),
+
     #[error("'jsonrpc' version should be '2.0'")]
     InvalidVersion,
+
+    #[error("TLS Error: {0}")]
+    Tls(String),
+
+    #[error("Missing or invalid credentials")]
+    Unauthorized,
+
+    #[error("Method '{0}' is not allowed for this user")]
+    MethodForbidden(String),
+
+    #[error("Too many long running requests, retry later")]
+    TooManyLongRunning,
 }
 
 impl From<String> for Error {
@@ -81,8 +96,13 @@ impl AsRpcError for InnerError {
             InnerError::Serde(_) => (PARSE_ERROR, None),
             InnerError::NoContentType => (NO_CONTENT_TYPE, None),
             InnerError::WrongContentType => (WRONG_CONTENT_TYPE, None),
+            InnerError::InvalidRequest(_) => (INVALID_REQUEST, None),
             InnerError::ReservedMethodPrefix => (METHOD_RESERVED, None),
             InnerError::InvalidVersion => (INVALID_VERSION, None),
+            InnerError::Tls(_) => (IO_ERROR, None),
+            InnerError::Unauthorized => (UNAUTHORIZED, None),
+            InnerError::MethodForbidden(_) => (METHOD_FORBIDDEN, None),
+            InnerError::TooManyLongRunning => (TOO_MANY_LONG_RUNNING, None),
         };
 
         RpcError {
@@ -123,7 +143,7 @@ impl AsRpcError for Error {
 const PARSE_ERROR: i64 = -32_700;
 
 // -32600 	Invalid Request 	The JSON sent is not a valid Request object.
-// const INVALID_REQUEST: i64 = -32_600; // TODO if failing to parse the request object, try to parse as Value and if succesfull return this instead of PARSE_ERROR
+const INVALID_REQUEST: i64 = -32_600;
 
 // -32601 	Method not found 	The method does not exist / is not available.
 pub(crate) const METHOD_NOT_FOUND: i64 = -32_601;
@@ -141,7 +161,9 @@ const WRONG_CONTENT_TYPE: i64 = -32_002;
 const METHOD_RESERVED: i64 = -32_003;
 const INVALID_VERSION: i64 = -32_004;
 
-// GENERIC = -32_098, // TODO remove
+const TOO_MANY_LONG_RUNNING: i64 = -32_096;
+const UNAUTHORIZED: i64 = -32_097;
+const METHOD_FORBIDDEN: i64 = -32_098;
 const STOP_ERROR: i64 = -32_099;
 
 #[derive(Debug)]
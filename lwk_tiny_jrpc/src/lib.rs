@@ -4,16 +4,18 @@
 use std::{
     fmt::Display,
     fs::File,
-    io::{ErrorKind, Read},
+    io::{self, ErrorKind, Read},
+    net::{SocketAddr, TcpListener},
     str::FromStr,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
         Arc, Mutex,
     },
     thread::{self, JoinHandle},
     time::Duration,
 };
 
+pub use auth::User;
 pub use config::Config;
 pub use error::Error;
 use error::{AsRpcError, InnerError, METHOD_NOT_FOUND};
@@ -21,9 +23,12 @@ use serde_derive::{Deserialize, Serialize};
 use serde_json::Value;
 use tiny_http::Server;
 use tiny_http::{Header, Response as HttpResponse};
+pub use tls::TlsConfig;
 
+pub mod auth;
 pub mod config;
 pub mod error;
+mod tls;
 
 // re-export
 pub use tiny_http;
@@ -33,6 +38,9 @@ pub struct JsonRpcServer {
     handles: Vec<JoinHandle<Result<(), Error>>>,
     running: Arc<AtomicBool>,
     config: Config,
+
+    /// The address accepting TLS connections, if TLS is enabled
+    tls_addr: Option<SocketAddr>,
 }
 
 impl JsonRpcServer {
@@ -45,14 +53,53 @@ impl JsonRpcServer {
         Self::run(Arc::new(server), config, state, func)
     }
 
+    /// Creates and runs a new JSON RPC Server listening on `addr`.
+    ///
+    /// If [`Config::tls`] is set, `addr` accepts only TLS connections, which are forwarded to
+    /// the underlying HTTP server listening on localhost.
+    pub fn bind<F, T>(
+        addr: SocketAddr,
+        config: Config,
+        state: Arc<Mutex<T>>,
+        func: F,
+    ) -> Result<Self, Error>
+    where
+        F: Fn(Request, Arc<Mutex<T>>) -> Result<Response, Error> + Clone + Send + Sync + 'static,
+        T: Send + 'static,
+    {
+        let Some(tls) = config.tls.clone() else {
+            let server = Server::http(addr).map_err(to_io_error)?;
+            return Ok(Self::new(server, config, state, func));
+        };
+        let listener = TcpListener::bind(addr).map_err(InnerError::Io)?;
+        listener.set_nonblocking(true).map_err(InnerError::Io)?;
+        let tls_addr = listener.local_addr().map_err(InnerError::Io)?;
+        let server = Server::http("127.0.0.1:0").map_err(to_io_error)?;
+        let backend = server
+            .server_addr()
+            .to_ip()
+            .ok_or_else(|| to_io_error("Unexpected non-IP address"))?;
+
+        let mut rpc = Self::new(server, config, state, func);
+        let proxy = tls::spawn_proxy(listener, backend, tls, rpc.running.clone());
+        rpc.handles.push(proxy);
+        rpc.tls_addr = Some(tls_addr);
+        Ok(rpc)
+    }
+
     /// Returns a reference to the [`tiny_http::ListenAddr`] of the server.
+    ///
+    /// If TLS is enabled, it's the address accepting TLS connections.
     pub fn server_addr(&self) -> tiny_http::ListenAddr {
-        self.server.server_addr()
+        match self.tls_addr {
+            Some(addr) => tiny_http::ListenAddr::IP(addr),
+            None => self.server.server_addr(),
+        }
     }
 
     /// Returns the IP port unless the underlying tiny_http server is listening on a Unix socket.
     pub fn port(&self) -> Option<u16> {
-        self.server.server_addr().to_ip().map(|addr| addr.port())
+        self.server_addr().to_ip().map(|addr| addr.port())
     }
 
     /// Returns a reference to the [`Config`] used when creating the JSON RPC Server.
@@ -68,12 +115,26 @@ impl JsonRpcServer {
         let mut handles = Vec::with_capacity(4);
         let running = Arc::new(AtomicBool::new(true));
 
+        let mut users = config.users.clone();
+        if let Some(path) = config.cookie_file.as_ref() {
+            match auth::write_cookie(path) {
+                Ok(user) => users.push(user),
+                // Authentication is still required, thus only the other users can access
+                Err(e) => log::error!("Cannot write cookie file {}: {}", path.display(), e),
+            }
+        }
+        let auth_required = !users.is_empty() || config.cookie_file.is_some();
+        let users = Arc::new(users);
+        let long_running = Arc::new(AtomicUsize::new(0));
+
         for _ in 0..config.num_threads.get() {
             let server = server.clone();
             let func = func.clone();
             let state = state.clone();
             let running = running.clone();
             let config = config.clone();
+            let users = users.clone();
+            let long_running = long_running.clone();
             let handle = thread::spawn(move || {
                 loop {
                     // receive http request
@@ -94,6 +155,33 @@ impl JsonRpcServer {
                         }
                     };
 
+                    // check credentials, CORS preflight requests never include them
+                    let user = if auth_required
+                        && *http_request.method() != tiny_http::Method::Options
+                    {
+                        match auth::authenticate(&users, &http_request) {
+                            Some(user) => Some(user.clone()),
+                            None => {
+                                let www =
+                                    Header::from_str("WWW-Authenticate: Basic realm=\"jsonrpc\"")
+                                        .expect("valid header");
+                                let response = Response::from_error(None, InnerError::Unauthorized);
+                                let result = send_jsonrpc_response(
+                                    http_request,
+                                    Body::Single(response),
+                                    401,
+                                    &[config.headers.as_slice(), &[www]].concat(),
+                                );
+                                if let Err(err) = result {
+                                    log::error!("send_response error: {}", err);
+                                }
+                                continue;
+                            }
+                        }
+                    } else {
+                        None
+                    };
+
                     // check request method
                     match http_request.method() {
                         tiny_http::Method::Get => {
@@ -169,35 +257,63 @@ impl JsonRpcServer {
                             send_http_response(http_request, response, message);
                         }
                         tiny_http::Method::Post => {
-                            // validate/parse the jsonrpc POST request
-                            let response = match validate_jsonrpc_request(&mut http_request) {
-                                Ok(request) => {
-                                    // handle the request
-                                    let id = request.id.clone();
-                                    match handle_jsonrpc_request(
-                                        request,
-                                        state.clone(),
-                                        func.clone(),
-                                    ) {
-                                        Ok(response) => response,
-                                        Err(Error::Stop) => {
-                                            running.store(false, Ordering::SeqCst);
-                                            Response::from_error(id, Error::Stop)
-                                        }
-                                        Err(err) => Response::from_error(id, err),
-                                    }
-                                }
-                                Err(err) => {
-                                    // no id since we couldn't validate the request...
-                                    Response::from_error(None, err)
-                                }
-                            };
-
-                            // send the response
-                            if let Err(err) =
-                                send_jsonrpc_response(http_request, response, &config.headers)
+                            let payload = validate_jsonrpc_request(&mut http_request);
+                            // long running requests would hold this worker, so they are
+                            // handled on their own thread, up to `max_long_running` at a time
+                            let is_long_running = matches!(&payload, Ok(Payload::Single(value))
+                            if value.get("method").and_then(Value::as_str).map_or(false, |m| {
+                                config.long_running_methods.iter().any(|l| l == m)
+                            }));
+                            if is_long_running
+                                && long_running.fetch_add(1, Ordering::SeqCst)
+                                    >= config.max_long_running
                             {
-                                log::error!("send_response error: {}", err);
+                                long_running.fetch_sub(1, Ordering::SeqCst);
+                                let id = payload.ok().and_then(|p| match p {
+                                    Payload::Single(value) => value
+                                        .get("id")
+                                        .and_then(|id| serde_json::from_value(id.clone()).ok()),
+                                    Payload::Batch(_) => None,
+                                });
+                                let response =
+                                    Response::from_error(id, InnerError::TooManyLongRunning);
+                                let result = send_jsonrpc_response(
+                                    http_request,
+                                    Body::Single(response),
+                                    503,
+                                    &config.headers,
+                                );
+                                if let Err(err) = result {
+                                    log::error!("send_response error: {}", err);
+                                }
+                            } else if is_long_running {
+                                let state = state.clone();
+                                let func = func.clone();
+                                let running = running.clone();
+                                let headers = config.headers.clone();
+                                let long_running = long_running.clone();
+                                thread::spawn(move || {
+                                    respond_post(
+                                        http_request,
+                                        payload,
+                                        user.as_ref(),
+                                        &state,
+                                        &func,
+                                        &running,
+                                        &headers,
+                                    );
+                                    long_running.fetch_sub(1, Ordering::SeqCst);
+                                });
+                            } else {
+                                respond_post(
+                                    http_request,
+                                    payload,
+                                    user.as_ref(),
+                                    &state,
+                                    &func,
+                                    &running,
+                                    &config.headers,
+                                );
                             }
                         }
                         other => {
@@ -219,6 +335,7 @@ impl JsonRpcServer {
             handles,
             running,
             config,
+            tls_addr: None,
         }
     }
 
@@ -237,6 +354,9 @@ impl JsonRpcServer {
         while let Some(handle) = self.handles.pop() {
             let _ = handle.join();
         }
+        if let Some(path) = self.config.cookie_file.as_ref() {
+            let _ = std::fs::remove_file(path);
+        }
     }
 }
 
@@ -256,7 +376,7 @@ where
     }
 }
 
-fn validate_jsonrpc_request(http_request: &mut tiny_http::Request) -> Result<Request, InnerError> {
+fn validate_jsonrpc_request(http_request: &mut tiny_http::Request) -> Result<Payload, InnerError> {
     log::debug!(
         "received request - method: {:?}, url: {:?}, headers: {:?}",
         http_request.method(),
@@ -282,13 +402,122 @@ fn validate_jsonrpc_request(http_request: &mut tiny_http::Request) -> Result<Req
         return Err(InnerError::WrongContentType);
     }
 
-    // parse json into request
+    // parse json, the single requests are validated later, so that the elements of a batch are
+    // handled independently
     let mut s = String::new(); // todo: performance
     http_request.as_reader().read_to_string(&mut s)?;
 
-    let request: Request = serde_json::from_str(&s)?;
+    let payload = match serde_json::from_str(&s)? {
+        Value::Array(values) => Payload::Batch(values),
+        value => Payload::Single(value),
+    };
+
+    Ok(payload)
+}
+
+/// Handle the payload of a POST request, each request of a batch in order, and respond to it.
+fn respond_post<F, T>(
+    http_request: tiny_http::Request,
+    payload: Result<Payload, InnerError>,
+    user: Option<&User>,
+    state: &Arc<Mutex<T>>,
+    func: &F,
+    running: &AtomicBool,
+    headers: &[Header],
+) where
+    F: Fn(Request, Arc<Mutex<T>>) -> Result<Response, Error> + Clone + Send + Sync + 'static,
+    T: Send + 'static,
+{
+    let dispatch = |value| dispatch(value, user, state, func, running);
+    let (status, body) = match payload {
+        Ok(Payload::Single(value)) => match dispatch(value) {
+            (status, Some(response)) => (status, Some(Body::Single(response))),
+            (_, None) => (204, None),
+        },
+        Ok(Payload::Batch(values)) if values.is_empty() => {
+            let err = InnerError::InvalidRequest("empty batch".into());
+            (200, Some(Body::Single(Response::from_error(None, err))))
+        }
+        Ok(Payload::Batch(values)) => {
+            let responses: Vec<_> = values
+                .into_iter()
+                .filter_map(|value| dispatch(value).1)
+                .collect();
+            if responses.is_empty() {
+                // a batch of notifications only
+                (204, None)
+            } else {
+                (200, Some(Body::Batch(responses)))
+            }
+        }
+        Err(err) => {
+            // no id since we couldn't validate the request...
+            (200, Some(Body::Single(Response::from_error(None, err))))
+        }
+    };
+
+    // send the response
+    let result = match body {
+        Some(body) => send_jsonrpc_response(http_request, body, status, headers),
+        None => {
+            let mut response = HttpResponse::empty(status);
+            for header in headers.iter() {
+                response.add_header(header.clone());
+            }
+            http_request.respond(response).map_err(InnerError::Io)
+        }
+    };
+    if let Err(err) = result {
+        log::error!("send_response error: {}", err);
+    }
+}
+
+/// Validate and handle a single request, returning the HTTP status code and the response.
+///
+/// The response is none if the request is a notification, i.e. it has no `id` member.
+fn dispatch<F, T>(
+    value: Value,
+    user: Option<&User>,
+    state: &Arc<Mutex<T>>,
+    func: &F,
+    running: &AtomicBool,
+) -> (u16, Option<Response>)
+where
+    F: Fn(Request, Arc<Mutex<T>>) -> Result<Response, Error> + Clone + Send + Sync + 'static,
+    T: Send + 'static,
+{
+    let is_notification = value.as_object().map_or(false, |o| !o.contains_key("id"));
+    let request: Request = match serde_json::from_value(value) {
+        Ok(request) => request,
+        Err(err) => {
+            // invalid requests are answered even if they look like notifications
+            let err = InnerError::InvalidRequest(err.to_string());
+            return (200, Some(Response::from_error(None, err)));
+        }
+    };
+
+    let id = request.id.clone();
+    let (status, response) = if !user.map_or(true, |u| u.is_allowed(&request.method)) {
+        let err = InnerError::MethodForbidden(request.method);
+        (403, Response::from_error(id, err))
+    } else {
+        // handle the request
+        match handle_jsonrpc_request(request, state.clone(), func.clone()) {
+            Ok(response) => (200, response),
+            Err(Error::Stop) => {
+                running.store(false, Ordering::SeqCst);
+                (200, Response::from_error(id, Error::Stop))
+            }
+            Err(err) => (200, Response::from_error(id, err)),
+        }
+    };
 
-    Ok(request)
+    if is_notification {
+        log::debug!("Not responding to notification: {:?}", response);
+        (204, None)
+    } else {
+        (status, Some(response))
+    }
 }
 
 fn handle_jsonrpc_request<F, T>(
@@ -327,17 +556,36 @@ where
 
 fn send_jsonrpc_response(
     request: tiny_http::Request,
-    response: Response,
+    response: Body,
+    status: u16,
     headers: &[Header],
 ) -> Result<(), InnerError> {
     let data = serde_json::to_string(&response)?;
-    let mut response = HttpResponse::from_string(data);
+    let mut response = HttpResponse::from_string(data).with_status_code(status);
     for header in headers.iter() {
         response.add_header(header.clone());
     }
     Ok(request.respond(response)?)
 }
 
+fn to_io_error(e: impl Display) -> Error {
+    InnerError::Io(io::Error::new(ErrorKind::Other, e.to_string())).into()
+}
+
+/// The body of a POST request, either a single request or a batch
+enum Payload {
+    Single(Value),
+    Batch(Vec<Value>),
+}
+
+/// The body of the response to a POST request
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Body {
+    Single(Response),
+    Batch(Vec<Response>),
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Request {
     pub jsonrpc: String,
@@ -424,7 +672,7 @@ pub enum Id {
 
 #[cfg(test)]
 mod test {
-    use std::{fs::File, io::Write, path::PathBuf};
+    use std::{fs::File, io::Write, num::NonZeroU8, path::PathBuf};
 
     use super::*;
     use jsonrpc::Client;
@@ -439,6 +687,10 @@ mod test {
                 result: request.params,
                 error: None,
             },
+            "sleep" => {
+                thread::sleep(Duration::from_secs(2));
+                Response::result(request.id, Value::Bool(true))
+            }
             _ => unimplemented!(),
         };
         Ok(response)
@@ -471,6 +723,78 @@ mod test {
         rpc.join_threads();
     }
 
+    #[test]
+    fn long_running() {
+        let addr = "127.0.0.1:0";
+        let server = Server::http(addr).unwrap();
+        let state = Arc::new(Mutex::new(()));
+        let config = Config::builder()
+            .with_num_threads(NonZeroU8::new(1).unwrap())
+            .with_long_running_method("sleep")
+            .build();
+        let mut rpc = JsonRpcServer::new(server, config, state, process);
+        let url = format!("http://127.0.0.1:{}", rpc.port().unwrap());
+
+        let sleep_url = url.clone();
+        let sleep = thread::spawn(move || {
+            let client = reqwest::blocking::Client::new();
+            let resp = post_echo(&client, &sleep_url, "sleep", None);
+            resp.json::<Response>().unwrap()
+        });
+        thread::sleep(Duration::from_millis(200));
+
+        // the only worker is not busy with the sleep request
+        let start = std::time::Instant::now();
+        let client = reqwest::blocking::Client::new();
+        let resp: Response = post_echo(&client, &url, "echo", None).json().unwrap();
+        assert_eq!(resp.result.unwrap(), json!("hello"));
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        assert!(sleep.join().unwrap().is_result());
+        rpc.stop();
+        rpc.join_threads();
+    }
+
+    #[test]
+    fn too_many_long_running() {
+        let addr = "127.0.0.1:0";
+        let server = Server::http(addr).unwrap();
+        let state = Arc::new(Mutex::new(()));
+        let config = Config::builder()
+            .with_long_running_method("sleep")
+            .with_max_long_running(1)
+            .build();
+        let mut rpc = JsonRpcServer::new(server, config, state, process);
+        let url = format!("http://127.0.0.1:{}", rpc.port().unwrap());
+
+        let sleep_url = url.clone();
+        let sleep = thread::spawn(move || {
+            let client = reqwest::blocking::Client::new();
+            let resp = post_echo(&client, &sleep_url, "sleep", None);
+            resp.json::<Response>().unwrap()
+        });
+        thread::sleep(Duration::from_millis(200));
+
+        let client = reqwest::blocking::Client::new();
+        let resp = post_echo(&client, &url, "sleep", None);
+        assert_eq!(resp.status(), 503);
+        let resp: Response = resp.json().unwrap();
+        assert!(matches!(resp.id, Some(Id::Number(1))));
+        assert_eq!(resp.error.unwrap().code, -32_096);
+
+        // other requests are still served
+        let resp: Response = post_echo(&client, &url, "echo", None).json().unwrap();
+        assert_eq!(resp.result.unwrap(), json!("hello"));
+
+        // the slot is released when the request completes
+        assert!(sleep.join().unwrap().is_result());
+        let resp: Response = post_echo(&client, &url, "sleep", None).json().unwrap();
+        assert!(resp.is_result());
+
+        rpc.stop();
+        rpc.join_threads();
+    }
+
     #[test]
     fn rpc_dot_reserved() {
         let addr = "127.0.0.1:0";
@@ -615,4 +939,175 @@ mod test {
         assert_eq!(resp.status(), 404);
         assert_eq!(resp.text().unwrap(), "404: File not found");
     }
+
+    fn post_echo(
+        client: &reqwest::blocking::Client,
+        url: &str,
+        method: &str,
+        auth: Option<(&str, &str)>,
+    ) -> reqwest::blocking::Response {
+        let body = json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": "hello"});
+        let mut request = client.post(url).json(&body);
+        if let Some((user, password)) = auth {
+            request = request.basic_auth(user, Some(password));
+        }
+        request.send().unwrap()
+    }
+
+    #[test]
+    fn batch_and_notifications() {
+        let addr = "127.0.0.1:0";
+        let server = Server::http(addr).unwrap();
+        let state = Arc::new(Mutex::new(()));
+        let rpc = JsonRpcServer::new(server, Config::default(), state, process);
+        let url = format!("http://127.0.0.1:{}", rpc.port().unwrap());
+        let client = reqwest::blocking::Client::new();
+        let post = |body: Value| client.post(&url).json(&body).send().unwrap();
+
+        let body = json!([
+            {"jsonrpc": "2.0", "id": 1, "method": "echo", "params": "a"},
+            {"jsonrpc": "2.0", "method": "echo", "params": "notification"},
+            {"jsonrpc": "2.0", "id": "2", "method": "rpc.reserved"},
+            {"foo": "bar"},
+            {"jsonrpc": "2.0", "id": 3, "method": "echo", "params": "c"},
+        ]);
+        let resp = post(body);
+        assert_eq!(resp.status(), 200);
+        let resp: Vec<Response> = resp.json().unwrap();
+        assert_eq!(resp.len(), 4);
+        assert_eq!(resp[0].result, Some(json!("a")));
+        assert!(matches!(resp[1].id, Some(Id::String(ref s)) if s == "2"));
+        assert!(resp[1].is_error());
+        assert!(resp[2].id.is_none());
+        assert_eq!(resp[2].error.as_ref().unwrap().code, -32_600);
+        assert_eq!(resp[3].result, Some(json!("c")));
+
+        // single notification and batch of notifications
+        let notification = json!({"jsonrpc": "2.0", "method": "echo", "params": "n"});
+        let resp = post(notification.clone());
+        assert_eq!(resp.status(), 204);
+        assert!(resp.bytes().unwrap().is_empty());
+        let resp = post(json!([notification.clone(), notification]));
+        assert_eq!(resp.status(), 204);
+        assert!(resp.bytes().unwrap().is_empty());
+
+        // empty batch
+        let resp: Response = post(json!([])).json().unwrap();
+        assert_eq!(resp.error.unwrap().code, -32_600);
+
+        // invalid json
+        let resp: Response = client
+            .post(&url)
+            .header("content-type", "application/json")
+            .body("[{")
+            .send()
+            .unwrap()
+            .json()
+            .unwrap();
+        assert_eq!(resp.error.unwrap().code, -32_700);
+    }
+
+    #[test]
+    fn basic_auth() {
+        let addr = "127.0.0.1:0";
+        let server = Server::http(addr).unwrap();
+        let state = Arc::new(Mutex::new(()));
+        let config = Config::builder()
+            .with_user(User::new("user", "pass"))
+            .with_user(User::new("limited", "pass").with_allowed_methods(["other"]))
+            .build();
+        let rpc = JsonRpcServer::new(server, config, state, process);
+        let url = format!("http://127.0.0.1:{}", rpc.port().unwrap());
+        let client = reqwest::blocking::Client::new();
+
+        let resp = post_echo(&client, &url, "echo", None);
+        assert_eq!(resp.status(), 401);
+        assert!(resp.headers().get("www-authenticate").is_some());
+
+        let resp = post_echo(&client, &url, "echo", Some(("user", "wrong")));
+        assert_eq!(resp.status(), 401);
+
+        let resp = post_echo(&client, &url, "echo", Some(("user", "pass")));
+        assert_eq!(resp.status(), 200);
+        let resp: Response = resp.json().unwrap();
+        assert_eq!(resp.result.unwrap(), json!("hello"));
+
+        let resp = post_echo(&client, &url, "echo", Some(("limited", "pass")));
+        assert_eq!(resp.status(), 403);
+        let resp: Response = resp.json().unwrap();
+        assert!(resp.is_error());
+
+        // CORS preflight requests are not authenticated
+        let resp = client
+            .request(reqwest::Method::OPTIONS, &url)
+            .send()
+            .unwrap();
+        assert_eq!(resp.status(), 204);
+    }
+
+    #[test]
+    fn cookie_auth() {
+        let addr = "127.0.0.1:0";
+        let server = Server::http(addr).unwrap();
+        let state = Arc::new(Mutex::new(()));
+        let dir = tempfile::tempdir().unwrap();
+        let cookie = dir.path().join(".cookie");
+        let config = Config::builder()
+            .with_cookie_file(Some(cookie.clone()))
+            .build();
+        let mut rpc = JsonRpcServer::new(server, config, state, process);
+        let url = format!("127.0.0.1:{}", rpc.port().unwrap());
+
+        let (user, password) = auth::read_cookie(&cookie).unwrap();
+        assert_eq!(user, auth::COOKIE_USER);
+        let client = Client::simple_http(&url, Some(user), Some(password)).unwrap();
+        let params = to_raw_value("hello").unwrap();
+        let response = client
+            .send_request(client.build_request("echo", Some(&params)))
+            .unwrap();
+        assert_eq!(response.result.unwrap().get(), "\"hello\"");
+
+        let client = Client::simple_http(&url, None, None).unwrap();
+        let request = client.build_request("echo", Some(&params));
+        assert!(client.send_request(request).is_err());
+
+        rpc.stop();
+        rpc.join_threads();
+        assert!(!cookie.exists());
+    }
+
+    #[test]
+    fn tls() {
+        let addr = "127.0.0.1:0".parse().unwrap();
+        let state = Arc::new(Mutex::new(()));
+        let tls = TlsConfig::from_pem(
+            include_bytes!("../test/data/tls_cert.pem"),
+            include_bytes!("../test/data/tls_key.pem"),
+        )
+        .unwrap();
+        let config = Config::builder().with_tls(Some(tls)).build();
+        let mut rpc = JsonRpcServer::bind(addr, config, state, process).unwrap();
+        let port = rpc.port().unwrap();
+
+        let ca = reqwest::Certificate::from_pem(include_bytes!("../test/data/tls_ca.pem")).unwrap();
+        let client = reqwest::blocking::Client::builder()
+            .add_root_certificate(ca)
+            .build()
+            .unwrap();
+        for _ in 0..2 {
+            let resp = post_echo(&client, &format!("https://127.0.0.1:{port}"), "echo", None);
+            assert_eq!(resp.status(), 200);
+            let resp: Response = resp.json().unwrap();
+            assert_eq!(resp.result.unwrap(), json!("hello"));
+        }
+
+        // plain http is not accepted
+        let plain = reqwest::blocking::Client::new();
+        let body = json!({"jsonrpc": "2.0", "id": 1, "method": "echo"});
+        let url = format!("http://127.0.0.1:{port}");
+        assert!(plain.post(url).json(&body).send().is_err());
+
+        rpc.stop();
+        rpc.join_threads();
+    }
 }
@@ -0,0 +1,137 @@
+//! TLS support
+//!
+//! `tiny_http` only supports an older rustls version, so TLS connections are terminated here and
+//! the decrypted stream is forwarded to the plain HTTP server listening on localhost.
+
+use std::fmt::Debug;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::{ServerConfig, ServerConnection, StreamOwned};
+
+use crate::error::InnerError;
+use crate::Error;
+
+/// Certificate and key used to serve the JSON RPC over TLS
+#[derive(Clone)]
+pub struct TlsConfig {
+    server_config: Arc<ServerConfig>,
+}
+
+impl Debug for TlsConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TlsConfig").finish_non_exhaustive()
+    }
+}
+
+impl TlsConfig {
+    /// Create the TLS config from the PEM encoded certificate chain and private key
+    pub fn from_pem(cert_chain: &[u8], private_key: &[u8]) -> Result<Self, Error> {
+        let certs: Vec<CertificateDer<'static>> = rustls_pemfile::certs(&mut &cert_chain[..])
+            .collect::<Result<_, _>>()
+            .map_err(InnerError::Io)?;
+        if certs.is_empty() {
+            return Err(InnerError::Tls("no certificate found".into()).into());
+        }
+        let key: PrivateKeyDer<'static> = rustls_pemfile::private_key(&mut &private_key[..])
+            .map_err(InnerError::Io)?
+            .ok_or_else(|| InnerError::Tls("no private key found".into()))?;
+
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let server_config = ServerConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .and_then(|b| b.with_no_client_auth().with_single_cert(certs, key))
+            .map_err(|e| InnerError::Tls(e.to_string()))?;
+
+        Ok(Self {
+            server_config: Arc::new(server_config),
+        })
+    }
+
+    /// Create the TLS config reading the PEM encoded certificate chain and private key from files
+    pub fn from_pem_files(cert_chain: &Path, private_key: &Path) -> Result<Self, Error> {
+        let cert_chain = std::fs::read(cert_chain).map_err(InnerError::Io)?;
+        let private_key = std::fs::read(private_key).map_err(InnerError::Io)?;
+        Self::from_pem(&cert_chain, &private_key)
+    }
+}
+
+/// Accept TLS connections on the non-blocking `listener` and forward them to the plain server at
+/// `backend`
+pub(crate) fn spawn_proxy(
+    listener: TcpListener,
+    backend: SocketAddr,
+    config: TlsConfig,
+    running: Arc<AtomicBool>,
+) -> JoinHandle<Result<(), Error>> {
+    thread::spawn(move || {
+        while running.load(Ordering::SeqCst) {
+            match listener.accept() {
+                Ok((stream, peer)) => {
+                    let server_config = config.server_config.clone();
+                    let running = running.clone();
+                    thread::spawn(move || {
+                        if let Err(e) = forward(stream, backend, server_config, &running) {
+                            log::debug!("TLS connection from {peer} ended with error: {e}");
+                        }
+                    });
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                    thread::sleep(Duration::from_millis(50))
+                }
+                Err(e) => log::error!("TLS accept error: {e}"),
+            }
+        }
+        Ok(())
+    })
+}
+
+fn forward(
+    stream: TcpStream,
+    backend: SocketAddr,
+    server_config: Arc<ServerConfig>,
+    running: &AtomicBool,
+) -> io::Result<()> {
+    let poll = Some(Duration::from_millis(10));
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(poll)?;
+    let mut plain = TcpStream::connect(backend)?;
+    plain.set_read_timeout(poll)?;
+
+    let connection =
+        ServerConnection::new(server_config).map_err(|e| io::Error::new(ErrorKind::Other, e))?;
+    let mut tls = StreamOwned::new(connection, stream);
+    let mut buf = [0u8; 16 * 1024];
+
+    // Both sides are polled, since the TLS stream can't be split for reading and writing
+    // concurrently
+    while running.load(Ordering::SeqCst) {
+        match tls.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => plain.write_all(&buf[..n])?,
+            Err(e) if is_timeout(&e) => (),
+            Err(e) => return Err(e),
+        }
+        match plain.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => {
+                tls.write_all(&buf[..n])?;
+                tls.flush()?;
+            }
+            Err(e) if is_timeout(&e) => (),
+            Err(e) => return Err(e),
+        }
+    }
+    tls.conn.send_close_notify();
+    tls.flush()
+}
+
+fn is_timeout(e: &io::Error) -> bool {
+    matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+}
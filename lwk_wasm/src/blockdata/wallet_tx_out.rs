@@ -55,13 +55,13 @@ impl WalletTxOut {
     }
 
     #[wasm_bindgen(js_name = wildcardIndex)]
-    pub fn wildcard_index(&self) -> u32 {
+    pub fn wildcard_index(&self) -> Option<u32> {
         self.inner.wildcard_index
     }
 
     #[wasm_bindgen(js_name = extInt)]
-    pub fn ext_int(&self) -> Chain {
-        self.inner.ext_int.into()
+    pub fn ext_int(&self) -> Option<Chain> {
+        self.inner.ext_int.map(Into::into)
     }
 }
 
@@ -105,8 +105,8 @@ mod tests {
                 1000,
                 elements::confidential::ValueBlindingFactor::zero(),
             ),
-            wildcard_index: 10,
-            ext_int: lwk_wollet::Chain::External,
+            wildcard_index: Some(10),
+            ext_int: Some(lwk_wollet::Chain::External),
         };
 
         let wallet_tx_out: WalletTxOut = el.clone().into();
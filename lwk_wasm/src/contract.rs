@@ -46,6 +46,7 @@ impl Contract {
             precision,
             ticker: ticker.to_string(),
             version,
+            extra: Default::default(),
         };
         inner.validate()?; // TODO validate should be the constructor
         Ok(Self { inner })
@@ -12,7 +12,10 @@ mod mnemonic;
 mod network;
 mod precision;
 mod pset;
+mod pset_analysis;
 mod pset_details;
+mod query;
+mod registry;
 #[cfg(all(feature = "serial", target_arch = "wasm32"))]
 mod serial;
 mod signer;
@@ -39,7 +42,10 @@ pub use mnemonic::Mnemonic;
 pub use network::Network;
 pub use precision::Precision;
 pub use pset::Pset;
+pub use pset_analysis::{PsetAnalysis, PsetInputAnalysis, PsetOutputAnalysis};
 pub use pset_details::PsetDetails;
+pub use query::{TxPage, TxQuery};
+pub use registry::Registry;
 pub use signer::Signer;
 pub use tx_builder::TxBuilder;
 pub use update::Update;
@@ -1,5 +1,6 @@
-use crate::{AssetId, Error, Transaction, Txid};
+use crate::{AssetId, Error, Network, PsetAnalysis, Transaction, Txid};
 use lwk_wollet::elements::pset::{Input, PartiallySignedTransaction};
+use lwk_wollet::ElementsNetwork;
 use std::fmt::Display;
 use wasm_bindgen::prelude::*;
 
@@ -56,6 +57,12 @@ impl Pset {
     pub fn inputs(&self) -> Vec<PsetInput> {
         self.inner.inputs().iter().map(Into::into).collect()
     }
+
+    /// Decode the PSET without a wallet, showing every input and output
+    pub fn analysis(&self, network: &Network) -> PsetAnalysis {
+        let network: ElementsNetwork = (*network).into();
+        lwk_common::pset_analysis(&self.inner, network.address_params()).into()
+    }
 }
 
 /// PSET input
@@ -0,0 +1,194 @@
+use wasm_bindgen::prelude::*;
+
+use crate::pset_details::{Issuance, PsetSignatures};
+use crate::{Address, AssetId, OutPoint, Script};
+
+/// A PSET decoded without a wallet, wrapper of [`lwk_common::PsetAnalysis`]
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct PsetAnalysis {
+    inner: lwk_common::PsetAnalysis,
+}
+
+/// An input of a decoded PSET, wrapper of [`lwk_common::PsetInputAnalysis`]
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct PsetInputAnalysis {
+    inner: lwk_common::PsetInputAnalysis,
+}
+
+/// An output of a decoded PSET, wrapper of [`lwk_common::PsetOutputAnalysis`]
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct PsetOutputAnalysis {
+    inner: lwk_common::PsetOutputAnalysis,
+}
+
+#[wasm_bindgen]
+impl PsetAnalysis {
+    pub fn inputs(&self) -> Vec<PsetInputAnalysis> {
+        self.inner
+            .inputs
+            .clone()
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
+    pub fn outputs(&self) -> Vec<PsetOutputAnalysis> {
+        self.inner
+            .outputs
+            .clone()
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
+    /// The sum of the fee outputs
+    pub fn fee(&self) -> Option<u64> {
+        self.inner.fee
+    }
+
+    /// The estimated size once signed, in virtual bytes
+    pub fn vsize(&self) -> Option<usize> {
+        self.inner.vsize
+    }
+
+    /// The estimated fee rate in sat/kvB
+    #[wasm_bindgen(js_name = feeRate)]
+    pub fn fee_rate(&self) -> Option<f32> {
+        self.inner.fee_rate
+    }
+
+    #[wasm_bindgen(js_name = missingSignatures)]
+    pub fn missing_signatures(&self) -> usize {
+        self.inner.missing_signatures()
+    }
+}
+
+#[wasm_bindgen]
+impl PsetInputAnalysis {
+    #[wasm_bindgen(js_name = previousOutpoint)]
+    pub fn previous_outpoint(&self) -> OutPoint {
+        self.inner.previous_outpoint.into()
+    }
+
+    #[wasm_bindgen(js_name = scriptPubkey)]
+    pub fn script_pubkey(&self) -> Option<Script> {
+        self.inner.script_pubkey.as_ref().map(Into::into)
+    }
+
+    #[wasm_bindgen(js_name = scriptType)]
+    pub fn script_type(&self) -> Option<String> {
+        self.inner.script_type.map(|t| t.to_string())
+    }
+
+    /// The unconfidential address of the previous output
+    pub fn address(&self) -> Option<Address> {
+        self.inner.address.as_ref().map(Into::into)
+    }
+
+    pub fn blinded(&self) -> Option<bool> {
+        self.inner.blinded
+    }
+
+    /// The asset, if explicit or proved by the blind asset proof
+    pub fn asset(&self) -> Option<AssetId> {
+        self.inner.asset.map(Into::into)
+    }
+
+    /// The amount, if explicit or proved by the blind value proof
+    pub fn satoshi(&self) -> Option<u64> {
+        self.inner.satoshi
+    }
+
+    pub fn issuance(&self) -> Issuance {
+        self.inner.issuance.clone().into()
+    }
+
+    pub fn sighash(&self) -> Option<String> {
+        self.inner.sighash.map(|h| h.to_string())
+    }
+
+    pub fn signatures(&self) -> PsetSignatures {
+        self.inner.signatures.clone().into()
+    }
+
+    pub fn finalized(&self) -> bool {
+        self.inner.finalized
+    }
+}
+
+#[wasm_bindgen]
+impl PsetOutputAnalysis {
+    #[wasm_bindgen(js_name = scriptPubkey)]
+    pub fn script_pubkey(&self) -> Script {
+        (&self.inner.script_pubkey).into()
+    }
+
+    #[wasm_bindgen(js_name = scriptType)]
+    pub fn script_type(&self) -> String {
+        self.inner.script_type.to_string()
+    }
+
+    /// The address, confidential if the output has a blinding key
+    pub fn address(&self) -> Option<Address> {
+        self.inner.address.as_ref().map(Into::into)
+    }
+
+    pub fn blinded(&self) -> bool {
+        self.inner.blinded
+    }
+
+    /// The asset, if explicit or proved by the blind asset proof
+    pub fn asset(&self) -> Option<AssetId> {
+        self.inner.asset.map(Into::into)
+    }
+
+    /// The amount, if explicit or proved by the blind value proof
+    pub fn satoshi(&self) -> Option<u64> {
+        self.inner.satoshi
+    }
+}
+
+impl From<lwk_common::PsetAnalysis> for PsetAnalysis {
+    fn from(inner: lwk_common::PsetAnalysis) -> Self {
+        Self { inner }
+    }
+}
+
+impl From<lwk_common::PsetInputAnalysis> for PsetInputAnalysis {
+    fn from(inner: lwk_common::PsetInputAnalysis) -> Self {
+        Self { inner }
+    }
+}
+
+impl From<lwk_common::PsetOutputAnalysis> for PsetOutputAnalysis {
+    fn from(inner: lwk_common::PsetOutputAnalysis) -> Self {
+        Self { inner }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::*;
+
+    use crate::{Network, Pset};
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_pset_analysis() {
+        let pset = include_str!("../test_data/pset_details/pset.base64");
+        let pset = Pset::new(pset).unwrap();
+
+        let analysis = pset.analysis(&Network::regtest_default());
+        assert_eq!(analysis.fee(), Some(254));
+        assert_eq!(analysis.inputs().len(), 1);
+        assert_eq!(analysis.missing_signatures(), 1);
+        let input = &analysis.inputs()[0];
+        assert_eq!(input.script_type(), Some("p2wsh".to_string()));
+        assert!(!input.issuance().is_issuance());
+        assert!(analysis.outputs().iter().any(|o| o.script_type() == "fee"));
+    }
+}
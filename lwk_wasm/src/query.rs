@@ -0,0 +1,94 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{Address, AssetId, WalletTx};
+
+/// Wrapper of [`lwk_wollet::TxQuery`]
+#[wasm_bindgen]
+#[derive(Debug, Default)]
+pub struct TxQuery {
+    inner: lwk_wollet::TxQuery,
+}
+
+impl From<lwk_wollet::TxQuery> for TxQuery {
+    fn from(inner: lwk_wollet::TxQuery) -> Self {
+        Self { inner }
+    }
+}
+
+impl AsRef<lwk_wollet::TxQuery> for TxQuery {
+    fn as_ref(&self) -> &lwk_wollet::TxQuery {
+        &self.inner
+    }
+}
+
+#[wasm_bindgen]
+impl TxQuery {
+    /// Creates a query returning all the transactions
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> TxQuery {
+        TxQuery::default()
+    }
+
+    /// Skip the first `offset` matching transactions
+    pub fn offset(self, offset: usize) -> TxQuery {
+        self.inner.offset(offset).into()
+    }
+
+    /// Return at most `limit` transactions
+    pub fn limit(self, limit: usize) -> TxQuery {
+        self.inner.limit(limit).into()
+    }
+
+    /// Only the transactions changing the balance of the given asset
+    pub fn asset(self, asset: &AssetId) -> TxQuery {
+        self.inner.asset((*asset).into()).into()
+    }
+
+    /// Only the transactions of the given type, for instance "incoming" or "outgoing"
+    #[wasm_bindgen(js_name = txType)]
+    pub fn tx_type(self, tx_type: &str) -> TxQuery {
+        self.inner.tx_type(tx_type).into()
+    }
+
+    /// Only the transactions confirmed at this height or later, or unconfirmed
+    #[wasm_bindgen(js_name = minHeight)]
+    pub fn min_height(self, height: u32) -> TxQuery {
+        self.inner.min_height(height).into()
+    }
+
+    /// Only the transactions confirmed at this height or earlier
+    #[wasm_bindgen(js_name = maxHeight)]
+    pub fn max_height(self, height: u32) -> TxQuery {
+        self.inner.max_height(height).into()
+    }
+
+    /// Only the transactions sending to or spending from the given address
+    pub fn address(self, address: &Address) -> TxQuery {
+        self.inner.address(address.as_ref()).into()
+    }
+}
+
+/// Wrapper of [`lwk_wollet::TxPage`]
+#[wasm_bindgen]
+pub struct TxPage {
+    inner: lwk_wollet::TxPage,
+}
+
+impl From<lwk_wollet::TxPage> for TxPage {
+    fn from(inner: lwk_wollet::TxPage) -> Self {
+        Self { inner }
+    }
+}
+
+#[wasm_bindgen]
+impl TxPage {
+    /// The transactions in the page, most recent first
+    pub fn txs(&self) -> Vec<WalletTx> {
+        self.inner.txs.iter().cloned().map(Into::into).collect()
+    }
+
+    /// The number of transactions matching the query, regardless of offset and limit
+    pub fn total(&self) -> usize {
+        self.inner.total
+    }
+}
@@ -0,0 +1,35 @@
+use crate::{AssetId, Contract, Error, Wollet};
+use serde::Serialize;
+use serde_wasm_bindgen::Serializer;
+use wasm_bindgen::prelude::*;
+
+/// Wrapper of [`lwk_wollet::Registry`]
+#[wasm_bindgen]
+pub struct Registry {
+    inner: lwk_wollet::Registry,
+}
+
+#[wasm_bindgen]
+impl Registry {
+    /// Creates a registry client with an in-memory cache, wrapper of [`lwk_wollet::Registry`]
+    #[wasm_bindgen(constructor)]
+    pub fn new(url: &str) -> Self {
+        let inner = lwk_wollet::Registry::new(url);
+        Self { inner }
+    }
+
+    /// Fetch the verified contract of the given asset
+    #[wasm_bindgen(js_name = fetchContract)]
+    pub async fn fetch_contract(&mut self, asset_id: &AssetId) -> Result<Contract, Error> {
+        let data = self.inner.fetch_async(&(*asset_id).into()).await?;
+        Ok(data.contract.into())
+    }
+
+    /// Get the balance and metadata of every asset held by the wallet,
+    /// using the contracts fetched by this registry
+    pub fn portfolio(&self, wollet: &Wollet) -> Result<JsValue, Error> {
+        let portfolio = wollet.as_ref().portfolio(self.inner.cache().contracts())?;
+        let serializer = Serializer::new().serialize_large_number_types_as_bigints(true);
+        Ok(portfolio.serialize(&serializer)?)
+    }
+}
@@ -143,6 +143,26 @@ impl TxBuilder {
             .into())
     }
 
+    /// Blind the (re)issuance amounts, wrapper of [`lwk_wollet::TxBuilder::blind_issuance()`]
+    #[wasm_bindgen(js_name = blindIssuance)]
+    pub fn blind_issuance(self, blind_issuance: bool) -> TxBuilder {
+        self.inner.blind_issuance(blind_issuance).into()
+    }
+
+    /// Do not spend unconfirmed utxos received from others,
+    /// wrapper of [`lwk_wollet::TxBuilder::only_trusted()`]
+    #[wasm_bindgen(js_name = onlyTrusted)]
+    pub fn only_trusted(self, only_trusted: bool) -> TxBuilder {
+        self.inner.only_trusted(only_trusted).into()
+    }
+
+    /// Lock the wallet utxos spent by the created PSET for the given number of blocks,
+    /// wrapper of [`lwk_wollet::TxBuilder::lock_inputs()`]
+    #[wasm_bindgen(js_name = lockInputs)]
+    pub fn lock_inputs(self, blocks: u32) -> TxBuilder {
+        self.inner.lock_inputs(blocks).into()
+    }
+
     /// Enable the confidential transaction discount
     #[wasm_bindgen(js_name = enableCtDiscount)]
     pub fn enable_ct_discount(self) -> TxBuilder {
@@ -177,15 +197,15 @@ mod tests {
         let policy = network.policy_asset();
 
         let mut builder = TxBuilder::new(&network);
-        assert_eq!(builder.to_string(), "TxBuilder { network: Liquid, recipients: [], fee_rate: 100.0, ct_discount: false, issuance_request: None, drain_lbtc: false, drain_to: None, external_utxos: [] }");
+        assert_eq!(builder.to_string(), "TxBuilder { network: Liquid, recipients: [], fee_rate: 100.0, ct_discount: false, issuance_requests: [], blind: true, blind_issuance: false, drain_lbtc: false, drain_to: None, external_utxos: [], only_trusted: false, lock_inputs: None }");
 
         builder = builder.fee_rate(Some(200.0));
-        assert_eq!(builder.to_string(), "TxBuilder { network: Liquid, recipients: [], fee_rate: 200.0, ct_discount: false, issuance_request: None, drain_lbtc: false, drain_to: None, external_utxos: [] }");
+        assert_eq!(builder.to_string(), "TxBuilder { network: Liquid, recipients: [], fee_rate: 200.0, ct_discount: false, issuance_requests: [], blind: true, blind_issuance: false, drain_lbtc: false, drain_to: None, external_utxos: [], only_trusted: false, lock_inputs: None }");
 
         builder = builder.add_burn(1000, &policy);
-        assert_eq!(builder.to_string(), "TxBuilder { network: Liquid, recipients: [Recipient { satoshi: 1000, script_pubkey: Script(OP_RETURN), blinding_pubkey: None, asset: 6f0279e9ed041c3d710a9f57d0c02928416460c4b722ae3457a11eec381c526d }], fee_rate: 200.0, ct_discount: false, issuance_request: None, drain_lbtc: false, drain_to: None, external_utxos: [] }");
+        assert_eq!(builder.to_string(), "TxBuilder { network: Liquid, recipients: [Recipient { satoshi: 1000, script_pubkey: Script(OP_RETURN), blinding_pubkey: None, asset: 6f0279e9ed041c3d710a9f57d0c02928416460c4b722ae3457a11eec381c526d }], fee_rate: 200.0, ct_discount: false, issuance_requests: [], blind: true, blind_issuance: false, drain_lbtc: false, drain_to: None, external_utxos: [], only_trusted: false, lock_inputs: None }");
 
         builder = builder.enable_ct_discount();
-        assert_eq!(builder.to_string(), "TxBuilder { network: Liquid, recipients: [Recipient { satoshi: 1000, script_pubkey: Script(OP_RETURN), blinding_pubkey: None, asset: 6f0279e9ed041c3d710a9f57d0c02928416460c4b722ae3457a11eec381c526d }], fee_rate: 200.0, ct_discount: true, issuance_request: None, drain_lbtc: false, drain_to: None, external_utxos: [] }");
+        assert_eq!(builder.to_string(), "TxBuilder { network: Liquid, recipients: [Recipient { satoshi: 1000, script_pubkey: Script(OP_RETURN), blinding_pubkey: None, asset: 6f0279e9ed041c3d710a9f57d0c02928416460c4b722ae3457a11eec381c526d }], fee_rate: 200.0, ct_discount: true, issuance_requests: [], blind: true, blind_issuance: false, drain_lbtc: false, drain_to: None, external_utxos: [], only_trusted: false, lock_inputs: None }");
     }
 }
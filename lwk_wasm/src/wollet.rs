@@ -1,4 +1,7 @@
-use crate::{AddressResult, Error, Network, Pset, PsetDetails, Update, WalletTx, WolletDescriptor};
+use crate::{
+    AddressResult, AssetId, Error, Network, OutPoint, Pset, PsetDetails, Transaction, TxPage,
+    TxQuery, Update, WalletTx, WolletDescriptor,
+};
 use lwk_jade::derivation_path_to_vec;
 use lwk_wollet::elements::pset::PartiallySignedTransaction;
 use lwk_wollet::elements_miniscript::ForEachKey;
@@ -67,12 +70,76 @@ impl Wollet {
         Ok(self.inner.apply_update(update.into())?)
     }
 
+    /// Apply a transaction created by this wallet after broadcasting it,
+    /// so that its inputs are considered spent before the next scan
+    #[wasm_bindgen(js_name = applyTransaction)]
+    pub fn apply_transaction(&mut self, tx: &Transaction) -> Result<(), Error> {
+        Ok(self.inner.apply_transaction(tx.clone().into())?)
+    }
+
     pub fn balance(&self) -> Result<JsValue, Error> {
         let balance = self.inner.balance()?;
         let serializer = Serializer::new().serialize_large_number_types_as_bigints(true);
         Ok(balance.serialize(&serializer)?)
     }
 
+    /// Get the balance of every asset held by the wallet, with the metadata known to the wallet,
+    /// use `Registry.portfolio` to include tickers and precisions of the registry assets
+    pub fn portfolio(&self) -> Result<JsValue, Error> {
+        let portfolio = self.inner.portfolio([])?;
+        let serializer = Serializer::new().serialize_large_number_types_as_bigints(true);
+        Ok(portfolio.serialize(&serializer)?)
+    }
+
+    /// Get the wallet birthday, the height before which the wallet has no transactions
+    pub fn birthday(&self) -> Option<u32> {
+        self.inner.birthday()
+    }
+
+    /// Set the wallet birthday, so that the following scans skip the previous history
+    #[wasm_bindgen(js_name = setBirthday)]
+    pub fn set_birthday(&mut self, birthday: Option<u32>) -> Result<(), Error> {
+        Ok(self.inner.set_birthday(birthday)?)
+    }
+
+    /// Lock a wallet utxo, so that it's not spent by the transactions created by the wallet
+    ///
+    /// If `expiry` is set, the lock is released once the wallet tip reaches that height
+    #[wasm_bindgen(js_name = lockUtxo)]
+    pub fn lock_utxo(&self, outpoint: &OutPoint, expiry: Option<u32>) -> Result<(), Error> {
+        Ok(self.inner.lock_utxo(outpoint.into(), expiry)?)
+    }
+
+    /// Unlock a wallet utxo, returns false if it wasn't locked
+    #[wasm_bindgen(js_name = unlockUtxo)]
+    pub fn unlock_utxo(&self, outpoint: &OutPoint) -> Result<bool, Error> {
+        Ok(self.inner.unlock_utxo(&outpoint.into())?)
+    }
+
+    /// Get the locked wallet utxos
+    #[wasm_bindgen(js_name = lockedUtxos)]
+    pub fn locked_utxos(&self) -> Result<JsValue, Error> {
+        let locked = self.inner.locked_utxos()?;
+        Ok(locked.serialize(&Serializer::new())?)
+    }
+
+    /// Get the wallet transactions matching the query, most recent first
+    #[wasm_bindgen(js_name = queryTransactions)]
+    pub fn query_transactions(&self, query: &TxQuery) -> Result<TxPage, Error> {
+        Ok(self.inner.query_transactions(query.as_ref())?.into())
+    }
+
+    /// Get the wallet transactions changing the balance of the given asset
+    #[wasm_bindgen(js_name = assetTransactions)]
+    pub fn asset_transactions(&self, asset: &AssetId) -> Result<Vec<WalletTx>, Error> {
+        Ok(self
+            .inner
+            .asset_transactions(&(*asset).into())?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
     pub fn transactions(&self) -> Result<Vec<WalletTx>, Error> {
         Ok(self
             .inner
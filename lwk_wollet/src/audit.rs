@@ -0,0 +1,476 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::bitcoin::bip32::DerivationPath;
+use crate::bitcoin::sign_message::{signed_msg_hash, MessageSignature};
+use crate::elements::confidential::{Asset, Value};
+use crate::elements::{AssetId, OutPoint, Transaction, TxOutSecrets, Txid};
+use crate::error::Error;
+use crate::hashes::{sha256, Hash};
+use crate::secp256k1::{PublicKey, Secp256k1};
+use crate::store::Height;
+use crate::wollet::Wollet;
+use lwk_common::{commitments, MessageSigner};
+
+/// The selection of wallet data disclosed by [`Wollet::audit_report()`]
+///
+/// By default every confirmed transaction is disclosed and the balances are at the wallet tip.
+#[derive(Debug, Clone, Default)]
+pub struct AuditQuery {
+    start_height: Option<Height>,
+    end_height: Option<Height>,
+    balance_height: Option<Height>,
+    txids: HashSet<Txid>,
+    outpoints: HashSet<OutPoint>,
+}
+
+impl AuditQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only the transactions confirmed at this height or later
+    pub fn start_height(mut self, height: Height) -> Self {
+        self.start_height = Some(height);
+        self
+    }
+
+    /// Only the transactions confirmed at this height or earlier
+    pub fn end_height(mut self, height: Height) -> Self {
+        self.end_height = Some(height);
+        self
+    }
+
+    /// Report the balances at this height instead of the wallet tip
+    pub fn balance_height(mut self, height: Height) -> Self {
+        self.balance_height = Some(height);
+        self
+    }
+
+    /// Only the given transaction, can be called multiple times to select several transactions
+    pub fn txid(mut self, txid: Txid) -> Self {
+        self.txids.insert(txid);
+        self
+    }
+
+    /// Disclose the given wallet output, regardless of the selected transactions
+    pub fn outpoint(mut self, outpoint: OutPoint) -> Self {
+        self.outpoints.insert(outpoint);
+        self
+    }
+
+    fn matches(&self, txid: &Txid, height: Option<Height>) -> bool {
+        let Some(height) = height else {
+            return false;
+        };
+        self.start_height.map_or(true, |h| height >= h)
+            && self.end_height.map_or(true, |h| height <= h)
+            && (self.txids.is_empty() || self.txids.contains(txid))
+    }
+}
+
+/// A wallet output disclosed in an [`AuditReport`], with the secrets to unblind it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditOutput {
+    pub outpoint: OutPoint,
+
+    /// The height of the transaction creating the output
+    pub height: Height,
+
+    pub unblinded: TxOutSecrets,
+
+    /// Whether the output is unspent at the balance height, and so counted in the balances
+    pub unspent: bool,
+}
+
+/// The signature of an [`AuditReport`], see [`AuditReport::sign()`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditSignature {
+    /// The key which signed the report, auditors must check it belongs to the wallet
+    pub public_key: PublicKey,
+
+    /// The base64 encoded message signature of [`AuditReport::message()`]
+    pub signature: String,
+}
+
+/// A report disclosing selected wallet transactions and outputs to an auditor
+///
+/// The report contains the raw transactions and the secrets of the disclosed outputs, but not
+/// the descriptor blinding key, so other wallet outputs stay confidential.
+/// [`AuditReport::verify()`] checks the report offline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditReport {
+    /// The network of the wallet
+    pub network: String,
+
+    /// The lowest height of the disclosed transactions, if restricted
+    pub start_height: Option<Height>,
+
+    /// The highest height of the disclosed transactions, if restricted
+    pub end_height: Option<Height>,
+
+    /// The ids of the disclosed transactions, sorted
+    pub txids: Vec<Txid>,
+
+    /// The wallet outputs created or spent by the disclosed transactions, the explicitly
+    /// selected ones and the ones unspent at the balance height, sorted by outpoint
+    pub outputs: Vec<AuditOutput>,
+
+    /// The height of the balances
+    pub balance_height: Height,
+
+    /// The sum of the outputs unspent at the balance height, per asset
+    pub balances: BTreeMap<AssetId, u64>,
+
+    /// The raw transactions referenced by `txids` and `outputs`, sorted by txid
+    pub txs: Vec<Transaction>,
+
+    /// The confirmation heights of the transactions in `txs`
+    #[serde(default)]
+    pub heights: BTreeMap<Txid, Height>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<AuditSignature>,
+}
+
+impl Wollet {
+    /// Create a report disclosing the wallet data selected by `query` to an auditor
+    pub fn audit_report(&self, query: &AuditQuery) -> Result<AuditReport, Error> {
+        let cache = &self.store.cache;
+        let txos = self.txos()?;
+        let balance_height = query.balance_height.unwrap_or(cache.tip.0);
+
+        let mut txids = BTreeSet::new();
+        let mut disclosed: BTreeSet<OutPoint> = query.outpoints.clone().into_iter().collect();
+        for (txid, height) in cache.heights.iter() {
+            if !query.matches(txid, *height) {
+                continue;
+            }
+            let tx = cache
+                .all_txs
+                .get(txid)
+                .ok_or_else(|| Error::Generic(format!("audit_report no tx {txid}")))?;
+            let spent = tx.input.iter().map(|i| i.previous_output);
+            let created = (0..tx.output.len()).map(|vout| OutPoint::new(*txid, vout as u32));
+            let mine: Vec<_> = spent
+                .chain(created)
+                .filter(|o| txos.contains_key(o))
+                .collect();
+            if mine.is_empty() {
+                continue;
+            }
+            txids.insert(*txid);
+            disclosed.extend(mine);
+        }
+
+        // Outputs spent by transactions confirmed at the balance height or earlier
+        let spent_at_balance: HashSet<OutPoint> = cache
+            .heights
+            .iter()
+            .filter(|(_, h)| h.map_or(false, |h| h <= balance_height))
+            .filter_map(|(txid, _)| cache.all_txs.get(txid))
+            .flat_map(|tx| tx.input.iter().map(|i| i.previous_output))
+            .collect();
+        let is_unspent = |height: Height, outpoint: &OutPoint| {
+            height <= balance_height && !spent_at_balance.contains(outpoint)
+        };
+
+        let mut balances = BTreeMap::new();
+        let mut outputs = vec![];
+        for txo in txos.values() {
+            let Some(height) = txo.height else {
+                continue;
+            };
+            let unspent = is_unspent(height, &txo.outpoint);
+            if unspent {
+                *balances.entry(txo.unblinded.asset).or_default() += txo.unblinded.value;
+            } else if !disclosed.contains(&txo.outpoint) {
+                continue;
+            }
+            outputs.push(AuditOutput {
+                outpoint: txo.outpoint,
+                height,
+                unblinded: txo.unblinded,
+                unspent,
+            });
+        }
+        outputs.sort_by_key(|o| o.outpoint);
+
+        let needed: BTreeSet<Txid> = txids
+            .iter()
+            .cloned()
+            .chain(outputs.iter().map(|o| o.outpoint.txid))
+            .collect();
+        let txs = needed
+            .iter()
+            .map(|txid| {
+                cache
+                    .all_txs
+                    .get(txid)
+                    .cloned()
+                    .ok_or_else(|| Error::Generic(format!("audit_report no tx {txid}")))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let heights = needed
+            .iter()
+            .filter_map(|txid| Some((*txid, cache.heights.get(txid).cloned().flatten()?)))
+            .collect();
+
+        Ok(AuditReport {
+            network: self.network().as_str().to_string(),
+            start_height: query.start_height,
+            end_height: query.end_height,
+            txids: txids.into_iter().collect(),
+            outputs,
+            balance_height,
+            balances,
+            txs,
+            heights,
+            signature: None,
+        })
+    }
+}
+
+impl AuditReport {
+    /// The message signed by [`AuditReport::sign()`], the hex sha256 of the unsigned report
+    pub fn message(&self) -> Result<String, Error> {
+        let unsigned = AuditReport {
+            signature: None,
+            ..self.clone()
+        };
+        let bytes = serde_json::to_vec(&unsigned)?;
+        Ok(sha256::Hash::hash(&bytes).to_string())
+    }
+
+    /// Sign the report with the key derived at `path` from `signer`
+    pub fn sign<S: MessageSigner>(
+        &mut self,
+        signer: &S,
+        path: &DerivationPath,
+    ) -> Result<(), Error> {
+        let message = self.message()?;
+        let public_key = signer
+            .derive_xpub(path)
+            .map_err(|e| Error::Generic(format!("{e:?}")))?
+            .public_key;
+        let signature = signer
+            .sign_message(&message, path)
+            .map_err(|e| Error::Generic(format!("{e:?}")))?;
+        self.signature = Some(AuditSignature {
+            public_key,
+            signature: signature.to_base64(),
+        });
+        Ok(())
+    }
+
+    /// Verify the report without access to the wallet
+    ///
+    /// Checks the signature, if any, that every disclosed output matches the commitments of the
+    /// raw transactions and is disclosed once, that the unspent outputs are not spent by a
+    /// disclosed transaction confirmed at the balance height or earlier and that the balances are
+    /// the sum of the unspent outputs.
+    pub fn verify(&self) -> Result<(), Error> {
+        let secp = Secp256k1::new();
+        if let Some(signature) = self.signature.as_ref() {
+            let message = self.message()?;
+            let msg_hash = signed_msg_hash(&message);
+            let recovered = MessageSignature::from_base64(&signature.signature)
+                .ok()
+                .and_then(|s| s.recover_pubkey(&secp, msg_hash).ok());
+            if recovered.map(|p| p.inner) != Some(signature.public_key) {
+                return Err(Error::Generic("Invalid report signature".into()));
+            }
+        }
+
+        let txs: BTreeMap<Txid, &Transaction> = self.txs.iter().map(|tx| (tx.txid(), tx)).collect();
+        if let Some(txid) = self.txids.iter().find(|t| !txs.contains_key(*t)) {
+            return Err(Error::Generic(format!("Missing transaction {txid}")));
+        }
+
+        // Outputs spent by disclosed transactions, with the height of the spending transaction
+        let spent: HashMap<OutPoint, Option<Height>> = txs
+            .iter()
+            .flat_map(|(txid, tx)| {
+                let height = self.heights.get(txid).cloned();
+                tx.input.iter().map(move |i| (i.previous_output, height))
+            })
+            .collect();
+
+        let mut seen = HashSet::new();
+        let mut balances: BTreeMap<AssetId, u64> = BTreeMap::new();
+        for output in self.outputs.iter() {
+            let outpoint = output.outpoint;
+            if !seen.insert(outpoint) {
+                return Err(Error::Generic(format!("Duplicate output {outpoint}")));
+            }
+            let txout = txs
+                .get(&outpoint.txid)
+                .and_then(|tx| tx.output.get(outpoint.vout as usize))
+                .ok_or_else(|| Error::Generic(format!("Missing output {outpoint}")))?;
+            let secrets = &output.unblinded;
+            let matches = match (txout.asset, txout.value) {
+                (Asset::Explicit(asset), Value::Explicit(value)) => {
+                    asset == secrets.asset && value == secrets.value
+                }
+                (Asset::Confidential(asset_comm), Value::Confidential(amount_comm)) => {
+                    (asset_comm, amount_comm) == commitments(&secp, secrets)
+                }
+                _ => false,
+            };
+            if !matches {
+                return Err(Error::Generic(format!(
+                    "Secrets don't match the commitments of output {outpoint}"
+                )));
+            }
+            if output.unspent {
+                if output.height > self.balance_height {
+                    return Err(Error::Generic(format!(
+                        "Output {outpoint} is after the balance height"
+                    )));
+                }
+                if let Some(height) = spent.get(&outpoint) {
+                    if height.map_or(true, |h| h <= self.balance_height) {
+                        return Err(Error::Generic(format!(
+                            "Output {outpoint} is spent at the balance height"
+                        )));
+                    }
+                }
+                let balance = balances.entry(secrets.asset).or_default();
+                *balance = balance
+                    .checked_add(secrets.value)
+                    .ok_or_else(|| Error::Generic("Balance overflow".into()))?;
+            }
+        }
+        if balances != self.balances {
+            return Err(Error::Generic(
+                "Balances don't match the unspent outputs".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AuditOutput, AuditQuery, AuditReport};
+    use crate::elements::confidential::{Asset, Nonce, Value};
+    use crate::elements::hashes::Hash;
+    use crate::elements::{
+        AssetId, LockTime, OutPoint, Script, Transaction, TxIn, TxOut, TxOutWitness, Txid,
+    };
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_audit_query() {
+        let txid = Txid::from_byte_array([1; 32]);
+        let other = Txid::from_byte_array([2; 32]);
+        let query = AuditQuery::new();
+        assert!(query.matches(&txid, Some(1)));
+        assert!(!query.matches(&txid, None));
+
+        let query = AuditQuery::new().start_height(10).end_height(20).txid(txid);
+        assert!(query.matches(&txid, Some(10)));
+        assert!(!query.matches(&txid, Some(21)));
+        assert!(!query.matches(&other, Some(15)));
+    }
+
+    #[test]
+    fn test_audit_report_verify() {
+        let mut report = AuditReport {
+            network: "liquid-testnet".into(),
+            start_height: None,
+            end_height: None,
+            txids: vec![],
+            outputs: vec![],
+            balance_height: 10,
+            balances: BTreeMap::new(),
+            txs: vec![],
+            heights: BTreeMap::new(),
+            signature: None,
+        };
+        report.verify().unwrap();
+
+        report.txids.push(Txid::from_byte_array([1; 32]));
+        assert!(report.verify().is_err());
+        report.txids.clear();
+
+        let mut balances = BTreeMap::new();
+        balances.insert(AssetId::default(), 1);
+        report.balances = balances;
+        let err = report.verify().unwrap_err();
+        assert_eq!(err.to_string(), "Balances don't match the unspent outputs");
+        report.balances.clear();
+
+        let unblinded = crate::elements::TxOutSecrets::new(
+            AssetId::default(),
+            crate::elements::confidential::AssetBlindingFactor::zero(),
+            1,
+            crate::elements::confidential::ValueBlindingFactor::zero(),
+        );
+        report.outputs.push(AuditOutput {
+            outpoint: OutPoint::default(),
+            height: 1,
+            unblinded,
+            unspent: true,
+        });
+        assert!(report.verify().is_err());
+        report.outputs.clear();
+
+        let tx = Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![],
+            output: vec![TxOut {
+                asset: Asset::Explicit(AssetId::default()),
+                value: Value::Explicit(1),
+                nonce: Nonce::Null,
+                script_pubkey: Script::new(),
+                witness: TxOutWitness::default(),
+            }],
+        };
+        let output = AuditOutput {
+            outpoint: OutPoint::new(tx.txid(), 0),
+            height: 1,
+            unblinded,
+            unspent: true,
+        };
+        report.txs.push(tx);
+        report.heights.insert(report.txs[0].txid(), 1);
+        report.outputs.push(output.clone());
+        report.balances.insert(AssetId::default(), 1);
+        report.verify().unwrap();
+
+        report.outputs.push(output.clone());
+        report.balances.insert(AssetId::default(), 2);
+        let err = report.verify().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Duplicate output {}", output.outpoint)
+        );
+        report.outputs.pop();
+        report.balances.insert(AssetId::default(), 1);
+
+        let spending = Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: output.outpoint,
+                ..Default::default()
+            }],
+            output: vec![],
+        };
+        report.heights.insert(spending.txid(), 11);
+        report.txs.push(spending.clone());
+        report.verify().unwrap();
+
+        report.heights.insert(spending.txid(), 10);
+        let err = report.verify().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Output {} is spent at the balance height", output.outpoint)
+        );
+
+        report.heights.remove(&spending.txid());
+        assert!(report.verify().is_err());
+    }
+}
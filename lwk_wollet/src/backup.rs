@@ -0,0 +1,210 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::elements::AssetId;
+use crate::error::Error;
+use crate::store::{Height, BATCH_SIZE};
+use crate::wollet::Wollet;
+use crate::{ElementsNetwork, WolletDescriptor};
+
+/// The latest version of the backup file format
+pub const BACKUP_VERSION: u32 = 1;
+
+/// A wallet backup, containing what is needed to restore the wallet elsewhere
+///
+/// The backup is serialized as a JSON file, which must be kept secret since the descriptor
+/// allows to see all the wallet transactions.
+#[derive(Debug, Clone)]
+pub struct WolletBackup {
+    version: u32,
+    descriptor: WolletDescriptor,
+    network: ElementsNetwork,
+    gap_limit: u32,
+    labels: BTreeMap<String, String>,
+    birthday_height: Option<Height>,
+}
+
+/// The JSON representation of [`WolletBackup`]
+#[derive(Serialize, Deserialize)]
+struct BackupFile {
+    version: u32,
+    descriptor: String,
+    network: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    policy_asset: Option<AssetId>,
+    gap_limit: u32,
+    #[serde(default)]
+    labels: BTreeMap<String, String>,
+    #[serde(default)]
+    birthday_height: Option<Height>,
+}
+
+impl WolletBackup {
+    /// Create the backup of a wallet, without labels
+    ///
+    /// The birthday height is the one of the wallet if set, otherwise the height of the first
+    /// confirmed wallet transaction, if any.
+    pub fn new(wollet: &Wollet) -> Self {
+        let first_height = wollet.store.cache.heights.values().flatten().min().cloned();
+        let birthday_height = wollet.birthday().or(first_height);
+        Self {
+            version: BACKUP_VERSION,
+            descriptor: wollet.wollet_descriptor(),
+            network: wollet.network(),
+            gap_limit: BATCH_SIZE,
+            labels: BTreeMap::new(),
+            birthday_height,
+        }
+    }
+
+    /// Add labels to the backup, keyed by txid or address
+    pub fn with_labels(mut self, labels: BTreeMap<String, String>) -> Self {
+        self.labels.extend(labels);
+        self
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn descriptor(&self) -> &WolletDescriptor {
+        &self.descriptor
+    }
+
+    pub fn network(&self) -> ElementsNetwork {
+        self.network
+    }
+
+    /// The number of consecutive unused addresses after which the scan stops
+    ///
+    /// Scans use a fixed gap limit, thus backups with a greater gap limit are rejected.
+    pub fn gap_limit(&self) -> u32 {
+        self.gap_limit
+    }
+
+    pub fn labels(&self) -> &BTreeMap<String, String> {
+        &self.labels
+    }
+
+    /// The height before which the wallet has no transactions
+    pub fn birthday_height(&self) -> Option<Height> {
+        self.birthday_height
+    }
+}
+
+impl Display for WolletBackup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let file = BackupFile {
+            version: self.version,
+            descriptor: self.descriptor.to_string(),
+            network: self.network.as_str().to_string(),
+            policy_asset: matches!(self.network, ElementsNetwork::ElementsRegtest { .. })
+                .then(|| self.network.policy_asset()),
+            gap_limit: self.gap_limit,
+            labels: self.labels.clone(),
+            birthday_height: self.birthday_height,
+        };
+        let s = serde_json::to_string_pretty(&file).map_err(|_| std::fmt::Error)?;
+        write!(f, "{s}")
+    }
+}
+
+impl FromStr for WolletBackup {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let file: BackupFile = serde_json::from_str(s)?;
+        if file.version == 0 || file.version > BACKUP_VERSION {
+            return Err(Error::Generic(format!(
+                "Unsupported backup version {}",
+                file.version
+            )));
+        }
+        let network = match (file.network.as_str(), file.policy_asset) {
+            ("liquid", _) => ElementsNetwork::Liquid,
+            ("liquid-testnet", _) => ElementsNetwork::LiquidTestnet,
+            ("liquid-regtest", Some(policy_asset)) => {
+                ElementsNetwork::ElementsRegtest { policy_asset }
+            }
+            ("liquid-regtest", None) => ElementsNetwork::default_regtest(),
+            (n, _) => return Err(Error::Generic(format!("Unknown network {n}"))),
+        };
+        if file.gap_limit > BATCH_SIZE {
+            // Scans stop after this many unused addresses, funds beyond them would be missed
+            return Err(Error::Generic(format!(
+                "Backup gap limit {} is greater than the supported {BATCH_SIZE}",
+                file.gap_limit
+            )));
+        }
+        let descriptor: WolletDescriptor = file.descriptor.parse()?;
+        if descriptor.is_mainnet() != matches!(network, ElementsNetwork::Liquid) {
+            return Err(Error::Generic(
+                "Backup descriptor is for the wrong network".into(),
+            ));
+        }
+        Ok(Self {
+            version: file.version,
+            descriptor,
+            network,
+            gap_limit: file.gap_limit,
+            labels: file.labels,
+            birthday_height: file.birthday_height,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{WolletBackup, BACKUP_VERSION};
+    use crate::{ElementsNetwork, NoPersist, Wollet};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_wollet_backup() {
+        let desc = "ct(slip77(9c8e4f05c7711a98c838be228bcb84924d4570ca53f35fa1c793e58841d47023),elwpkh([73c5da0a/84'/1'/0']tpubDC8msFGeGuwnKG9Upg7DM2b4DaRqg3CUZa5g8v2SRQ6K4NSkxUgd7HsL2XVWbVm39yBA4LAxysQAm397zwQSQoQgewGiYZqrA9DsP4zbQ1M/<0;1>/*))";
+        let network = ElementsNetwork::default_regtest();
+        let wollet = Wollet::new(network, NoPersist::new(), desc.parse().unwrap()).unwrap();
+
+        let mut labels = BTreeMap::new();
+        labels.insert("txid".to_string(), "label".to_string());
+        let backup = WolletBackup::new(&wollet).with_labels(labels.clone());
+        assert_eq!(backup.version(), BACKUP_VERSION);
+        assert_eq!(backup.birthday_height(), None);
+        let mut born = Wollet::new(network, NoPersist::new(), desc.parse().unwrap()).unwrap();
+        born.set_birthday(Some(100)).unwrap();
+        assert_eq!(WolletBackup::new(&born).birthday_height(), Some(100));
+
+        let restored: WolletBackup = backup.to_string().parse().unwrap();
+        assert_eq!(
+            restored.descriptor().to_string(),
+            backup.descriptor().to_string()
+        );
+        assert_eq!(restored.network(), network);
+        assert_eq!(restored.gap_limit(), 20);
+        assert_eq!(restored.labels(), &labels);
+
+        let newer = backup
+            .to_string()
+            .replace("\"version\": 1", "\"version\": 2");
+        let err = newer.parse::<WolletBackup>().unwrap_err();
+        assert_eq!(err.to_string(), "Unsupported backup version 2");
+
+        let gap = backup
+            .to_string()
+            .replace("\"gap_limit\": 20", "\"gap_limit\": 21");
+        let err = gap.parse::<WolletBackup>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Backup gap limit 21 is greater than the supported 20"
+        );
+        let gap = backup
+            .to_string()
+            .replace("\"gap_limit\": 20", "\"gap_limit\": 10");
+        assert_eq!(gap.parse::<WolletBackup>().unwrap().gap_limit(), 10);
+
+        let mainnet = backup.to_string().replace("liquid-regtest", "liquid");
+        assert!(mainnet.parse::<WolletBackup>().is_err());
+    }
+}
@@ -1,10 +1,13 @@
 use super::try_unblind;
-use crate::{Chain, ElementsNetwork, Error, WalletTxOut, WolletDescriptor};
+use crate::store::Height;
+use crate::{Chain, ElementsNetwork, Error, WalletTxOut, Wollet, WolletDescriptor};
 
 use std::collections::HashMap;
 
 use bitcoincore_rpc::{Auth, Client, RpcApi};
-use elements::{encode::deserialize, hex::FromHex, OutPoint, Script, Transaction, TxOut, Txid};
+use elements::{
+    encode::deserialize, hex::FromHex, Block, OutPoint, Script, Transaction, TxOut, Txid,
+};
 
 /// A client to issue RPCs to a Elements node
 pub struct ElementsRpcClient {
@@ -48,10 +51,28 @@ impl ElementsRpcClient {
             .ok_or_else(|| Error::ElementsRpcUnexpectedReturn("getblockcount".into()))
     }
 
-    fn get_txout(&self, outpoint: &OutPoint, height: u32) -> Result<TxOut, Error> {
-        let blockhash = self
+    fn get_blockhash(&self, height: u32) -> Result<serde_json::Value, Error> {
+        Ok(self
+            .inner
+            .call::<serde_json::Value>("getblockhash", &[height.into()])?)
+    }
+
+    fn get_block(&self, height: u32) -> Result<Block, Error> {
+        let blockhash = self.get_blockhash(height)?;
+        let method = "getblock";
+        let r = self
             .inner
-            .call::<serde_json::Value>("getblockhash", &[height.into()])?;
+            .call::<serde_json::Value>(method, &[blockhash, 0.into()])?;
+        let hex = r
+            .as_str()
+            .ok_or_else(|| Error::ElementsRpcUnexpectedReturn(method.into()))?;
+        let bytes = Vec::<u8>::from_hex(hex)
+            .map_err(|_| Error::ElementsRpcUnexpectedReturn(method.into()))?;
+        deserialize(&bytes[..]).map_err(|_| Error::ElementsRpcUnexpectedReturn(method.into()))
+    }
+
+    fn get_txout(&self, outpoint: &OutPoint, height: u32) -> Result<TxOut, Error> {
+        let blockhash = self.get_blockhash(height)?;
 
         let method = "getrawtransaction";
         let txid = outpoint.txid.to_string();
@@ -92,16 +113,7 @@ impl ElementsRpcClient {
             .inner
             .call("scantxoutset", &["start".into(), scanobjects])?;
         let mut utxos = vec![];
-
-        // TODO: make this more efficient
-        let params = self.network.address_params();
-        let mut spk_map = HashMap::new();
-        for i in 0..range {
-            let spk_ext = desc.address(i, params)?.script_pubkey();
-            let spk_int = desc.change(i, params)?.script_pubkey();
-            spk_map.insert(spk_ext, (Chain::External, i));
-            spk_map.insert(spk_int, (Chain::Internal, i));
-        }
+        let spk_map = self.script_pubkeys(desc, range)?;
 
         for u in r.unspents {
             let outpoint = OutPoint::new(u.txid, u.vout);
@@ -115,12 +127,87 @@ impl ElementsRpcClient {
                 script_pubkey: u.script_pubkey,
                 height: Some(u.height),
                 unblinded,
-                wildcard_index,
-                ext_int,
+                wildcard_index: Some(wildcard_index),
+                ext_int: Some(ext_int),
             })
         }
         Ok(utxos)
     }
+
+    /// Get the confirmed utxos for a descriptor, scanning the blocks from `start_height` to the tip
+    ///
+    /// Outputs received before `start_height` are not found, this is considerably cheaper than
+    /// [`ElementsRpcClient::confirmed_utxos()`] for recent wallets.
+    pub fn scan_blocks(
+        &self,
+        desc: &WolletDescriptor,
+        range: u32,
+        start_height: Height,
+    ) -> Result<Vec<WalletTxOut>, Error> {
+        let spk_map = self.script_pubkeys(desc, range)?;
+        let tip = self.height()? as Height;
+        let mut utxos: HashMap<OutPoint, WalletTxOut> = HashMap::new();
+        for height in start_height..=tip {
+            let block = self.get_block(height)?;
+            for tx in block.txdata {
+                for input in tx.input.iter() {
+                    utxos.remove(&input.previous_output);
+                }
+                let txid = tx.txid();
+                for (vout, txout) in tx.output.into_iter().enumerate() {
+                    let Some((ext_int, wildcard_index)) = spk_map.get(&txout.script_pubkey) else {
+                        continue;
+                    };
+                    let outpoint = OutPoint::new(txid, vout as u32);
+                    let script_pubkey = txout.script_pubkey.clone();
+                    let unblinded = try_unblind(txout, desc)?;
+                    utxos.insert(
+                        outpoint,
+                        WalletTxOut {
+                            outpoint,
+                            script_pubkey,
+                            height: Some(height),
+                            unblinded,
+                            wildcard_index: Some(*wildcard_index),
+                            ext_int: Some(*ext_int),
+                        },
+                    );
+                }
+            }
+        }
+        let mut utxos: Vec<_> = utxos.into_values().collect();
+        utxos.sort_by_key(|u| (u.height, u.outpoint));
+        Ok(utxos)
+    }
+
+    /// Get the confirmed utxos of a wallet
+    ///
+    /// If the wallet has a birthday only the blocks since then are scanned with
+    /// [`ElementsRpcClient::scan_blocks()`], otherwise the whole utxo set is scanned.
+    pub fn wollet_utxos(&self, wollet: &Wollet, range: u32) -> Result<Vec<WalletTxOut>, Error> {
+        let desc = wollet.wollet_descriptor();
+        match wollet.birthday() {
+            Some(birthday) => self.scan_blocks(&desc, range, birthday),
+            None => self.confirmed_utxos(&desc, range),
+        }
+    }
+
+    // TODO: make this more efficient
+    fn script_pubkeys(
+        &self,
+        desc: &WolletDescriptor,
+        range: u32,
+    ) -> Result<HashMap<Script, (Chain, u32)>, Error> {
+        let params = self.network.address_params();
+        let mut spk_map = HashMap::new();
+        for i in 0..range {
+            let spk_ext = desc.address(i, params)?.script_pubkey();
+            let spk_int = desc.change(i, params)?.script_pubkey();
+            spk_map.insert(spk_ext, (Chain::External, i));
+            spk_map.insert(spk_int, (Chain::Internal, i));
+        }
+        Ok(spk_map)
+    }
 }
 
 #[derive(serde::Serialize)]
@@ -1,7 +1,7 @@
 //! NOTE This module is temporary, as soon we make the other clients async this will be merged in
 //! the standard esplora client of which contain a lot of duplicated code.
 
-use super::{try_unblind, History};
+use super::{try_unblind, txid_height_changes, History};
 use crate::{
     clients::waterfalls::{encrypt, WaterfallsResult},
     clients::Data,
@@ -162,13 +162,8 @@ impl EsploraWasmClient {
         let descriptor = wollet.wollet_descriptor();
         let store = &wollet.store;
 
-        let Data {
-            txid_height,
-            scripts,
-            last_unused,
-            height_blockhash,
-            height_timestamp,
-        } = if self.waterfalls {
+        let waterfalls = self.waterfalls && descriptor.descriptor().has_wildcard();
+        let mut data = if waterfalls {
             match self.get_history_waterfalls(&descriptor, store).await {
                 Ok(d) => d,
                 Err(Error::UsingWaterfallsWithElip151) => {
@@ -179,12 +174,24 @@ impl EsploraWasmClient {
         } else {
             self.get_history(&descriptor, store).await?
         };
+        let watched: Vec<_> = wollet.watched.keys().collect();
+        if !watched.is_empty() {
+            let result = self.get_scripts_history(&watched).await?;
+            data.insert_history(result.into_iter().flatten());
+        }
+        let Data {
+            txid_height,
+            scripts,
+            last_unused,
+            height_blockhash,
+            height_timestamp,
+        } = data;
 
         let tip = self.tip().await?;
 
         let history_txs_id: HashSet<Txid> = txid_height.keys().cloned().collect();
         let new_txs = self
-            .download_txs(&history_txs_id, &scripts, store, &descriptor)
+            .download_txs(&history_txs_id, &scripts, wollet, &descriptor)
             .await?;
         let history_txs_heights_plus_tip: HashSet<Height> = txid_height
             .values()
@@ -212,30 +219,24 @@ impl EsploraWasmClient {
         let last_unused_changed = store_last_unused_external != last_unused.external
             || store_last_unused_internal != last_unused.internal;
 
+        let (txid_height_new, txid_height_delete) = txid_height_changes(
+            &txid_height,
+            &store.cache.heights,
+            &wollet.applied,
+            tip.height,
+        );
+
         let changed = !new_txs.txs.is_empty()
             || last_unused_changed
             || !scripts.is_empty()
             || !timestamps.is_empty()
+            || !txid_height_new.is_empty()
+            || !txid_height_delete.is_empty()
             || store.cache.tip != (tip.height, tip.block_hash());
 
         if changed {
             log::debug!("something changed: !new_txs.txs.is_empty():{} last_unused_changed:{} !scripts.is_empty():{} !timestamps.is_empty():{}", !new_txs.txs.is_empty(), last_unused_changed, !scripts.is_empty(), !timestamps.is_empty() );
 
-            let txid_height_new: Vec<_> = txid_height
-                .iter()
-                .filter(|(k, v)| match store.cache.heights.get(*k) {
-                    Some(e) => e != *v,
-                    None => true,
-                })
-                .map(|(k, v)| (*k, *v))
-                .collect();
-            let txid_height_delete: Vec<_> = store
-                .cache
-                .heights
-                .keys()
-                .filter(|k| txid_height.get(*k).is_none())
-                .cloned()
-                .collect();
             let wollet_status = wollet.status();
 
             let update = Update {
@@ -264,6 +265,10 @@ impl EsploraWasmClient {
             let chain: Chain = (&descriptor).try_into().unwrap_or(Chain::External);
             loop {
                 let batch = store.get_script_batch(batch_count, &descriptor)?;
+                if batch.value.is_empty() {
+                    // Non-ranged descriptors have a single script
+                    break;
+                }
 
                 let s: Vec<_> = batch.value.iter().map(|e| &e.0).collect();
                 let result: Vec<Vec<History>> = self.get_scripts_history(&s).await?;
@@ -293,21 +298,7 @@ impl EsploraWasmClient {
                     break;
                 }
 
-                for el in flattened {
-                    // el.height = -1 means unconfirmed with unconfirmed parents
-                    // el.height =  0 means unconfirmed with confirmed parents
-                    // but we threat those tx the same
-                    let height = el.height.max(0);
-                    let txid = el.txid;
-                    if height == 0 {
-                        data.txid_height.insert(txid, None);
-                    } else {
-                        data.txid_height.insert(txid, Some(height as u32));
-                        if let Some(block_hash) = el.block_hash {
-                            data.height_blockhash.insert(height as u32, block_hash);
-                        }
-                    }
-                }
+                data.insert_history(flattened);
 
                 batch_count += 1;
             }
@@ -421,13 +412,21 @@ impl EsploraWasmClient {
         &self,
         history_txs_id: &HashSet<Txid>,
         scripts: &HashMap<Script, (Chain, ChildNumber)>,
-        store: &Store,
+        wollet: &Wollet,
         descriptor: &WolletDescriptor,
     ) -> Result<DownloadTxResult, Error> {
+        let store = &wollet.store;
         let mut txs = vec![];
         let mut unblinds = vec![];
 
-        let mut txs_in_db = store.cache.all_txs.keys().cloned().collect();
+        // Locally applied transactions are downloaded again, so that they are persisted
+        let mut txs_in_db: HashSet<Txid> = store
+            .cache
+            .all_txs
+            .keys()
+            .filter(|txid| !wollet.applied.contains_key(*txid))
+            .cloned()
+            .collect();
         let txs_to_download: Vec<Txid> = history_txs_id.difference(&txs_in_db).cloned().collect();
 
         for txid in txs_to_download {
@@ -437,13 +436,21 @@ impl EsploraWasmClient {
 
             for (i, output) in tx.output.iter().enumerate() {
                 // could be the searched script it's not yet in the store, because created in the current run, thus it's searched also in the `scripts`
-                if store.cache.paths.contains_key(&output.script_pubkey)
+                let unblinded = if store.cache.paths.contains_key(&output.script_pubkey)
                     || scripts.contains_key(&output.script_pubkey)
                 {
+                    Some(try_unblind(output.clone(), descriptor))
+                } else {
+                    wollet
+                        .watched
+                        .get(&output.script_pubkey)
+                        .map(|w| w.unblind(output))
+                };
+                if let Some(unblinded) = unblinded {
                     let vout = i as u32;
                     let outpoint = OutPoint { txid, vout };
 
-                    match try_unblind(output.clone(), descriptor) {
+                    match unblinded {
                             Ok(unblinded) => unblinds.push((outpoint, unblinded)),
                             Err(_) => log::info!("{} cannot unblind, ignoring (could be sender messed up with the blinding process)", outpoint),
                         }
@@ -2,7 +2,7 @@ use crate::{
     store::{Height, Timestamp, BATCH_SIZE},
     update::{DownloadTxResult, Update},
     wollet::WolletState,
-    Chain, Error, WolletDescriptor, EC,
+    Chain, Error, MultiWollet, WolletDescriptor, EC,
 };
 use elements::{
     bitcoin::bip32::ChildNumber,
@@ -68,6 +68,34 @@ pub struct Data {
     pub height_timestamp: HashMap<Height, Timestamp>,
 }
 
+impl Data {
+    /// Drop the transactions confirmed before `birthday` which are not already in the wallet
+    fn skip_before(&mut self, birthday: Height, known: &HashMap<Txid, Option<Height>>) {
+        self.txid_height.retain(|txid, height| {
+            height.map_or(true, |h| h >= birthday) || known.contains_key(txid)
+        });
+    }
+
+    /// Add the transactions of the given history
+    pub(crate) fn insert_history(&mut self, history: impl IntoIterator<Item = History>) {
+        for el in history {
+            // el.height = -1 means unconfirmed with unconfirmed parents
+            // el.height =  0 means unconfirmed with confirmed parents
+            // but we threat those tx the same
+            let height = el.height.max(0);
+            let txid = el.txid;
+            if height == 0 {
+                self.txid_height.insert(txid, None);
+            } else {
+                self.txid_height.insert(txid, Some(height as u32));
+                if let Some(block_hash) = el.block_hash {
+                    self.height_blockhash.insert(height as u32, block_hash);
+                }
+            }
+        }
+    }
+}
+
 /// Capabilities that can be supported by a [`BlockchainBackend`]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Capability {
@@ -108,16 +136,54 @@ pub trait BlockchainBackend {
         descriptor: &WolletDescriptor,
         state: &S,
     ) -> Result<Data, Error> {
-        let mut data = Data::default();
+        let mut data = self.get_history_multi(&[(descriptor, state)])?;
+        Ok(data.pop().unwrap_or_default())
+    }
 
-        for descriptor in descriptor.descriptor().clone().into_single_descriptors()? {
-            let mut batch_count = 0;
-            let chain: Chain = (&descriptor).try_into().unwrap_or(Chain::External);
-            loop {
-                let batch = state.get_script_batch(batch_count, &descriptor)?;
+    /// Get the history of several descriptors, returning the data of each one
+    ///
+    /// The batches of scripts of every descriptor are queried together, so that a round trip to
+    /// the backend covers all the descriptors which are not yet past the gap limit.
+    fn get_history_multi<S: WolletState>(
+        &mut self,
+        wollets: &[(&WolletDescriptor, &S)],
+    ) -> Result<Vec<Data>, Error> {
+        let mut data: Vec<Data> = wollets.iter().map(|_| Data::default()).collect();
+
+        // (wollet index, single descriptor, chain, next batch) of the descriptors to scan
+        let mut cursors = vec![];
+        for (i, (descriptor, _)) in wollets.iter().enumerate() {
+            for descriptor in descriptor.descriptor().clone().into_single_descriptors()? {
+                let chain: Chain = (&descriptor).try_into().unwrap_or(Chain::External);
+                cursors.push((i, descriptor, chain, 0));
+            }
+        }
 
-                let s: Vec<_> = batch.value.iter().map(|e| &e.0).collect();
-                let result: Vec<Vec<History>> = self.get_scripts_history(&s)?;
+        while !cursors.is_empty() {
+            let batches = cursors
+                .iter()
+                .map(|(i, descriptor, _, batch_count)| {
+                    wollets[*i].1.get_script_batch(*batch_count, descriptor)
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            let s: Vec<_> = batches
+                .iter()
+                .flat_map(|b| &b.value)
+                .map(|e| &e.0)
+                .collect();
+            if s.is_empty() {
+                // Non-ranged descriptors have a single script
+                break;
+            }
+            let mut results = self.get_scripts_history(&s)?.into_iter();
+
+            let mut next_cursors = vec![];
+            for ((i, descriptor, chain, batch_count), batch) in cursors.into_iter().zip(batches) {
+                let result: Vec<Vec<History>> = results.by_ref().take(batch.value.len()).collect();
+                if batch.value.is_empty() {
+                    continue;
+                }
+                let data = &mut data[i];
                 if !batch.cached {
                     data.scripts.extend(batch.value);
                 }
@@ -141,27 +207,14 @@ pub trait BlockchainBackend {
                 let flattened: Vec<History> = result.into_iter().flatten().collect();
 
                 if flattened.is_empty() {
-                    break;
+                    continue;
                 }
 
-                for el in flattened {
-                    // el.height = -1 means unconfirmed with unconfirmed parents
-                    // el.height =  0 means unconfirmed with confirmed parents
-                    // but we threat those tx the same
-                    let height = el.height.max(0);
-                    let txid = el.txid;
-                    if height == 0 {
-                        data.txid_height.insert(txid, None);
-                    } else {
-                        data.txid_height.insert(txid, Some(height as u32));
-                        if let Some(block_hash) = el.block_hash {
-                            data.height_blockhash.insert(height as u32, block_hash);
-                        }
-                    }
-                }
+                data.insert_history(flattened);
 
-                batch_count += 1;
+                next_cursors.push((i, descriptor, chain, batch_count + 1));
             }
+            cursors = next_cursors;
         }
         Ok(data)
     }
@@ -178,13 +231,9 @@ pub trait BlockchainBackend {
     fn full_scan<S: WolletState>(&mut self, state: &S) -> Result<Option<Update>, Error> {
         let descriptor = state.descriptor();
 
-        let Data {
-            txid_height,
-            scripts,
-            last_unused,
-            height_blockhash,
-            height_timestamp: _height_timestamp,
-        } = if self.capabilities().contains(&Capability::Waterfalls) {
+        let waterfalls = self.capabilities().contains(&Capability::Waterfalls)
+            && descriptor.descriptor().has_wildcard();
+        let mut data = if waterfalls {
             match self.get_history_waterfalls(&descriptor, state) {
                 Ok(d) => d,
                 Err(Error::UsingWaterfallsWithElip151) => self.get_history(&descriptor, state)?,
@@ -193,11 +242,37 @@ pub trait BlockchainBackend {
         } else {
             self.get_history(&descriptor, state)?
         };
-
+        let watched: Vec<_> = state.watched().keys().collect();
+        if !watched.is_empty() {
+            let result = self.get_scripts_history(&watched)?;
+            data.insert_history(result.into_iter().flatten());
+        }
         let tip = self.tip()?;
+        self.update_from_data(state, &descriptor, data, tip)
+    }
+
+    /// Download the transactions and headers of the history in `data` missing in `state`,
+    /// returning the update of the wallet if something changed
+    fn update_from_data<S: WolletState>(
+        &mut self,
+        state: &S,
+        descriptor: &WolletDescriptor,
+        mut data: Data,
+        tip: BlockHeader,
+    ) -> Result<Option<Update>, Error> {
+        if let Some(birthday) = state.birthday() {
+            data.skip_before(birthday, state.heights());
+        }
+        let Data {
+            txid_height,
+            scripts,
+            last_unused,
+            height_blockhash,
+            height_timestamp: _height_timestamp,
+        } = data;
 
         let history_txs_id: HashSet<Txid> = txid_height.keys().cloned().collect();
-        let new_txs = self.download_txs(&history_txs_id, &scripts, state, &descriptor)?;
+        let new_txs = self.download_txs(&history_txs_id, &scripts, state, descriptor)?;
         let history_txs_heights_plus_tip: HashSet<Height> = txid_height
             .values()
             .filter_map(|e| *e)
@@ -212,29 +287,20 @@ pub trait BlockchainBackend {
         let last_unused_changed = store_last_unused_external != last_unused.external
             || store_last_unused_internal != last_unused.internal;
 
+        let (txid_height_new, txid_height_delete) =
+            txid_height_changes(&txid_height, state.heights(), state.applied(), tip.height);
+
         let changed = !new_txs.txs.is_empty()
             || last_unused_changed
             || !scripts.is_empty()
             || !timestamps.is_empty()
+            || !txid_height_new.is_empty()
+            || !txid_height_delete.is_empty()
             || state.tip() != (tip.height, tip.block_hash());
 
         if changed {
             log::debug!("something changed: !new_txs.txs.is_empty():{} last_unused_changed:{} !scripts.is_empty():{} !timestamps.is_empty():{}", !new_txs.txs.is_empty(), last_unused_changed, !scripts.is_empty(), !timestamps.is_empty() );
 
-            let txid_height_new: Vec<_> = txid_height
-                .iter()
-                .filter(|(k, v)| match state.heights().get(*k) {
-                    Some(e) => e != *v,
-                    None => true,
-                })
-                .map(|(k, v)| (*k, *v))
-                .collect();
-            let txid_height_delete: Vec<_> = state
-                .heights()
-                .keys()
-                .filter(|k| txid_height.get(*k).is_none())
-                .cloned()
-                .collect();
             let wollet_status = state.wollet_status();
 
             let update = Update {
@@ -252,6 +318,42 @@ pub trait BlockchainBackend {
         }
     }
 
+    /// Scan the blockchain for the scripts of every descriptor of a [`MultiWollet`]
+    ///
+    /// The scripts of all the descriptors are queried together and the tip is fetched once.
+    /// With a Waterfalls backend each descriptor is scanned with its own requests.
+    ///
+    /// Returns an update for each descriptor, to be applied with [`MultiWollet::apply_updates()`]
+    fn full_scan_multi(&mut self, wollet: &MultiWollet) -> Result<Vec<Option<Update>>, Error> {
+        let wollets = wollet.wollets();
+        if self.capabilities().contains(&Capability::Waterfalls) {
+            return wollets.into_iter().map(|w| self.full_scan(w)).collect();
+        }
+        let descriptors: Vec<_> = wollets
+            .iter()
+            .map(|w| WolletState::descriptor(*w))
+            .collect();
+        let pairs: Vec<_> = descriptors.iter().zip(wollets.iter().copied()).collect();
+        let mut data = self.get_history_multi(&pairs)?;
+
+        let watched: Vec<_> = wollets.iter().flat_map(|w| w.watched().keys()).collect();
+        if !watched.is_empty() {
+            let mut results = self.get_scripts_history(&watched)?.into_iter();
+            for (w, data) in wollets.iter().zip(data.iter_mut()) {
+                let result = results.by_ref().take(w.watched().len());
+                data.insert_history(result.flatten());
+            }
+        }
+
+        let tip = self.tip()?;
+        wollets
+            .into_iter()
+            .zip(descriptors.iter())
+            .zip(data)
+            .map(|((w, descriptor), data)| self.update_from_data(w, descriptor, data, tip.clone()))
+            .collect()
+    }
+
     /// Download and unblind the transactions
     fn download_txs<S: WolletState>(
         &self,
@@ -264,6 +366,8 @@ pub trait BlockchainBackend {
         let mut unblinds = vec![];
 
         let mut txs_in_db = state.txs().clone();
+        // Locally applied transactions are downloaded again, so that they are persisted
+        txs_in_db.retain(|txid| !state.applied().contains_key(txid));
         let txs_to_download: Vec<Txid> = history_txs_id.difference(&txs_in_db).cloned().collect();
 
         let txs_downloaded = self.get_transactions(&txs_to_download)?;
@@ -274,16 +378,24 @@ pub trait BlockchainBackend {
 
             for (i, output) in tx.output.iter().enumerate() {
                 // could be the searched script it's not yet in the store, because created in the current run, thus it's searched also in the `scripts`
-                if state.paths().contains_key(&output.script_pubkey)
+                let unblinded = if state.paths().contains_key(&output.script_pubkey)
                     || scripts.contains_key(&output.script_pubkey)
                 {
+                    Some(try_unblind(output.clone(), descriptor))
+                } else {
+                    state
+                        .watched()
+                        .get(&output.script_pubkey)
+                        .map(|w| w.unblind(output))
+                };
+                if let Some(unblinded) = unblinded {
                     let vout = i as u32;
                     let outpoint = OutPoint {
                         txid: tx.txid(),
                         vout,
                     };
 
-                    match try_unblind(output.clone(), descriptor) {
+                    match unblinded {
                             Ok(unblinded) => unblinds.push((outpoint, unblinded)),
                             Err(_) => log::info!("{} cannot unblind, ignoring (could be sender messed up with the blinding process)", outpoint),
                         }
@@ -341,6 +453,37 @@ pub struct History {
     pub block_timestamp: Option<Timestamp>,
 }
 
+/// Compare the wallet transactions heights with the ones returned by the server
+///
+/// Returns the transactions with a new or changed height and the transactions to remove.
+/// Locally applied transactions are always reported once the server returns them, and they are
+/// removed only if the server doesn't know them after a new block, to allow them to propagate.
+pub(crate) fn txid_height_changes(
+    txid_height: &HashMap<Txid, Option<Height>>,
+    heights: &HashMap<Txid, Option<Height>>,
+    applied: &HashMap<Txid, Height>,
+    tip: Height,
+) -> (Vec<(Txid, Option<Height>)>, Vec<Txid>) {
+    let txid_height_new = txid_height
+        .iter()
+        .filter(|(k, v)| {
+            applied.contains_key(*k)
+                || match heights.get(*k) {
+                    Some(e) => e != *v,
+                    None => true,
+                }
+        })
+        .map(|(k, v)| (*k, *v))
+        .collect();
+    let txid_height_delete = heights
+        .keys()
+        .filter(|k| txid_height.get(*k).is_none())
+        .filter(|k| applied.get(*k).map_or(true, |h| tip > *h))
+        .cloned()
+        .collect();
+    (txid_height_new, txid_height_delete)
+}
+
 pub fn try_unblind(output: TxOut, descriptor: &WolletDescriptor) -> Result<TxOutSecrets, Error> {
     match (output.asset, output.value, output.nonce) {
         (Asset::Confidential(_), Value::Confidential(_), Nonce::Confidential(_)) => {
@@ -430,4 +573,110 @@ mod tests {
         }
     }
     * */
+
+    use super::{BlockchainBackend, Data, History};
+    use crate::elements::hashes::Hash;
+    use crate::elements::{BlockHash, BlockHeader, Script, Transaction, Txid};
+    use crate::store::Height;
+    use crate::{ElementsNetwork, Error, Wollet};
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    /// A backend with the history of some scripts, counting the history requests
+    struct MockBackend {
+        history: HashMap<Script, Vec<History>>,
+        /// The number of scripts of each history request
+        requests: RefCell<Vec<usize>>,
+    }
+
+    impl BlockchainBackend for MockBackend {
+        fn tip(&mut self) -> Result<BlockHeader, Error> {
+            unimplemented!()
+        }
+
+        fn broadcast(&self, _tx: &Transaction) -> Result<Txid, Error> {
+            unimplemented!()
+        }
+
+        fn get_transactions(&self, _txids: &[Txid]) -> Result<Vec<Transaction>, Error> {
+            unimplemented!()
+        }
+
+        fn get_headers(
+            &self,
+            _heights: &[Height],
+            _height_blockhash: &HashMap<Height, BlockHash>,
+        ) -> Result<Vec<BlockHeader>, Error> {
+            unimplemented!()
+        }
+
+        fn get_scripts_history(&self, scripts: &[&Script]) -> Result<Vec<Vec<History>>, Error> {
+            self.requests.borrow_mut().push(scripts.len());
+            let history = scripts
+                .iter()
+                .map(|s| self.history.get(*s).cloned().unwrap_or_default())
+                .collect();
+            Ok(history)
+        }
+    }
+
+    #[test]
+    fn test_get_history_multi() {
+        let network = ElementsNetwork::LiquidTestnet;
+        let xpub = "[73c5da0a/84'/1'/0']tpubDC8msFGeGuwnKG9Upg7DM2b4DaRqg3CUZa5g8v2SRQ6K4NSkxUgd7HsL2XVWbVm39yBA4LAxysQAm397zwQSQoQgewGiYZqrA9DsP4zbQ1M";
+        let view_key = "slip77(9c8e4f05c7711a98c838be228bcb84924d4570ca53f35fa1c793e58841d47023)";
+        let wollet = |desc: String| Wollet::without_persist(network, desc.parse().unwrap());
+        let w1 = wollet(format!("ct({view_key},elwpkh({xpub}/<0;1>/*))")).unwrap();
+        let w2 = wollet(format!("ct({view_key},elsh(wpkh({xpub}/<0;1>/*)))")).unwrap();
+
+        let used = w1.address(Some(3)).unwrap().address().script_pubkey();
+        let history = History {
+            txid: Txid::from_byte_array([1; 32]),
+            height: 0,
+            block_hash: None,
+            block_timestamp: None,
+        };
+        let mut backend = MockBackend {
+            history: HashMap::from([(used, vec![history.clone()])]),
+            requests: RefCell::new(vec![]),
+        };
+
+        let d1 = w1.wollet_descriptor();
+        let d2 = w2.wollet_descriptor();
+        let data = backend
+            .get_history_multi(&[(&d1, &w1), (&d2, &w2)])
+            .unwrap();
+
+        // the first batch of both chains of both descriptors, then the next external batch
+        assert_eq!(*backend.requests.borrow(), vec![80, 20]);
+        assert_eq!(data.len(), 2);
+        assert_eq!(data[0].last_unused.external, 4);
+        assert_eq!(data[0].last_unused.internal, 0);
+        assert_eq!(data[0].txid_height.get(&history.txid), Some(&None));
+        assert!(data[1].txid_height.is_empty());
+        assert_eq!(data[1].last_unused.external, 0);
+
+        // the single descriptor history is the same
+        backend.requests.borrow_mut().clear();
+        assert_eq!(backend.get_history(&d1, &w1).unwrap(), data[0]);
+        assert_eq!(*backend.requests.borrow(), vec![40, 20]);
+    }
+
+    #[test]
+    fn test_skip_before_birthday() {
+        let txid = |i: u8| Txid::from_byte_array([i; 32]);
+        let mut data = Data::default();
+        data.txid_height.insert(txid(1), Some(99));
+        data.txid_height.insert(txid(2), Some(100));
+        data.txid_height.insert(txid(3), None);
+        data.txid_height.insert(txid(4), Some(50));
+        let mut known = HashMap::new();
+        known.insert(txid(4), Some(50));
+
+        data.skip_before(100, &known);
+        assert!(!data.txid_height.contains_key(&txid(1)));
+        assert!(data.txid_height.contains_key(&txid(2)));
+        assert!(data.txid_height.contains_key(&txid(3)));
+        assert!(data.txid_height.contains_key(&txid(4)));
+    }
 }
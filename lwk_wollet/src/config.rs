@@ -27,6 +27,24 @@ impl ElementsNetwork {
         }
     }
 
+    /// The ticker of the policy asset, prefixed with "t" on testnet and "r" on regtest
+    pub fn policy_asset_ticker(&self) -> &'static str {
+        match self {
+            ElementsNetwork::Liquid => "L-BTC",
+            ElementsNetwork::LiquidTestnet => "tL-BTC",
+            ElementsNetwork::ElementsRegtest { .. } => "rL-BTC",
+        }
+    }
+
+    /// The name of the policy asset
+    pub fn policy_asset_name(&self) -> &'static str {
+        match self {
+            ElementsNetwork::Liquid => "Liquid Bitcoin",
+            ElementsNetwork::LiquidTestnet => "Testnet Liquid Bitcoin",
+            ElementsNetwork::ElementsRegtest { .. } => "Regtest Liquid Bitcoin",
+        }
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             ElementsNetwork::Liquid => "liquid",
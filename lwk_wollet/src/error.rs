@@ -51,6 +51,9 @@ pub enum Error {
     #[error(transparent)]
     Secp256k1(#[from] crate::secp256k1::Error),
 
+    #[error(transparent)]
+    Secp256k1Zkp(#[from] crate::elements::secp256k1_zkp::Error),
+
     #[error(transparent)]
     HexToBytesError(#[from] crate::hashes::hex::HexToBytesError),
 
@@ -133,9 +136,6 @@ pub enum Error {
     #[error("Invalid issuer pubkey")]
     InvalidIssuerPubkey,
 
-    #[error("Descriptor without wildcard not supported")]
-    UnsupportedDescriptorWithoutWildcard,
-
     #[error(
         "Multipath descriptor must have only the external/internal multipath (eg '.../<0;1>/*')"
     )]
@@ -156,6 +156,9 @@ pub enum Error {
     #[error("Contract does not commit to asset id")]
     ContractDoesNotCommitToAssetId,
 
+    #[error("Invalid domain proof at {0}")]
+    InvalidDomainProof(String),
+
     #[error("Update height {update_tip_height} too old (internal height {store_tip_height})")]
     UpdateHeightTooOld {
         update_tip_height: u32,
@@ -168,8 +171,14 @@ pub enum Error {
         update_status: u64,
     },
 
-    #[error("An issuance has already being set on this tx builder")]
-    IssuanceAlreadySet,
+    #[error("Not enough inputs to attach all the issuances")]
+    NotEnoughIssuanceInputs,
+
+    #[error("Utxo {0} not found in the wallet")]
+    UtxoNotFound(crate::elements::OutPoint),
+
+    #[error("Transaction {0} does not spend or receive wallet outputs")]
+    TransactionNotMine(crate::elements::Txid),
 
     #[error("Blockchain backend have not implemented waterfalls method")]
     WaterfallsUnimplemented,
@@ -82,36 +82,54 @@
 //! # }
 //! ```
 
+mod audit;
+mod backup;
 mod clients;
 mod config;
 mod descriptor;
 mod domain;
 mod error;
+mod lock;
 mod model;
+mod multi;
 mod pegin;
 mod persister;
+mod portfolio;
 mod pset_create;
+mod pset_interop;
+mod query;
 mod registry;
 mod store;
 mod tx_builder;
 mod update;
 mod util;
+mod watch;
 mod wollet;
 
+pub use crate::audit::{AuditOutput, AuditQuery, AuditReport, AuditSignature};
+pub use crate::backup::{WolletBackup, BACKUP_VERSION};
 pub use crate::clients::{BlockchainBackend, Capability, History};
 pub use crate::config::ElementsNetwork;
 pub use crate::descriptor::{Chain, WolletDescriptor};
 pub use crate::error::Error;
+pub use crate::lock::LockedUtxo;
 pub use crate::model::{
     AddressResult, ExternalUtxo, IssuanceDetails, Recipient, UnvalidatedRecipient, WalletTx,
-    WalletTxOut,
+    WalletTxOut, WalletTxSummary,
 };
+pub use crate::multi::{ChangePolicy, MultiWollet};
 pub use crate::pegin::{fed_peg_script, fetch_last_full_header};
 pub use crate::persister::{FsPersister, NoPersist, PersistError, Persister};
-pub use crate::registry::{asset_ids, issuance_ids, Contract, Entity};
+pub use crate::portfolio::{AssetBalance, PortfolioAsset};
+pub use crate::query::{TxPage, TxQuery};
+pub use crate::registry::{
+    asset_ids, issuance_ids, Contract, Entity, Registry, RegistryCache, RegistryData,
+    RegistryPrevout, RegistryTxIn,
+};
 pub use crate::tx_builder::{TxBuilder, WolletTxBuilder};
 pub use crate::update::{DownloadTxResult, Update};
 pub use crate::util::EC;
+pub use crate::watch::WatchedAddress;
 pub use crate::wollet::{Tip, Wollet};
 
 #[cfg(feature = "electrum")]
@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::MutexGuard;
+
+use crate::elements::pset::PartiallySignedTransaction;
+use crate::elements::OutPoint;
+use crate::error::Error;
+use crate::model::WalletTxOut;
+use crate::store::Height;
+use crate::wollet::Wollet;
+
+/// A wallet utxo excluded from coin selection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedUtxo {
+    pub outpoint: OutPoint,
+
+    /// The lock is released once the wallet tip reaches this height,
+    /// if `None` the utxo is locked until [`Wollet::unlock_utxo()`] is called
+    pub expiry: Option<Height>,
+}
+
+impl LockedUtxo {
+    pub fn is_expired(&self, tip: Height) -> bool {
+        self.expiry.map(|e| tip >= e).unwrap_or(false)
+    }
+}
+
+impl Wollet {
+    /// Lock a wallet utxo, so that it's not spent by the transactions created by the wallet
+    ///
+    /// If `expiry` is set, the lock is released once the wallet tip reaches that height.
+    /// Locking an already locked utxo replaces its expiry.
+    pub fn lock_utxo(&self, outpoint: OutPoint, expiry: Option<Height>) -> Result<(), Error> {
+        if !self.utxos()?.iter().any(|u| u.outpoint == outpoint) {
+            return Err(Error::UtxoNotFound(outpoint));
+        }
+        let mut locked = self.lock_state()?;
+        locked.insert(outpoint, LockedUtxo { outpoint, expiry });
+        self.persist_locked(&locked)
+    }
+
+    /// Unlock a wallet utxo, returns false if it wasn't locked
+    pub fn unlock_utxo(&self, outpoint: &OutPoint) -> Result<bool, Error> {
+        let mut locked = self.lock_state()?;
+        let was_locked = locked.remove(outpoint).is_some();
+        if was_locked {
+            self.persist_locked(&locked)?;
+        }
+        Ok(was_locked)
+    }
+
+    /// Get the locked wallet utxos, excluding the expired locks and the spent utxos
+    pub fn locked_utxos(&self) -> Result<Vec<LockedUtxo>, Error> {
+        self.locked_among(&self.utxos()?)
+    }
+
+    /// Get the locks of the given utxos, excluding the expired ones
+    pub(crate) fn locked_among(&self, utxos: &[WalletTxOut]) -> Result<Vec<LockedUtxo>, Error> {
+        let tip = self.tip().height();
+        let locked = self.lock_state()?;
+        Ok(utxos
+            .iter()
+            .filter_map(|u| locked.get(&u.outpoint))
+            .filter(|l| !l.is_expired(tip))
+            .cloned()
+            .collect())
+    }
+
+    /// Lock the inputs of the given PSET belonging to the wallet until the given height
+    pub(crate) fn lock_inputs(
+        &self,
+        pset: &PartiallySignedTransaction,
+        expiry: Height,
+    ) -> Result<(), Error> {
+        let txos = self.txos()?;
+        let mut locked = self.lock_state()?;
+        for input in pset.inputs() {
+            let outpoint = OutPoint::new(input.previous_txid, input.previous_output_index);
+            if txos.contains_key(&outpoint) {
+                let expiry = Some(expiry);
+                locked.insert(outpoint, LockedUtxo { outpoint, expiry });
+            }
+        }
+        self.persist_locked(&locked)
+    }
+
+    fn lock_state(&self) -> Result<MutexGuard<'_, HashMap<OutPoint, LockedUtxo>>, Error> {
+        self.locked
+            .lock()
+            .map_err(|e| Error::Generic(format!("Locked utxos poisoned: {e}")))
+    }
+
+    /// Persist the locks, dropping the expired ones
+    fn persist_locked(&self, locked: &HashMap<OutPoint, LockedUtxo>) -> Result<(), Error> {
+        let tip = self.tip().height();
+        let mut locked: Vec<_> = locked
+            .values()
+            .filter(|l| !l.is_expired(tip))
+            .cloned()
+            .collect();
+        locked.sort_by_key(|l| l.outpoint);
+        self.persister.set_locked_utxos(&locked)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LockedUtxo;
+    use crate::elements::OutPoint;
+    use crate::util::{deserialize_decrypted, serialize_encrypted};
+    use crate::{ElementsNetwork, Error, NoPersist, Wollet, WolletDescriptor};
+
+    #[test]
+    fn test_locked_utxos() {
+        let desc: WolletDescriptor = "ct(slip77(9c8e4f05c7711a98c838be228bcb84924d4570ca53f35fa1c793e58841d47023),elwpkh([73c5da0a/84'/1'/0']tpubDC8msFGeGuwnKG9Upg7DM2b4DaRqg3CUZa5g8v2SRQ6K4NSkxUgd7HsL2XVWbVm39yBA4LAxysQAm397zwQSQoQgewGiYZqrA9DsP4zbQ1M/<0;1>/*))".parse().unwrap();
+
+        let locked = LockedUtxo {
+            outpoint: OutPoint::default(),
+            expiry: Some(10),
+        };
+        assert!(!locked.is_expired(9));
+        assert!(locked.is_expired(10));
+        let forever = LockedUtxo {
+            expiry: None,
+            ..locked
+        };
+        assert!(!forever.is_expired(u32::MAX));
+
+        let locked = vec![locked, forever];
+        let bytes = serialize_encrypted(&locked, &desc).unwrap();
+        let decrypted: Vec<LockedUtxo> = deserialize_decrypted(&bytes, &desc).unwrap();
+        assert_eq!(decrypted, locked);
+
+        let wollet = Wollet::new(ElementsNetwork::LiquidTestnet, NoPersist::new(), desc).unwrap();
+        let err = wollet.lock_utxo(OutPoint::default(), None).unwrap_err();
+        assert!(matches!(err, Error::UtxoNotFound(_)));
+        assert!(!wollet.unlock_utxo(&OutPoint::default()).unwrap());
+        assert!(wollet.locked_utxos().unwrap().is_empty());
+    }
+}
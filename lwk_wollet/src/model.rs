@@ -1,4 +1,5 @@
 use crate::descriptor::Chain;
+use crate::elements::confidential::ValueBlindingFactor;
 use crate::elements::{Address, AssetId, OutPoint, Script, Transaction, TxOutSecrets, Txid};
 use crate::pset_create::validate_address;
 use crate::secp256k1::PublicKey;
@@ -18,8 +19,10 @@ pub struct WalletTxOut {
     pub script_pubkey: Script,
     pub height: Option<u32>,
     pub unblinded: TxOutSecrets,
-    pub wildcard_index: u32,
-    pub ext_int: Chain,
+    /// The derivation index of the output script, `None` for outputs to watched addresses
+    pub wildcard_index: Option<u32>,
+    /// The descriptor chain of the output script, `None` for outputs to watched addresses
+    pub ext_int: Option<Chain>,
 }
 
 /// A UTXO owned by another wallet
@@ -54,6 +57,55 @@ pub struct WalletTx {
     pub outputs: Vec<Option<WalletTxOut>>,
 }
 
+/// A [`WalletTx`] without the raw transaction,
+/// returned by [`crate::Wollet::query_transaction_summaries()`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WalletTxSummary {
+    pub txid: Txid,
+    pub height: Option<u32>,
+    pub balance: BTreeMap<AssetId, i64>,
+    pub fee: u64,
+    pub type_: String,
+    pub timestamp: Option<Timestamp>,
+    pub inputs: Vec<Option<WalletTxOut>>,
+    pub outputs: Vec<Option<WalletTxOut>>,
+}
+
+impl WalletTxSummary {
+    pub(crate) fn with_tx(self, tx: Transaction) -> WalletTx {
+        WalletTx {
+            tx,
+            txid: self.txid,
+            height: self.height,
+            balance: self.balance,
+            fee: self.fee,
+            type_: self.type_,
+            timestamp: self.timestamp,
+            inputs: self.inputs,
+            outputs: self.outputs,
+        }
+    }
+
+    pub fn unblinded_url(&self, explorer_url: &str) -> String {
+        unblinded_url(explorer_url, &self.txid, &self.inputs, &self.outputs)
+    }
+}
+
+impl From<WalletTx> for WalletTxSummary {
+    fn from(tx: WalletTx) -> Self {
+        Self {
+            txid: tx.txid,
+            height: tx.height,
+            balance: tx.balance,
+            fee: tx.fee,
+            type_: tx.type_,
+            timestamp: tx.timestamp,
+            inputs: tx.inputs,
+            outputs: tx.outputs,
+        }
+    }
+}
+
 /// A recipient of a transaction.
 ///
 /// Note that, since it doesn't use the [`Address`] but the [`Script`] and the [`PublicKey`] it's
@@ -222,11 +274,20 @@ pub struct IssuanceDetails {
     pub entropy: [u8; 32],
     pub asset: AssetId,
     pub token: AssetId,
+    /// The issued asset amount, `None` if it's confidential and it cannot be unblinded
     pub asset_amount: Option<u64>,
+    /// The issued token amount, `None` if it's confidential and it cannot be unblinded
     pub token_amount: Option<u64>,
     pub is_reissuance: bool,
-    // asset_blinder
-    // token_blinder
+    /// Whether the asset or token amounts are blinded
+    #[serde(default)]
+    pub is_confidential: bool,
+    /// The value blinding factor of the asset amount, if blinded and unblinded by the wallet
+    #[serde(default)]
+    pub asset_blinder: Option<ValueBlindingFactor>,
+    /// The value blinding factor of the token amount, if blinded and unblinded by the wallet
+    #[serde(default)]
+    pub token_blinder: Option<ValueBlindingFactor>,
 }
 
 pub(crate) struct DisplayTxOutSecrets<'a>(&'a TxOutSecrets);
@@ -240,12 +301,15 @@ impl<'a> std::fmt::Display for DisplayTxOutSecrets<'a> {
     }
 }
 
-pub(crate) struct DisplayWalletTxInputOutputs<'a>(&'a WalletTx);
+pub(crate) struct DisplayWalletTxInputOutputs<'a>(
+    &'a [Option<WalletTxOut>],
+    &'a [Option<WalletTxOut>],
+);
 impl<'a> std::fmt::Display for DisplayWalletTxInputOutputs<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         let mut first = true;
 
-        for input in self.0.inputs.iter() {
+        for input in self.0.iter() {
             if let Some(input) = input.as_ref() {
                 if !first {
                     write!(f, ",")?;
@@ -255,7 +319,7 @@ impl<'a> std::fmt::Display for DisplayWalletTxInputOutputs<'a> {
             }
         }
 
-        for output in self.0.outputs.iter() {
+        for output in self.1.iter() {
             if let Some(output) = output.as_ref() {
                 if !first {
                     write!(f, ",")?;
@@ -270,15 +334,24 @@ impl<'a> std::fmt::Display for DisplayWalletTxInputOutputs<'a> {
 
 impl WalletTx {
     pub fn unblinded_url(&self, explorer_url: &str) -> String {
-        format!(
-            "{}tx/{}#blinded={}",
-            explorer_url,
-            &self.txid,
-            DisplayWalletTxInputOutputs(self)
-        )
+        unblinded_url(explorer_url, &self.txid, &self.inputs, &self.outputs)
     }
 }
 
+fn unblinded_url(
+    explorer_url: &str,
+    txid: &Txid,
+    inputs: &[Option<WalletTxOut>],
+    outputs: &[Option<WalletTxOut>],
+) -> String {
+    format!(
+        "{}tx/{}#blinded={}",
+        explorer_url,
+        txid,
+        DisplayWalletTxInputOutputs(inputs, outputs)
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -295,9 +368,16 @@ mod tests {
     fn test_wollet_tx() {
         let json_str = include_str!("../tests/data/wallet_tx.json");
         let wallet_tx: WalletTx = serde_json::from_str(json_str).unwrap();
+        let url = wallet_tx.unblinded_url("https://blockstream.info/liquidtestnet/");
         assert_eq!(
-            wallet_tx.unblinded_url("https://blockstream.info/liquidtestnet/"),
+            url,
             "https://blockstream.info/liquidtestnet/tx/c6e3187f028942973ad27224ca79baa8382e90ad686e927fc29896e8a2edf3f3#blinded=5000,38fca2d939696061a8f76d4e6b5eecd54e3b4221c846f24a6b279e79952850a5,ab9a42053c7a6ae0d55b774f3d462b1adfaa630e5d0f9b3c0f16640d55b8f6ab,6c5c2b44a0777e463d25eecb70adee84b316c2597b8a28108ffeea38c7acf45d"
         );
+        let summary = WalletTxSummary::from(wallet_tx.clone());
+        assert_eq!(
+            summary.unblinded_url("https://blockstream.info/liquidtestnet/"),
+            url
+        );
+        assert_eq!(summary.with_tx(wallet_tx.tx.clone()), wallet_tx);
     }
 }
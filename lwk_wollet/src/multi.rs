@@ -0,0 +1,291 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::elements::pset::PartiallySignedTransaction;
+use crate::elements::{AssetId, Txid};
+use crate::error::Error;
+use crate::model::{AddressResult, WalletTx, WalletTxOut};
+use crate::tx_builder::{TxBuilder, WolletTxBuilder};
+use crate::update::Update;
+use crate::wollet::{tx_type, Wollet};
+use crate::{ElementsNetwork, WolletDescriptor};
+use lwk_common::{pset_issuances, pset_signatures, PsetBalance, PsetDetails};
+
+/// How the transactions created by a [`MultiWollet`] use one of its descriptors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangePolicy {
+    /// The descriptor can receive the change, the first of these descriptors receives it
+    Receive,
+
+    /// The descriptor is only spent from and its utxos are selected first,
+    /// for instance to migrate the funds of a legacy descriptor
+    SpendOnly,
+}
+
+/// A wallet tracking several descriptors of the same network
+///
+/// Balance, utxos and transactions are merged, transactions between the descriptors only
+/// account for the fee. The transactions created with [`MultiWollet::tx_builder()`] can spend
+/// from every descriptor.
+#[derive(Debug)]
+pub struct MultiWollet {
+    wollets: Vec<(Wollet, ChangePolicy)>,
+}
+
+impl MultiWollet {
+    /// Create a wallet with a first descriptor, which receives the change
+    pub fn new(wollet: Wollet) -> Self {
+        Self {
+            wollets: vec![(wollet, ChangePolicy::Receive)],
+        }
+    }
+
+    /// Add a descriptor to the wallet
+    pub fn add(&mut self, wollet: Wollet, policy: ChangePolicy) -> Result<(), Error> {
+        if wollet.network() != self.network() {
+            return Err(Error::Generic(format!(
+                "Cannot add a {:?} descriptor to a {:?} wallet",
+                wollet.network(),
+                self.network()
+            )));
+        }
+        if self
+            .wollets
+            .iter()
+            .any(|(w, _)| w.descriptor() == wollet.descriptor())
+        {
+            return Err(Error::Generic("Descriptor already in the wallet".into()));
+        }
+        self.wollets.push((wollet, policy));
+        Ok(())
+    }
+
+    /// Set the change policy of the descriptor at `index`
+    pub fn set_change_policy(&mut self, index: usize, policy: ChangePolicy) -> Result<(), Error> {
+        let entry = self
+            .wollets
+            .get_mut(index)
+            .ok_or_else(|| Error::Generic(format!("No descriptor at index {index}")))?;
+        entry.1 = policy;
+        Ok(())
+    }
+
+    /// Get the network of the wallet
+    pub fn network(&self) -> ElementsNetwork {
+        self.wollets[0].0.network()
+    }
+
+    /// Get the policy asset of the wallet
+    pub fn policy_asset(&self) -> AssetId {
+        self.network().policy_asset()
+    }
+
+    /// Get the wallets of every descriptor, in the order they were added
+    pub fn wollets(&self) -> Vec<&Wollet> {
+        self.wollets.iter().map(|(w, _)| w).collect()
+    }
+
+    /// Get the descriptors, in the order they were added
+    pub fn descriptors(&self) -> Vec<WolletDescriptor> {
+        self.wollets
+            .iter()
+            .map(|(w, _)| w.wollet_descriptor())
+            .collect()
+    }
+
+    /// Get the wallet of the first descriptor receiving the change
+    pub fn change_wollet(&self) -> Result<&Wollet, Error> {
+        self.wollets
+            .iter()
+            .find(|(_, p)| *p == ChangePolicy::Receive)
+            .map(|(w, _)| w)
+            .ok_or_else(|| Error::Generic("No descriptor receives the change".into()))
+    }
+
+    /// Get a receiving address of the descriptor receiving the change,
+    /// see [`Wollet::address()`]
+    pub fn address(&self, index: Option<u32>) -> Result<AddressResult, Error> {
+        self.change_wollet()?.address(index)
+    }
+
+    /// Apply the updates returned by [`crate::BlockchainBackend::full_scan_multi()`]
+    pub fn apply_updates(&mut self, updates: Vec<Option<Update>>) -> Result<(), Error> {
+        if updates.len() != self.wollets.len() {
+            return Err(Error::Generic(format!(
+                "Expected {} updates, got {}",
+                self.wollets.len(),
+                updates.len()
+            )));
+        }
+        for ((wollet, _), update) in self.wollets.iter_mut().zip(updates) {
+            if let Some(update) = update {
+                wollet.apply_update(update)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the balance of every descriptor, summed
+    pub fn balance(&self) -> Result<BTreeMap<AssetId, u64>, Error> {
+        let mut balance = BTreeMap::new();
+        for (wollet, _) in self.wollets.iter() {
+            for (asset, satoshi) in wollet.balance()? {
+                *balance.entry(asset).or_default() += satoshi;
+            }
+        }
+        Ok(balance)
+    }
+
+    /// Get the unspent outputs of every descriptor
+    pub fn utxos(&self) -> Result<Vec<WalletTxOut>, Error> {
+        let mut utxos = vec![];
+        for (wollet, _) in self.wollets.iter() {
+            utxos.extend(wollet.utxos()?);
+        }
+        Ok(utxos)
+    }
+
+    /// Get the transactions of every descriptor, most recent first
+    ///
+    /// A transaction involving several descriptors is returned once, with the balances summed.
+    pub fn transactions(&self) -> Result<Vec<WalletTx>, Error> {
+        let mut merged: HashMap<Txid, WalletTx> = HashMap::new();
+        for (wollet, _) in self.wollets.iter() {
+            for tx in wollet.transactions()? {
+                match merged.get_mut(&tx.txid) {
+                    Some(m) => merge_tx(m, tx),
+                    None => {
+                        merged.insert(tx.txid, tx);
+                    }
+                }
+            }
+        }
+
+        let policy_asset = self.policy_asset();
+        let mut txs: Vec<_> = merged.into_values().collect();
+        for tx in txs.iter_mut() {
+            tx.type_ = tx_type(&tx.tx, &policy_asset, &tx.balance, tx.fee);
+        }
+        txs.sort_by(|a, b| {
+            b.height
+                .unwrap_or(u32::MAX)
+                .cmp(&a.height.unwrap_or(u32::MAX))
+                .then(b.txid.cmp(&a.txid))
+        });
+        Ok(txs)
+    }
+
+    /// Create a transaction builder spending from every descriptor
+    ///
+    /// Utxos of descriptors with [`ChangePolicy::SpendOnly`] are selected first,
+    /// change and issued assets are sent to [`MultiWollet::change_wollet()`].
+    pub fn tx_builder(&self) -> WolletTxBuilder {
+        WolletTxBuilder::new_multi(self)
+    }
+
+    pub(crate) fn finish(&self, builder: TxBuilder) -> Result<PartiallySignedTransaction, Error> {
+        let change = self.change_wollet()?;
+        let spend_only = self
+            .wollets
+            .iter()
+            .filter(|(_, p)| *p == ChangePolicy::SpendOnly);
+        let receive = self
+            .wollets
+            .iter()
+            .filter(|(_, p)| *p == ChangePolicy::Receive);
+        let wollets: Vec<_> = spend_only.chain(receive).map(|(w, _)| w).collect();
+        builder.finish_inner(change, &wollets)
+    }
+
+    /// Add the PSET details with respect to every descriptor
+    pub fn add_details(&self, pset: &mut PartiallySignedTransaction) -> Result<(), Error> {
+        for (wollet, _) in self.wollets.iter() {
+            wollet.add_details(pset)?;
+        }
+        Ok(())
+    }
+
+    /// Get the PSET details with respect to the whole wallet
+    pub fn get_details(&self, pset: &PartiallySignedTransaction) -> Result<PsetDetails, Error> {
+        let mut balance = PsetBalance {
+            fee: 0,
+            balances: BTreeMap::new(),
+        };
+        for (wollet, _) in self.wollets.iter() {
+            let details = wollet.get_details(pset)?;
+            balance.fee = details.balance.fee;
+            for (asset, satoshi) in details.balance.balances {
+                *balance.balances.entry(asset).or_default() += satoshi;
+            }
+        }
+        Ok(PsetDetails {
+            balance,
+            sig_details: pset_signatures(pset),
+            issuances: pset_issuances(pset),
+        })
+    }
+}
+
+/// Merge the view of a transaction from another descriptor
+fn merge_tx(merged: &mut WalletTx, other: WalletTx) {
+    for (asset, satoshi) in other.balance {
+        *merged.balance.entry(asset).or_default() += satoshi;
+    }
+    for (m, o) in merged.inputs.iter_mut().zip(other.inputs) {
+        if m.is_none() {
+            *m = o;
+        }
+    }
+    for (m, o) in merged.outputs.iter_mut().zip(other.outputs) {
+        if m.is_none() {
+            *m = o;
+        }
+    }
+    merged.height = merged.height.or(other.height);
+    merged.timestamp = merged.timestamp.or(other.timestamp);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChangePolicy, MultiWollet};
+    use crate::{ElementsNetwork, NoPersist, Wollet};
+
+    fn new_wollet(network: ElementsNetwork, desc: &str) -> Wollet {
+        Wollet::new(network, NoPersist::new(), desc.parse().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_multi_wollet() {
+        let desc_a = "ct(slip77(9c8e4f05c7711a98c838be228bcb84924d4570ca53f35fa1c793e58841d47023),elwpkh([73c5da0a/84'/1'/0']tpubDC8msFGeGuwnKG9Upg7DM2b4DaRqg3CUZa5g8v2SRQ6K4NSkxUgd7HsL2XVWbVm39yBA4LAxysQAm397zwQSQoQgewGiYZqrA9DsP4zbQ1M/<0;1>/*))";
+        let desc_b = "ct(slip77(9c8e4f05c7711a98c838be228bcb84924d4570ca53f35fa1c793e58841d47023),elsh(wpkh([73c5da0a/49'/1'/0']tpubDD7tXK8KeQ3YY83yWq755fHY2JW8Ha8Q765tknUM5rSvjPcGWfUppDFMpQ1ScziKfW3ZNtZvAD7M3u7bSs7HofjTD3KP3YxPK7X6hwV8Rk2/<0;1>/*)))";
+        let network = ElementsNetwork::LiquidTestnet;
+
+        let mut multi = MultiWollet::new(new_wollet(network, desc_a));
+        let err = multi
+            .add(new_wollet(network, desc_a), ChangePolicy::Receive)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Descriptor already in the wallet");
+        let other_network = ElementsNetwork::default_regtest();
+        assert!(multi
+            .add(new_wollet(other_network, desc_b), ChangePolicy::Receive)
+            .is_err());
+        multi
+            .add(new_wollet(network, desc_b), ChangePolicy::SpendOnly)
+            .unwrap();
+        assert_eq!(multi.descriptors().len(), 2);
+        let address = multi.wollets()[0].address(Some(0)).unwrap();
+        assert_eq!(multi.address(Some(0)).unwrap().address(), address.address());
+
+        // The change goes to the first descriptor receiving it
+        multi.set_change_policy(0, ChangePolicy::SpendOnly).unwrap();
+        assert!(multi.change_wollet().is_err());
+        multi.set_change_policy(1, ChangePolicy::Receive).unwrap();
+        let change = multi.change_wollet().unwrap();
+        assert_eq!(change.descriptor(), multi.wollets()[1].descriptor());
+        assert!(multi.set_change_policy(2, ChangePolicy::Receive).is_err());
+
+        assert!(multi.balance().unwrap().values().all(|v| *v == 0));
+        assert!(multi.transactions().unwrap().is_empty());
+        assert!(multi.apply_updates(vec![None]).is_err());
+        multi.apply_updates(vec![None, None]).unwrap();
+    }
+}
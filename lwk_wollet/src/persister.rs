@@ -9,7 +9,8 @@ use std::{
 
 use elements::{bitcoin::hashes::Hash, hashes::sha256t_hash_newtype};
 
-use crate::{ElementsNetwork, Error, Update, WolletDescriptor};
+use crate::store::Height;
+use crate::{ElementsNetwork, Error, LockedUtxo, Update, WatchedAddress, WolletDescriptor};
 
 #[derive(thiserror::Error, Debug)]
 pub enum PersistError {
@@ -31,6 +32,42 @@ pub trait Persister {
     ///
     /// Implementors are encouraged to coalesce consequent updates with `update.only_tip() == true`
     fn push(&self, update: Update) -> Result<(), PersistError>;
+
+    /// Return the persisted locked utxos
+    ///
+    /// The default implementation doesn't persist locked utxos
+    fn get_locked_utxos(&self) -> Result<Vec<LockedUtxo>, PersistError> {
+        Ok(vec![])
+    }
+
+    /// Persist the locked utxos, replacing the previous ones
+    fn set_locked_utxos(&self, _locked: &[LockedUtxo]) -> Result<(), PersistError> {
+        Ok(())
+    }
+
+    /// Return the persisted watched addresses
+    ///
+    /// The default implementation doesn't persist watched addresses
+    fn get_watched_addresses(&self) -> Result<Vec<WatchedAddress>, PersistError> {
+        Ok(vec![])
+    }
+
+    /// Persist the watched addresses, replacing the previous ones
+    fn set_watched_addresses(&self, _watched: &[WatchedAddress]) -> Result<(), PersistError> {
+        Ok(())
+    }
+
+    /// Return the persisted wallet birthday
+    ///
+    /// The default implementation doesn't persist the birthday
+    fn get_birthday(&self) -> Result<Option<Height>, PersistError> {
+        Ok(None)
+    }
+
+    /// Persist the wallet birthday
+    fn set_birthday(&self, _birthday: Option<Height>) -> Result<(), PersistError> {
+        Ok(())
+    }
 }
 
 sha256t_hash_newtype! {
@@ -121,6 +158,20 @@ impl FsPersister {
 }
 
 impl FsPersisterInner {
+    /// The locked utxos are kept out of the updates directory, next to it
+    fn locked_path(&self) -> PathBuf {
+        self.path.with_extension("locked")
+    }
+
+    /// The watched addresses are kept next to the locked utxos
+    fn watched_path(&self) -> PathBuf {
+        self.path.with_extension("watched")
+    }
+
+    fn birthday_path(&self) -> PathBuf {
+        self.path.with_extension("birthday")
+    }
+
     fn path(&self, counter: &Counter) -> PathBuf {
         let mut path = self.path.clone();
         path.push(counter.to_string());
@@ -180,6 +231,63 @@ impl Persister for FsPersister {
         inner.next = inner.next.clone() + 1;
         Ok(())
     }
+
+    fn get_locked_utxos(&self) -> Result<Vec<LockedUtxo>, PersistError> {
+        let inner = self.inner.lock().map_err(to_other)?;
+        let path = inner.locked_path();
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        let bytes = fs::read(path)?;
+        crate::util::deserialize_decrypted(&bytes, &inner.desc)
+            .map_err(|e| PersistError::Other(e.to_string()))
+    }
+
+    fn set_locked_utxos(&self, locked: &[LockedUtxo]) -> Result<(), PersistError> {
+        let inner = self.inner.lock().map_err(to_other)?;
+        let ciphertext = crate::util::serialize_encrypted(locked, &inner.desc)
+            .map_err(|e| PersistError::Other(e.to_string()))?;
+        fs::write(inner.locked_path(), ciphertext)?;
+        Ok(())
+    }
+
+    fn get_watched_addresses(&self) -> Result<Vec<WatchedAddress>, PersistError> {
+        let inner = self.inner.lock().map_err(to_other)?;
+        let path = inner.watched_path();
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        let bytes = fs::read(path)?;
+        crate::util::deserialize_decrypted(&bytes, &inner.desc)
+            .map_err(|e| PersistError::Other(e.to_string()))
+    }
+
+    fn set_watched_addresses(&self, watched: &[WatchedAddress]) -> Result<(), PersistError> {
+        let inner = self.inner.lock().map_err(to_other)?;
+        let ciphertext = crate::util::serialize_encrypted(watched, &inner.desc)
+            .map_err(|e| PersistError::Other(e.to_string()))?;
+        fs::write(inner.watched_path(), ciphertext)?;
+        Ok(())
+    }
+
+    fn get_birthday(&self) -> Result<Option<Height>, PersistError> {
+        let inner = self.inner.lock().map_err(to_other)?;
+        let path = inner.birthday_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let bytes = fs::read(path)?;
+        crate::util::deserialize_decrypted(&bytes, &inner.desc)
+            .map_err(|e| PersistError::Other(e.to_string()))
+    }
+
+    fn set_birthday(&self, birthday: Option<Height>) -> Result<(), PersistError> {
+        let inner = self.inner.lock().map_err(to_other)?;
+        let ciphertext = crate::util::serialize_encrypted(&birthday, &inner.desc)
+            .map_err(|e| PersistError::Other(e.to_string()))?;
+        fs::write(inner.birthday_path(), ciphertext)?;
+        Ok(())
+    }
 }
 
 const PERSISTED_FILE_NAME_LENGTH: usize = 12;
@@ -234,7 +342,11 @@ mod test {
         sync::{Arc, Mutex},
     };
 
-    use crate::{ElementsNetwork, FsPersister, PersistError, Update, WolletDescriptor};
+    use crate::elements::OutPoint;
+    use crate::{
+        ElementsNetwork, FsPersister, LockedUtxo, PersistError, Update, WatchedAddress,
+        WolletDescriptor,
+    };
 
     use super::{Counter, NoPersist, Persister};
 
@@ -285,6 +397,64 @@ mod test {
         WolletDescriptor::from_str(exp).unwrap()
     }
 
+    #[test]
+    fn test_fs_persister_locked_utxos() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let desc = wollet_descriptor_test_vector();
+        let network = ElementsNetwork::LiquidTestnet;
+        let persister = FsPersister::new(&tempdir, network, &desc).unwrap();
+        assert!(persister.get_locked_utxos().unwrap().is_empty());
+
+        let locked = vec![LockedUtxo {
+            outpoint: OutPoint::default(),
+            expiry: Some(10),
+        }];
+        persister.set_locked_utxos(&locked).unwrap();
+        assert_eq!(persister.get_locked_utxos().unwrap(), locked);
+
+        // The locked utxos file doesn't interfere with the updates
+        let persister = FsPersister::new(&tempdir, network, &desc).unwrap();
+        assert_eq!(persister.get_locked_utxos().unwrap(), locked);
+        assert!(persister.get(0).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_fs_persister_watched_addresses() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let desc = wollet_descriptor_test_vector();
+        let network = ElementsNetwork::LiquidTestnet;
+        let persister = FsPersister::new(&tempdir, network, &desc).unwrap();
+        assert!(persister.get_watched_addresses().unwrap().is_empty());
+
+        let blinding_key = crate::secp256k1::SecretKey::from_slice(&[1; 32]).unwrap();
+        let address = desc.address(0, network.address_params()).unwrap();
+        let blinding_pubkey = blinding_key.public_key(&crate::EC);
+        let watched = vec![WatchedAddress::new(
+            &address.to_unconfidential().to_confidential(blinding_pubkey),
+            blinding_key,
+        )
+        .unwrap()];
+        persister.set_watched_addresses(&watched).unwrap();
+        let persister = FsPersister::new(&tempdir, network, &desc).unwrap();
+        assert_eq!(persister.get_watched_addresses().unwrap(), watched);
+        assert!(persister.get(0).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_fs_persister_birthday() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let desc = wollet_descriptor_test_vector();
+        let network = ElementsNetwork::LiquidTestnet;
+        let persister = FsPersister::new(&tempdir, network, &desc).unwrap();
+        assert_eq!(persister.get_birthday().unwrap(), None);
+
+        persister.set_birthday(Some(100)).unwrap();
+        let persister = FsPersister::new(&tempdir, network, &desc).unwrap();
+        assert_eq!(persister.get_birthday().unwrap(), Some(100));
+        persister.set_birthday(None).unwrap();
+        assert_eq!(persister.get_birthday().unwrap(), None);
+    }
+
     #[test]
     fn test_memory_persister() {
         let persister = MemoryPersister::new();
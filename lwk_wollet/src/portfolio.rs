@@ -0,0 +1,267 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use lwk_common::precision::Precision;
+use serde::{Deserialize, Serialize};
+
+use crate::elements::AssetId;
+use crate::error::Error;
+use crate::model::{IssuanceDetails, WalletTx, WalletTxOut};
+use crate::registry::Contract;
+use crate::wollet::Wollet;
+use crate::TxQuery;
+
+/// Confirmations needed to spend a coinbase output
+const COINBASE_MATURITY: u32 = 100;
+
+/// The status of a wallet output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UtxoStatus {
+    Confirmed,
+
+    /// Unconfirmed, in a transaction spending wallet outputs, e.g. our change
+    TrustedPending,
+
+    /// Unconfirmed, in a transaction created by someone else
+    UntrustedPending,
+
+    /// Can't be spent, e.g. an immature coinbase output, a locked utxo or an output to a
+    /// watched address
+    Frozen,
+}
+
+/// The balance of an asset, split by the status of the outputs holding it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetBalance {
+    /// Amount in confirmed outputs
+    pub confirmed: u64,
+
+    /// Amount in unconfirmed outputs of transactions spending wallet outputs, like our change
+    pub trusted_pending: u64,
+
+    /// Amount in unconfirmed outputs of transactions created by others
+    pub untrusted_pending: u64,
+
+    /// Amount in outputs that can't be spent, like immature coinbase outputs, locked utxos
+    /// or outputs to watched addresses
+    pub frozen: u64,
+}
+
+impl AssetBalance {
+    pub fn total(&self) -> u64 {
+        self.confirmed + self.trusted_pending + self.untrusted_pending + self.frozen
+    }
+
+    /// Amount that can be spent without relying on transactions created by others to confirm
+    pub fn trusted_spendable(&self) -> u64 {
+        self.confirmed + self.trusted_pending
+    }
+
+    fn add(&mut self, status: UtxoStatus, value: u64) {
+        match status {
+            UtxoStatus::Confirmed => self.confirmed += value,
+            UtxoStatus::TrustedPending => self.trusted_pending += value,
+            UtxoStatus::UntrustedPending => self.untrusted_pending += value,
+            UtxoStatus::Frozen => self.frozen += value,
+        }
+    }
+}
+
+/// An asset held by the wallet, with its balance and metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioAsset {
+    pub asset: AssetId,
+
+    pub balance: AssetBalance,
+
+    /// The ticker, if the contract of the asset is known
+    pub ticker: Option<String>,
+
+    /// The name, if the contract of the asset is known
+    pub name: Option<String>,
+
+    /// The precision, 0 if the contract of the asset is not known
+    pub precision: u8,
+
+    /// The issuance of the asset, or of the asset of this reissuance token,
+    /// if it was made by the wallet
+    pub issuance: Option<IssuanceDetails>,
+}
+
+impl PortfolioAsset {
+    /// Format an amount of this asset according to its precision
+    ///
+    /// For example 100 satoshi of an asset with precision 2 is "1.00"
+    pub fn format(&self, satoshi: u64) -> String {
+        match Precision::new(self.precision) {
+            Ok(precision) => precision.sats_to_string(satoshi as i64),
+            Err(_) => satoshi.to_string(),
+        }
+    }
+}
+
+impl Wollet {
+    /// The wallet utxos with their status
+    pub(crate) fn utxos_with_status(&self) -> Result<Vec<(WalletTxOut, UtxoStatus)>, Error> {
+        let tip = self.tip().height();
+        // The utxos are derived from the txos, which are needed anyway to know if unconfirmed
+        // transactions spend wallet outputs
+        let txos = self.txos()?;
+        let spent = self.store.spent()?;
+        let mut utxos: Vec<_> = txos
+            .values()
+            .filter(|txo| !spent.contains(&txo.outpoint))
+            .cloned()
+            .collect();
+        utxos.sort_by(|a, b| b.unblinded.value.cmp(&a.unblinded.value));
+        let locked: HashSet<_> = self
+            .locked_among(&utxos)?
+            .iter()
+            .map(|l| l.outpoint)
+            .collect();
+        let mut result = vec![];
+        for utxo in utxos {
+            let tx = self.store.cache.all_txs.get(&utxo.outpoint.txid);
+            let status = match (utxo.height, tx) {
+                _ if locked.contains(&utxo.outpoint) => UtxoStatus::Frozen,
+                _ if self.watched.contains_key(&utxo.script_pubkey) => UtxoStatus::Frozen,
+                (Some(height), Some(tx))
+                    if tx.is_coinbase() && tip + 1 < height + COINBASE_MATURITY =>
+                {
+                    UtxoStatus::Frozen
+                }
+                (Some(_), _) => UtxoStatus::Confirmed,
+                (None, Some(tx))
+                    if tx
+                        .input
+                        .iter()
+                        .any(|i| txos.contains_key(&i.previous_output)) =>
+                {
+                    UtxoStatus::TrustedPending
+                }
+                (None, _) => UtxoStatus::UntrustedPending,
+            };
+            result.push((utxo, status));
+        }
+        Ok(result)
+    }
+
+    /// The wallet utxos that can be spent, sorted by value,
+    /// if `only_trusted` unconfirmed utxos received from others are excluded
+    pub(crate) fn spendable_utxos(&self, only_trusted: bool) -> Result<Vec<WalletTxOut>, Error> {
+        Ok(self
+            .utxos_with_status()?
+            .into_iter()
+            .filter(|(_, status)| match status {
+                UtxoStatus::Frozen => false,
+                UtxoStatus::UntrustedPending => !only_trusted,
+                UtxoStatus::Confirmed | UtxoStatus::TrustedPending => true,
+            })
+            .map(|(utxo, _)| utxo)
+            .collect())
+    }
+
+    /// Get the balance of every asset, split by the status of the outputs holding it
+    ///
+    /// Contrary to [`Wollet::balance()`], which sums all the wallet utxos, this allows to know
+    /// how much can be spent without waiting for others' transactions to confirm.
+    pub fn balance_details(&self) -> Result<BTreeMap<AssetId, AssetBalance>, Error> {
+        let mut balances = BTreeMap::new();
+        balances.insert(self.policy_asset(), AssetBalance::default());
+        for (utxo, status) in self.utxos_with_status()? {
+            let balance: &mut AssetBalance = balances.entry(utxo.unblinded.asset).or_default();
+            balance.add(status, utxo.unblinded.value);
+        }
+        Ok(balances)
+    }
+
+    /// Get the balance and metadata of every asset held by the wallet, policy asset first
+    ///
+    /// Tickers, names and precisions are taken from `contracts`, which may come for instance
+    /// from a [`crate::RegistryCache`], the policy asset is described with the ticker and name of
+    /// the wallet network, see [`crate::ElementsNetwork::policy_asset_ticker`].
+    pub fn portfolio<'a>(
+        &self,
+        contracts: impl IntoIterator<Item = (&'a AssetId, &'a Contract)>,
+    ) -> Result<Vec<PortfolioAsset>, Error> {
+        let contracts: HashMap<_, _> = contracts.into_iter().collect();
+        let network = self.network();
+        let policy_asset = network.policy_asset();
+        let balances = self.balance_details()?;
+
+        let mut issuances = HashMap::new();
+        for issuance in self.issuances()?.into_iter().filter(|i| !i.is_reissuance) {
+            issuances.insert(issuance.token, issuance.clone());
+            issuances.insert(issuance.asset, issuance);
+        }
+
+        let mut portfolio: Vec<_> = balances
+            .into_iter()
+            .map(|(asset, balance)| {
+                let (ticker, name, precision) = if asset == policy_asset {
+                    (
+                        Some(network.policy_asset_ticker().to_string()),
+                        Some(network.policy_asset_name().to_string()),
+                        8,
+                    )
+                } else if let Some(contract) = contracts.get(&asset) {
+                    (
+                        Some(contract.ticker.clone()),
+                        Some(contract.name.clone()),
+                        contract.precision,
+                    )
+                } else {
+                    (None, None, 0)
+                };
+                PortfolioAsset {
+                    asset,
+                    balance,
+                    ticker,
+                    name,
+                    precision,
+                    issuance: issuances.remove(&asset),
+                }
+            })
+            .collect();
+        portfolio.sort_by_key(|a| a.asset != policy_asset);
+        Ok(portfolio)
+    }
+
+    /// Get the wallet transactions changing the balance of the given asset
+    pub fn asset_transactions(&self, asset: &AssetId) -> Result<Vec<WalletTx>, Error> {
+        Ok(self.query_transactions(&TxQuery::new().asset(*asset))?.txs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AssetBalance, ElementsNetwork, NoPersist, Wollet};
+
+    #[test]
+    fn test_portfolio() {
+        let desc = "ct(slip77(9c8e4f05c7711a98c838be228bcb84924d4570ca53f35fa1c793e58841d47023),elwpkh([73c5da0a/84'/1'/0']tpubDC8msFGeGuwnKG9Upg7DM2b4DaRqg3CUZa5g8v2SRQ6K4NSkxUgd7HsL2XVWbVm39yBA4LAxysQAm397zwQSQoQgewGiYZqrA9DsP4zbQ1M/<0;1>/*))";
+        let network = ElementsNetwork::LiquidTestnet;
+        let wollet = Wollet::new(network, NoPersist::new(), desc.parse().unwrap()).unwrap();
+
+        // The policy asset is always present
+        let portfolio = wollet.portfolio([]).unwrap();
+        assert_eq!(portfolio.len(), 1);
+        let lbtc = &portfolio[0];
+        assert_eq!(lbtc.asset, network.policy_asset());
+        assert_eq!(lbtc.ticker.as_deref(), Some("tL-BTC"));
+        assert_eq!(lbtc.name.as_deref(), Some("Testnet Liquid Bitcoin"));
+        assert_eq!(lbtc.balance, AssetBalance::default());
+        assert_eq!(lbtc.format(100_000_000), "1.00000000");
+        assert!(wollet.asset_transactions(&lbtc.asset).unwrap().is_empty());
+
+        let balance = AssetBalance {
+            confirmed: 1,
+            trusted_pending: 2,
+            untrusted_pending: 3,
+            frozen: 4,
+        };
+        assert_eq!(balance.total(), 10);
+        assert_eq!(balance.trusted_spendable(), 3);
+        assert!(wollet.spendable_utxos(true).unwrap().is_empty());
+        assert_eq!(wollet.balance_details().unwrap().len(), 1);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::bitcoin::PublicKey as BitcoinPublicKey;
-use crate::elements::confidential::AssetBlindingFactor;
+use crate::elements::confidential::{AssetBlindingFactor, ValueBlindingFactor};
 use crate::elements::issuance::ContractHash;
 use crate::elements::pset::{Input, Output, PartiallySignedTransaction};
 use crate::elements::{Address, AssetId, OutPoint, Transaction, TxOut, TxOutSecrets, Txid};
@@ -8,28 +8,21 @@ use crate::hashes::Hash;
 use crate::model::{Recipient, WalletTxOut};
 use crate::registry::Contract;
 use crate::wollet::Wollet;
-use crate::ElementsNetwork;
+use crate::{ElementsNetwork, EC};
 use elements::pset::elip100::AssetMetadata;
+use elements::secp256k1_zkp::{Generator, PedersenCommitment, RangeProof, SecretKey};
+use elements::{BlindValueProofs, RangeProofMessage};
+use lwk_common::{derive_blinding_key, issuance_blinding_script};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 #[derive(Debug, Serialize, Deserialize)]
-// We make issuance and reissuance are mutually exclusive for simplicity
 pub enum IssuanceRequest {
-    None,
     Issuance(u64, Option<Address>, u64, Option<Address>, Option<Contract>),
     Reissuance(AssetId, u64, Option<Address>, Option<Transaction>),
 }
 
 impl Wollet {
-    pub(crate) fn asset_utxos(&self, asset: &AssetId) -> Result<Vec<WalletTxOut>, Error> {
-        Ok(self
-            .utxos()?
-            .into_iter()
-            .filter(|utxo| &utxo.unblinded.asset == asset)
-            .collect())
-    }
-
     fn get_tx(&self, txid: &Txid) -> Result<Transaction, Error> {
         Ok(self
             .store
@@ -40,7 +33,7 @@ impl Wollet {
             .clone())
     }
 
-    fn get_txout(&self, outpoint: &OutPoint) -> Result<TxOut, Error> {
+    pub(crate) fn get_txout(&self, outpoint: &OutPoint) -> Result<TxOut, Error> {
         Ok(self
             .get_tx(&outpoint.txid)?
             .output
@@ -89,8 +82,8 @@ impl Wollet {
         // Needed by ledger
         let mut rng = rand::thread_rng();
         let secp = elements::secp256k1_zkp::Secp256k1::new();
-        use elements::secp256k1_zkp::{RangeProof, SurjectionProof};
-        use elements::{BlindAssetProofs, BlindValueProofs};
+        use elements::secp256k1_zkp::SurjectionProof;
+        use elements::BlindAssetProofs;
 
         input.asset = Some(utxo.unblinded.asset);
         input.blind_asset_proof = Some(Box::new(
@@ -177,6 +170,47 @@ impl Wollet {
         Ok(())
     }
 
+    /// Blind the (re)issuance amounts of the input `idx`, returning the issued asset and token
+    ///
+    /// The secrets of the issuance values are added to `issuance_txout_sec`, they must be passed
+    /// to [`blinding_secrets`] so that the transaction is balanced when blinding.
+    pub(crate) fn blind_issuance(
+        &self,
+        pset: &mut PartiallySignedTransaction,
+        idx: usize,
+        issuance_txout_sec: &mut Vec<TxOutSecrets>,
+    ) -> Result<(AssetId, AssetId), Error> {
+        let input = pset
+            .inputs_mut()
+            .get_mut(idx)
+            .ok_or_else(|| Error::MissingVin)?;
+        let prevout = OutPoint::new(input.previous_txid, input.previous_output_index);
+        let blinding_key =
+            derive_blinding_key(self.descriptor(), &issuance_blinding_script(&prevout))
+                .ok_or_else(|| Error::MissingPrivateBlindingKey)?;
+
+        // The token id depends on the issuance being confidential
+        let (asset, _) = input.issuance_ids();
+        if let Some(satoshi) = input.issuance_value_amount {
+            let (comm, rangeproof, proof, value_bf) =
+                blind_issuance_value(satoshi, asset, blinding_key)?;
+            input.issuance_value_comm = Some(comm);
+            input.issuance_value_rangeproof = Some(Box::new(rangeproof));
+            input.in_issuance_blind_value_proof = Some(Box::new(proof));
+            issuance_txout_sec.push(issuance_secrets(asset, satoshi, value_bf));
+        }
+        let (_, token) = input.issuance_ids();
+        if let Some(satoshi) = input.issuance_inflation_keys {
+            let (comm, rangeproof, proof, value_bf) =
+                blind_issuance_value(satoshi, token, blinding_key)?;
+            input.issuance_inflation_keys_comm = Some(comm);
+            input.issuance_keys_rangeproof = Some(Box::new(rangeproof));
+            input.in_issuance_blind_inflation_keys_proof = Some(Box::new(proof));
+            issuance_txout_sec.push(issuance_secrets(token, satoshi, value_bf));
+        }
+        Ok((asset, token))
+    }
+
     pub(crate) fn addressee_change(
         &self,
         satoshi: u64,
@@ -200,6 +234,70 @@ impl Wollet {
     }
 }
 
+/// Commit to an issuance value as Elements Core does, returning also the rangeproof, which can
+/// be rewound with `blinding_key`, the PSET blind value proof and the value blinding factor
+fn blind_issuance_value(
+    satoshi: u64,
+    asset: AssetId,
+    blinding_key: SecretKey,
+) -> Result<
+    (
+        PedersenCommitment,
+        RangeProof,
+        RangeProof,
+        ValueBlindingFactor,
+    ),
+    Error,
+> {
+    let mut rng = rand::thread_rng();
+    let gen = Generator::new_unblinded(&EC, asset.into_tag());
+    let value_bf = ValueBlindingFactor::new(&mut rng);
+    let comm = PedersenCommitment::new(&EC, satoshi, value_bf.into_inner(), gen);
+    let message = RangeProofMessage {
+        asset,
+        bf: AssetBlindingFactor::zero(),
+    };
+    let rangeproof = RangeProof::new(
+        &EC,
+        1,
+        comm,
+        satoshi,
+        value_bf.into_inner(),
+        &message.to_bytes(),
+        &[],
+        blinding_key,
+        0,
+        52,
+        gen,
+    )?;
+    let proof = RangeProof::blind_value_proof(&mut rng, &EC, satoshi, comm, gen, value_bf)?;
+    Ok((comm, rangeproof, proof, value_bf))
+}
+
+/// The secrets to pass to [`PartiallySignedTransaction::blind_last`] for a transaction with
+/// `n_inputs` inputs, whose secrets are `inp_txout_sec`, and blinded issuances
+///
+/// The blinding factors of all the secrets are balanced, while only the keys lower than
+/// `n_inputs` refer to inputs, thus the issuance secrets are put after them.
+pub(crate) fn blinding_secrets(
+    n_inputs: usize,
+    inp_txout_sec: &HashMap<usize, TxOutSecrets>,
+    issuance_txout_sec: &[TxOutSecrets],
+) -> HashMap<usize, TxOutSecrets> {
+    let mut secrets = inp_txout_sec.clone();
+    secrets.extend((n_inputs..).zip(issuance_txout_sec.iter().cloned()));
+    secrets
+}
+
+fn issuance_secrets(asset: AssetId, value: u64, value_bf: ValueBlindingFactor) -> TxOutSecrets {
+    TxOutSecrets {
+        asset,
+        asset_bf: AssetBlindingFactor::zero(),
+        value,
+        value_bf,
+    }
+}
+
 fn convert_pubkey(pk: crate::elements::secp256k1_zkp::PublicKey) -> BitcoinPublicKey {
     BitcoinPublicKey::new(pk)
 }
@@ -215,7 +313,19 @@ pub(crate) fn validate_address(address: &str, network: ElementsNetwork) -> Resul
 
 #[cfg(test)]
 mod test {
-    use crate::{pset_create::validate_address, ElementsNetwork};
+    use std::collections::HashMap;
+
+    use elements::confidential::{AssetBlindingFactor, ValueBlindingFactor};
+    use elements::pset::{Input, PartiallySignedTransaction};
+    use elements::secp256k1_zkp::Generator;
+    use elements::{OutPoint, Script, TxOut, TxOutSecrets};
+
+    use crate::model::Recipient;
+    use crate::tx_builder::extract_issuances;
+    use crate::{
+        pset_create::{blinding_secrets, validate_address},
+        ElementsNetwork, NoPersist, Wollet, EC,
+    };
 
     #[test]
     fn test_validate() {
@@ -227,4 +337,96 @@ mod test {
         let network = ElementsNetwork::Liquid;
         assert!(validate_address(testnet_address, network).is_err())
     }
+
+    #[test]
+    fn test_blind_issuance() {
+        let network = ElementsNetwork::LiquidTestnet;
+        let desc = "ct(slip77(9c8e4f05c7711a98c838be228bcb84924d4570ca53f35fa1c793e58841d47023),elwpkh([73c5da0a/84'/1'/0']tpubDC8msFGeGuwnKG9Upg7DM2b4DaRqg3CUZa5g8v2SRQ6K4NSkxUgd7HsL2XVWbVm39yBA4LAxysQAm397zwQSQoQgewGiYZqrA9DsP4zbQ1M/<0;1>/*))";
+        let wollet = Wollet::new(network, NoPersist::new(), desc.parse().unwrap()).unwrap();
+        let policy_asset = network.policy_asset();
+
+        // Spend an explicit L-BTC output
+        let satoshi_in = 10_000;
+        let prevout = OutPoint::new(
+            "0000000000000000000000000000000000000000000000000000000000000001"
+                .parse()
+                .unwrap(),
+            0,
+        );
+        let address = wollet.address(Some(0)).unwrap();
+        let txout = TxOut::new_fee(satoshi_in, policy_asset);
+        let mut pset = PartiallySignedTransaction::new_v2();
+        let mut input = Input::from_prevout(prevout);
+        input.witness_utxo = Some(txout.clone());
+        pset.add_input(input);
+        let mut inp_txout_sec = HashMap::new();
+        inp_txout_sec.insert(
+            0,
+            TxOutSecrets::new(
+                policy_asset,
+                AssetBlindingFactor::zero(),
+                satoshi_in,
+                ValueBlindingFactor::zero(),
+            ),
+        );
+
+        let (satoshi_asset, satoshi_token, fee) = (1_000, 1, 100);
+        let (asset, explicit_token) = wollet
+            .set_issuance(&mut pset, 0, satoshi_asset, satoshi_token, None)
+            .unwrap();
+        let mut issuance_txout_sec = vec![];
+        let (asset_b, token) = wollet
+            .blind_issuance(&mut pset, 0, &mut issuance_txout_sec)
+            .unwrap();
+        assert_eq!(issuance_txout_sec.len(), 2);
+        assert_eq!(asset, asset_b);
+        assert_ne!(explicit_token, token);
+        for (satoshi, asset) in [
+            (satoshi_asset, asset),
+            (satoshi_token, token),
+            (satoshi_in - fee, policy_asset),
+        ] {
+            let recipient = Recipient::from_address(satoshi, address.address(), asset);
+            wollet.add_output(&mut pset, &recipient).unwrap();
+        }
+        pset.add_output(elements::pset::Output::new_explicit(
+            Script::default(),
+            fee,
+            policy_asset,
+            None,
+        ));
+        let secrets = blinding_secrets(pset.n_inputs(), &inp_txout_sec, &issuance_txout_sec);
+        pset.blind_last(&mut rand::thread_rng(), &EC, &secrets)
+            .unwrap();
+
+        let issuance = lwk_common::Issuance::new(&pset.inputs()[0]);
+        assert!(issuance.is_confidential());
+        assert_eq!(issuance.asset_satoshi(), Some(satoshi_asset));
+        assert_eq!(issuance.token_satoshi(), Some(satoshi_token));
+
+        let tx = pset.extract_tx().unwrap();
+        tx.verify_tx_amt_proofs(&EC, &[txout]).unwrap();
+        let issuance = &tx.input[0].asset_issuance;
+        assert!(issuance.amount.is_confidential());
+        assert!(issuance.inflation_keys.is_confidential());
+        let gen = Generator::new_unblinded(&EC, asset.into_tag());
+        let rangeproof = tx.input[0].witness.amount_rangeproof.as_ref().unwrap();
+        let comm = issuance.amount.commitment().unwrap();
+        rangeproof.verify(&EC, comm, &[], gen).unwrap();
+
+        let details = &extract_issuances(&tx, wollet.descriptor())[0];
+        assert_eq!((details.asset, details.token), (asset, token));
+        assert!(details.is_confidential);
+        assert_eq!(details.asset_amount, Some(satoshi_asset));
+        assert_eq!(details.token_amount, Some(satoshi_token));
+        assert!(details.asset_blinder.is_some());
+
+        // Other wallets can't unblind the amounts
+        let other = desc.replace("9c8e", "9c8f");
+        let other = Wollet::new(network, NoPersist::new(), other.parse().unwrap()).unwrap();
+        let details = &extract_issuances(&tx, other.descriptor())[0];
+        assert!(details.is_confidential);
+        assert!(details.asset_amount.is_none());
+        assert!(details.token_amount.is_none());
+    }
 }
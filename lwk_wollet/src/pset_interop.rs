@@ -0,0 +1,163 @@
+//! Interoperability with PSETs and transactions created by other software
+//!
+//! LWK creates PSETs with [`Wollet::tx_builder`], which include everything signers and
+//! blinders need. Transactions and PSETs coming from other tools, for instance the
+//! `createrawtransaction` and `createpsbt` RPCs of Elements Core, usually do not have the
+//! previous outputs nor the key derivations, so they are completed with the wallet data.
+//!
+//! Only PSET version 2 is supported, as the version 0 PSBT format has never been defined for
+//! Elements. Blinding data exchanged separately from the transaction, for instance the
+//! unblinded values of inputs not owned by the wallet, is not imported: such inputs can be
+//! added with [`crate::TxBuilder::add_external_utxos`] instead.
+
+use crate::elements::pset::PartiallySignedTransaction;
+use crate::elements::{OutPoint, Transaction};
+use crate::error::Error;
+use crate::wollet::Wollet;
+
+impl Wollet {
+    /// Create a PSET from an unsigned raw transaction, filled with the wallet data
+    ///
+    /// Unblinded outputs having a blinding public key in the nonce, as created by
+    /// `createrawtransaction` for confidential addresses, are blinded by the first input
+    /// owned by the wallet.
+    pub fn pset_from_unsigned_tx(
+        &self,
+        tx: &Transaction,
+    ) -> Result<PartiallySignedTransaction, Error> {
+        if tx
+            .input
+            .iter()
+            .any(|i| !i.witness.is_empty() || !i.script_sig.is_empty())
+        {
+            return Err(Error::Generic("Transaction is signed".into()));
+        }
+        let mut pset = PartiallySignedTransaction::from_tx(tx.clone());
+        self.import_pset(&mut pset)?;
+
+        let blinder_index = pset
+            .inputs()
+            .iter()
+            .position(|i| {
+                i.witness_utxo
+                    .as_ref()
+                    .is_some_and(|txout| self.index(&txout.script_pubkey).is_ok())
+            })
+            .map(|i| i as u32);
+        for output in pset.outputs_mut() {
+            if output.blinding_key.is_some() && output.blinder_index.is_none() {
+                output.blinder_index = blinder_index;
+            }
+        }
+        Ok(pset)
+    }
+
+    /// Complete a PSET created by other software with the wallet data
+    ///
+    /// Inputs spending transactions known by the wallet get `witness_utxo` and
+    /// `in_utxo_rangeproof`, then inputs and outputs belonging to the wallet get their
+    /// key derivations as in [`Wollet::add_details`].
+    pub fn import_pset(&self, pset: &mut PartiallySignedTransaction) -> Result<(), Error> {
+        for input in pset.inputs_mut() {
+            // Converted raw transactions have empty final fields, which mean "finalized"
+            if input
+                .final_script_sig
+                .as_ref()
+                .is_some_and(|s| s.is_empty())
+            {
+                input.final_script_sig = None;
+            }
+            if input
+                .final_script_witness
+                .as_ref()
+                .is_some_and(|w| w.is_empty())
+            {
+                input.final_script_witness = None;
+            }
+            if input.witness_utxo.is_some() {
+                continue;
+            }
+            let outpoint = OutPoint::new(input.previous_txid, input.previous_output_index);
+            match self.get_txout(&outpoint) {
+                Ok(mut txout) => {
+                    input.in_utxo_rangeproof = txout.witness.rangeproof.take();
+                    input.witness_utxo = Some(txout);
+                }
+                Err(Error::MissingTransaction) => (),
+                Err(e) => return Err(e),
+            }
+        }
+        self.add_details(pset)
+    }
+
+    /// Export a PSET in the subset of fields accepted by `walletprocesspsbt` of Elements Core
+    ///
+    /// The PSET is completed with [`Wollet::import_pset`], every input must have its previous
+    /// output. Proprietary and unknown fields, like the asset metadata used by hardware
+    /// signers, are removed.
+    pub fn export_pset(
+        &self,
+        pset: &PartiallySignedTransaction,
+    ) -> Result<PartiallySignedTransaction, Error> {
+        let mut pset = pset.clone();
+        self.import_pset(&mut pset)?;
+        if let Some(vin) = pset.inputs().iter().position(|i| i.witness_utxo.is_none()) {
+            return Err(Error::Generic(format!(
+                "Input {vin} is missing the previous output"
+            )));
+        }
+
+        pset.global.proprietary.clear();
+        pset.global.unknown.clear();
+        for input in pset.inputs_mut() {
+            input.proprietary.clear();
+            input.unknown.clear();
+        }
+        for output in pset.outputs_mut() {
+            output.proprietary.clear();
+            output.unknown.clear();
+        }
+        Ok(pset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::elements::{LockTime, OutPoint, Transaction, TxIn};
+    use crate::{ElementsNetwork, NoPersist, Wollet, WolletDescriptor};
+
+    #[test]
+    fn test_pset_interop() {
+        let desc = "ct(slip77(9c8e4f05c7711a98c838be228bcb84924d4570ca53f35fa1c793e58841d47023),elwpkh([73c5da0a/84'/1'/0']tpubDC8msFGeGuwnKG9Upg7DM2b4DaRqg3CUZa5g8v2SRQ6K4NSkxUgd7HsL2XVWbVm39yBA4LAxysQAm397zwQSQoQgewGiYZqrA9DsP4zbQ1M/<0;1>/*))";
+        let desc: WolletDescriptor = desc.parse().unwrap();
+        let wollet = Wollet::new(ElementsNetwork::LiquidTestnet, NoPersist::new(), desc).unwrap();
+
+        // An unsigned transaction spending an output unknown to the wallet
+        let mut tx = Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::default(),
+                ..Default::default()
+            }],
+            output: vec![],
+        };
+
+        let pset = wollet.pset_from_unsigned_tx(&tx).unwrap();
+        let input = &pset.inputs()[0];
+        assert!(input.final_script_sig.is_none());
+        assert!(input.final_script_witness.is_none());
+        assert!(input.witness_utxo.is_none());
+        let err = wollet.export_pset(&pset).unwrap_err();
+        assert_eq!(err.to_string(), "Input 0 is missing the previous output");
+
+        tx.input[0].witness.script_witness.push(vec![1]);
+        let err = wollet.pset_from_unsigned_tx(&tx).unwrap_err();
+        assert_eq!(err.to_string(), "Transaction is signed");
+
+        tx.input[0].witness.script_witness.clear();
+        tx.input[0].script_sig = vec![1].into();
+        let err = wollet.pset_from_unsigned_tx(&tx).unwrap_err();
+        assert_eq!(err.to_string(), "Transaction is signed");
+    }
+}
@@ -0,0 +1,211 @@
+use std::cmp::Ordering;
+
+use crate::elements::{Address, AssetId, Script, Transaction, Txid};
+use crate::error::Error;
+use crate::model::{WalletTx, WalletTxSummary};
+use crate::store::Height;
+use crate::wollet::{tx_balance, tx_fee, tx_inputs, tx_outputs, tx_type, Wollet};
+
+/// A query on the wallet transactions, see [`Wollet::query_transactions()`]
+///
+/// By default all the transactions are returned.
+#[derive(Debug, Clone, Default)]
+pub struct TxQuery {
+    offset: usize,
+    limit: Option<usize>,
+    asset: Option<AssetId>,
+    type_: Option<String>,
+    min_height: Option<Height>,
+    max_height: Option<Height>,
+    script_pubkey: Option<Script>,
+}
+
+impl TxQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Skip the first `offset` matching transactions
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Return at most `limit` transactions
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Only the transactions changing the balance of the given asset
+    pub fn asset(mut self, asset: AssetId) -> Self {
+        self.asset = Some(asset);
+        self
+    }
+
+    /// Only the transactions of the given type, see [`WalletTx::type_`]
+    pub fn tx_type(mut self, type_: &str) -> Self {
+        self.type_ = Some(type_.to_string());
+        self
+    }
+
+    /// Only the transactions confirmed at this height or later, or unconfirmed
+    pub fn min_height(mut self, height: Height) -> Self {
+        self.min_height = Some(height);
+        self
+    }
+
+    /// Only the transactions confirmed at this height or earlier, unconfirmed ones are excluded
+    pub fn max_height(mut self, height: Height) -> Self {
+        self.max_height = Some(height);
+        self
+    }
+
+    /// Only the transactions sending to or spending from the given address
+    pub fn address(mut self, address: &Address) -> Self {
+        self.script_pubkey = Some(address.script_pubkey());
+        self
+    }
+
+    fn matches_height(&self, height: Option<Height>) -> bool {
+        match height {
+            Some(h) => {
+                self.min_height.map_or(true, |m| h >= m) && self.max_height.map_or(true, |m| h <= m)
+            }
+            None => self.max_height.is_none(),
+        }
+    }
+}
+
+/// A page of wallet transactions returned by [`Wollet::query_transactions()`]
+/// or [`Wollet::query_transaction_summaries()`]
+#[derive(Debug, Clone)]
+pub struct TxPage<T = WalletTx> {
+    /// The transactions in the page, most recent first
+    pub txs: Vec<T>,
+
+    /// The number of transactions matching the query, regardless of offset and limit
+    pub total: usize,
+}
+
+impl Wollet {
+    /// Get the wallet transactions matching the query, most recent first
+    ///
+    /// All the wallet transactions are inspected to be filtered and counted, while the inputs
+    /// and outputs details are computed only for the transactions in the requested page.
+    pub fn query_transactions(&self, query: &TxQuery) -> Result<TxPage, Error> {
+        self.query_inner(query, |tx, summary| summary.with_tx(tx.clone()))
+    }
+
+    /// Get the wallet transactions matching the query, most recent first, without the raw
+    /// transactions
+    pub fn query_transaction_summaries(
+        &self,
+        query: &TxQuery,
+    ) -> Result<TxPage<WalletTxSummary>, Error> {
+        self.query_inner(query, |_, summary| summary)
+    }
+
+    fn query_inner<T>(
+        &self,
+        query: &TxQuery,
+        f: impl Fn(&Transaction, WalletTxSummary) -> T,
+    ) -> Result<TxPage<T>, Error> {
+        let mut my_txids: Vec<(&Txid, &Option<Height>)> = self.store.cache.heights.iter().collect();
+        my_txids.sort_by(|a, b| {
+            let height_cmp =
+                b.1.unwrap_or(std::u32::MAX)
+                    .cmp(&a.1.unwrap_or(std::u32::MAX));
+            match height_cmp {
+                Ordering::Equal => b.0.cmp(a.0),
+                h => h,
+            }
+        });
+
+        let txos = self.txos()?;
+        let policy_asset = self.policy_asset();
+        let mut total = 0;
+        let mut txs = vec![];
+        for (txid, height) in my_txids {
+            if !query.matches_height(*height) {
+                continue;
+            }
+            let tx = self
+                .store
+                .cache
+                .all_txs
+                .get(txid)
+                .ok_or_else(|| Error::Generic(format!("list_tx no tx {}", txid)))?;
+
+            let balance = tx_balance(*txid, tx, &txos);
+            if balance.is_empty() {
+                // Transaction has no output or input that the wollet can unblind,
+                // ignore this transaction
+                continue;
+            }
+            if let Some(asset) = &query.asset {
+                if !balance.contains_key(asset) {
+                    continue;
+                }
+            }
+            let fee = tx_fee(tx);
+            let type_ = tx_type(tx, &policy_asset, &balance, fee);
+            if let Some(t) = &query.type_ {
+                if t != &type_ {
+                    continue;
+                }
+            }
+            if let Some(script_pubkey) = &query.script_pubkey {
+                let in_outputs = tx.output.iter().any(|o| &o.script_pubkey == script_pubkey);
+                let in_inputs = tx.input.iter().any(|i| {
+                    txos.get(&i.previous_output)
+                        .map_or(false, |o| &o.script_pubkey == script_pubkey)
+                });
+                if !in_outputs && !in_inputs {
+                    continue;
+                }
+            }
+
+            total += 1;
+            if total <= query.offset || query.limit.map_or(false, |l| txs.len() >= l) {
+                continue;
+            }
+            let timestamp = height.and_then(|h| self.store.cache.timestamps.get(&h).cloned());
+            let summary = WalletTxSummary {
+                txid: *txid,
+                height: *height,
+                balance,
+                fee,
+                type_,
+                timestamp,
+                inputs: tx_inputs(tx, &txos),
+                outputs: tx_outputs(*txid, tx, &txos),
+            };
+            txs.push(f(tx, summary));
+        }
+
+        Ok(TxPage { txs, total })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TxQuery;
+
+    #[test]
+    fn test_tx_query_heights() {
+        let query = TxQuery::new();
+        assert!(query.matches_height(None));
+        assert!(query.matches_height(Some(1)));
+
+        let query = TxQuery::new().min_height(10);
+        assert!(query.matches_height(None));
+        assert!(query.matches_height(Some(10)));
+        assert!(!query.matches_height(Some(9)));
+
+        let query = TxQuery::new().min_height(10).max_height(20);
+        assert!(!query.matches_height(None));
+        assert!(query.matches_height(Some(20)));
+        assert!(!query.matches_height(Some(21)));
+    }
+}
@@ -1,42 +1,49 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use crate::domain::verify_domain_name;
 use crate::elements::hashes::{sha256, Hash};
-use crate::elements::{AssetId, ContractHash, OutPoint};
+use crate::elements::{AssetId, ContractHash, OutPoint, Txid};
 use crate::error::Error;
 use crate::util::{serde_from_hex, serde_to_hex, verify_pubkey};
 use once_cell::sync::Lazy;
 use regex_lite::Regex;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Map, Value};
 
 static RE_NAME: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[[:ascii:]]{1,255}$").expect("static"));
 static RE_TICKER: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"^[a-zA-Z0-9.\-]{3,24}$").expect("static"));
 
+/// The entity issuing the asset
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum Entity {
     #[serde(rename = "domain")]
     Domain(String),
+
+    /// An entity type unknown to this version, kept as is so that it contributes to the contract
+    /// hash
+    #[serde(untagged)]
+    Other(Map<String, Value>),
 }
 
 impl Entity {
-    pub fn domain(&self) -> &str {
+    /// The domain of the entity, if it's a domain
+    pub fn domain(&self) -> Option<&str> {
         match self {
-            Entity::Domain(d) => d.as_str(),
+            Entity::Domain(d) => Some(d.as_str()),
+            Entity::Other(_) => None,
         }
     }
 }
 
-// TODO: should we allow the caller to set extra arbitrary fields? if so how should we treat them?
-// should we allow them to contribute to the contract hash, but we should skip validation for
-// those? For instance how should we handle the nonce field that asset
-// 123465c803ae336c62180e52d94ee80d80828db54df9bedbb9860060f49de2eb has?
-
-// Order of the fields here determines the serialization order, make sure it's ordered
-// lexicographically.
-
 /// A contract defining metadata of an asset such the name and the ticker
+///
+/// Fields not known by this version, such as the `nonce` of asset
+/// 123465c803ae336c62180e52d94ee80d80828db54df9bedbb9860060f49de2eb, are kept in `extra` and
+/// contribute to the contract hash. Fields are sorted lexicographically when computing the hash,
+/// regardless of the declaration order.
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Contract {
     pub entity: Entity,
@@ -46,6 +53,8 @@ pub struct Contract {
     pub precision: u8,
     pub ticker: String,
     pub version: u8,
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
 }
 
 impl Contract {
@@ -53,6 +62,10 @@ impl Contract {
         Ok(serde_json::from_value(value.clone())?)
     }
 
+    /// Validate the contract according to the rules of version 0, the only version known
+    ///
+    /// Contracts of other versions can still be parsed and hashed, so that they can be verified
+    /// against an asset id, see [`RegistryData::verify`].
     pub fn validate(&self) -> Result<(), Error> {
         if self.version != 0 {
             return Err(Error::InvalidVersion);
@@ -72,7 +85,7 @@ impl Contract {
 
         verify_pubkey(&self.issuer_pubkey).map_err(|_| Error::InvalidIssuerPubkey)?;
 
-        let Entity::Domain(domain) = &self.entity;
+        let domain = self.entity.domain().ok_or(Error::InvalidDomain)?;
         verify_domain_name(domain)?;
 
         Ok(())
@@ -121,8 +134,244 @@ pub fn issuance_ids(
     Ok((asset_id, token_id))
 }
 
+/// The input of the issuance transaction, as returned by the asset registry
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RegistryTxIn {
+    pub txid: Txid,
+    pub vin: u32,
+}
+
+/// The outpoint spent by the issuance input, as returned by the asset registry
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RegistryPrevout {
+    pub txid: Txid,
+    pub vout: u32,
+}
+
+/// The data returned by the asset registry for an asset
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RegistryData {
+    pub asset_id: AssetId,
+    pub contract: Contract,
+    pub issuance_txin: RegistryTxIn,
+    pub issuance_prevout: RegistryPrevout,
+}
+
+impl RegistryData {
+    /// The outpoint spent by the issuance input
+    pub fn issuance_prevout(&self) -> OutPoint {
+        OutPoint::new(self.issuance_prevout.txid, self.issuance_prevout.vout)
+    }
+
+    /// Verify that the contract is valid and that it commits to the asset id,
+    /// together with the issuance prevout
+    ///
+    /// Contracts of versions unknown to this library are only checked against the asset id.
+    pub fn verify(&self) -> Result<(), Error> {
+        if self.contract.version == 0 {
+            self.contract.validate()?;
+        }
+        // The asset id does not depend on the issuance being confidential
+        let (asset_id, _) = issuance_ids(&self.contract, self.issuance_prevout(), false)?;
+        if asset_id != self.asset_id {
+            return Err(Error::ContractDoesNotCommitToAssetId);
+        }
+        Ok(())
+    }
+
+    /// The url of the proof linking the contract domain to the asset, if the entity is a domain
+    pub fn domain_proof_url(&self) -> Option<String> {
+        let domain = self.contract.entity.domain()?;
+        Some(format!(
+            "https://{domain}/.well-known/liquid-asset-proof-{}",
+            self.asset_id
+        ))
+    }
+
+    /// Verify the content of the file at [`RegistryData::domain_proof_url`]
+    pub fn verify_domain_proof(&self, proof: &str) -> Result<(), Error> {
+        let domain = self.contract.entity.domain().ok_or(Error::InvalidDomain)?;
+        let expected = format!(
+            "Authorize linking the domain name {domain} to the Liquid asset {}",
+            self.asset_id
+        );
+        if proof.trim() != expected {
+            return Err(Error::InvalidDomainProof(format!(
+                "https://{domain}/.well-known/liquid-asset-proof-{}",
+                self.asset_id
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// A cache of verified registry data
+///
+/// Data is kept in memory and, if a directory is given, also stored in one file per asset.
+#[derive(Debug, Default)]
+pub struct RegistryCache {
+    dir: Option<PathBuf>,
+    data: HashMap<AssetId, RegistryData>,
+}
+
+impl RegistryCache {
+    /// Create an in-memory cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a cache stored in `dir`, loading the data already there
+    ///
+    /// Files that can't be read or verified are ignored.
+    pub fn with_dir<P: AsRef<Path>>(dir: P) -> Result<Self, Error> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)?;
+        let mut data = HashMap::new();
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            let registry_data = std::fs::read(&path)
+                .ok()
+                .and_then(|bytes| serde_json::from_slice::<RegistryData>(&bytes).ok());
+            match registry_data {
+                Some(d) if d.verify().is_ok() => {
+                    data.insert(d.asset_id, d);
+                }
+                _ => log::warn!("ignoring invalid registry cache file {}", path.display()),
+            }
+        }
+        Ok(Self {
+            dir: Some(dir),
+            data,
+        })
+    }
+
+    /// Get the registry data of an asset, if cached
+    pub fn get(&self, asset_id: &AssetId) -> Option<&RegistryData> {
+        self.data.get(asset_id)
+    }
+
+    /// Verify and insert registry data in the cache
+    pub fn insert(&mut self, data: RegistryData) -> Result<(), Error> {
+        data.verify()?;
+        if let Some(dir) = self.dir.as_ref() {
+            let path = dir.join(format!("{}.json", data.asset_id));
+            std::fs::write(path, serde_json::to_vec(&data)?)?;
+        }
+        self.data.insert(data.asset_id, data);
+        Ok(())
+    }
+
+    /// The contracts of all the cached assets
+    pub fn contracts(&self) -> impl Iterator<Item = (&AssetId, &Contract)> {
+        self.data.iter().map(|(a, d)| (a, &d.contract))
+    }
+}
+
+/// A client of the asset registry, caching the verified responses
+///
+/// Fetched data is verified with [`RegistryData::verify`], and unless disabled with
+/// [`Registry::verify_domain`], the domain proof of domain entities is fetched and checked.
+#[derive(Debug)]
+pub struct Registry {
+    base_url: String,
+    verify_domain: bool,
+    cache: RegistryCache,
+}
+
+impl Registry {
+    /// Create a registry client with an in-memory cache
+    ///
+    /// `base_url` is for instance `https://assets.blockstream.info/`
+    pub fn new(base_url: &str) -> Self {
+        Self::with_cache(base_url, RegistryCache::new())
+    }
+
+    /// Create a registry client with the given cache
+    pub fn with_cache(base_url: &str, cache: RegistryCache) -> Self {
+        let mut base_url = base_url.to_string();
+        if !base_url.ends_with('/') {
+            base_url.push('/');
+        }
+        Self {
+            base_url,
+            verify_domain: true,
+            cache,
+        }
+    }
+
+    /// Whether to fetch and check the domain proof of the fetched assets, default true
+    pub fn verify_domain(mut self, verify_domain: bool) -> Self {
+        self.verify_domain = verify_domain;
+        self
+    }
+
+    pub fn cache(&self) -> &RegistryCache {
+        &self.cache
+    }
+
+    fn asset_url(&self, asset_id: &AssetId) -> String {
+        format!("{}{asset_id}", self.base_url)
+    }
+
+    /// Get the verified registry data of an asset, from the cache or from the registry
+    #[cfg(feature = "esplora")]
+    pub fn fetch(&mut self, asset_id: &AssetId) -> Result<RegistryData, Error> {
+        if let Some(data) = self.cache.get(asset_id) {
+            return Ok(data.clone());
+        }
+        let client = reqwest::blocking::Client::new();
+        let url = self.asset_url(asset_id);
+        log::debug!("getting registry data {url}");
+        let data: RegistryData = client.get(url).send()?.error_for_status()?.json()?;
+        if &data.asset_id != asset_id {
+            return Err(Error::ContractDoesNotCommitToAssetId);
+        }
+        data.verify()?;
+        if let Some(url) = data.domain_proof_url().filter(|_| self.verify_domain) {
+            log::debug!("getting domain proof {url}");
+            let proof = client.get(url).send()?.error_for_status()?.text()?;
+            data.verify_domain_proof(&proof)?;
+        }
+        self.cache.insert(data.clone())?;
+        Ok(data)
+    }
+
+    /// Get the verified registry data of an asset, from the cache or from the registry
+    #[cfg(feature = "esplora_wasm")]
+    pub async fn fetch_async(&mut self, asset_id: &AssetId) -> Result<RegistryData, Error> {
+        if let Some(data) = self.cache.get(asset_id) {
+            return Ok(data.clone());
+        }
+        let client = reqwest::Client::new();
+        let url = self.asset_url(asset_id);
+        let data: RegistryData = client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if &data.asset_id != asset_id {
+            return Err(Error::ContractDoesNotCommitToAssetId);
+        }
+        data.verify()?;
+        if let Some(url) = data.domain_proof_url().filter(|_| self.verify_domain) {
+            let proof = client
+                .get(url)
+                .send()
+                .await?
+                .error_for_status()?
+                .text()
+                .await?;
+            data.verify_domain_proof(&proof)?;
+        }
+        self.cache.insert(data.clone())?;
+        Ok(data)
+    }
+}
+
 pub fn contract_json_hash(contract: &Value) -> Result<ContractHash, Error> {
-    let contract_str = serde_json::to_string(contract)?;
+    let contract_str = serde_json::to_string(&sort_keys(contract))?;
 
     // use the ContractHash representation for correct (reverse) hex encoding,
     // but use a single SHA256 instead of the double hash assumed by
@@ -131,6 +380,18 @@ pub fn contract_json_hash(contract: &Value) -> Result<ContractHash, Error> {
     Ok(ContractHash::from_raw_hash(hash))
 }
 
+/// Recursively sort the keys of the json objects in lexicographic order
+fn sort_keys(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let sorted: BTreeMap<_, _> = map.iter().map(|(k, v)| (k, sort_keys(v))).collect();
+            Value::Object(sorted.into_iter().map(|(k, v)| (k.clone(), v)).collect())
+        }
+        Value::Array(values) => Value::Array(values.iter().map(sort_keys).collect()),
+        _ => value.clone(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -204,4 +465,104 @@ mod tests {
         contract.version = 1;
         assert!(asset_ids(&tx.input[0], &contract).is_err());
     }
+
+    #[test]
+    fn test_registry_data() {
+        let contract = "{\"entity\":{\"domain\":\"tether.to\"},\"issuer_pubkey\":\"0337cceec0beea0232ebe14cba0197a9fbd45fcf2ec946749de920e71434c2b904\",\"name\":\"Tether USD\",\"precision\":8,\"ticker\":\"USDt\",\"version\":0}";
+        let tx_hex = include_str!("../tests/data/usdt-issuance-tx.hex");
+        let tx: elements::Transaction =
+            elements::encode::deserialize(&Vec::<u8>::from_hex(tx_hex).unwrap()).unwrap();
+        let prevout = tx.input[0].previous_output;
+        let json = format!(
+            "{{\"asset_id\":\"ce091c998b83c78bb71a632313ba3760f1763d9cfcffae02258ffa9865a37bd2\",\"contract\":{contract},\"issuance_txin\":{{\"txid\":\"{}\",\"vin\":0}},\"issuance_prevout\":{{\"txid\":\"{}\",\"vout\":{}}},\"version\":0,\"name\":\"Tether USD\"}}",
+            tx.txid(),
+            prevout.txid,
+            prevout.vout
+        );
+        let data: RegistryData = serde_json::from_str(&json).unwrap();
+        data.verify().unwrap();
+        assert_eq!(data.issuance_prevout(), prevout);
+        assert_eq!(
+            data.domain_proof_url().unwrap(),
+            "https://tether.to/.well-known/liquid-asset-proof-ce091c998b83c78bb71a632313ba3760f1763d9cfcffae02258ffa9865a37bd2"
+        );
+        data.verify_domain_proof("Authorize linking the domain name tether.to to the Liquid asset ce091c998b83c78bb71a632313ba3760f1763d9cfcffae02258ffa9865a37bd2\n").unwrap();
+        assert!(data.verify_domain_proof("").is_err());
+
+        let mut other = data.clone();
+        other.issuance_prevout.vout += 1;
+        assert!(other.verify().is_err());
+        let mut other = data.clone();
+        other.contract.name = "Tether EUR".to_string();
+        assert!(other.verify().is_err());
+
+        // Only verified data is cached, and it's loaded back from the directory
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut cache = RegistryCache::with_dir(tempdir.path()).unwrap();
+        assert!(cache.insert(other).is_err());
+        cache.insert(data.clone()).unwrap();
+        std::fs::write(tempdir.path().join("invalid.json"), "{}").unwrap();
+        let cache = RegistryCache::with_dir(tempdir.path()).unwrap();
+        assert_eq!(cache.get(&data.asset_id), Some(&data));
+        assert_eq!(cache.contracts().count(), 1);
+
+        let registry = Registry::with_cache("https://assets.blockstream.info", cache);
+        assert_eq!(
+            registry.asset_url(&data.asset_id),
+            format!("https://assets.blockstream.info/{}", data.asset_id)
+        );
+    }
+
+    #[test]
+    fn test_contract_extensions() {
+        // Unknown fields contribute to the hash in lexicographic order
+        let contract_string = "{\"entity\":{\"domain\":\"tether.to\"},\"issuer_pubkey\":\"0337cceec0beea0232ebe14cba0197a9fbd45fcf2ec946749de920e71434c2b904\",\"name\":\"Tether USD\",\"nonce\":\"1234\",\"precision\":8,\"ticker\":\"USDt\",\"version\":0}";
+        let contract = Contract::from_str(contract_string).unwrap();
+        assert_eq!(contract.extra.get("nonce").unwrap(), "1234");
+        let expected = sha256::Hash::hash(contract_string.as_bytes());
+        assert_eq!(
+            contract.contract_hash().unwrap(),
+            ContractHash::from_raw_hash(expected)
+        );
+        let value = serde_json::to_value(&contract).unwrap();
+        assert_eq!(Contract::from_value(&value).unwrap(), contract);
+
+        // Unknown versions and entities can be parsed and hashed, but not validated
+        let contract_string = "{\"entity\":{\"pubkey\":\"0337cceec0beea0232ebe14cba0197a9fbd45fcf2ec946749de920e71434c2b904\"},\"issuer_pubkey\":\"0337cceec0beea0232ebe14cba0197a9fbd45fcf2ec946749de920e71434c2b904\",\"name\":\"Tether USD\",\"precision\":8,\"ticker\":\"USDt\",\"version\":1}";
+        let value = serde_json::Value::from_str(contract_string).unwrap();
+        let contract = Contract::from_value(&value).unwrap();
+        assert!(matches!(contract.entity, Entity::Other(_)));
+        assert_eq!(contract.entity.domain(), None);
+        assert!(contract.validate().is_err());
+        let expected = sha256::Hash::hash(contract_string.as_bytes());
+        assert_eq!(
+            contract.contract_hash().unwrap(),
+            ContractHash::from_raw_hash(expected)
+        );
+
+        let mut contract_v0 = contract.clone();
+        contract_v0.version = 0;
+        assert!(matches!(
+            contract_v0.validate().unwrap_err(),
+            Error::InvalidDomain
+        ));
+
+        // Registry data of unknown versions is verified against the asset id
+        let prevout = OutPoint::default();
+        let (asset_id, _) = issuance_ids(&contract, prevout, false).unwrap();
+        let data = RegistryData {
+            asset_id,
+            contract,
+            issuance_txin: RegistryTxIn {
+                txid: prevout.txid,
+                vin: 0,
+            },
+            issuance_prevout: RegistryPrevout {
+                txid: prevout.txid,
+                vout: prevout.vout,
+            },
+        };
+        data.verify().unwrap();
+        assert!(data.domain_proof_url().is_none());
+    }
 }
@@ -62,7 +62,20 @@ impl Default for RawCache {
 
 impl std::hash::Hash for RawCache {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        let mut vec: Vec<_> = self.all_txs.keys().collect();
+        self.hash_excluding(state, |_| false)
+    }
+}
+
+impl RawCache {
+    /// Hash the cache as if the transactions for which `excluded` returns true were not there
+    pub(crate) fn hash_excluding<H: std::hash::Hasher>(
+        &self,
+        state: &mut H,
+        excluded: impl Fn(&Txid) -> bool,
+    ) {
+        use std::hash::Hash as _;
+
+        let mut vec: Vec<_> = self.all_txs.keys().filter(|t| !excluded(t)).collect();
         vec.sort();
         vec.hash(state);
 
@@ -74,11 +87,15 @@ impl std::hash::Hash for RawCache {
         vec.sort();
         vec.hash(state);
 
-        let mut vec: Vec<_> = self.heights.iter().collect();
+        let mut vec: Vec<_> = self.heights.iter().filter(|(t, _)| !excluded(t)).collect();
         vec.sort();
         vec.hash(state);
 
-        let mut vec: Vec<_> = self.unblinded.iter().collect();
+        let mut vec: Vec<_> = self
+            .unblinded
+            .iter()
+            .filter(|(o, _)| !excluded(&o.txid))
+            .collect();
         vec.sort_by_key(|kv| kv.0);
         vec.hash(state);
 
@@ -121,7 +138,12 @@ impl Store {
         };
 
         let start = batch * BATCH_SIZE;
-        let end = start + BATCH_SIZE;
+        let end = match descriptor.has_wildcard() {
+            true => start + BATCH_SIZE,
+            // A non-ranged descriptor has a single script, in the first batch
+            false if batch == 0 => 1,
+            false => start,
+        };
         let ext_int: Chain = descriptor.try_into().unwrap_or(Chain::External);
         for j in start..end {
             let child = ChildNumber::from_normal_idx(j)?;
@@ -1,22 +1,28 @@
 use std::collections::{HashMap, HashSet};
 
 use elements::{
-    confidential::Value,
+    confidential::{Value, ValueBlindingFactor},
     issuance::ContractHash,
     pset::{Output, PartiallySignedTransaction},
-    secp256k1_zkp::ZERO_TWEAK,
-    Address, AssetId, Script, Transaction,
+    secp256k1_zkp::{Generator, RangeProof, SecretKey, ZERO_TWEAK},
+    Address, AssetId, OutPoint, Script, Transaction,
 };
+use elements_miniscript::{ConfidentialDescriptor, DescriptorPublicKey};
+use lwk_common::{derive_blinding_key, issuance_blinding_script};
 use rand::thread_rng;
 
 use crate::{
     hashes::Hash,
     model::{ExternalUtxo, IssuanceDetails, Recipient},
-    pset_create::{validate_address, IssuanceRequest},
-    Contract, ElementsNetwork, Error, UnvalidatedRecipient, Wollet, EC,
+    pset_create::{blinding_secrets, validate_address, IssuanceRequest},
+    Contract, ElementsNetwork, Error, MultiWollet, UnvalidatedRecipient, Wollet, EC,
 };
 
-pub fn extract_issuances(tx: &Transaction) -> Vec<IssuanceDetails> {
+/// Extract the (re)issuances of `tx`, blinded amounts are unblinded if `descriptor` created them
+pub fn extract_issuances(
+    tx: &Transaction,
+    descriptor: &ConfidentialDescriptor<DescriptorPublicKey>,
+) -> Vec<IssuanceDetails> {
     let mut r = vec![];
     for (vin, txin) in tx.input.iter().enumerate() {
         if txin.has_issuance() {
@@ -25,16 +31,22 @@ pub fn extract_issuances(tx: &Transaction) -> Vec<IssuanceDetails> {
                 .to_byte_array();
             let (asset, token) = txin.issuance_ids();
             let is_reissuance = txin.asset_issuance.asset_blinding_nonce != ZERO_TWEAK;
-            // FIXME: attempt to unblind if blinded
-            let asset_amount = match txin.asset_issuance.amount {
-                Value::Explicit(a) => Some(a),
-                _ => None,
-            };
-            let token_amount = match txin.asset_issuance.inflation_keys {
-                Value::Explicit(a) => Some(a),
-                _ => None,
-            };
-            // FIXME: comment if the issuance is blinded
+            let blinding_key =
+                derive_blinding_key(descriptor, &issuance_blinding_script(&txin.previous_output));
+            let (asset_amount, asset_blinder) = unblind_issuance_value(
+                txin.asset_issuance.amount,
+                txin.witness.amount_rangeproof.as_deref(),
+                asset,
+                blinding_key,
+            );
+            let (token_amount, token_blinder) = unblind_issuance_value(
+                txin.asset_issuance.inflation_keys,
+                txin.witness.inflation_keys_rangeproof.as_deref(),
+                token,
+                blinding_key,
+            );
+            let is_confidential = txin.asset_issuance.amount.is_confidential()
+                || txin.asset_issuance.inflation_keys.is_confidential();
             r.push(IssuanceDetails {
                 txid: tx.txid(),
                 vin: vin as u32,
@@ -44,12 +56,38 @@ pub fn extract_issuances(tx: &Transaction) -> Vec<IssuanceDetails> {
                 is_reissuance,
                 asset_amount,
                 token_amount,
+                is_confidential,
+                asset_blinder,
+                token_blinder,
             });
         }
     }
     r
 }
 
+/// Returns the issuance value and, if it was blinded, its blinding factor
+fn unblind_issuance_value(
+    value: Value,
+    rangeproof: Option<&RangeProof>,
+    asset: AssetId,
+    blinding_key: Option<SecretKey>,
+) -> (Option<u64>, Option<ValueBlindingFactor>) {
+    match (value, rangeproof, blinding_key) {
+        (Value::Explicit(a), _, _) => (Some(a), None),
+        (Value::Confidential(comm), Some(rangeproof), Some(blinding_key)) => {
+            let gen = Generator::new_unblinded(&EC, asset.into_tag());
+            match rangeproof.rewind(&EC, comm, blinding_key, &[], gen) {
+                Ok((opening, _)) => (
+                    Some(opening.value),
+                    ValueBlindingFactor::from_slice(opening.blinding_factor.as_ref()).ok(),
+                ),
+                Err(_) => (None, None),
+            }
+        }
+        _ => (None, None),
+    }
+}
+
 /// "Clone" of Wollet.add_input
 fn add_external_input(
     pset: &mut PartiallySignedTransaction,
@@ -74,6 +112,12 @@ fn add_external_input(
     *inp_weight += utxo.max_weight_to_satisfy;
 }
 
+fn is_input(pset: &PartiallySignedTransaction, outpoint: &OutPoint) -> bool {
+    pset.inputs()
+        .iter()
+        .any(|i| i.previous_txid == outpoint.txid && i.previous_output_index == outpoint.vout)
+}
+
 /// A transaction builder
 ///
 /// See [`WolletTxBuilder`] for usage from rust.
@@ -90,11 +134,14 @@ pub struct TxBuilder {
     recipients: Vec<Recipient>,
     fee_rate: f32,
     ct_discount: bool,
-    issuance_request: IssuanceRequest,
+    issuance_requests: Vec<IssuanceRequest>,
     blind: bool,
+    blind_issuance: bool,
     drain_lbtc: bool,
     drain_to: Option<Address>,
     external_utxos: Vec<ExternalUtxo>,
+    only_trusted: bool,
+    lock_inputs: Option<u32>,
 }
 
 impl TxBuilder {
@@ -105,11 +152,14 @@ impl TxBuilder {
             recipients: vec![],
             fee_rate: 100.0,
             ct_discount: false,
-            issuance_request: IssuanceRequest::None,
+            issuance_requests: vec![],
             blind: true,
+            blind_issuance: false,
             drain_lbtc: false,
             drain_to: None,
             external_utxos: vec![],
+            only_trusted: false,
+            lock_inputs: None,
         }
     }
 
@@ -186,6 +236,39 @@ impl TxBuilder {
         self
     }
 
+    /// Blind the (re)issuance amounts, so that they are not published on chain
+    ///
+    /// Only the wallet creating the (re)issuance can unblind them and report them in
+    /// [`Wollet::issuances()`]. Note that a confidential issuance creates a different reissuance
+    /// token than an explicit one.
+    ///
+    /// Ignored if the transaction is not blinded.
+    pub fn blind_issuance(mut self, blind_issuance: bool) -> Self {
+        self.blind_issuance = blind_issuance;
+        self
+    }
+
+    /// Only spend confirmed utxos or unconfirmed utxos of transactions spending wallet utxos,
+    /// such as our change, not unconfirmed utxos received from others
+    ///
+    /// Immature coinbase outputs are never spent.
+    pub fn only_trusted(mut self, only_trusted: bool) -> Self {
+        self.only_trusted = only_trusted;
+        self
+    }
+
+    /// Lock the wallet utxos spent by the created PSET for the given number of blocks,
+    /// so that they are not spent by other transactions while this one is not broadcast
+    ///
+    /// Inputs are not locked by default, since PSETs may be created only to be inspected,
+    /// the JSON RPC server of `lwk_app` locks them for the PSETs it creates.
+    ///
+    /// See [`Wollet::lock_utxo()`].
+    pub fn lock_inputs(mut self, blocks: u32) -> Self {
+        self.lock_inputs = Some(blocks);
+        self
+    }
+
     /// Use ELIP200 discounted fees for Confidential Transactions
     ///
     /// Note: if ELIP200 was not activated by miners and nodes relaying transactions, using
@@ -212,7 +295,8 @@ impl TxBuilder {
     ///
     /// If a `contract` is provided, it's metadata will be committed in the generated asset id.
     ///
-    /// Can't be used if `reissue_asset` has been called
+    /// Can be called multiple times, and together with `reissue_asset`, each issuance is attached
+    /// to a distinct input of the transaction.
     pub fn issue_asset(
         mut self,
         asset_sats: u64,
@@ -221,9 +305,6 @@ impl TxBuilder {
         token_receiver: Option<Address>,
         contract: Option<Contract>,
     ) -> Result<Self, Error> {
-        if !matches!(self.issuance_request, IssuanceRequest::None) {
-            return Err(Error::IssuanceAlreadySet);
-        }
         if let Some(addr) = asset_receiver.as_ref() {
             validate_address(&addr.to_string(), self.network())?;
         }
@@ -233,13 +314,13 @@ impl TxBuilder {
         if asset_sats == 0 {
             return Err(Error::InvalidAmount);
         }
-        self.issuance_request = IssuanceRequest::Issuance(
+        self.issuance_requests.push(IssuanceRequest::Issuance(
             asset_sats,
             asset_receiver,
             token_sats,
             token_receiver,
             contract,
-        );
+        ));
         Ok(self)
     }
 
@@ -254,6 +335,8 @@ impl TxBuilder {
     ///
     /// If the issuance transaction does not involve this wallet,
     /// pass the issuance transaction in `issuance_tx`.
+    ///
+    /// Can be called multiple times, each reissuance spends a distinct reissuance token utxo.
     pub fn reissue_asset(
         mut self,
         asset_to_reissue: AssetId,
@@ -261,21 +344,18 @@ impl TxBuilder {
         asset_receiver: Option<Address>,
         issuance_tx: Option<Transaction>,
     ) -> Result<Self, Error> {
-        if !matches!(self.issuance_request, IssuanceRequest::None) {
-            return Err(Error::IssuanceAlreadySet);
-        }
         if let Some(addr) = asset_receiver.as_ref() {
             validate_address(&addr.to_string(), self.network())?;
         }
         if satoshi_to_reissue == 0 {
             return Err(Error::InvalidAmount);
         }
-        self.issuance_request = IssuanceRequest::Reissuance(
+        self.issuance_requests.push(IssuanceRequest::Reissuance(
             asset_to_reissue,
             satoshi_to_reissue,
             asset_receiver,
             issuance_tx,
-        );
+        ));
         Ok(self)
     }
 
@@ -309,14 +389,40 @@ impl TxBuilder {
 
     /// Finish building the transaction
     pub fn finish(self, wollet: &Wollet) -> Result<PartiallySignedTransaction, Error> {
+        self.finish_inner(wollet, &[wollet])
+    }
+
+    /// Finish building the transaction, spending the utxos of all the `wollets`
+    ///
+    /// Change and new issuances are sent to `wollet`. Utxos are selected in the order of `wollets`.
+    pub(crate) fn finish_inner(
+        self,
+        wollet: &Wollet,
+        wollets: &[&Wollet],
+    ) -> Result<PartiallySignedTransaction, Error> {
         // Init PSET
         let mut pset = PartiallySignedTransaction::new_v2();
         let mut inp_txout_sec = HashMap::new();
+        let mut issuance_txout_sec = vec![];
         let mut last_unused_internal = wollet.change(None)?.index();
         let mut last_unused_external = wollet.address(None)?.index();
 
         let mut inp_weight = 0;
 
+        let mut utxos = vec![];
+        for w in wollets {
+            for utxo in w.spendable_utxos(self.only_trusted)? {
+                utxos.push((*w, utxo));
+            }
+        }
+        let asset_utxos = |asset: &AssetId| {
+            utxos
+                .iter()
+                .filter(|(_, u)| &u.unblinded.asset == asset)
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
         let policy_asset = self.network().policy_asset();
         let (addressees_lbtc, addressees_asset): (Vec<_>, Vec<_>) = self
             .recipients
@@ -332,8 +438,8 @@ impl TxBuilder {
                 wollet.add_output(&mut pset, addressee)?;
                 satoshi_out += addressee.satoshi;
             }
-            for utxo in wollet.asset_utxos(&asset)? {
-                wollet.add_input(&mut pset, &mut inp_txout_sec, &mut inp_weight, &utxo)?;
+            for (owner, utxo) in asset_utxos(&asset) {
+                owner.add_input(&mut pset, &mut inp_txout_sec, &mut inp_weight, &utxo)?;
                 satoshi_in += utxo.unblinded.value;
                 if satoshi_in >= satoshi_out {
                     if satoshi_in > satoshi_out {
@@ -372,74 +478,122 @@ impl TxBuilder {
         }
 
         // FIXME: For implementation simplicity now we always add all L-BTC inputs
-        for utxo in wollet.asset_utxos(&wollet.policy_asset())? {
-            wollet.add_input(&mut pset, &mut inp_txout_sec, &mut inp_weight, &utxo)?;
+        for (owner, utxo) in asset_utxos(&policy_asset) {
+            owner.add_input(&mut pset, &mut inp_txout_sec, &mut inp_weight, &utxo)?;
             satoshi_in += utxo.unblinded.value;
         }
 
-        // Set (re)issuance data
-        match self.issuance_request {
-            IssuanceRequest::None => {}
-            IssuanceRequest::Issuance(
-                satoshi_asset,
-                address_asset,
-                satoshi_token,
-                address_token,
-                contract,
-            ) => {
-                // At least a L-BTC input for the fee was added.
-                let idx = 0;
-                let (asset, token) =
-                    wollet.set_issuance(&mut pset, idx, satoshi_asset, satoshi_token, contract)?;
-
-                let addressee = match address_asset {
-                    Some(address) => Recipient::from_address(satoshi_asset, &address, asset),
-                    None => wollet.addressee_external(
+        // Set (re)issuance data, each (re)issuance is attached to a distinct input.
+        // Reissuances go first since they need the inputs holding the reissuance tokens.
+        let blind_issuance = self.blind && self.blind_issuance;
+        let mut issuance_inputs = HashSet::new();
+        let (reissuance_requests, issuance_requests): (Vec<_>, Vec<_>) = self
+            .issuance_requests
+            .into_iter()
+            .partition(|r| matches!(r, IssuanceRequest::Reissuance(..)));
+        for request in reissuance_requests.into_iter().chain(issuance_requests) {
+            match request {
+                IssuanceRequest::Issuance(
+                    satoshi_asset,
+                    address_asset,
+                    satoshi_token,
+                    address_token,
+                    contract,
+                ) => {
+                    // At least a L-BTC input for the fee was added, if all inputs already have
+                    // an issuance, add another wallet utxo sending it back to the wallet.
+                    let idx = match (0..pset.n_inputs()).find(|i| !issuance_inputs.contains(i)) {
+                        Some(idx) => idx,
+                        None => {
+                            let (owner, utxo) = utxos
+                                .iter()
+                                .find(|(_, u)| !is_input(&pset, &u.outpoint))
+                                .cloned()
+                                .ok_or_else(|| Error::NotEnoughIssuanceInputs)?;
+                            let idx = owner.add_input(
+                                &mut pset,
+                                &mut inp_txout_sec,
+                                &mut inp_weight,
+                                &utxo,
+                            )?;
+                            let addressee = wollet.addressee_change(
+                                utxo.unblinded.value,
+                                utxo.unblinded.asset,
+                                &mut last_unused_internal,
+                            )?;
+                            wollet.add_output(&mut pset, &addressee)?;
+                            idx
+                        }
+                    };
+                    issuance_inputs.insert(idx);
+
+                    let (mut asset, mut token) = wollet.set_issuance(
+                        &mut pset,
+                        idx,
                         satoshi_asset,
-                        asset,
-                        &mut last_unused_external,
-                    )?,
-                };
-                wollet.add_output(&mut pset, &addressee)?;
-
-                if satoshi_token > 0 {
-                    let addressee = match address_token {
-                        Some(address) => Recipient::from_address(satoshi_token, &address, token),
+                        satoshi_token,
+                        contract,
+                    )?;
+                    if blind_issuance {
+                        (asset, token) =
+                            wollet.blind_issuance(&mut pset, idx, &mut issuance_txout_sec)?;
+                    }
+
+                    let addressee = match address_asset {
+                        Some(address) => Recipient::from_address(satoshi_asset, &address, asset),
                         None => wollet.addressee_external(
-                            satoshi_token,
-                            token,
+                            satoshi_asset,
+                            asset,
                             &mut last_unused_external,
                         )?,
                     };
                     wollet.add_output(&mut pset, &addressee)?;
+
+                    if satoshi_token > 0 {
+                        let addressee = match address_token {
+                            Some(address) => {
+                                Recipient::from_address(satoshi_token, &address, token)
+                            }
+                            None => wollet.addressee_external(
+                                satoshi_token,
+                                token,
+                                &mut last_unused_external,
+                            )?,
+                        };
+                        wollet.add_output(&mut pset, &addressee)?;
+                    }
                 }
-            }
-            IssuanceRequest::Reissuance(asset, satoshi_asset, address_asset, issuance_tx) => {
-                let issuance = if let Some(issuance_tx) = issuance_tx {
-                    extract_issuances(&issuance_tx)
+                IssuanceRequest::Reissuance(asset, satoshi_asset, address_asset, issuance_tx) => {
+                    let issuance = if let Some(issuance_tx) = issuance_tx {
+                        wollets
+                            .iter()
+                            .flat_map(|w| extract_issuances(&issuance_tx, w.descriptor()))
+                            .find(|i| i.asset == asset)
+                            .ok_or_else(|| Error::MissingIssuance)?
+                    } else {
+                        wollets
+                            .iter()
+                            .find_map(|w| w.issuance(&asset).ok())
+                            .ok_or_else(|| Error::MissingIssuance)?
+                    };
+                    let token = issuance.token;
+                    // Find or add an input for the token, not used by another reissuance
+                    let (idx, token_asset_bf) = match inp_txout_sec
                         .iter()
-                        .find(|i| i.asset == asset)
-                        .ok_or_else(|| Error::MissingIssuance)?
-                        .clone()
-                } else {
-                    wollet.issuance(&asset)?
-                };
-                let token = issuance.token;
-                // Find or add input for the token
-                let (idx, token_asset_bf) =
-                    match inp_txout_sec.iter().find(|(_, u)| u.asset == token) {
+                        .find(|(i, u)| u.asset == token && !issuance_inputs.contains(*i))
+                    {
                         Some((idx, u)) => (*idx, u.asset_bf),
                         None => {
                             // Add an input sending the token,
-                            let utxos_token = wollet.asset_utxos(&token)?;
-                            let utxo_token = utxos_token
-                                .first()
+                            let (owner, utxo_token) = asset_utxos(&token)
+                                .into_iter()
+                                .find(|(_, u)| !is_input(&pset, &u.outpoint))
                                 .ok_or_else(|| Error::InsufficientFunds)?;
-                            let idx = wollet.add_input(
+                            let idx = owner.add_input(
                                 &mut pset,
                                 &mut inp_txout_sec,
                                 &mut inp_weight,
-                                utxo_token,
+                                &utxo_token,
                             )?;
 
                             // and an outpout receiving the token
@@ -454,25 +608,30 @@ impl TxBuilder {
                             (idx, utxo_token.unblinded.asset_bf)
                         }
                     };
+                    issuance_inputs.insert(idx);
 
-                // Set reissuance data
-                wollet.set_reissuance(
-                    &mut pset,
-                    idx,
-                    satoshi_asset,
-                    &token_asset_bf,
-                    &issuance.entropy,
-                )?;
-
-                let addressee = match address_asset {
-                    Some(address) => Recipient::from_address(satoshi_asset, &address, asset),
-                    None => wollet.addressee_external(
+                    // Set reissuance data
+                    wollet.set_reissuance(
+                        &mut pset,
+                        idx,
                         satoshi_asset,
-                        asset,
-                        &mut last_unused_external,
-                    )?,
-                };
-                wollet.add_output(&mut pset, &addressee)?;
+                        &token_asset_bf,
+                        &issuance.entropy,
+                    )?;
+                    if blind_issuance {
+                        wollet.blind_issuance(&mut pset, idx, &mut issuance_txout_sec)?;
+                    }
+
+                    let addressee = match address_asset {
+                        Some(address) => Recipient::from_address(satoshi_asset, &address, asset),
+                        None => wollet.addressee_external(
+                            satoshi_asset,
+                            asset,
+                            &mut last_unused_external,
+                        )?,
+                    };
+                    wollet.add_output(&mut pset, &addressee)?;
+                }
             }
         }
 
@@ -500,7 +659,9 @@ impl TxBuilder {
         let weight = {
             let mut rng = thread_rng();
             let mut temp_pset = pset.clone();
-            temp_pset.blind_last(&mut rng, &EC, &inp_txout_sec)?;
+            let secrets =
+                blinding_secrets(temp_pset.n_inputs(), &inp_txout_sec, &issuance_txout_sec);
+            temp_pset.blind_last(&mut rng, &EC, &secrets)?;
             let tx_weight = {
                 let tx = temp_pset.extract_tx()?;
                 if self.ct_discount {
@@ -531,20 +692,36 @@ impl TxBuilder {
         // Blind the transaction
         if self.blind {
             let mut rng = thread_rng();
-            pset.blind_last(&mut rng, &EC, &inp_txout_sec)?;
+            let secrets = blinding_secrets(pset.n_inputs(), &inp_txout_sec, &issuance_txout_sec);
+            pset.blind_last(&mut rng, &EC, &secrets)?;
+
+            // Add details to the pset from our descriptors, like bip32derivation and keyorigin
+            for w in wollets {
+                w.add_details(&mut pset)?;
+            }
+        }
 
-            // Add details to the pset from our descriptor, like bip32derivation and keyorigin
-            wollet.add_details(&mut pset)?;
+        if let Some(blocks) = self.lock_inputs {
+            for w in wollets {
+                w.lock_inputs(&pset, w.tip().height().saturating_add(blocks))?;
+            }
         }
 
         Ok(pset)
     }
 }
 
+/// The wallet spending the transaction built by a [`WolletTxBuilder`]
+#[derive(Debug, Clone, Copy)]
+enum Spender<'a> {
+    Wollet(&'a Wollet),
+    Multi(&'a MultiWollet),
+}
+
 /// A transaction builder.
 #[derive(Debug)]
 pub struct WolletTxBuilder<'a> {
-    wollet: &'a Wollet,
+    wollet: Spender<'a>,
     inner: TxBuilder,
 }
 
@@ -552,14 +729,26 @@ impl<'a> WolletTxBuilder<'a> {
     /// Creates a transaction builder. Could be conveniently created with [`Wollet::tx_builder()`]
     pub fn new(wollet: &'a Wollet) -> Self {
         WolletTxBuilder {
-            wollet,
+            wollet: Spender::Wollet(wollet),
+            inner: TxBuilder::new(wollet.network()),
+        }
+    }
+
+    /// Creates a transaction builder spending from all the descriptors of a [`MultiWollet`],
+    /// see [`MultiWollet::tx_builder()`]
+    pub fn new_multi(wollet: &'a MultiWollet) -> Self {
+        WolletTxBuilder {
+            wollet: Spender::Multi(wollet),
             inner: TxBuilder::new(wollet.network()),
         }
     }
 
     /// Consume this builder and create a transaction
     pub fn finish(self) -> Result<PartiallySignedTransaction, Error> {
-        self.inner.finish(self.wollet)
+        match self.wollet {
+            Spender::Wollet(wollet) => self.inner.finish(wollet),
+            Spender::Multi(wollet) => wollet.finish(self.inner),
+        }
     }
 
     /// Wrapper of [`TxBuilder::add_recipient()`]
@@ -692,6 +881,30 @@ impl<'a> WolletTxBuilder<'a> {
         }
     }
 
+    /// Wrapper of [`TxBuilder::blind_issuance()`]
+    pub fn blind_issuance(self, blind_issuance: bool) -> Self {
+        Self {
+            wollet: self.wollet,
+            inner: self.inner.blind_issuance(blind_issuance),
+        }
+    }
+
+    /// Wrapper of [`TxBuilder::only_trusted()`]
+    pub fn only_trusted(self, only_trusted: bool) -> Self {
+        Self {
+            wollet: self.wollet,
+            inner: self.inner.only_trusted(only_trusted),
+        }
+    }
+
+    /// Wrapper of [`TxBuilder::lock_inputs()`]
+    pub fn lock_inputs(self, blocks: u32) -> Self {
+        Self {
+            wollet: self.wollet,
+            inner: self.inner.lock_inputs(blocks),
+        }
+    }
+
     /// Wrapper of [`TxBuilder::drain_lbtc_wallet()`]
     pub fn drain_lbtc_wallet(self) -> Self {
         Self {
@@ -1,7 +1,8 @@
+use crate::clients::try_unblind;
 use crate::descriptor::Chain;
 use crate::elements::{OutPoint, Script, Transaction, TxOutSecrets, Txid};
 use crate::error::Error;
-use crate::store::{Height, Timestamp};
+use crate::store::{Height, Store, Timestamp};
 use crate::wollet::WolletState;
 use crate::{Wollet, WolletDescriptor};
 use aes_gcm_siv::aead::generic_array::GenericArray;
@@ -185,49 +186,18 @@ impl Wollet {
             .scripts
             .extend(scripts.clone().into_iter().map(|(a, b)| (b, a)));
         store.cache.paths.extend(scripts);
-        let mut last_used_internal = None;
-        let mut last_used_external = None;
-        for (txid, _) in txid_height_new {
-            if let Some(tx) = store.cache.all_txs.get(&txid) {
-                for (vout, output) in tx.output.iter().enumerate() {
-                    if !store
-                        .cache
-                        .unblinded
-                        .contains_key(&OutPoint::new(txid, vout as u32))
-                    {
-                        // Output cannot be unblinded by wallet
-                        continue;
-                    }
-                    if let Some((ext_int, ChildNumber::Normal { index })) =
-                        store.cache.paths.get(&output.script_pubkey)
-                    {
-                        match ext_int {
-                            Chain::External => match last_used_external {
-                                None => last_used_external = Some(index),
-                                Some(last) if index > last => last_used_external = Some(index),
-                                _ => {}
-                            },
-                            Chain::Internal => match last_used_internal {
-                                None => last_used_internal = Some(index),
-                                Some(last) if index > last => last_used_internal = Some(index),
-                                _ => {}
-                            },
-                        }
-                    }
-                }
-            }
-        }
-        if let Some(last_used_external) = last_used_external {
-            store
-                .cache
-                .last_unused_external
-                .store(last_used_external + 1, atomic::Ordering::Relaxed);
+        update_last_unused(store, txid_height_new.iter().map(|(txid, _)| *txid));
+
+        // Locally applied transactions seen by the server are now part of the update,
+        // the ones it never received are removed
+        for (txid, _) in txid_height_new.iter() {
+            self.applied.remove(txid);
         }
-        if let Some(last_used_internal) = last_used_internal {
-            store
-                .cache
-                .last_unused_internal
-                .store(last_used_internal + 1, atomic::Ordering::Relaxed);
+        for txid in txid_height_delete.iter() {
+            if self.applied.remove(txid).is_some() {
+                store.cache.all_txs.remove(txid);
+                store.cache.unblinded.retain(|o, _| o.txid != *txid);
+            }
         }
 
         if do_persist {
@@ -236,6 +206,113 @@ impl Wollet {
 
         Ok(())
     }
+
+    /// Apply a transaction created by this wallet, before it's seen by the blockchain backend
+    ///
+    /// Call this after broadcasting a transaction, so that its inputs are considered spent and
+    /// its outputs are available until the next scan returns it. The transaction is unconfirmed
+    /// and it's not persisted, and it doesn't change the [`Wollet::status()`] the updates are
+    /// checked against: the next scan downloads it again and persists it as part of its update. If the server still doesn't know the transaction after a new
+    /// block, the next scan removes it.
+    ///
+    /// Returns an error if the transaction doesn't spend or receive any wallet output.
+    pub fn apply_transaction(&mut self, tx: Transaction) -> Result<(), Error> {
+        let txid = tx.txid();
+        if self.store.cache.heights.contains_key(&txid) {
+            return Ok(());
+        }
+
+        let txos = self.txos()?;
+        let spends_mine = tx
+            .input
+            .iter()
+            .any(|i| txos.contains_key(&i.previous_output));
+        let descriptor = self.wollet_descriptor();
+        let mut unblinds = vec![];
+        for (vout, output) in tx.output.iter().enumerate() {
+            let unblinded = if self.store.cache.paths.contains_key(&output.script_pubkey) {
+                Some(try_unblind(output.clone(), &descriptor))
+            } else {
+                self.watched
+                    .get(&output.script_pubkey)
+                    .map(|w| w.unblind(output))
+            };
+            if let Some(unblinded) = unblinded {
+                let outpoint = OutPoint::new(txid, vout as u32);
+                match unblinded {
+                    Ok(unblinded) => unblinds.push((outpoint, unblinded)),
+                    Err(_) => log::info!("{} cannot unblind, ignoring", outpoint),
+                }
+            }
+        }
+        if !spends_mine && unblinds.is_empty() {
+            return Err(Error::TransactionNotMine(txid));
+        }
+
+        let store = &mut self.store;
+        store.cache.unblinded.extend(unblinds);
+        store.cache.all_txs.insert(txid, tx);
+        store.cache.heights.insert(txid, None);
+        self.applied.insert(txid, store.cache.tip.0);
+        Ok(())
+    }
+}
+
+/// Update the last unused indexes with the wallet outputs of the given transactions
+fn update_last_unused(store: &Store, txids: impl Iterator<Item = Txid>) {
+    let (last_used_external, last_used_internal) = last_used(store, txids);
+    if let Some(last_used_external) = last_used_external {
+        store
+            .cache
+            .last_unused_external
+            .store(last_used_external + 1, atomic::Ordering::Relaxed);
+    }
+    if let Some(last_used_internal) = last_used_internal {
+        store
+            .cache
+            .last_unused_internal
+            .store(last_used_internal + 1, atomic::Ordering::Relaxed);
+    }
+}
+
+/// The highest external and internal indexes of the wallet outputs of the given transactions
+pub(crate) fn last_used(
+    store: &Store,
+    txids: impl Iterator<Item = Txid>,
+) -> (Option<u32>, Option<u32>) {
+    let mut last_used_internal = None;
+    let mut last_used_external = None;
+    for txid in txids {
+        if let Some(tx) = store.cache.all_txs.get(&txid) {
+            for (vout, output) in tx.output.iter().enumerate() {
+                if !store
+                    .cache
+                    .unblinded
+                    .contains_key(&OutPoint::new(txid, vout as u32))
+                {
+                    // Output cannot be unblinded by wallet
+                    continue;
+                }
+                if let Some((ext_int, ChildNumber::Normal { index })) =
+                    store.cache.paths.get(&output.script_pubkey)
+                {
+                    match ext_int {
+                        Chain::External => match last_used_external {
+                            None => last_used_external = Some(*index),
+                            Some(last) if *index > last => last_used_external = Some(*index),
+                            _ => {}
+                        },
+                        Chain::Internal => match last_used_internal {
+                            None => last_used_internal = Some(*index),
+                            Some(last) if *index > last => last_used_internal = Some(*index),
+                            _ => {}
+                        },
+                    }
+                }
+            }
+        }
+    }
+    (last_used_external, last_used_internal)
 }
 
 impl Encodable for DownloadTxResult {
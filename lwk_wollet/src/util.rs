@@ -2,8 +2,12 @@ use crate::elements::bitcoin::secp256k1::PublicKey;
 use crate::elements::hex::{FromHex, ToHex};
 use crate::error::Error;
 use crate::secp256k1;
-use rand::thread_rng;
-use serde::Deserialize;
+use crate::WolletDescriptor;
+use aes_gcm_siv::aead::generic_array::GenericArray;
+use aes_gcm_siv::aead::AeadMutInPlace;
+use rand::{thread_rng, Rng};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 
 pub static EC: once_cell::sync::Lazy<secp256k1::Secp256k1<secp256k1::All>> =
     once_cell::sync::Lazy::new(|| {
@@ -33,6 +37,39 @@ where
     serializer.serialize_str(&buffer.as_ref().to_hex())
 }
 
+/// Serialize as json and encrypt with the descriptor cipher, prefixing the random nonce
+pub(crate) fn serialize_encrypted<T: Serialize + ?Sized>(
+    value: &T,
+    desc: &WolletDescriptor,
+) -> Result<Vec<u8>, Error> {
+    let mut plaintext = serde_json::to_vec(value)?;
+
+    let mut nonce_bytes = [0u8; 12];
+    thread_rng().fill(&mut nonce_bytes);
+    let nonce = GenericArray::from_slice(&nonce_bytes);
+    desc.cipher().encrypt_in_place(nonce, b"", &mut plaintext)?;
+
+    let mut result = Vec::with_capacity(plaintext.len() + 12);
+    result.extend(nonce.as_slice());
+    result.extend(&plaintext);
+    Ok(result)
+}
+
+/// Inverse of [`serialize_encrypted()`]
+pub(crate) fn deserialize_decrypted<T: DeserializeOwned>(
+    bytes: &[u8],
+    desc: &WolletDescriptor,
+) -> Result<T, Error> {
+    if bytes.len() < 12 {
+        return Err(Error::Generic("Encrypted file too short".to_string()));
+    }
+    let nonce = GenericArray::from_slice(&bytes[..12]);
+    let mut ciphertext = bytes[12..].to_vec();
+    desc.cipher()
+        .decrypt_in_place(nonce, b"", &mut ciphertext)?;
+    Ok(serde_json::from_slice(&ciphertext)?)
+}
+
 pub fn verify_pubkey(pubkey: &[u8]) -> Result<(), Error> {
     PublicKey::from_slice(pubkey)?;
     Ok(())
@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+
+use crate::elements::{Address, Script, TxOut, TxOutSecrets};
+use crate::error::Error;
+use crate::secp256k1::SecretKey;
+use crate::wollet::Wollet;
+use crate::EC;
+
+/// A confidential address watched by the wallet, with its blinding private key
+///
+/// The address doesn't need to belong to the wallet descriptor, the outputs it receives are
+/// unblinded with the given key and included in the wallet balance and transactions, but they
+/// can't be spent by the transactions created by the wallet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchedAddress {
+    address: Address,
+    blinding_key: SecretKey,
+}
+
+impl WatchedAddress {
+    /// Create a watched address, failing if the blinding key doesn't match the address
+    pub fn new(address: &Address, blinding_key: SecretKey) -> Result<Self, Error> {
+        let blinding_pubkey = address
+            .blinding_pubkey
+            .ok_or_else(|| Error::Generic(format!("Address {address} is not confidential")))?;
+        if blinding_pubkey != blinding_key.public_key(&EC) {
+            return Err(Error::Generic(format!(
+                "Blinding key doesn't match address {address}"
+            )));
+        }
+        Ok(Self {
+            address: address.clone(),
+            blinding_key,
+        })
+    }
+
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    pub fn blinding_key(&self) -> SecretKey {
+        self.blinding_key
+    }
+
+    pub fn script_pubkey(&self) -> Script {
+        self.address.script_pubkey()
+    }
+
+    /// Unblind an output sent to this address
+    pub(crate) fn unblind(&self, output: &TxOut) -> Result<TxOutSecrets, Error> {
+        Ok(output.unblind(&EC, self.blinding_key)?)
+    }
+}
+
+impl Wollet {
+    /// Watch a confidential address, the blinding key must be the one of the address
+    ///
+    /// The address is scanned by the next [`crate::BlockchainBackend::full_scan()`],
+    /// watching an already watched address replaces its blinding key.
+    pub fn watch_address(
+        &mut self,
+        address: &Address,
+        blinding_key: SecretKey,
+    ) -> Result<(), Error> {
+        if address.params != self.config.address_params() {
+            return Err(Error::Generic(format!(
+                "Address {address} is not for network {:?}",
+                self.network()
+            )));
+        }
+        let watched = WatchedAddress::new(address, blinding_key)?;
+        self.watched.insert(watched.script_pubkey(), watched);
+        self.persist_watched()
+    }
+
+    /// Stop watching an address, returns false if it wasn't watched
+    ///
+    /// The transactions already downloaded for the address are kept.
+    pub fn unwatch_address(&mut self, address: &Address) -> Result<bool, Error> {
+        let was_watched = self.watched.remove(&address.script_pubkey()).is_some();
+        if was_watched {
+            self.persist_watched()?;
+        }
+        Ok(was_watched)
+    }
+
+    /// Get the watched addresses, sorted by address
+    pub fn watched_addresses(&self) -> Vec<WatchedAddress> {
+        let mut watched: Vec<_> = self.watched.values().cloned().collect();
+        watched.sort_by_key(|w| w.address.to_string());
+        watched
+    }
+
+    fn persist_watched(&self) -> Result<(), Error> {
+        self.persister
+            .set_watched_addresses(&self.watched_addresses())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WatchedAddress;
+    use crate::secp256k1::SecretKey;
+    use crate::{ElementsNetwork, NoPersist, Wollet, EC};
+
+    #[test]
+    fn test_watch_address() {
+        let desc = "ct(slip77(9c8e4f05c7711a98c838be228bcb84924d4570ca53f35fa1c793e58841d47023),elwpkh([73c5da0a/84'/1'/0']tpubDC8msFGeGuwnKG9Upg7DM2b4DaRqg3CUZa5g8v2SRQ6K4NSkxUgd7HsL2XVWbVm39yBA4LAxysQAm397zwQSQoQgewGiYZqrA9DsP4zbQ1M/<0;1>/*))";
+        let network = ElementsNetwork::LiquidTestnet;
+        let mut wollet = Wollet::new(network, NoPersist::new(), desc.parse().unwrap()).unwrap();
+
+        let blinding_key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let other_key = SecretKey::from_slice(&[2; 32]).unwrap();
+        let address = wollet
+            .address(Some(0))
+            .unwrap()
+            .address()
+            .to_unconfidential()
+            .to_confidential(blinding_key.public_key(&EC));
+
+        assert!(WatchedAddress::new(&address, other_key).is_err());
+        assert!(WatchedAddress::new(&address.to_unconfidential(), blinding_key).is_err());
+
+        wollet.watch_address(&address, blinding_key).unwrap();
+        let watched = wollet.watched_addresses();
+        assert_eq!(watched.len(), 1);
+        assert_eq!(watched[0].address(), &address);
+        assert_eq!(watched[0].blinding_key(), blinding_key);
+        assert!(wollet.utxos().unwrap().is_empty());
+
+        assert!(wollet.unwatch_address(&address).unwrap());
+        assert!(!wollet.unwatch_address(&address).unwrap());
+        assert!(wollet.watched_addresses().is_empty());
+    }
+}
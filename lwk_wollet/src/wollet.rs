@@ -14,8 +14,12 @@ use crate::model::{
 use crate::persister::PersistError;
 use crate::store::{Height, ScriptBatch, Store, Timestamp, BATCH_SIZE};
 use crate::tx_builder::{extract_issuances, WolletTxBuilder};
+use crate::update::last_used;
 use crate::util::EC;
-use crate::{FsPersister, NoPersist, Persister, Update, WolletDescriptor};
+use crate::{
+    FsPersister, LockedUtxo, NoPersist, Persister, TxQuery, Update, WatchedAddress,
+    WolletDescriptor,
+};
 use elements::bitcoin;
 use elements::bitcoin::bip32::ChildNumber;
 use elements_miniscript::psbt::PsbtExt;
@@ -25,17 +29,24 @@ use elements_miniscript::{
 };
 use fxhash::FxHasher;
 use lwk_common::{burn_script, pset_balance, pset_issuances, pset_signatures, PsetDetails};
-use std::cmp::Ordering;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::hash::Hasher;
 use std::path::Path;
-use std::sync::{atomic, Arc};
+use std::sync::{atomic, Arc, Mutex};
 
 /// A watch-only wallet defined by a CT descriptor.
 pub struct Wollet {
     pub(crate) config: Config,
     pub(crate) store: Store,
     pub(crate) persister: Arc<dyn Persister + Send + Sync>,
+    pub(crate) locked: Mutex<HashMap<OutPoint, LockedUtxo>>,
+    /// Transactions applied with [`Wollet::apply_transaction()`] and not yet seen by a scan,
+    /// with the tip height at the time they were applied
+    pub(crate) applied: HashMap<Txid, Height>,
+    /// Addresses watched with [`Wollet::watch_address()`], by script pubkey
+    pub(crate) watched: HashMap<Script, WatchedAddress>,
+    /// Transactions confirmed before this height are ignored by the scans
+    birthday: Option<Height>,
     descriptor: WolletDescriptor,
     // cached value
     max_weight_to_satisfy: usize,
@@ -51,6 +62,9 @@ pub struct WolletConciseState {
     paths: HashMap<Script, (Chain, ChildNumber)>,
     scripts: HashMap<(Chain, ChildNumber), Script>,
     heights: HashMap<Txid, Option<Height>>,
+    applied: HashMap<Txid, Height>,
+    watched: HashMap<Script, WatchedAddress>,
+    birthday: Option<Height>,
     tip: (Height, BlockHash),
     last_unused: LastUnused,
 }
@@ -68,6 +82,12 @@ pub trait WolletState {
         descriptor: &Descriptor<DescriptorPublicKey>,
     ) -> Result<(Script, bool), Error>;
     fn heights(&self) -> &HashMap<Txid, Option<Height>>;
+    /// Transactions applied locally and not yet seen by a scan, see [`Wollet::apply_transaction()`]
+    fn applied(&self) -> &HashMap<Txid, Height>;
+    /// Addresses watched in addition to the descriptor ones, see [`Wollet::watch_address()`]
+    fn watched(&self) -> &HashMap<Script, WatchedAddress>;
+    /// The height before which the wallet has no transactions, see [`Wollet::set_birthday()`]
+    fn birthday(&self) -> Option<Height>;
     fn paths(&self) -> &HashMap<Script, (Chain, ChildNumber)>;
     fn txs(&self) -> HashSet<Txid>;
     fn tip(&self) -> (Height, BlockHash);
@@ -89,7 +109,12 @@ impl WolletState for WolletConciseState {
         };
 
         let start = batch * BATCH_SIZE;
-        let end = start + BATCH_SIZE;
+        let end = match descriptor.has_wildcard() {
+            true => start + BATCH_SIZE,
+            // A non-ranged descriptor has a single script, in the first batch
+            false if batch == 0 => 1,
+            false => start,
+        };
         let ext_int: Chain = descriptor.try_into().unwrap_or(Chain::External);
         for j in start..end {
             let child = ChildNumber::from_normal_idx(j)?;
@@ -124,6 +149,18 @@ impl WolletState for WolletConciseState {
         &self.heights
     }
 
+    fn applied(&self) -> &HashMap<Txid, Height> {
+        &self.applied
+    }
+
+    fn watched(&self) -> &HashMap<Script, WatchedAddress> {
+        &self.watched
+    }
+
+    fn birthday(&self) -> Option<Height> {
+        self.birthday
+    }
+
     fn paths(&self) -> &HashMap<Script, (Chain, ChildNumber)> {
         &self.paths
     }
@@ -177,6 +214,18 @@ impl WolletState for Wollet {
         &self.store.cache.heights
     }
 
+    fn applied(&self) -> &HashMap<Txid, Height> {
+        &self.applied
+    }
+
+    fn watched(&self) -> &HashMap<Script, WatchedAddress> {
+        &self.watched
+    }
+
+    fn birthday(&self) -> Option<Height> {
+        self.birthday
+    }
+
     fn paths(&self) -> &HashMap<Script, (Chain, ChildNumber)> {
         &self.store.cache.paths
     }
@@ -217,7 +266,11 @@ impl WolletState for Wollet {
 impl std::hash::Hash for Wollet {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.config.hash(state);
-        self.store.hash(state);
+        // Locally applied transactions are not persisted, so they must not change the status
+        // the updates are checked against
+        self.store
+            .cache
+            .hash_excluding(state, |txid| self.applied.contains_key(txid));
         self.descriptor.hash(state);
     }
 }
@@ -235,11 +288,26 @@ impl Wollet {
         let max_weight_to_satisfy = descriptor
             .definite_descriptor(Chain::External, 0)?
             .max_weight_to_satisfy()?;
+        let locked = persister
+            .get_locked_utxos()?
+            .into_iter()
+            .map(|l| (l.outpoint, l))
+            .collect();
+        let watched = persister
+            .get_watched_addresses()?
+            .into_iter()
+            .map(|w| (w.script_pubkey(), w))
+            .collect();
+        let birthday = persister.get_birthday()?;
         let mut wollet = Wollet {
             store,
             config,
             descriptor,
             persister,
+            locked: Mutex::new(locked),
+            applied: HashMap::new(),
+            watched,
+            birthday,
             max_weight_to_satisfy,
         };
 
@@ -267,6 +335,9 @@ impl Wollet {
             paths: cache.paths.clone(),
             scripts: cache.scripts.clone(),
             heights: cache.heights.clone(),
+            applied: self.applied.clone(),
+            watched: self.watched.clone(),
+            birthday: self.birthday,
             tip: cache.tip,
             last_unused: LastUnused {
                 internal: cache.last_unused_internal.load(atomic::Ordering::Relaxed),
@@ -311,6 +382,21 @@ impl Wollet {
         self.config.network()
     }
 
+    /// Get the wallet birthday, the height before which the wallet has no transactions
+    pub fn birthday(&self) -> Option<Height> {
+        self.birthday
+    }
+
+    /// Set the wallet birthday, so that the following scans skip the previous history
+    ///
+    /// Transactions confirmed before the birthday are ignored, backends scanning blocks start
+    /// from it instead of genesis. Transactions already in the wallet are kept.
+    pub fn set_birthday(&mut self, birthday: Option<Height>) -> Result<(), Error> {
+        self.birthday = birthday;
+        self.persister.set_birthday(birthday)?;
+        Ok(())
+    }
+
     /// Get a reference of the wallet descriptor
     pub fn descriptor(&self) -> &ConfidentialDescriptor<DescriptorPublicKey> {
         self.descriptor.as_ref()
@@ -373,14 +459,21 @@ impl Wollet {
     fn unwrap_or_last_unused(&self, index: Option<u32>) -> u32 {
         match index {
             Some(i) => i,
-            None => self
-                .store
-                .cache
-                .last_unused_external
-                .load(atomic::Ordering::Relaxed),
+            None => self.last_unused_with_applied(Chain::External),
         }
     }
 
+    /// The last unused index of the given chain, considering also the locally applied transactions
+    fn last_unused_with_applied(&self, ext_int: Chain) -> u32 {
+        let (external, internal) = last_used(&self.store, self.applied.keys().cloned());
+        let (store, applied) = match ext_int {
+            Chain::External => (&self.store.cache.last_unused_external, external),
+            Chain::Internal => (&self.store.cache.last_unused_internal, internal),
+        };
+        let last_unused = store.load(atomic::Ordering::Relaxed);
+        applied.map_or(last_unused, |i| last_unused.max(i + 1))
+    }
+
     /// Get a wallet change address
     ///
     /// If a specific descriptor is given for change addresses  it's used to derive this address
@@ -391,11 +484,7 @@ impl Wollet {
     pub fn change(&self, index: Option<u32>) -> Result<AddressResult, Error> {
         let index = match index {
             Some(i) => i,
-            None => self
-                .store
-                .cache
-                .last_unused_internal
-                .load(atomic::Ordering::Relaxed),
+            None => self.last_unused_with_applied(Chain::Internal),
         };
 
         let address = self
@@ -404,6 +493,9 @@ impl Wollet {
         Ok(AddressResult::new(address, index))
     }
 
+    /// Get the wallet transaction outputs, if `unspent` only the unspent ones
+    ///
+    /// Outputs to watched addresses are included, without derivation index and chain.
     pub fn txos_inner(&self, unspent: bool) -> Result<Vec<WalletTxOut>, Error> {
         let mut txos = vec![];
         let spent = if unspent {
@@ -434,14 +526,20 @@ impl Wollet {
                 .filter(|(outpoint, _)| !spent.contains(outpoint))
                 .filter_map(|(outpoint, output)| {
                     if let Some(unblinded) = self.store.cache.unblinded.get(&outpoint) {
-                        let index = self.index(&output.script_pubkey).ok()?;
+                        let (ext_int, wildcard_index) = match self.index(&output.script_pubkey) {
+                            Ok((ext_int, index)) => (Some(ext_int), Some(index)),
+                            Err(_) if self.watched.contains_key(&output.script_pubkey) => {
+                                (None, None)
+                            }
+                            Err(_) => return None,
+                        };
                         return Some(WalletTxOut {
                             outpoint,
                             script_pubkey: output.script_pubkey.clone(),
                             height: *height,
                             unblinded: *unblinded,
-                            wildcard_index: index.1,
-                            ext_int: index.0,
+                            wildcard_index,
+                            ext_int,
                         });
                     }
                     None
@@ -459,7 +557,7 @@ impl Wollet {
         Ok(utxos)
     }
 
-    fn txos(&self) -> Result<HashMap<OutPoint, WalletTxOut>, Error> {
+    pub(crate) fn txos(&self) -> Result<HashMap<OutPoint, WalletTxOut>, Error> {
         Ok(self
             .txos_inner(false)?
             .iter()
@@ -518,55 +616,11 @@ impl Wollet {
         self.balance_from_utxos(&utxos)
     }
 
-    /// Get the wallet transactions
+    /// Get the wallet transactions, most recent first
+    ///
+    /// For wallets with many transactions consider using [`Wollet::query_transactions()`]
     pub fn transactions(&self) -> Result<Vec<WalletTx>, Error> {
-        let mut txs = vec![];
-        let mut my_txids: Vec<(&Txid, &Option<u32>)> = self.store.cache.heights.iter().collect();
-        my_txids.sort_by(|a, b| {
-            let height_cmp =
-                b.1.unwrap_or(std::u32::MAX)
-                    .cmp(&a.1.unwrap_or(std::u32::MAX));
-            match height_cmp {
-                Ordering::Equal => b.0.cmp(a.0),
-                h => h,
-            }
-        });
-
-        let txos = self.txos()?;
-        for (txid, height) in my_txids.iter() {
-            let tx = self
-                .store
-                .cache
-                .all_txs
-                .get(*txid)
-                .ok_or_else(|| Error::Generic(format!("list_tx no tx {}", txid)))?;
-
-            let balance = tx_balance(**txid, tx, &txos);
-            if balance.is_empty() {
-                // Transaction has no output or input that the wollet can unblind,
-                // ignore this transaction
-                continue;
-            }
-            let fee = tx_fee(tx);
-            let policy_asset = self.policy_asset();
-            let type_ = tx_type(tx, &policy_asset, &balance, fee);
-            let timestamp = height.and_then(|h| self.store.cache.timestamps.get(&h).cloned());
-            let inputs = tx_inputs(tx, &txos);
-            let outputs = tx_outputs(**txid, tx, &txos);
-            txs.push(WalletTx {
-                tx: tx.clone(),
-                txid: **txid,
-                height: **height,
-                balance,
-                fee,
-                type_,
-                timestamp,
-                inputs,
-                outputs,
-            });
-        }
-
-        Ok(txs)
+        Ok(self.query_transactions(&TxQuery::new())?.txs)
     }
 
     /// Get a wallet transaction
@@ -604,7 +658,7 @@ impl Wollet {
     pub fn issuances(&self) -> Result<Vec<IssuanceDetails>, Error> {
         let mut r = vec![];
         for tx in self.transactions()? {
-            r.extend(extract_issuances(&tx.tx));
+            r.extend(extract_issuances(&tx.tx, self.descriptor()));
         }
         Ok(r)
     }
@@ -761,7 +815,7 @@ impl Wollet {
     }
 }
 
-fn tx_balance(
+pub(crate) fn tx_balance(
     txid: Txid,
     tx: &Transaction,
     txos: &HashMap<OutPoint, WalletTxOut>,
@@ -797,7 +851,7 @@ pub fn full_scan_with_electrum_client(
     Ok(())
 }
 
-fn tx_fee(tx: &Transaction) -> u64 {
+pub(crate) fn tx_fee(tx: &Transaction) -> u64 {
     tx.output
         .iter()
         .filter(|o| o.script_pubkey.is_empty())
@@ -809,7 +863,7 @@ fn tx_fee(tx: &Transaction) -> u64 {
 ///
 /// Defining clear rules for types is highly arbitrary so here we provide a string that should
 /// define the type, but it might be inaccurate in some cases.
-fn tx_type(
+pub(crate) fn tx_type(
     tx: &Transaction,
     policy_asset: &AssetId,
     balance: &BTreeMap<AssetId, i64>,
@@ -844,14 +898,17 @@ fn tx_type(
     }
 }
 
-fn tx_inputs(tx: &Transaction, txos: &HashMap<OutPoint, WalletTxOut>) -> Vec<Option<WalletTxOut>> {
+pub(crate) fn tx_inputs(
+    tx: &Transaction,
+    txos: &HashMap<OutPoint, WalletTxOut>,
+) -> Vec<Option<WalletTxOut>> {
     tx.input
         .iter()
         .map(|i| txos.get(&i.previous_output).cloned())
         .collect()
 }
 
-fn tx_outputs(
+pub(crate) fn tx_outputs(
     txid: Txid, // passed to avoid expensive re-computation
     tx: &Transaction,
     txos: &HashMap<OutPoint, WalletTxOut>,
@@ -1079,6 +1136,88 @@ mod tests {
         assert_eq!(4667218140179748739, wollet.status());
     }
 
+    #[test]
+    fn test_apply_transaction_reload() {
+        let bytes = lwk_test_util::update_test_vector_bytes();
+        let tip = crate::Update::deserialize(&bytes[..]).unwrap().tip;
+        let exp = "ct(slip77(9c8e4f05c7711a98c838be228bcb84924d4570ca53f35fa1c793e58841d47023),elwpkh([73c5da0a/84'/1'/0']tpubDC8msFGeGuwnKG9Upg7DM2b4DaRqg3CUZa5g8v2SRQ6K4NSkxUgd7HsL2XVWbVm39yBA4LAxysQAm397zwQSQoQgewGiYZqrA9DsP4zbQ1M/<0;1>/*))";
+        let desc: WolletDescriptor = format!("{}#{}", exp, desc_checksum(exp).unwrap())
+            .parse()
+            .unwrap();
+        let network = ElementsNetwork::LiquidTestnet;
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let mut wollet = Wollet::with_fs_persist(network, desc.clone(), &tempdir).unwrap();
+
+        // Fund the wallet with an update
+        let script = wollet.address(Some(0)).unwrap().address().script_pubkey();
+        let funding = Transaction {
+            version: 2,
+            lock_time: elements::LockTime::ZERO,
+            input: vec![],
+            output: vec![elements::TxOut {
+                script_pubkey: script.clone(),
+                ..Default::default()
+            }],
+        };
+        let funding_txid = funding.txid();
+        let utxo = OutPoint::new(funding_txid, 0);
+        let secrets = TxOutSecrets::new(
+            network.policy_asset(),
+            AssetBlindingFactor::zero(),
+            1000,
+            ValueBlindingFactor::zero(),
+        );
+        let update = crate::Update {
+            wollet_status: wollet.status(),
+            new_txs: crate::DownloadTxResult {
+                txs: vec![(funding_txid, funding)],
+                unblinds: vec![(utxo, secrets)],
+            },
+            txid_height_new: vec![(funding_txid, Some(tip.height))],
+            txid_height_delete: vec![],
+            timestamps: vec![],
+            scripts: [(script, (Chain::External, ChildNumber::from(0)))].into(),
+            tip,
+        };
+        wollet.apply_update(update.clone()).unwrap();
+        let status = wollet.status();
+
+        // A transaction spending a wallet utxo doesn't change the status
+        let tx = Transaction {
+            version: 2,
+            lock_time: elements::LockTime::ZERO,
+            input: vec![elements::TxIn {
+                previous_output: utxo,
+                ..Default::default()
+            }],
+            output: vec![],
+        };
+        let txid = tx.txid();
+        wollet.apply_transaction(tx.clone()).unwrap();
+        assert!(wollet.utxos().unwrap().is_empty());
+        assert_eq!(wollet.status(), status);
+
+        // The scan returning the transaction is persisted and the wallet can be reloaded
+        let sync = crate::Update {
+            wollet_status: wollet.status(),
+            new_txs: crate::DownloadTxResult {
+                txs: vec![(txid, tx)],
+                unblinds: vec![],
+            },
+            txid_height_new: vec![(txid, None)],
+            txid_height_delete: vec![],
+            timestamps: vec![],
+            scripts: HashMap::new(),
+            tip: update.tip,
+        };
+        wollet.apply_update(sync).unwrap();
+        let status = wollet.status();
+        let wollet = Wollet::with_fs_persist(network, desc, &tempdir).unwrap();
+        assert_eq!(wollet.status(), status);
+        assert!(wollet.transaction(&txid).unwrap().is_some());
+        assert!(wollet.utxos().unwrap().is_empty());
+    }
+
     #[test]
     fn test_wollet_pegin_address() {
         let fed_desc: BtcDescriptor<bitcoin::PublicKey> =
@@ -7,7 +7,7 @@ use electrum_client::ScriptStatus;
 use elements::bitcoin::{bip32::DerivationPath, XKeyIdentifier};
 use elements::encode::deserialize;
 use elements::hex::{FromHex, ToHex};
-use elements::Transaction;
+use elements::{OutPoint, Transaction, Txid};
 use lwk_common::electrum_ssl::{LIQUID_SOCKET, LIQUID_TESTNET_SOCKET};
 use lwk_common::Signer;
 use lwk_containers::testcontainers::clients::Cli;
@@ -249,7 +249,6 @@ fn unsupported_descriptor() {
     let desc_p2pkh = format!("ct({view_key},elpkh({xpub1}/*))");
     let desc_p2sh = format!("ct({view_key},elsh(multi(2,{xpub1}/*,{xpub2}/*)))",);
     let desc_p2tr = format!("ct({view_key},eltr({xpub1}/*))");
-    let desc_no_wildcard = format!("ct({view_key},elwpkh({xpub1}))");
 
     let desc_multi_path_1 = format!("ct({view_key},elwpkh({xpub1}/<0;1;2>/*))");
     let desc_multi_path_2 = format!("ct({view_key},elwpkh({xpub1}/<0;1>/0/*))");
@@ -261,10 +260,6 @@ fn unsupported_descriptor() {
         (desc_p2pkh, Error::UnsupportedDescriptorNonV0),
         (desc_p2sh, Error::UnsupportedDescriptorNonV0),
         (desc_p2tr, Error::UnsupportedDescriptorNonV0),
-        (
-            desc_no_wildcard,
-            Error::UnsupportedDescriptorWithoutWildcard,
-        ),
         (desc_multi_path_1, Error::UnsupportedMultipathDescriptor),
         (desc_multi_path_2, Error::UnsupportedMultipathDescriptor),
         (desc_multi_path_3, Error::UnsupportedMultipathDescriptor),
@@ -445,6 +440,553 @@ fn contract() {
     }
 }
 
+#[test]
+fn portfolio() {
+    let contract = "{\"entity\":{\"domain\":\"test.com\"},\"issuer_pubkey\":\"0337cceec0beea0232ebe14cba0197a9fbd45fcf2ec946749de920e71434c2b904\",\"name\":\"Test\",\"precision\":2,\"ticker\":\"TEST\",\"version\":0}";
+
+    let server = setup();
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer.xpub());
+    let signers = [&AnySigner::Software(signer)];
+
+    let client = test_client_electrum(&server.electrs.electrum_url);
+    let mut wallet = TestWollet::new(client, &desc);
+    wallet.fund_btc(&server);
+    let (asset, token) = wallet.issueasset(&signers, 10_000, 1, Some(contract), None);
+    let policy_asset = wallet.policy_asset();
+    let lbtc_balance = wallet.balance(&policy_asset);
+
+    // Wait for the issuance to be confirmed
+    server.elementsd_generate(1);
+    let contract = Contract::from_str(contract).unwrap();
+    let mut portfolio = vec![];
+    for _ in 0..120 {
+        wallet.sync();
+        portfolio = wallet.wollet.portfolio([(&asset, &contract)]).unwrap();
+        if portfolio
+            .iter()
+            .all(|a| a.balance.total() == a.balance.confirmed)
+        {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(500));
+    }
+    assert_eq!(portfolio.len(), 3);
+    let lbtc = &portfolio[0];
+    assert_eq!(lbtc.asset, policy_asset);
+    assert_eq!(lbtc.balance.confirmed, lbtc_balance);
+
+    let a = portfolio.iter().find(|a| a.asset == asset).unwrap();
+    assert_eq!(a.ticker.as_deref(), Some("TEST"));
+    assert_eq!(a.balance.confirmed, 10_000);
+    assert_eq!(a.format(a.balance.total()), "100.00");
+    assert_eq!(a.issuance.as_ref().unwrap().token, token);
+
+    // The token has no contract, but it's linked to the issuance
+    let t = portfolio.iter().find(|a| a.asset == token).unwrap();
+    assert_eq!(t.ticker, None);
+    assert_eq!(t.format(t.balance.total()), "1");
+    assert_eq!(t.issuance.as_ref().unwrap().asset, asset);
+
+    // Unconfirmed outputs received from others are untrusted
+    wallet.fund(&server, 1_000, None, None);
+    let portfolio = wallet.wollet.portfolio([]).unwrap();
+    assert_eq!(portfolio[0].balance.untrusted_pending, 1_000);
+    assert_eq!(portfolio[0].balance.confirmed, lbtc_balance);
+
+    // Untrusted outputs are not spent if requested
+    let address = wallet.address();
+    let err = wallet
+        .tx_builder()
+        .add_lbtc_recipient(&address, lbtc_balance)
+        .unwrap()
+        .only_trusted(true)
+        .finish()
+        .unwrap_err();
+    assert!(matches!(err, Error::InsufficientFunds));
+    let mut pset = wallet
+        .tx_builder()
+        .add_lbtc_recipient(&address, lbtc_balance)
+        .unwrap()
+        .finish()
+        .unwrap();
+    wallet.sign(signers[0], &mut pset);
+    wallet.send(&mut pset);
+
+    // The unconfirmed outputs of our own transaction are trusted
+    let balance = wallet.wollet.balance_details().unwrap()[&policy_asset];
+    assert_eq!(balance.untrusted_pending, 0);
+    assert_eq!(balance.confirmed, 0);
+    assert!(balance.trusted_pending > lbtc_balance);
+    assert_eq!(balance.trusted_spendable(), balance.total());
+
+    // Per asset history
+    let txs = wallet.wollet.asset_transactions(&asset).unwrap();
+    assert_eq!(txs.len(), 1);
+    assert!(txs[0].balance.contains_key(&token));
+    let txs = wallet.wollet.asset_transactions(&policy_asset).unwrap();
+    assert_eq!(txs.len(), 4);
+}
+
+#[test]
+fn lock_utxos() {
+    let server = setup();
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer.xpub());
+    let signers = [&AnySigner::Software(signer)];
+
+    let client = test_client_electrum(&server.electrs.electrum_url);
+    let mut wallet = TestWollet::new(client, &desc);
+    wallet.fund_btc(&server);
+    wallet.fund_btc(&server);
+    let policy_asset = wallet.policy_asset();
+    let address = wallet.address();
+    let utxos = wallet.wollet.utxos().unwrap();
+    assert_eq!(utxos.len(), 2);
+
+    // A locked utxo is frozen and is not spent
+    let outpoint = utxos[0].outpoint;
+    wallet.wollet.lock_utxo(outpoint, None).unwrap();
+    let expected = vec![LockedUtxo {
+        outpoint,
+        expiry: None,
+    }];
+    assert_eq!(wallet.wollet.locked_utxos().unwrap(), expected);
+    let balance = wallet.wollet.balance_details().unwrap()[&policy_asset];
+    assert_eq!(balance.frozen, 1_000_000);
+    let pset = wallet
+        .tx_builder()
+        .add_lbtc_recipient(&address, 10_000)
+        .unwrap()
+        .finish()
+        .unwrap();
+    assert_eq!(pset.n_inputs(), 1);
+    assert_eq!(pset.inputs()[0].previous_txid, utxos[1].outpoint.txid);
+
+    // Only wallet utxos can be locked
+    let err = wallet
+        .wollet
+        .lock_utxo(OutPoint::default(), None)
+        .unwrap_err();
+    assert!(matches!(err, Error::UtxoNotFound(_)));
+
+    // Lock the inputs of a PSET until it's broadcast or the lock expires
+    let tip = wallet.tip().height();
+    let mut pset = wallet
+        .tx_builder()
+        .add_lbtc_recipient(&address, 10_000)
+        .unwrap()
+        .lock_inputs(2)
+        .finish()
+        .unwrap();
+    assert_eq!(wallet.wollet.locked_utxos().unwrap().len(), 2);
+    let err = wallet
+        .tx_builder()
+        .add_lbtc_recipient(&address, 10_000)
+        .unwrap()
+        .finish()
+        .unwrap_err();
+    assert!(matches!(err, Error::InsufficientFunds));
+    assert!(wallet.wollet.unlock_utxo(&outpoint).unwrap());
+    assert!(!wallet.wollet.unlock_utxo(&outpoint).unwrap());
+    let locked = wallet.wollet.locked_utxos().unwrap();
+    assert_eq!(locked.len(), 1);
+    assert_eq!(locked[0].expiry, Some(tip + 2));
+
+    // Locks are persisted
+    let network = ElementsNetwork::default_regtest();
+    let descriptor = wallet.wollet.descriptor().to_string();
+    let db_root_dir = wallet.db_root_path();
+    let wollet =
+        Wollet::with_fs_persist(network, descriptor.parse().unwrap(), db_root_dir).unwrap();
+    assert_eq!(wollet.locked_utxos().unwrap(), locked);
+
+    // Once the PSET is broadcast, its inputs are spent and not locked anymore
+    wallet.sign(signers[0], &mut pset);
+    wallet.send(&mut pset);
+    assert!(wallet.wollet.locked_utxos().unwrap().is_empty());
+
+    // Expired locks are released
+    let outpoint = wallet.wollet.utxos().unwrap()[0].outpoint;
+    wallet.wollet.lock_utxo(outpoint, Some(tip + 1)).unwrap();
+    assert_eq!(wallet.wollet.locked_utxos().unwrap().len(), 1);
+    server.elementsd_generate(1);
+    wallet.wait_height(tip + 1);
+    assert!(wallet.wollet.locked_utxos().unwrap().is_empty());
+}
+
+#[test]
+fn query_transactions() {
+    let server = setup();
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer.xpub());
+    let signers = [&AnySigner::Software(signer)];
+
+    let client = test_client_electrum(&server.electrs.electrum_url);
+    let mut wallet = TestWollet::new(client, &desc);
+    let address = wallet.address();
+    wallet.fund(&server, 1_000_000, Some(address.clone()), None);
+    wallet.fund_btc(&server);
+    let asset = wallet.fund_asset(&server);
+    wallet.send_btc(&signers, None, None);
+
+    let all = wallet.wollet.transactions().unwrap();
+    assert_eq!(all.len(), 4);
+
+    // Without filters the query returns the same transactions
+    let page = wallet.wollet.query_transactions(&TxQuery::new()).unwrap();
+    assert_eq!(page.total, 4);
+    assert_eq!(page.txs, all);
+
+    // Pagination
+    let query = TxQuery::new().offset(1).limit(2);
+    let page = wallet.wollet.query_transactions(&query).unwrap();
+    assert_eq!(page.total, 4);
+    assert_eq!(page.txs, all[1..3]);
+    let query = TxQuery::new().offset(10);
+    let page = wallet.wollet.query_transactions(&query).unwrap();
+    assert_eq!(page.total, 4);
+    assert!(page.txs.is_empty());
+
+    // Filters
+    let query = TxQuery::new().asset(asset);
+    let page = wallet.wollet.query_transactions(&query).unwrap();
+    assert_eq!(page.total, 1);
+    assert!(page.txs[0].balance.contains_key(&asset));
+    let query = TxQuery::new().tx_type("incoming");
+    let page = wallet.wollet.query_transactions(&query).unwrap();
+    assert_eq!(page.total, 3);
+    let query = TxQuery::new().address(&address);
+    let page = wallet.wollet.query_transactions(&query).unwrap();
+    assert_eq!(page.total, 1);
+    let tip = wallet.tip().height();
+    let query = TxQuery::new().max_height(tip);
+    let page = wallet.wollet.query_transactions(&query).unwrap();
+    assert_eq!(
+        page.total,
+        all.iter().filter(|tx| tx.height.is_some()).count()
+    );
+    let query = TxQuery::new().min_height(tip + 1);
+    let page = wallet.wollet.query_transactions(&query).unwrap();
+    assert_eq!(
+        page.total,
+        all.iter().filter(|tx| tx.height.is_none()).count()
+    );
+
+    // The summaries match the transactions, without the raw transaction
+    let query = TxQuery::new().limit(2);
+    let page = wallet.wollet.query_transactions(&query).unwrap();
+    let summaries = wallet.wollet.query_transaction_summaries(&query).unwrap();
+    assert_eq!(summaries.total, page.total);
+    let expected: Vec<WalletTxSummary> = page.txs.into_iter().map(Into::into).collect();
+    assert_eq!(summaries.txs, expected);
+}
+
+#[test]
+fn apply_transaction() {
+    let server = setup();
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer.xpub());
+    let signers = [&AnySigner::Software(signer)];
+
+    let client = test_client_electrum(&server.electrs.electrum_url);
+    let mut wallet = TestWollet::new(client, &desc);
+    wallet.fund_btc(&server);
+    wallet.fund_btc(&server);
+    let address = wallet.address();
+    let utxos = wallet.wollet.utxos().unwrap();
+    assert_eq!(utxos.len(), 2);
+
+    // A broadcast transaction is known by the wallet before the server returns it
+    let mut pset = wallet
+        .tx_builder()
+        .add_lbtc_recipient(&address, 10_000)
+        .unwrap()
+        .finish()
+        .unwrap();
+    wallet.sign(signers[0], &mut pset);
+    let tx = wallet.wollet.finalize(&mut pset).unwrap();
+    let txid = wallet.client.broadcast(&tx).unwrap();
+    wallet.wollet.apply_transaction(tx.clone()).unwrap();
+    let wallet_tx = wallet.wollet.transaction(&txid).unwrap().unwrap();
+    assert_eq!(wallet_tx.height, None);
+    let spent = OutPoint::new(
+        tx.input[0].previous_output.txid,
+        tx.input[0].previous_output.vout,
+    );
+    let utxos = wallet.wollet.utxos().unwrap();
+    assert!(!utxos.iter().any(|u| u.outpoint == spent));
+    assert!(utxos.iter().any(|u| u.outpoint.txid == txid));
+
+    // Applying it twice has no effect
+    wallet.wollet.apply_transaction(tx).unwrap();
+    assert_eq!(wallet.wollet.utxos().unwrap(), utxos);
+
+    // Once the server sees it, it's persisted
+    let tip = wallet.tip().height();
+    server.elementsd_generate(1);
+    wallet.wait_height(tip + 1);
+    let wallet_tx = wallet.wollet.transaction(&txid).unwrap().unwrap();
+    assert_eq!(wallet_tx.height, Some(tip + 1));
+    let network = ElementsNetwork::default_regtest();
+    let descriptor = wallet.wollet.descriptor().to_string();
+    let db_root_dir = wallet.db_root_path();
+    let wollet =
+        Wollet::with_fs_persist(network, descriptor.parse().unwrap(), db_root_dir).unwrap();
+    assert!(wollet.transaction(&txid).unwrap().is_some());
+
+    // A transaction never seen by the server is removed after a new block
+    let balance = wallet.wollet.balance().unwrap();
+    let mut pset = wallet
+        .tx_builder()
+        .add_lbtc_recipient(&address, 10_000)
+        .unwrap()
+        .finish()
+        .unwrap();
+    wallet.sign(signers[0], &mut pset);
+    let tx = wallet.wollet.finalize(&mut pset).unwrap();
+    let txid = tx.txid();
+    wallet.wollet.apply_transaction(tx).unwrap();
+    wallet.sync();
+    assert!(wallet.wollet.transaction(&txid).unwrap().is_some());
+    let db_root_dir = wallet.db_root_path();
+    let wollet =
+        Wollet::with_fs_persist(network, descriptor.parse().unwrap(), db_root_dir).unwrap();
+    assert_eq!(wollet.balance().unwrap(), balance);
+    let tip = wallet.tip().height();
+    server.elementsd_generate(1);
+    wallet.wait_height(tip + 1);
+    assert!(wallet.wollet.transaction(&txid).unwrap().is_none());
+    assert_eq!(wallet.wollet.balance().unwrap(), balance);
+
+    // Only wallet transactions can be applied
+    let mut tx = wallet_tx.tx;
+    tx.input.clear();
+    tx.output.clear();
+    let err = wallet.wollet.apply_transaction(tx).unwrap_err();
+    assert!(matches!(err, Error::TransactionNotMine(_)));
+}
+
+fn wait_multi_tx<C: BlockchainBackend>(client: &mut C, multi: &mut MultiWollet, txid: &Txid) {
+    for _ in 0..120 {
+        let updates = client.full_scan_multi(multi).unwrap();
+        multi.apply_updates(updates).unwrap();
+        if multi
+            .transactions()
+            .unwrap()
+            .iter()
+            .any(|tx| &tx.txid == txid)
+        {
+            return;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(500));
+    }
+    panic!("Wallet does not have {} in its list", txid);
+}
+
+#[test]
+fn multi_wollet() {
+    // A wallet migrating from elsh(wpkh) to elwpkh
+    let server = setup();
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc_legacy = format!("ct({},elsh(wpkh({}/*)))", view_key, signer.xpub());
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer.xpub());
+    let network = ElementsNetwork::default_regtest();
+    let new_wollet =
+        |desc: &str| Wollet::new(network, NoPersist::new(), desc.parse().unwrap()).unwrap();
+    let mut multi = MultiWollet::new(new_wollet(&desc));
+    multi
+        .add(new_wollet(&desc_legacy), ChangePolicy::SpendOnly)
+        .unwrap();
+    let mut client = test_client_electrum(&server.electrs.electrum_url);
+    let policy_asset = multi.policy_asset();
+
+    // Fund both descriptors
+    let address = multi.address(None).unwrap().address().clone();
+    let address_legacy = multi.wollets()[1].address(None).unwrap().address().clone();
+    let txid = server.elementsd_sendtoaddress(&address, 1_000_000, None);
+    wait_multi_tx(&mut client, &mut multi, &txid);
+    let txid = server.elementsd_sendtoaddress(&address_legacy, 1_000_000, None);
+    wait_multi_tx(&mut client, &mut multi, &txid);
+    assert_eq!(multi.balance().unwrap()[&policy_asset], 2_000_000);
+    assert_eq!(multi.utxos().unwrap().len(), 2);
+    assert_eq!(multi.transactions().unwrap().len(), 2);
+
+    // Spend more than each descriptor holds
+    let node_address = server.elementsd_getnewaddress();
+    let mut pset = multi
+        .tx_builder()
+        .add_lbtc_recipient(&node_address, 1_500_000)
+        .unwrap()
+        .finish()
+        .unwrap();
+    assert_eq!(pset.n_inputs(), 2);
+    let details = multi.get_details(&pset).unwrap();
+    let fee = details.balance.fee as i64;
+    assert_eq!(details.balance.balances[&policy_asset], -1_500_000 - fee);
+    // Details are added for the inputs of both descriptors
+    assert_eq!(signer.sign(&mut pset).unwrap(), 2);
+    let tx = multi.wollets()[0].finalize(&mut pset).unwrap();
+    let txid = client.broadcast(&tx).unwrap();
+    wait_multi_tx(&mut client, &mut multi, &txid);
+    let balance = 500_000 - fee as u64;
+    assert_eq!(multi.balance().unwrap()[&policy_asset], balance);
+    // The change goes to the new descriptor
+    assert_eq!(
+        multi.wollets()[0].balance().unwrap()[&policy_asset],
+        balance
+    );
+    assert_eq!(multi.wollets()[1].balance().unwrap()[&policy_asset], 0);
+
+    // A transfer between the descriptors only accounts for the fee
+    let address_legacy = multi.wollets()[1].address(None).unwrap().address().clone();
+    let mut pset = multi
+        .tx_builder()
+        .add_lbtc_recipient(&address_legacy, 100_000)
+        .unwrap()
+        .finish()
+        .unwrap();
+    signer.sign(&mut pset).unwrap();
+    let tx = multi.wollets()[0].finalize(&mut pset).unwrap();
+    let txid = client.broadcast(&tx).unwrap();
+    wait_multi_tx(&mut client, &mut multi, &txid);
+    let txs = multi.transactions().unwrap();
+    assert_eq!(txs.len(), 4);
+    let tx = txs.iter().find(|tx| tx.txid == txid).unwrap();
+    assert_eq!(tx.balance[&policy_asset], -(tx.fee as i64));
+    assert_eq!(tx.inputs.iter().filter(|i| i.is_some()).count(), 1);
+    assert_eq!(tx.outputs.iter().filter(|o| o.is_some()).count(), 2);
+    assert_eq!(multi.balance().unwrap()[&policy_asset], balance - tx.fee);
+}
+
+#[test]
+fn non_ranged_descriptor_and_watched_address() {
+    let server = setup();
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}))", view_key, signer.xpub());
+
+    // A non-ranged descriptor always returns the same address
+    let client = test_client_electrum(&server.electrs.electrum_url);
+    let mut wallet = TestWollet::new(client, &desc);
+    let address = wallet.address();
+    wallet.fund_btc(&server);
+    wallet.fund_btc(&server);
+    assert_eq!(wallet.address(), address);
+    assert_eq!(wallet.wollet.address(Some(1)).unwrap().address(), &address);
+    assert_eq!(wallet.balance_btc(), 2_000_000);
+    assert_eq!(wallet.wollet.utxos().unwrap().len(), 2);
+
+    // An address outside the descriptor, watched with its blinding key
+    let blinding_key = secp256k1::SecretKey::from_slice(&[3; 32]).unwrap();
+    let watched = server
+        .elementsd_getnewaddress()
+        .to_unconfidential()
+        .to_confidential(blinding_key.public_key(&EC));
+    wallet.wollet.watch_address(&watched, blinding_key).unwrap();
+    let txid = server.elementsd_sendtoaddress(&watched, 100_000, None);
+    wallet.wait_for_tx(&txid);
+    let tx = wallet.get_tx(&txid);
+    assert_eq!(tx.balance[&wallet.policy_asset()], 100_000);
+    assert_eq!(wallet.balance_btc(), 2_100_000);
+
+    // Outputs to watched addresses are not spendable
+    let details = wallet.wollet.balance_details().unwrap();
+    assert_eq!(details[&wallet.policy_asset()].frozen, 100_000);
+
+    // Watched addresses are persisted
+    let network = ElementsNetwork::default_regtest();
+    let descriptor = wallet.wollet.descriptor().to_string();
+    let db_root_dir = wallet.db_root_path();
+    let wollet =
+        Wollet::with_fs_persist(network, descriptor.parse().unwrap(), db_root_dir).unwrap();
+    assert_eq!(wollet.watched_addresses().len(), 1);
+    assert_eq!(wollet.balance().unwrap(), wallet.wollet.balance().unwrap());
+
+    assert!(wallet.wollet.unwatch_address(&watched).unwrap());
+    assert_eq!(wallet.balance_btc(), 2_000_000);
+}
+
+#[test]
+fn wallet_birthday() {
+    let server = setup();
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer.xpub());
+    let client = test_client_electrum(&server.electrs.electrum_url);
+    let mut wallet = TestWollet::new(client, &desc);
+    wallet.fund_btc(&server);
+    server.elementsd_generate(1);
+    let birthday = server.elementsd_height() as u32 + 1;
+    wallet.fund_btc(&server);
+    assert_eq!(wallet.balance_btc(), 2_000_000);
+
+    // A wallet born after the first funding doesn't see it
+    let network = ElementsNetwork::default_regtest();
+    let db_root_dir = tempfile::tempdir().unwrap();
+    let mut wollet = Wollet::with_fs_persist(network, desc.parse().unwrap(), &db_root_dir).unwrap();
+    wollet.set_birthday(Some(birthday)).unwrap();
+    let mut client = test_client_electrum(&server.electrs.electrum_url);
+    let update = client.full_scan(&wollet).unwrap().unwrap();
+    wollet.apply_update(update).unwrap();
+    assert_eq!(wollet.balance().unwrap()[&wallet.policy_asset()], 1_000_000);
+
+    // The birthday is persisted
+    let wollet = Wollet::with_fs_persist(network, desc.parse().unwrap(), &db_root_dir).unwrap();
+    assert_eq!(wollet.birthday(), Some(birthday));
+    assert_eq!(wollet.balance().unwrap()[&wallet.policy_asset()], 1_000_000);
+}
+
+#[test]
+fn audit_report() {
+    let server = setup();
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer.xpub());
+    let client = test_client_electrum(&server.electrs.electrum_url);
+    let mut wallet = TestWollet::new(client, &desc);
+    wallet.fund_btc(&server);
+    server.elementsd_generate(1);
+    let first_height = server.elementsd_height() as u32;
+    wallet.fund_btc(&server);
+    server.elementsd_generate(1);
+    wallet.sync();
+
+    // Disclose the transactions after the first funding, with the balance at the tip
+    let query = AuditQuery::new().start_height(first_height + 1);
+    let mut report = wallet.wollet.audit_report(&query).unwrap();
+    assert_eq!(report.txids.len(), 1);
+    assert_eq!(report.balances[&wallet.policy_asset()], 2_000_000);
+    assert_eq!(report.outputs.iter().filter(|o| o.unspent).count(), 2);
+    report.verify().unwrap();
+
+    // Balance before the second funding
+    let query = AuditQuery::new().balance_height(first_height);
+    let report_before = wallet.wollet.audit_report(&query).unwrap();
+    assert_eq!(report_before.balances[&wallet.policy_asset()], 1_000_000);
+    report_before.verify().unwrap();
+
+    // Signed reports can be checked offline
+    let path: elements::bitcoin::bip32::DerivationPath = "m/0".parse().unwrap();
+    report.sign(&signer, &path).unwrap();
+    let json = serde_json::to_string(&report).unwrap();
+    let received: AuditReport = serde_json::from_str(&json).unwrap();
+    received.verify().unwrap();
+
+    let mut tampered = received.clone();
+    tampered.outputs[0].unblinded.value += 1;
+    assert!(tampered.verify().is_err());
+    let mut tampered = received;
+    tampered.balance_height -= 1;
+    let err = tampered.verify().unwrap_err();
+    assert_eq!(err.to_string(), "Invalid report signature");
+}
+
 #[test]
 fn multiple_descriptors() {
     // Use a different descriptors for the asset and the reissuance token
@@ -571,8 +1113,7 @@ fn multiple_descriptors() {
         .transaction(&issuance.txid)
         .unwrap()
         .unwrap()
-        .tx
-        .clone();
+        .tx;
     let address_a = wallet_a.address();
     let mut pset = wallet_nt
         .tx_builder()
@@ -587,6 +1128,170 @@ fn multiple_descriptors() {
     assert_eq!(wallet_a.balance(asset), satoshi_a + satoshi_ar * 2);
 }
 
+#[test]
+fn confidential_issuance() {
+    let server = setup();
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer.xpub());
+    let client = test_client_electrum(&server.electrs.electrum_url);
+    let mut wallet = TestWollet::new(client, &desc);
+    wallet.fund_btc(&server);
+
+    // Issue an asset with blinded amounts, sending the token to another wallet
+    let signer_t = generate_signer();
+    let view_key_t = generate_view_key();
+    let desc_t = format!("ct({},elwpkh({}/*))", view_key_t, signer_t.xpub());
+    let client = test_client_electrum(&server.electrs.electrum_url);
+    let mut wallet_t = TestWollet::new(client, &desc_t);
+    wallet_t.fund_btc(&server);
+
+    let satoshi_a = 100_000;
+    let satoshi_t = 1;
+    let address_t = wallet_t.address();
+    let mut pset = wallet
+        .tx_builder()
+        .issue_asset(satoshi_a, None, satoshi_t, Some(address_t), None)
+        .unwrap()
+        .blind_issuance(true)
+        .finish()
+        .unwrap();
+    let (asset, token) = pset.inputs()[0].issuance_ids();
+    let details = wallet.wollet.get_details(&pset).unwrap();
+    let issuance = &details.issuances[0];
+    assert!(issuance.is_confidential());
+    assert_eq!(issuance.token(), Some(token));
+    assert_eq!(issuance.asset_satoshi(), Some(satoshi_a));
+    assert_eq!(issuance.token_satoshi(), Some(satoshi_t));
+    assert_eq!(
+        *details.balance.balances.get(&asset).unwrap(),
+        satoshi_a as i64
+    );
+
+    wallet.sign(&signer, &mut pset);
+    let txid = wallet.send(&mut pset);
+    wallet_t.sync();
+    assert_eq!(wallet.balance(&asset), satoshi_a);
+    assert_eq!(wallet_t.balance(&token), satoshi_t);
+
+    // The amounts are not published
+    let tx = wallet.wollet.transaction(&txid).unwrap().unwrap().tx;
+    assert!(tx.input[0].asset_issuance.amount.is_confidential());
+    assert!(tx.input[0].asset_issuance.inflation_keys.is_confidential());
+
+    // Only the issuer can unblind them
+    let issuance = wallet.wollet.issuance(&asset).unwrap();
+    assert!(issuance.is_confidential);
+    assert_eq!(issuance.token, token);
+    assert_eq!(issuance.asset_amount, Some(satoshi_a));
+    assert_eq!(issuance.token_amount, Some(satoshi_t));
+    assert!(issuance.asset_blinder.is_some());
+    let issuance_t = wallet_t.wollet.issuance(&asset).unwrap();
+    assert!(issuance_t.is_confidential);
+    assert!(issuance_t.asset_amount.is_none());
+    assert!(issuance_t.token_amount.is_none());
+
+    // Reissue with a blinded amount from the token wallet
+    let satoshi_ar = 1_000;
+    let address_a = wallet.address();
+    let mut pset = wallet_t
+        .tx_builder()
+        .reissue_asset(asset, satoshi_ar, Some(address_a), None)
+        .unwrap()
+        .blind_issuance(true)
+        .finish()
+        .unwrap();
+    let details = wallet_t.wollet.get_details(&pset).unwrap();
+    let reissuance = details
+        .issuances
+        .iter()
+        .find(|i| i.is_reissuance())
+        .unwrap();
+    assert!(reissuance.is_confidential());
+    assert_eq!(reissuance.asset_satoshi(), Some(satoshi_ar));
+    wallet_t.sign(&signer_t, &mut pset);
+    let txid = wallet_t.send(&mut pset);
+    wallet.sync();
+    assert_eq!(wallet.balance(&asset), satoshi_a + satoshi_ar);
+    assert_eq!(wallet_t.balance(&token), satoshi_t);
+
+    let issuances = wallet_t.wollet.issuances().unwrap();
+    let reissuance = issuances.iter().find(|i| i.txid == txid).unwrap();
+    assert!(reissuance.is_reissuance);
+    assert!(reissuance.is_confidential);
+    assert_eq!(reissuance.asset_amount, Some(satoshi_ar));
+}
+
+#[test]
+fn multiple_issuances() {
+    let server = setup();
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer.xpub());
+    let client = test_client_electrum(&server.electrs.electrum_url);
+    let mut wallet = TestWollet::new(client, &desc);
+    wallet.fund_btc(&server);
+    let signers: [&AnySigner; 1] = [&AnySigner::Software(signer.clone())];
+    let (asset_a, token_a) = wallet.issueasset(&signers, 10_000, 1, None, None);
+
+    // Reissue an asset and issue two new ones in the same transaction,
+    // the wallet has one utxo for each of L-BTC, the token and the asset.
+    let satoshi_ar = 1_000;
+    let (satoshi_b, satoshi_c) = (20_000, 30_000);
+    let mut pset = wallet
+        .tx_builder()
+        .issue_asset(satoshi_b, None, 1, None, None)
+        .unwrap()
+        .reissue_asset(asset_a, satoshi_ar, None, None)
+        .unwrap()
+        .issue_asset(satoshi_c, None, 0, None, None)
+        .unwrap()
+        .finish()
+        .unwrap();
+    let details = wallet.wollet.get_details(&pset).unwrap();
+    assert_eq!(details.issuances.len(), 3);
+    assert_eq!(
+        details
+            .issuances
+            .iter()
+            .filter(|i| i.is_reissuance())
+            .count(),
+        1
+    );
+    let issued: Vec<_> = pset
+        .inputs()
+        .iter()
+        .filter(|i| i.has_issuance())
+        .map(|i| i.issuance_ids().0)
+        .collect();
+    assert_eq!(issued.len(), 3);
+    assert!(issued.contains(&asset_a));
+
+    wallet.sign(&signer, &mut pset);
+    wallet.send(&mut pset);
+    assert_eq!(wallet.balance(&asset_a), 10_000 + satoshi_ar);
+    assert_eq!(wallet.balance(&token_a), 1);
+    let balances: Vec<_> = issued.iter().map(|a| wallet.balance(a)).collect();
+    assert!(balances.contains(&satoshi_b));
+    assert!(balances.contains(&satoshi_c));
+
+    // Each issuance is found by the wallet
+    for asset in issued {
+        wallet.wollet.issuance(&asset).unwrap();
+    }
+
+    // Two reissuances of the same asset need two token utxos
+    let err = wallet
+        .tx_builder()
+        .reissue_asset(asset_a, 1, None, None)
+        .unwrap()
+        .reissue_asset(asset_a, 1, None, None)
+        .unwrap()
+        .finish()
+        .unwrap_err();
+    assert!(matches!(err, Error::InsufficientFunds));
+}
+
 #[test]
 fn create_pset_error() {
     let server = setup();
@@ -1615,6 +2320,19 @@ fn test_elements_rpc() {
     server.elementsd_generate(1);
     let utxos = elements_rpc_client.confirmed_utxos(&wd, 20).unwrap();
     assert_eq!(utxos.len(), 1);
+
+    // Scanning the blocks from the wallet birthday finds the same utxo
+    let height = server.elementsd_height() as u32;
+    wallet.wollet.set_birthday(Some(height)).unwrap();
+    let scanned = elements_rpc_client
+        .wollet_utxos(&wallet.wollet, 20)
+        .unwrap();
+    assert_eq!(scanned.len(), 1);
+    assert_eq!(scanned[0].outpoint, utxos[0].outpoint);
+    let scanned = elements_rpc_client
+        .scan_blocks(&wd, 20, height + 1)
+        .unwrap();
+    assert!(scanned.is_empty());
 }
 
 #[cfg(feature = "esplora")]
@@ -1738,3 +2456,99 @@ fn test_esplora_client() {
     let wallet = TestWollet::new(client, &desc);
     roundtrip_inner(wallet, &server, signers);
 }
+
+#[test]
+fn pset_interop() {
+    let server = setup();
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer.xpub());
+    let client = test_client_electrum(&server.electrs.electrum_url);
+    let mut wallet = TestWollet::new(client, &desc);
+    wallet.fund_btc(&server);
+    let node_address = server.elementsd_getnewaddress();
+
+    // Export a PSET created by LWK, Elements Core finalizes it
+    let mut pset = wallet
+        .tx_builder()
+        .add_lbtc_recipient(&node_address, 10_000)
+        .unwrap()
+        .finish()
+        .unwrap();
+    wallet.sign(&signer, &mut pset);
+    let exported = wallet.wollet.export_pset(&pset).unwrap().to_string();
+    assert_eq!(server.elementsd_expected_next(&exported), "finalizer");
+    let psbt = server.elementsd_walletprocesspsbt(&exported);
+    let tx_hex = server.elementsd_finalizepsbt(&psbt);
+    let txid = server.elementsd_sendrawtransaction(&tx_hex);
+    wallet.wait_for_tx(&Txid::from_str(&txid).unwrap());
+
+    // Import a PSET created by Elements Core spending a wallet utxo
+    let utxo = wallet.wollet.utxos().unwrap()[0].clone();
+    let fee = 1_000;
+    let btc = |sat: u64| format!("{}.{:08}", sat / 100_000_000, sat % 100_000_000);
+    let txid = utxo.outpoint.txid.to_string();
+    let inputs = serde_json::json!([{"txid": txid, "vout": utxo.outpoint.vout}]);
+    let address = node_address.to_string();
+    let outputs = serde_json::json!([
+        {address: btc(utxo.unblinded.value - fee), "blinder_index": 0},
+        {"fee": btc(fee)}
+    ]);
+    let psbt = server.elementsd_raw_createpsbt(inputs, outputs);
+    assert_eq!(server.elementsd_expected_next(&psbt), "updater");
+
+    let mut pset: elements::pset::PartiallySignedTransaction = psbt.parse().unwrap();
+    wallet.wollet.import_pset(&mut pset).unwrap();
+    assert!(pset.inputs()[0].witness_utxo.is_some());
+    assert_eq!(pset.inputs()[0].bip32_derivation.len(), 1);
+    assert_eq!(server.elementsd_expected_next(&pset.to_string()), "blinder");
+
+    let inp_txout_sec = std::collections::HashMap::from([(0, utxo.unblinded)]);
+    pset.blind_last(&mut rand::thread_rng(), &EC, &inp_txout_sec)
+        .unwrap();
+    wallet.sign(&signer, &mut pset);
+    let exported = wallet.wollet.export_pset(&pset).unwrap().to_string();
+    assert_eq!(server.elementsd_expected_next(&exported), "finalizer");
+    wallet.send(&mut pset);
+}
+
+#[test]
+fn pset_from_unsigned_tx() {
+    let server = setup();
+    let signer = generate_signer();
+    let view_key = generate_view_key();
+    let desc = format!("ct({},elwpkh({}/*))", view_key, signer.xpub());
+    let client = test_client_electrum(&server.electrs.electrum_url);
+    let mut wallet = TestWollet::new(client, &desc);
+    wallet.fund_btc(&server);
+    let node_address = server.elementsd_getnewaddress();
+
+    // Elements Core creates a transaction spending a wallet utxo
+    let utxo = wallet.wollet.utxos().unwrap()[0].clone();
+    let fee = 1_000;
+    let btc = |sat: u64| format!("{}.{:08}", sat / 100_000_000, sat % 100_000_000);
+    let txid = utxo.outpoint.txid.to_string();
+    let inputs = serde_json::json!([{"txid": txid, "vout": utxo.outpoint.vout}]);
+    let address = node_address.to_string();
+    let outputs = serde_json::json!([
+        {address: btc(utxo.unblinded.value - fee)},
+        {"fee": btc(fee)}
+    ]);
+    let tx_hex = server.elementsd_createrawtransaction(inputs, outputs);
+    let tx: Transaction = deserialize(&Vec::<u8>::from_hex(&tx_hex).unwrap()).unwrap();
+
+    // LWK converts it to a PSET, blinds it, signs it and broadcasts it
+    let mut pset = wallet.wollet.pset_from_unsigned_tx(&tx).unwrap();
+    assert!(pset.inputs()[0].witness_utxo.is_some());
+    assert_eq!(pset.inputs()[0].bip32_derivation.len(), 1);
+    assert!(pset.outputs()[0].blinding_key.is_some());
+    assert_eq!(pset.outputs()[0].blinder_index, Some(0));
+
+    let inp_txout_sec = std::collections::HashMap::from([(0, utxo.unblinded)]);
+    pset.blind_last(&mut rand::thread_rng(), &EC, &inp_txout_sec)
+        .unwrap();
+    wallet.sign(&signer, &mut pset);
+    let txid = wallet.send(&mut pset);
+    let tx = wallet.wollet.transaction(&txid).unwrap().unwrap();
+    assert_eq!(tx.fee, fee);
+}
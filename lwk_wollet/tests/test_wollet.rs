@@ -86,6 +86,10 @@ impl<C: BlockchainBackend> TestWollet<C> {
         self.db_root_dir
     }
 
+    pub fn db_root_path(&self) -> &std::path::Path {
+        self.db_root_dir.path()
+    }
+
     pub fn policy_asset(&self) -> AssetId {
         self.wollet.policy_asset()
     }
@@ -107,7 +111,7 @@ impl<C: BlockchainBackend> TestWollet<C> {
     }
 
     /// Wait until tx appears in tx list (max 1 min)
-    fn wait_for_tx(&mut self, txid: &Txid) {
+    pub fn wait_for_tx(&mut self, txid: &Txid) {
         for _ in 0..120 {
             sync(&mut self.wollet, &mut self.client);
             let list = self.wollet.transactions().unwrap();
@@ -139,11 +143,11 @@ impl<C: BlockchainBackend> TestWollet<C> {
         *balance.get(asset).unwrap_or(&0u64)
     }
 
-    fn balance_btc(&mut self) -> u64 {
+    pub fn balance_btc(&mut self) -> u64 {
         self.balance(&self.wollet.policy_asset())
     }
 
-    fn get_tx(&mut self, txid: &Txid) -> WalletTx {
+    pub fn get_tx(&mut self, txid: &Txid) -> WalletTx {
         self.wollet.transaction(txid).unwrap().unwrap()
     }
 